@@ -0,0 +1,72 @@
+// Cell of the board, see `CellMaterial` in `src/style.rs`
+#import bevy_sprite::mesh2d_vertex_output MeshVertexOutput
+
+struct CellMaterial {
+    color: vec4<f32>,
+    outline_color: vec4<f32>,
+    radius: f32,
+    sides: f32,
+    rounding: f32,
+    outline: f32,
+    bevel: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: CellMaterial;
+
+const PI: f32 = 3.14159265;
+/// How much lighter or darker the bevel gets
+const BEVEL_CONTRAST: f32 = 0.35;
+
+// Angle of the normal of the closest edge of a regular polygon, whose edges face every `2 * PI / sides`
+// starting from the right. A pointy hex for 6 sides and an axis-aligned square for 4.
+fn edge_angle(p: vec2<f32>, sides: f32) -> f32 {
+    let turn = 2.0 * PI / sides;
+    return round(atan2(p.y, p.x) / turn) * turn;
+}
+
+// Signed distance to a regular polygon with corners at `radius` from the center
+fn sd_polygon(point: vec2<f32>, radius: f32, sides: f32) -> f32 {
+    let half_turn = PI / sides;
+    // into the frame of the closest edge, which faces along x with the corners above and below
+    let angle = atan2(point.y, point.x) - edge_angle(point, sides);
+    var p = length(point) * vec2<f32>(cos(angle), abs(sin(angle)));
+    let corner = radius * vec2<f32>(cos(half_turn), sin(half_turn));
+    p -= corner;
+    p.y += clamp(-p.y, 0.0, corner.y);
+    return length(p) * sign(p.x);
+}
+
+@fragment
+fn fragment(mesh: MeshVertexOutput) -> @location(0) vec4<f32> {
+    var vertex_color = vec4<f32>(1.0);
+#ifdef VERTEX_COLORS
+    vertex_color = mesh.color;
+#endif
+    // irregular cells have no polygon to find the edges of, so they stay flat
+    if material.sides < 3.0 {
+        return material.color * vertex_color;
+    }
+    // uv covers the face of the cell, see `build_mesh`
+    let p = (mesh.uv - 0.5) * 2.0 * material.radius;
+    // rounded corners are the corners of a smaller polygon grown by the rounding
+    let inner_radius = material.radius - material.rounding / cos(PI / material.sides);
+    let depth = material.rounding - sd_polygon(p, inner_radius, material.sides);
+    // outside of the face, like the shadow
+    if depth < 0.0 {
+        return material.color * vertex_color;
+    }
+
+    var color = material.color;
+    if depth < abs(material.bevel) {
+        // lit from the top left, pressed cells are lit from the opposite side
+        let side = edge_angle(p, material.sides);
+        let normal = vec2<f32>(cos(side), sin(side));
+        let light = dot(normal, normalize(vec2<f32>(-0.6, 0.8))) * sign(material.bevel);
+        color = vec4<f32>(color.rgb * (1.0 + BEVEL_CONTRAST * light), color.a);
+    }
+    if depth < material.outline {
+        color = material.outline_color;
+    }
+    return color * vertex_color;
+}
@@ -0,0 +1,172 @@
+//! Optional assists that play trivial moves after every action, one move at a time so it's visible why:
+//! the number that allows a move is pressed right before it's made.
+//!
+//! - auto-open reveals the rest of the hexes around a number whose mines are all flagged
+//! - auto-flag flags the covered hexes around a number that needs every one of them as a mine
+//!
+//! Assisted games are kept apart in the statistics. Assists are also bundled into [`AssistPreset`]s,
+//! and the loosest preset of a game goes along with its result, so rankings can be compared fairly.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    any_events,
+    board::{sorted_hexes, Board},
+    game::{ActionRequest, Authority, Game, GameAction, GameSet},
+    settings::Settings,
+    AppState, HexGrid, Pressed, PRESS_TIME,
+};
+
+/// Pause between assisted moves, long enough to follow them
+const STEP_TIME: Duration = Duration::from_millis(250);
+
+pub struct AssistPlugin;
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Assist>().add_systems(
+            Update,
+            (
+                play_trivial_moves
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(resource_equals(Authority::Local)),
+                track_preset,
+            )
+                .after(GameSet::Actions),
+        );
+    }
+}
+
+/// Named sets of assists, from none at all to every one of them. Each one includes the assists of the previous ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssistPreset {
+    /// Nothing but the board
+    #[default]
+    Purist,
+    /// Safe chords, which only prevent a misclick
+    Standard,
+    /// Safe chords, auto-open and auto-flag
+    Relaxed,
+}
+
+impl AssistPreset {
+    pub const ALL: [Self; 3] = [Self::Purist, Self::Standard, Self::Relaxed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Purist => "purist",
+            Self::Standard => "standard",
+            Self::Relaxed => "relaxed",
+        }
+    }
+
+    /// The strictest preset with every assist enabled in the `settings`
+    pub fn of(settings: &Settings) -> Self {
+        if settings.auto_open || settings.auto_flag {
+            Self::Relaxed
+        } else if settings.safe_chord {
+            Self::Standard
+        } else {
+            Self::Purist
+        }
+    }
+
+    pub fn apply(self, settings: &mut Settings) {
+        settings.safe_chord = self >= Self::Standard;
+        settings.auto_open = self >= Self::Relaxed;
+        settings.auto_flag = self >= Self::Relaxed;
+    }
+}
+
+/// Progress of the assist between frames
+#[derive(Resource, Default)]
+struct Assist {
+    /// Set by every action, the assist keeps going until it runs out of moves
+    pending: bool,
+    since_last_move: Duration,
+}
+
+/// Next trivial move and the number it follows from. Numbers are checked in a fixed order,
+/// so the assist plays the same moves on the same board.
+fn next_move(board: &Board, settings: &Settings) -> Option<(Hex, GameAction)> {
+    // arrows don't tell how many mines are around
+    if board.config.rules.directional {
+        return None;
+    }
+    let mut numbers: Vec<_> = board
+        .numbers
+        .iter()
+        .filter(|(hex, _)| !board.covered.contains(*hex))
+        .collect();
+    numbers.sort_unstable_by_key(|(hex, _)| (hex.x, hex.y));
+    // flags of two colors can't be told apart by a number
+    let auto_flag = settings.auto_flag && board.config.rules.flags && !board.config.rules.colored;
+    numbers.into_iter().find_map(|(hex, number)| {
+        let number = *number as usize;
+        let flags = board
+            .neighbors(*hex)
+            .filter(|hex| board.flagged.contains(hex))
+            .count();
+        let unknown = sorted_hexes(
+            board
+                .neighbors(*hex)
+                .filter(|hex| board.covered.contains(hex) && !board.flagged.contains(hex)),
+        );
+        let first = *unknown.first()?;
+        if settings.auto_open && flags == number {
+            Some((*hex, GameAction::Chord(*hex)))
+        } else if auto_flag && flags + unknown.len() == number {
+            Some((*hex, GameAction::ToggleFlag(first)))
+        } else {
+            None
+        }
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_trivial_moves(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    mut game: ResMut<Game>,
+    mut assist: ResMut<Assist>,
+    mut actions: EventReader<GameAction>,
+    mut requests: EventWriter<ActionRequest>,
+) {
+    if any_events(&mut actions) {
+        assist.pending = true;
+    }
+    if !assist.pending || game.outcome.is_some() {
+        return;
+    }
+    assist.since_last_move += time.delta();
+    if assist.since_last_move < STEP_TIME {
+        return;
+    }
+    let Some((reason, action)) = next_move(&board, &settings) else {
+        assist.pending = false;
+        return;
+    };
+    assist.since_last_move = Duration::ZERO;
+    if let Some(entity) = grid.entities.get(&reason) {
+        commands.entity(*entity).insert(Pressed(PRESS_TIME));
+    }
+    requests.send(ActionRequest(action));
+    game.assisted = true;
+}
+
+/// Assists turned on in the middle of a game count for all of it
+fn track_preset(settings: Res<Settings>, mut game: ResMut<Game>) {
+    let preset = AssistPreset::of(&settings);
+    // `Game` is reset by every new game, so this starts over from the preset of the settings
+    if preset > game.assists && game.outcome.is_none() {
+        game.assists = preset;
+    }
+}
@@ -0,0 +1,168 @@
+//! Autosave of the unfinished game, so a crash or power loss doesn't destroy a long game.
+//! The board is restored from its seed (or layout) and the recorded actions are applied again.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    any_events,
+    board::{Board, BoardConfig},
+    game::{Authority, CellsChanged, Game, GameAction, GameSet, NewCustomGame, NewGame},
+    puzzle::Puzzle,
+    replay::Replay,
+    storage, AppState,
+};
+
+/// Saves also happen without actions, so the clock is not too far behind after recovering
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(autosave) = storage::load::<Option<Autosave>>(Autosave::FILE) {
+            app.insert_resource(Recoverable(autosave));
+        }
+        app.add_event::<RecoverGame>().add_systems(
+            Update,
+            (
+                // the recovery is offered in the pause menu right after the start
+                offer_recovery
+                    .after(GameSet::NewGame)
+                    .run_if(resource_added::<Recoverable>()),
+                start_recovery.in_set(GameSet::Route),
+                (
+                    apply_recovered_actions.in_set(GameSet::Route),
+                    finish_recovery.after(GameSet::Actions),
+                )
+                    .run_if(resource_exists::<Recovering>()),
+                write_autosave
+                    .after(GameSet::Actions)
+                    .after(finish_recovery)
+                    .run_if(resource_equals(Authority::Local)),
+            ),
+        );
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Autosave {
+    pub config: BoardConfig,
+    pub seed: u64,
+    /// Hand-made boards can't be generated from the seed
+    layout: Option<Puzzle>,
+    replay: Replay,
+    pub elapsed_ms: u64,
+}
+
+impl Autosave {
+    const FILE: &str = "autosave";
+}
+
+/// Game that was autosaved and never finished, offered to continue until it's recovered
+#[derive(Resource)]
+pub struct Recoverable(pub Autosave);
+
+/// Request to continue the [`Recoverable`] game
+#[derive(Event)]
+pub struct RecoverGame;
+
+/// Recovery in progress, which takes a frame to start the new game and another one to apply the actions
+#[derive(Resource)]
+pub struct Recovering {
+    autosave: Autosave,
+    actions_sent: bool,
+}
+
+fn offer_recovery(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::Paused);
+}
+
+fn start_recovery(
+    mut commands: Commands,
+    mut requests: EventReader<RecoverGame>,
+    recoverable: Option<Res<Recoverable>>,
+    mut new_game: EventWriter<NewGame>,
+    mut custom_game: EventWriter<NewCustomGame>,
+) {
+    let (Some(_), Some(recoverable)) = (requests.iter().last(), recoverable) else {
+        return;
+    };
+    let autosave = recoverable.0.clone();
+    match &autosave.layout {
+        Some(puzzle) => custom_game.send(NewCustomGame {
+            layout: puzzle.layout(),
+            rules: autosave.config.rules,
+            seed: autosave.seed,
+        }),
+        None => new_game.send(NewGame {
+            config: autosave.config,
+            seed: autosave.seed,
+        }),
+    }
+    commands.remove_resource::<Recoverable>();
+    commands.insert_resource(Recovering {
+        autosave,
+        actions_sent: false,
+    });
+}
+
+fn apply_recovered_actions(
+    mut recovering: ResMut<Recovering>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if !recovering.actions_sent {
+        recovering.actions_sent = true;
+        let steps = &recovering.autosave.replay.steps;
+        actions.send_batch(steps.iter().map(|step| step.action()));
+    }
+}
+
+/// Actions were applied at the time of recovery, so the original timings are put back
+fn finish_recovery(mut commands: Commands, recovering: Res<Recovering>, mut game: ResMut<Game>) {
+    if !recovering.actions_sent {
+        return;
+    }
+    game.replay = recovering.autosave.replay.clone();
+    game.elapsed = Duration::from_millis(recovering.autosave.elapsed_ms);
+    commands.remove_resource::<Recovering>();
+}
+
+fn write_autosave(
+    time: Res<Time>,
+    mut since_saved: Local<Duration>,
+    mut has_autosave: Local<bool>,
+    board: Res<Board>,
+    game: Res<Game>,
+    mut cells_changed: EventReader<CellsChanged>,
+) {
+    *since_saved += time.delta();
+    if game.outcome.is_some() {
+        // nothing to recover in a finished game
+        if *has_autosave {
+            storage::remove(Autosave::FILE);
+            *has_autosave = false;
+        }
+        return;
+    }
+    let changed = any_events(&mut cells_changed);
+    if game.replay.is_empty() || (!changed && *since_saved < SAVE_INTERVAL) {
+        return;
+    }
+    *since_saved = Duration::ZERO;
+
+    let autosave = Autosave {
+        config: board.config,
+        seed: board.seed,
+        layout: board
+            .layout
+            .as_ref()
+            .map(|layout| Puzzle::new(String::new(), None, layout)),
+        replay: game.replay.clone(),
+        elapsed_ms: game.elapsed.as_millis() as u64,
+    };
+    storage::save(Autosave::FILE, &Some(autosave));
+    *has_autosave = true;
+}
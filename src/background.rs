@@ -0,0 +1,127 @@
+//! Big faint hexes drifting slowly behind the board. Closer ones are larger and move faster,
+//! and all of them stand still with [`Settings::reduced_motion`].
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    geometry::Geometry,
+    rng::{self, Rng},
+    settings::{GridStyle, Settings},
+    style,
+    theme::Theme,
+    BoardView,
+};
+
+const MOTIFS: usize = 14;
+/// Smallest and largest motif relative to a hex of the board
+const MOTIF_SCALE: (f32, f32) = (3.0, 9.0);
+/// Of the window size per second, for the closest motifs
+const DRIFT_SPEED: f32 = 0.015;
+/// In radians per second, for the closest motifs
+const SPIN_SPEED: f32 = 0.05;
+/// Part of the window motifs go beyond before they wrap around, so they don't pop in
+const MARGIN: f32 = 0.2;
+/// Behind the board, but still in front of the camera's near plane
+const MOTIF_Z: f32 = -0.05;
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_motifs)
+            .add_systems(Update, (apply_theme, drift_motifs));
+    }
+}
+
+#[derive(Resource)]
+struct MotifMaterial(Handle<ColorMaterial>);
+
+#[derive(Component)]
+struct Motif {
+    /// Position in the window, from 0 to 1 on both axes
+    pos: Vec2,
+    direction: Vec2,
+    /// From 0 for the farthest motifs to 1 for the closest ones
+    depth: f32,
+}
+
+fn spawn_motifs(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let mesh = meshes.add(style::face_mesh(
+        &GridStyle::default(),
+        Geometry::Hex,
+        false,
+    ));
+    let material = materials.add(theme.motif.into());
+    let mut rng = Rng::new(rng::random_seed());
+    let mut unit = move || rng.below(1000) as f32 / 1000.0;
+    for _ in 0..MOTIFS {
+        let angle = unit() * std::f32::consts::TAU;
+        commands.spawn((
+            ColorMesh2dBundle {
+                mesh: mesh.clone().into(),
+                material: material.clone(),
+                transform: Transform::from_rotation(Quat::from_rotation_z(angle)),
+                ..default()
+            },
+            Motif {
+                pos: Vec2::new(unit(), unit()),
+                direction: Vec2::from_angle(unit() * std::f32::consts::TAU),
+                depth: unit(),
+            },
+        ));
+    }
+    commands.insert_resource(MotifMaterial(material));
+}
+
+fn apply_theme(
+    theme: Res<Theme>,
+    motif_material: Res<MotifMaterial>,
+    mut clear_color: ResMut<ClearColor>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+    clear_color.0 = theme.clear;
+    if let Some(material) = materials.get_mut(&motif_material.0) {
+        material.color = theme.motif;
+    }
+}
+
+fn drift_motifs(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    view: Res<BoardView>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut motifs: Query<(&mut Motif, &mut Transform)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+    let dt = if settings.reduced_motion {
+        0.0
+    } else {
+        time.delta_seconds()
+    };
+    for (mut motif, mut transform) in &mut motifs {
+        // farther motifs move slower, which gives a bit of parallax
+        let speed = 0.3 + 0.7 * motif.depth;
+        let pos = motif.pos + motif.direction * DRIFT_SPEED * speed * dt;
+        let wrap = |value: f32| (value + MARGIN).rem_euclid(1.0 + 2.0 * MARGIN) - MARGIN;
+        motif.pos = Vec2::new(wrap(pos.x), wrap(pos.y));
+
+        // fixed to the window, whatever part of the board is shown
+        let offset =
+            Vec2::from_angle(view.rotation).rotate((motif.pos - 0.5) * window_size * view.scale);
+        let scale = MOTIF_SCALE.0 + (MOTIF_SCALE.1 - MOTIF_SCALE.0) * motif.depth;
+        transform.translation = (view.center + offset).extend(MOTIF_Z);
+        transform.scale = Vec3::splat(scale * view.scale);
+        transform.rotate_z(SPIN_SPEED * speed * dt);
+    }
+}
@@ -0,0 +1,4 @@
+// Dedicated server for the multiplayer modes, which lives in the library like the game itself
+fn main() {
+    hex_minesweeper::server::run();
+}
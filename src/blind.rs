@@ -0,0 +1,148 @@
+//! Blind numbers challenge: numbers show up only for a moment after they are revealed and then fade out,
+//! so the board has to be solved from memory. A few pings per game show numbers around a hex again.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap, window::PrimaryWindow};
+use hexx::Hex;
+
+use crate::{
+    any_events,
+    board::Board,
+    cursor_hex,
+    game::{CellsChanged, GameSet, NewCustomGame, NewGame},
+    AppState, BoardView, CellNumber,
+};
+
+/// How long numbers stay visible after they are revealed or pinged
+const SHOW_TIME: Duration = Duration::from_secs(3);
+/// The last part of `SHOW_TIME`, during which numbers fade out
+const FADE_TIME: Duration = Duration::from_secs(1);
+const PING_RADIUS: u32 = 1;
+
+pub struct BlindPlugin;
+
+impl Plugin for BlindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Memory>().add_systems(
+            Update,
+            (
+                reset_memory.after(GameSet::NewGame),
+                (show_revealed, ping.run_if(in_state(AppState::Playing))).after(GameSet::Actions),
+                fade_numbers.after(show_revealed).after(ping),
+            ),
+        );
+    }
+}
+
+/// Numbers that are visible for now under [`Rules::blind`](crate::board::Rules::blind)
+#[derive(Resource, Default)]
+pub struct Memory {
+    pub pings_left: u32,
+    /// Time left for every visible number
+    shown: HashMap<Hex, Duration>,
+}
+
+impl Memory {
+    pub fn is_shown(&self, hex: Hex) -> bool {
+        self.shown.contains_key(&hex)
+    }
+
+    fn show(&mut self, hexes: impl IntoIterator<Item = Hex>) {
+        self.shown
+            .extend(hexes.into_iter().map(|hex| (hex, SHOW_TIME)));
+    }
+}
+
+fn reset_memory(
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    board: Res<Board>,
+    mut memory: ResMut<Memory>,
+) {
+    if any_events(&mut new_games) | any_events(&mut custom_games) {
+        *memory = Memory {
+            pings_left: board.config.rules.blind_pings,
+            ..default()
+        };
+    }
+}
+
+fn show_revealed(
+    mut cells_changed: EventReader<CellsChanged>,
+    board: Res<Board>,
+    mut memory: ResMut<Memory>,
+) {
+    let revealed = cells_changed
+        .iter()
+        .flat_map(|CellsChanged(hexes)| hexes)
+        .filter(|hex| !board.covered.contains(*hex) && board.numbers.contains_key(*hex))
+        // numbers that are already visible keep their timers
+        .filter(|hex| !memory.is_shown(**hex))
+        .copied()
+        .collect::<Vec<_>>();
+    if board.config.rules.blind {
+        memory.show(revealed);
+    }
+}
+
+fn ping(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    view: Res<BoardView>,
+    buttons: Res<Input<MouseButton>>,
+    board: Res<Board>,
+    mut memory: ResMut<Memory>,
+) {
+    if !board.config.rules.blind
+        || memory.pings_left == 0
+        || !buttons.just_pressed(MouseButton::Middle)
+    {
+        return;
+    }
+    let Some(target) = cursor_hex(windows.single(), &view, board.config.rules.geometry)
+        .filter(|hex| board.contains(*hex))
+    else {
+        return;
+    };
+    let pinged: Vec<_> = board
+        .config
+        .rules
+        .geometry
+        .around(target, PING_RADIUS)
+        .into_iter()
+        .filter(|hex| !board.covered.contains(hex) && board.numbers.contains_key(hex))
+        .collect();
+    // a ping that shows nothing isn't spent
+    if !pinged.is_empty() {
+        memory.pings_left -= 1;
+        memory.show(pinged);
+    }
+}
+
+fn fade_numbers(
+    time: Res<Time>,
+    board: Res<Board>,
+    mut memory: ResMut<Memory>,
+    mut numbers: Query<(&CellNumber, Option<&mut Sprite>, Option<&mut Text>)>,
+) {
+    if !board.config.rules.blind || memory.shown.is_empty() {
+        return;
+    }
+    memory.shown.retain(|_, left| {
+        *left = left.saturating_sub(time.delta());
+        !left.is_zero()
+    });
+    for (CellNumber(hex), sprite, text) in &mut numbers {
+        // hidden numbers are not drawn at all, see `apply_fog`
+        let Some(left) = memory.shown.get(hex) else {
+            continue;
+        };
+        let alpha = (left.as_secs_f32() / FADE_TIME.as_secs_f32()).min(1.0);
+        if let Some(mut sprite) = sprite {
+            sprite.color.set_a(alpha);
+        }
+        if let Some(mut text) = text {
+            text.sections[0].style.color.set_a(alpha);
+        }
+    }
+}
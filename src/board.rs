@@ -0,0 +1,803 @@
+//! Core minesweeper rules, independent from rendering and input
+
+use std::{fmt, sync::Arc};
+
+use bevy::{
+    prelude::Resource,
+    utils::{HashMap, HashSet},
+};
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clues, colors, defuse,
+    geometry::Geometry,
+    gradient::{self, Gradient},
+    gravity,
+    kernel::Kernel,
+    rng::Rng,
+    treasure::{self, Treasure},
+};
+
+/// Parameters that together with a seed fully define a board
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoardConfig {
+    pub radius: u32,
+    /// Fraction of cells with mines
+    pub mine_density: f32,
+    /// Older configs without rules are classic games
+    #[serde(default)]
+    pub rules: Rules,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self {
+            radius: 16,
+            mine_density: 0.16,
+            rules: Rules::CLASSIC,
+        }
+    }
+}
+
+/// Rule modifiers on top of the classic game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    pub flags: bool,
+    /// Distance within which mines are counted by numbers
+    pub adjacency: u32,
+    /// Mines that can be revealed before the game is lost
+    pub lives: u32,
+    /// Numbers are visible only within this distance from the cursor
+    pub fog: Option<u32>,
+    /// Relaxed game without the clock, where revealed mines are just marked and cost nothing
+    pub zen: bool,
+    /// Learning mode, where mines are visible through the cover and revealing them costs nothing
+    pub sandbox: bool,
+    /// Percent of empty safe hexes that hide a [`Treasure`]
+    pub treasures: u32,
+    /// Hard mode, where a covered mine moves after every this many reveals. 0 disables it.
+    pub moving_mines: u32,
+    /// Only hexes within this distance from revealed ones are visible at all,
+    /// so the board is explored outward from the center
+    pub darkness: Option<u32>,
+    /// Endless survival on a rectangular board, see `gravity.rs`
+    pub gravity: bool,
+    /// Countdown of timed mines that have a revealed neighbor, see `defuse.rs`. 0 disables them.
+    pub defuse_seconds: u32,
+    /// Consumables earned during play, see `powerups.rs`
+    pub powerups: bool,
+    /// Custom shape of the neighborhood that replaces the [`Rules::adjacency`] range
+    pub kernel: Option<Kernel>,
+    /// Mines and flags come in two colors, see `colors.rs`
+    pub colored: bool,
+    /// Safe hexes show an arrow toward the nearest mine instead of a number, see `clues.rs`
+    pub directional: bool,
+    /// How mines are spread over the board
+    pub gradient: Gradient,
+    /// Numbers fade out after they are revealed, see `blind.rs`
+    pub blind: bool,
+    /// Pings that show numbers of [`Rules::blind`] games again
+    pub blind_pings: u32,
+    /// Shape of the cells, see `geometry.rs`. [`Rules::gravity`] boards keep their rows of hexes.
+    pub geometry: Geometry,
+}
+
+impl Rules {
+    pub const CLASSIC: Self = Self {
+        flags: true,
+        adjacency: 1,
+        lives: 1,
+        fog: None,
+        zen: false,
+        sandbox: false,
+        treasures: 0,
+        moving_mines: 0,
+        darkness: None,
+        gravity: false,
+        defuse_seconds: 0,
+        powerups: false,
+        kernel: None,
+        colored: false,
+        directional: false,
+        gradient: Gradient::Uniform,
+        blind: false,
+        blind_pings: 0,
+        geometry: Geometry::Hex,
+    };
+
+    /// Whether times of the game can be compared with other games, which isn't the case for relaxed modes
+    pub fn is_ranked(&self) -> bool {
+        !self.zen && !self.sandbox
+    }
+
+    /// Offsets of hexes counted by a number
+    pub fn neighbors(&self) -> Vec<Hex> {
+        match self.kernel {
+            Some(kernel) => kernel.offsets(),
+            None => {
+                let mut offsets = self.geometry.range(self.adjacency);
+                offsets.retain(|offset| *offset != Hex::ZERO);
+                offsets
+            }
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::CLASSIC
+    }
+}
+
+impl fmt::Display for BoardConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "radius {}, {:.0}% mines",
+            self.radius,
+            self.mine_density * 100.0
+        )?;
+        let rules = &self.rules;
+        if !rules.flags {
+            write!(f, ", no flags")?;
+        }
+        if rules.adjacency != Rules::CLASSIC.adjacency {
+            write!(f, ", adjacency {}", rules.adjacency)?;
+        }
+        if rules.kernel.is_some() {
+            write!(f, ", custom neighbors")?;
+        }
+        if rules.geometry != Geometry::Hex {
+            write!(f, ", {}", rules.geometry.name())?;
+        }
+        if rules.lives != Rules::CLASSIC.lives {
+            write!(f, ", {} lives", rules.lives)?;
+        }
+        if let Some(radius) = rules.fog {
+            write!(f, ", fog {radius}")?;
+        }
+        if rules.zen {
+            write!(f, ", zen")?;
+        }
+        if rules.sandbox {
+            write!(f, ", sandbox")?;
+        }
+        if rules.moving_mines > 0 {
+            write!(f, ", mines move every {} reveals", rules.moving_mines)?;
+        }
+        if let Some(radius) = rules.darkness {
+            write!(f, ", darkness {radius}")?;
+        }
+        if rules.gravity {
+            write!(f, ", gravity")?;
+        }
+        if rules.defuse_seconds > 0 {
+            write!(f, ", defuse in {}s", rules.defuse_seconds)?;
+        }
+        if rules.powerups {
+            write!(f, ", power-ups")?;
+        }
+        if rules.colored {
+            write!(f, ", colored mines")?;
+        }
+        if rules.directional {
+            write!(f, ", directional clues")?;
+        }
+        if rules.gradient != Gradient::Uniform {
+            write!(f, ", {} density", rules.gradient.name())?;
+        }
+        match (rules.blind, rules.blind_pings) {
+            (false, _) => {}
+            (true, 0) => write!(f, ", blind")?,
+            (true, pings) => write!(f, ", blind with {pings} pings")?,
+        }
+        if rules.treasures > 0 {
+            write!(f, ", {}% treasures", rules.treasures)?;
+        }
+        Ok(())
+    }
+}
+
+impl BoardConfig {
+    /// Amount of hexes in [`Self::shape`]
+    pub fn cells_count(&self) -> usize {
+        self.shape().len()
+    }
+
+    pub fn mines_count(&self) -> usize {
+        (self.cells_count() as f32 * self.mine_density).round() as usize
+    }
+
+    /// Hexes of a generated board, in a stable order
+    pub fn shape(&self) -> Vec<Hex> {
+        if self.rules.gravity {
+            gravity::shape(self.radius)
+        } else {
+            self.rules.geometry.shape(self.radius)
+        }
+    }
+}
+
+/// Number shown on a safe hex instead of the classic amount of neighbor mines, see [`Board::number_hook`]
+pub type NumberHook = Arc<dyn Fn(Hex, u8) -> u8 + Send + Sync>;
+
+/// Explicit content of a board, for boards that are made by hand instead of generated from a seed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Layout {
+    /// Shape of the board, hexes outside of it are voids
+    pub cells: HashSet<Hex>,
+    pub mines: HashSet<Hex>,
+    /// Hexes that are revealed from the start
+    pub revealed: HashSet<Hex>,
+}
+
+#[derive(Resource, Clone)]
+pub struct Board {
+    pub config: BoardConfig,
+    pub seed: u64,
+
+    /// All hexes of the board, a hexagon for generated boards
+    pub cells: HashSet<Hex>,
+    pub covered: HashSet<Hex>,
+    /// Amount of neighbor mines, only for hexes with at least one neighbor mine
+    pub numbers: HashMap<Hex, u8>,
+    pub mines: HashSet<Hex>,
+    pub flagged: HashSet<Hex>,
+    /// Mine that was revealed, which ends the game
+    pub exploded: Option<Hex>,
+    /// Mines that can still be revealed, see [`Rules::lives`]
+    pub lives: u32,
+    /// Content of hand-made boards, which can't be reproduced from the seed
+    pub layout: Option<Layout>,
+    pub treasures: HashMap<Hex, Treasure>,
+    /// Hexes visible under [`Rules::darkness`], which only grows as the board is revealed
+    pub lit: HashSet<Hex>,
+    /// Row pairs that collapsed under [`Rules::gravity`]
+    pub collapsed: u32,
+    /// Mines with a countdown under [`Rules::defuse_seconds`], until they are flagged
+    pub timed_mines: HashSet<Hex>,
+    /// Covered mines found by the radar power-up, drawn like in sandbox mode
+    pub scanned: HashSet<Hex>,
+    /// Red mines under [`Rules::colored`], the rest are blue
+    pub red_mines: HashSet<Hex>,
+    /// Red mines among [`Self::numbers`], the rest of every number are blue ones
+    pub red_numbers: HashMap<Hex, u8>,
+    /// Flags marking red mines, always a part of `flagged`
+    pub red_flags: HashSet<Hex>,
+    /// Direction toward the nearest mine of every safe hex under [`Rules::directional`],
+    /// as an index into [`clues::directions`]
+    pub clues: HashMap<Hex, u8>,
+    /// Neighbors of every cell, resolved once as they are used on every reveal
+    pub adjacency: Adjacency,
+    /// Numbers of a rule script, applied on every recount so they survive mines that move
+    pub number_hook: Option<NumberHook>,
+}
+
+impl Board {
+    pub fn generate(config: BoardConfig, seed: u64) -> Self {
+        // shapes have a stable order, unlike hash sets, so the shuffle is reproducible
+        let mut hexes = config.shape();
+        let mines = gradient::pick_mines(
+            &mut hexes,
+            config.mines_count(),
+            config.rules.gradient,
+            config.rules.geometry,
+            config.radius,
+            &mut Rng::new(seed),
+        )
+        .into_iter()
+        .collect();
+        Self::with_mines(config, seed, hexes.into_iter().collect(), mines)
+    }
+
+    /// Board with the given content. The config is derived from the layout, except for the `rules`.
+    pub fn from_layout(layout: &Layout, rules: Rules) -> Self {
+        Self::from_seeded_layout(layout, rules, 0)
+    }
+
+    /// Same as [`Self::from_layout`], with the `seed` of what the layout doesn't hold,
+    /// such as treasures, timed mines and mines that move
+    pub fn from_seeded_layout(layout: &Layout, rules: Rules, seed: u64) -> Self {
+        let config = BoardConfig {
+            radius: layout
+                .cells
+                .iter()
+                .map(|hex| rules.geometry.distance(Hex::ZERO, *hex))
+                .max()
+                .unwrap_or(0),
+            mine_density: layout.mines.len() as f32 / layout.cells.len().max(1) as f32,
+            rules,
+        };
+        let mines = layout.mines.intersection(&layout.cells).copied().collect();
+        let mut board = Self::with_mines(config, seed, layout.cells.clone(), mines);
+        for hex in &layout.revealed {
+            board.covered.remove(hex);
+        }
+        let revealed: Vec<_> = layout.revealed.iter().copied().collect();
+        board.light_around(&revealed);
+        board.layout = Some(layout.clone());
+        board
+    }
+
+    /// Content of the board with everything uncovered so far as revealed, flags are not kept
+    pub fn layout(&self) -> Layout {
+        Layout {
+            cells: self.cells.clone(),
+            mines: self.mines.clone(),
+            revealed: self.cells.difference(&self.covered).copied().collect(),
+        }
+    }
+
+    fn with_mines(
+        config: BoardConfig,
+        seed: u64,
+        cells: HashSet<Hex>,
+        mines: HashSet<Hex>,
+    ) -> Self {
+        let adjacency = Adjacency::new(&cells, &config.rules);
+        let numbers = count_numbers(&cells, &mines, &adjacency);
+        let treasures = treasure::place(&cells, &mines, &numbers, config.rules.treasures, seed);
+        let timed_mines = match config.rules.defuse_seconds {
+            0 => HashSet::new(),
+            _ => defuse::pick_timed(&mines, seed),
+        };
+        let red_mines = match config.rules.colored {
+            true => colors::pick_red(&mines, seed),
+            false => HashSet::new(),
+        };
+        let red_numbers = colors::count_red(&cells, &numbers, &red_mines, &adjacency);
+        let clues = match config.rules.directional {
+            true => clues::compute(&cells, &mines),
+            false => HashMap::new(),
+        };
+
+        let mut board = Self {
+            config,
+            seed,
+            // all hexes are covered by default
+            covered: cells.clone(),
+            cells,
+            numbers,
+            mines,
+            flagged: HashSet::new(),
+            exploded: None,
+            lives: config.rules.lives,
+            layout: None,
+            treasures,
+            lit: HashSet::new(),
+            collapsed: 0,
+            timed_mines,
+            scanned: HashSet::new(),
+            red_mines,
+            red_numbers,
+            red_flags: HashSet::new(),
+            clues,
+            adjacency,
+            number_hook: None,
+        };
+        // exploration starts from the center
+        board.light_around(&[Hex::ZERO]);
+        board
+    }
+
+    /// Whether the hex can be seen and played, which is always the case without [`Rules::darkness`]
+    pub fn is_lit(&self, hex: Hex) -> bool {
+        self.config.rules.darkness.is_none() || self.lit.contains(&hex)
+    }
+
+    fn light_around(&mut self, hexes: &[Hex]) {
+        let Some(radius) = self.config.rules.darkness else {
+            return;
+        };
+        let geometry = self.config.rules.geometry;
+        for hex in hexes {
+            let lit = geometry
+                .around(*hex, radius)
+                .into_iter()
+                .filter(|hex| self.cells.contains(hex));
+            self.lit.extend(lit);
+        }
+    }
+
+    pub fn contains(&self, hex: Hex) -> bool {
+        self.cells.contains(&hex)
+    }
+
+    /// Hexes of the board counted by the number on `hex`
+    pub fn neighbors(&self, hex: Hex) -> impl Iterator<Item = Hex> + '_ {
+        self.adjacency.neighbors(hex)
+    }
+
+    /// Hexes of the board whose numbers count `hex`. The same as [`Self::neighbors`] unless the kernel is asymmetric.
+    pub fn counted_by(&self, hex: Hex) -> impl Iterator<Item = Hex> + '_ {
+        self.adjacency.counted_by(hex)
+    }
+
+    /// Minimal amount of reveals needed to clear the board, known as 3BV: one per opening
+    /// (connected area of hexes without neighbor mines) plus one per number outside of openings
+    pub fn min_clicks(&self) -> usize {
+        let is_empty = |hex: &Hex| !self.mines.contains(hex) && !self.numbers.contains_key(hex);
+        let mut opened = HashSet::new();
+        let mut clicks = 0;
+        for &hex in &self.cells {
+            if !is_empty(&hex) || opened.contains(&hex) {
+                continue;
+            }
+            clicks += 1;
+            // same expansion as in `reveal`
+            let mut buffer = vec![hex];
+            opened.insert(hex);
+            while let Some(hex) = buffer.pop() {
+                for neighbor in self.neighbors(hex) {
+                    if opened.insert(neighbor) && is_empty(&neighbor) {
+                        buffer.push(neighbor);
+                    }
+                }
+            }
+        }
+        clicks
+            + self
+                .numbers
+                .keys()
+                .filter(|hex| !opened.contains(*hex))
+                .count()
+    }
+
+    /// All safe hexes are revealed
+    pub fn is_cleared(&self) -> bool {
+        self.remaining_safe() == 0
+    }
+
+    /// Covered hexes without mines
+    pub fn remaining_safe(&self) -> usize {
+        // mines can be uncovered with extra lives, so count them directly
+        self.covered
+            .iter()
+            .filter(|hex| !self.mines.contains(hex))
+            .count()
+    }
+
+    /// Mines that are neither flagged nor revealed
+    pub fn mines_left(&self) -> i32 {
+        let revealed = self.mines.len() - self.covered.intersection(&self.mines).count();
+        self.mines.len() as i32 - self.flagged.len() as i32 - revealed as i32
+    }
+
+    /// Fraction of safe hexes that are revealed
+    pub fn progress(&self) -> f32 {
+        let safe = self.cells.len() - self.mines.len();
+        if safe == 0 {
+            return 1.0;
+        }
+        1.0 - self.remaining_safe() as f32 / safe as f32
+    }
+
+    /// Flags or unflags a covered hex, under [`Rules::colored`] a blue flag turns red first.
+    /// Returns whether anything changed.
+    pub fn toggle_flag(&mut self, hex: Hex) -> bool {
+        if !self.config.rules.flags || !self.covered.contains(&hex) || !self.is_lit(hex) {
+            return false;
+        }
+        if self.flagged.insert(hex) {
+            return true;
+        }
+        if self.config.rules.colored && self.red_flags.insert(hex) {
+            return true;
+        }
+        self.flagged.remove(&hex);
+        self.red_flags.remove(&hex);
+        true
+    }
+
+    /// Flag is on a mine of the same color
+    pub fn is_correct_flag(&self, hex: Hex) -> bool {
+        self.flagged.contains(&hex)
+            && self.mines.contains(&hex)
+            && self.red_flags.contains(&hex) == self.red_mines.contains(&hex)
+    }
+
+    /// Flags on safe hexes or of the wrong color, see [`Self::is_correct_flag`]
+    pub fn wrong_flags(&self) -> impl Iterator<Item = Hex> + '_ {
+        self.flagged
+            .iter()
+            .copied()
+            .filter(|hex| !self.is_correct_flag(*hex))
+    }
+
+    /// Recounts [`Self::numbers`] after mines changed, through the [`Self::number_hook`] if there is one
+    pub fn recount_numbers(&mut self) {
+        let classic = count_numbers(&self.cells, &self.mines, &self.adjacency);
+        let Some(hook) = &self.number_hook else {
+            self.numbers = classic;
+            return;
+        };
+        self.numbers = self
+            .cells
+            .iter()
+            .filter(|hex| !self.mines.contains(hex))
+            .filter_map(|hex| {
+                let number = hook(*hex, classic.get(hex).copied().unwrap_or(0));
+                // zero is drawn as an empty hex and opens the area around it
+                (number > 0).then_some((*hex, number))
+            })
+            .collect();
+    }
+
+    /// Recounts [`Self::red_numbers`] and [`Self::clues`] after mines changed
+    pub fn recount_variants(&mut self) {
+        self.red_numbers =
+            colors::count_red(&self.cells, &self.numbers, &self.red_mines, &self.adjacency);
+        if self.config.rules.directional {
+            self.clues = clues::compute(&self.cells, &self.mines);
+        }
+    }
+
+    /// Moves a random covered unflagged mine to a covered hex away from revealed ones, so the move can't be seen
+    /// right away. Returns hexes to redraw, empty if nothing moved.
+    pub fn move_mine(&mut self, seed: u64) -> Vec<Hex> {
+        let sources = sorted_hexes(
+            self.mines
+                .iter()
+                .filter(|hex| self.covered.contains(*hex) && !self.flagged.contains(*hex))
+                .copied(),
+        );
+        let targets = sorted_hexes(
+            self.covered
+                .iter()
+                .filter(|hex| !self.mines.contains(*hex) && !self.flagged.contains(*hex))
+                .filter(|hex| {
+                    self.counted_by(**hex)
+                        .all(|near| self.covered.contains(&near))
+                })
+                .copied(),
+        );
+        if sources.is_empty() || targets.is_empty() {
+            return Vec::new();
+        }
+        let mut rng = Rng::new(seed);
+        let from = sources[rng.below(sources.len() as u64) as usize];
+        let to = targets[rng.below(targets.len() as u64) as usize];
+
+        self.mines.remove(&from);
+        self.mines.insert(to);
+        if self.timed_mines.remove(&from) {
+            self.timed_mines.insert(to);
+        }
+        if self.red_mines.remove(&from) {
+            self.red_mines.insert(to);
+        }
+        // only numbers around both hexes change, and they do so through the hook of a rule script too
+        self.recount_numbers();
+        let old_clues = std::mem::take(&mut self.clues);
+        self.recount_variants();
+
+        [from, to]
+            .into_iter()
+            .chain(self.counted_by(from))
+            .chain(self.counted_by(to))
+            // the nearest mine can be far away, so clues change anywhere
+            .chain(
+                self.clues
+                    .iter()
+                    .filter(|(hex, clue)| old_clues.get(*hex) != Some(*clue))
+                    .map(|(hex, _)| *hex),
+            )
+            .collect()
+    }
+
+    /// Reveals all unflagged neighbors of a revealed number once it has as many flags around as its number,
+    /// known as chording. Revealed mines count as flags. Returns all newly revealed hexes.
+    pub fn chord(&mut self, hex: Hex) -> Vec<Hex> {
+        // arrows don't tell how many flags are enough
+        if self.config.rules.directional || self.covered.contains(&hex) {
+            return Vec::new();
+        }
+        let Some(&number) = self.numbers.get(&hex) else {
+            return Vec::new();
+        };
+        let neighbors: Vec<_> = self.neighbors(hex).collect();
+        let flags = neighbors.iter().filter(|hex| self.is_marked(**hex)).count();
+        if flags != number as usize {
+            return Vec::new();
+        }
+        neighbors
+            .into_iter()
+            .flat_map(|hex| self.reveal(hex))
+            .collect()
+    }
+
+    /// Flagged hex or a mine revealed with spare lives, which is as good as a flag
+    fn is_marked(&self, hex: Hex) -> bool {
+        self.flagged.contains(&hex) || (self.mines.contains(&hex) && !self.covered.contains(&hex))
+    }
+
+    /// Whether [`Self::chord`] on the hex would open something without contradicting any revealed number.
+    /// A misplaced flag shows up either as a number with too many mines marked around it,
+    /// or as a number that needs every unmarked hex around it, including one that the chord would open.
+    pub fn is_safe_chord(&self, hex: Hex) -> bool {
+        if self.config.rules.directional || self.covered.contains(&hex) {
+            return false;
+        }
+        let Some(&number) = self.numbers.get(&hex) else {
+            return false;
+        };
+        let is_marked = |hex: &Hex| self.is_marked(*hex);
+        let is_unknown = |hex: &Hex| self.covered.contains(hex) && !self.flagged.contains(hex);
+        let flags: Vec<_> = self.neighbors(hex).filter(is_marked).collect();
+        let to_open: HashSet<_> = self.neighbors(hex).filter(is_unknown).collect();
+        if flags.len() != number as usize || to_open.is_empty() {
+            return false;
+        }
+        flags
+            .iter()
+            .chain(&to_open)
+            .flat_map(|hex| self.counted_by(*hex))
+            .filter(|hex| !self.covered.contains(hex) && !self.mines.contains(hex))
+            .all(|revealed| {
+                let number = self.numbers.get(&revealed).copied().unwrap_or(0) as usize;
+                let marked = self.neighbors(revealed).filter(is_marked).count();
+                let unknown: Vec<_> = self.neighbors(revealed).filter(is_unknown).collect();
+                let needs_all = marked + unknown.len() == number
+                    && unknown.iter().any(|hex| to_open.contains(hex));
+                marked <= number && !needs_all
+            })
+    }
+
+    /// Reveals a covered unflagged hex, expanding over empty hexes. Returns all newly revealed hexes.
+    pub fn reveal(&mut self, hex: Hex) -> Vec<Hex> {
+        if !self.is_lit(hex) {
+            return Vec::new();
+        }
+        let revealed = self.uncover(hex);
+        self.light_around(&revealed);
+        revealed
+    }
+
+    fn uncover(&mut self, hex: Hex) -> Vec<Hex> {
+        if self.flagged.contains(&hex) || !self.covered.remove(&hex) {
+            return Vec::new();
+        }
+        if self.mines.contains(&hex) {
+            if self.config.rules.zen || self.config.rules.sandbox {
+                return vec![hex];
+            }
+            self.lives = self.lives.saturating_sub(1);
+            if self.lives == 0 {
+                self.exploded = Some(hex);
+            }
+            return vec![hex];
+        }
+        if self.numbers.contains_key(&hex) {
+            return vec![hex];
+        }
+
+        // Flood fill algorithm, adjusted to the MineSweeper game logic
+        let mut visited = HashSet::<Hex>::from([hex]);
+
+        // this buffer stores the current line of expansion of the flood fill
+        let mut buffer = vec![hex];
+        while !buffer.is_empty() {
+            buffer = buffer
+                .into_iter()
+                // take neighbors
+                .flat_map(|hex| self.neighbors(hex))
+                // Contains+Insert in a single insert, which with the following check against
+                // `self.numbers` implements the core game logic - we add adjusted numbers to the `visited`,
+                // but we expand only those neighbor who are not numbers
+                .filter(|neighbor| visited.insert(*neighbor))
+                // don't need to check against `mines` as mines are always surrounded by numbers
+                // so we just stop exporation on numbers
+                .filter(|neighbor| !self.numbers.contains_key(neighbor))
+                .collect();
+        }
+
+        let mut revealed = vec![hex];
+        for hex in visited {
+            if !self.flagged.contains(&hex) && self.covered.remove(&hex) {
+                revealed.push(hex);
+            }
+        }
+        revealed
+    }
+}
+
+/// Neighbors of the cells of a board: [`Rules::neighbors`] offsets laid onto every cell,
+/// or the edges of organic cells within [`Rules::adjacency`] steps
+#[derive(Clone)]
+pub struct Adjacency {
+    neighbors: HashMap<Hex, Vec<Hex>>,
+    counted_by: HashMap<Hex, Vec<Hex>>,
+}
+
+impl Adjacency {
+    pub fn new(cells: &HashSet<Hex>, rules: &Rules) -> Self {
+        let geometry = rules.geometry;
+        let on_board = |hexes: Vec<Hex>| -> Vec<Hex> {
+            hexes
+                .into_iter()
+                .filter(|hex| cells.contains(hex))
+                .collect()
+        };
+        if geometry == Geometry::Organic {
+            // edges are shared, so cells count each other
+            let neighbors: HashMap<_, Vec<_>> = cells
+                .iter()
+                .map(|hex| {
+                    let mut around = geometry.around(*hex, rules.adjacency);
+                    around.retain(|near| near != hex);
+                    (*hex, on_board(around))
+                })
+                .collect();
+            return Self {
+                counted_by: neighbors.clone(),
+                neighbors,
+            };
+        }
+        let offsets = rules.neighbors();
+        let laid = |to: fn(Geometry, Hex, Hex) -> Hex| -> HashMap<Hex, Vec<Hex>> {
+            cells
+                .iter()
+                .map(|hex| {
+                    let hexes = offsets.iter().map(|offset| to(geometry, *hex, *offset));
+                    (*hex, on_board(hexes.collect()))
+                })
+                .collect()
+        };
+        Self {
+            neighbors: laid(Geometry::neighbor),
+            counted_by: laid(Geometry::counted_by),
+        }
+    }
+
+    pub fn neighbors(&self, hex: Hex) -> impl Iterator<Item = Hex> + '_ {
+        self.neighbors.get(&hex).into_iter().flatten().copied()
+    }
+
+    pub fn counted_by(&self, hex: Hex) -> impl Iterator<Item = Hex> + '_ {
+        self.counted_by.get(&hex).into_iter().flatten().copied()
+    }
+}
+
+/// Hexes ordered by their coordinates. Hash sets iterate in a different order on every run, so anything
+/// picked by a seed starts from a sorted list to be reproducible.
+pub fn sorted_hexes(hexes: impl IntoIterator<Item = Hex>) -> Vec<Hex> {
+    let mut hexes: Vec<_> = hexes.into_iter().collect();
+    hexes.sort_unstable_by_key(|hex| (hex.x, hex.y));
+    hexes
+}
+
+/// Amount of neighbor mines of every safe hex, only for hexes with at least one
+pub fn count_numbers(
+    cells: &HashSet<Hex>,
+    mines: &HashSet<Hex>,
+    adjacency: &Adjacency,
+) -> HashMap<Hex, u8> {
+    // Count neighbor mines simply iterating over all mines and increment counter for each hex that counts it
+    mines
+        .iter()
+        .flat_map(|hex| adjacency.counted_by(*hex))
+        // we don't want to draw number over the mine
+        .filter(|hex| !mines.contains(hex))
+        .filter(|hex| cells.contains(hex))
+        .fold(HashMap::with_capacity(cells.len() / 2), |mut acc, hex| {
+            *acc.entry(hex).or_insert(0) += 1;
+            acc
+        })
+}
+
+/// Serializes [`Hex`] as `[x, y]`, for use with `#[serde(with = "hex_serde")]`
+pub mod hex_serde {
+    use hexx::Hex;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(hex: &Hex, serializer: S) -> Result<S::Ok, S::Error> {
+        [hex.x, hex.y].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Hex, D::Error> {
+        let [x, y] = <[i32; 2]>::deserialize(deserializer)?;
+        Ok(Hex::new(x, y))
+    }
+}
@@ -0,0 +1,169 @@
+//! Human-readable text format of hand-made boards, for bug reports and boards written in a text editor.
+//!
+//! Every line is a row of hexes from the top of the board to the bottom, and a hex takes two columns,
+//! so rows are shifted by one column against each other just like on screen:
+//!
+//! ```text
+//! # comment lines are skipped
+//!  . . *
+//! . o . .
+//!  * . .
+//! ```
+//!
+//! `.` is a covered hex, `*` is a mine, `o` is a revealed hex and spaces are voids.
+//! Boards are centered on import, so the indentation only has to be consistent.
+
+use bevy::utils::HashSet;
+use hexx::Hex;
+
+use crate::board::Layout;
+
+const CELL: char = '.';
+const MINE: char = '*';
+const REVEALED: char = 'o';
+const VOID: char = ' ';
+const COMMENT: char = '#';
+
+pub fn encode_board_text(layout: &Layout) -> String {
+    // doubled coordinates, where the column of a hex is `2 * x + y`
+    let column = |hex: &Hex| 2 * hex.x + hex.y;
+    let (Some(min_column), Some(min_row), Some(max_row)) = (
+        layout.cells.iter().map(column).min(),
+        layout.cells.iter().map(|hex| hex.y).min(),
+        layout.cells.iter().map(|hex| hex.y).max(),
+    ) else {
+        return String::new();
+    };
+
+    let mut text = String::new();
+    // y axis points up on screen, so the top row has the largest `y`
+    for y in (min_row..=max_row).rev() {
+        let mut hexes: Vec<_> = layout.cells.iter().filter(|hex| hex.y == y).collect();
+        hexes.sort_unstable_by_key(|hex| column(hex));
+        let mut line = String::new();
+        for hex in hexes {
+            let offset = (column(hex) - min_column) as usize;
+            line.extend(std::iter::repeat(VOID).take(offset - line.len()));
+            line.push(if layout.mines.contains(hex) {
+                MINE
+            } else if layout.revealed.contains(hex) {
+                REVEALED
+            } else {
+                CELL
+            });
+        }
+        text += &line;
+        text.push('\n');
+    }
+    text
+}
+
+pub fn decode_board_text(text: &str) -> Result<Layout, String> {
+    let rows = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with(COMMENT))
+        .skip_while(|line| line.trim().is_empty());
+
+    let mut layout = Layout::default();
+    // all hexes have the same parity of `column + row`, the first hex sets it
+    let mut parity = None;
+    for (row, line) in rows.enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            if c == VOID {
+                continue;
+            }
+            let position = || format!("line {} column {}", row + 1, column + 1);
+            if !matches!(c, CELL | MINE | REVEALED) {
+                return Err(format!("unexpected character '{c}' at {}", position()));
+            }
+            let (row, column) = (row as i32, column as i32);
+            let expected = *parity.get_or_insert((row + column) % 2);
+            if (row + column) % 2 != expected {
+                return Err(format!(
+                    "hex at {} is not aligned with the others",
+                    position()
+                ));
+            }
+            // rows go down, while `y` goes up
+            let y = -row;
+            let hex = Hex::new((column - expected - y) / 2, y);
+            layout.cells.insert(hex);
+            match c {
+                MINE => layout.mines.insert(hex),
+                REVEALED => layout.revealed.insert(hex),
+                _ => false,
+            };
+        }
+    }
+    if layout.cells.is_empty() {
+        return Err("board has no hexes".to_string());
+    }
+    Ok(centered(layout))
+}
+
+/// Moves the middle of the bounding box to the origin
+fn centered(layout: Layout) -> Layout {
+    let column = |hex: &Hex| 2 * hex.x + hex.y;
+    let bounds = |values: Vec<i32>| {
+        let min = values.iter().min().copied().unwrap_or(0);
+        let max = values.iter().max().copied().unwrap_or(0);
+        (min + max).div_euclid(2)
+    };
+    let y = bounds(layout.cells.iter().map(|hex| hex.y).collect());
+    let middle_column = bounds(layout.cells.iter().map(column).collect());
+    // the middle has to be a hex, so it's rounded to the column of the same parity as the row
+    let x = (middle_column - y).div_euclid(2);
+    let shift = |hexes: &HashSet<Hex>| hexes.iter().map(|hex| *hex - Hex::new(x, y)).collect();
+    Layout {
+        cells: shift(&layout.cells),
+        mines: shift(&layout.mines),
+        revealed: shift(&layout.revealed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::shapes;
+
+    use super::*;
+
+    #[test]
+    fn boards_survive_a_round_trip() {
+        let layout = Layout {
+            cells: shapes::hexagon(Hex::ZERO, 2).collect(),
+            mines: [Hex::new(1, 0), Hex::new(-2, 2)].into_iter().collect(),
+            revealed: [Hex::ZERO, Hex::new(0, -1)].into_iter().collect(),
+        };
+        let text = encode_board_text(&layout);
+        assert_eq!(decode_board_text(&text), Ok(layout));
+    }
+
+    #[test]
+    fn comments_and_indentation_are_skipped() {
+        let text = "# two hexes\n\n    . *\n";
+        let layout = decode_board_text(text).expect("valid board");
+        assert_eq!(layout.cells.len(), 2);
+        assert_eq!(layout.mines.len(), 1);
+    }
+
+    #[test]
+    fn ragged_rows_are_rejected() {
+        // the second row has to be shifted by one column
+        assert!(decode_board_text(" . .\n. . .\n . .\n").is_ok());
+        assert!(decode_board_text(" . .\n . .\n").is_err());
+        // neighbors in a row are two columns apart
+        assert!(decode_board_text("..\n").is_err());
+    }
+
+    #[test]
+    fn unknown_glyphs_are_rejected() {
+        let err = decode_board_text(". x .\n").expect_err("unknown glyph");
+        assert!(err.contains("'x'"), "{err}");
+    }
+
+    #[test]
+    fn empty_boards_are_rejected() {
+        assert!(decode_board_text("").is_err());
+        assert!(decode_board_text("# only a comment\n\n   \n").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! Focus pull on a big opening: when the first reveal opens a large area, the camera briefly frames it
+//! and then goes back to the whole board. Board input waits until it's done,
+//! and there is no pull at all with [`Settings::reduced_motion`].
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    board::Board,
+    game::{BoardProgress, Game, GameSet},
+    settings::Settings,
+    BoardView,
+};
+
+/// Hexes the first reveal has to uncover to be worth a pull
+const MIN_OPENING: usize = 20;
+/// Seconds to move to the opening, to stay there, and to move back
+const PULL_IN: f32 = 0.4;
+const HOLD: f32 = 0.5;
+const PULL_OUT: f32 = 0.4;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (start_focus_pull, animate_focus_pull)
+                .chain()
+                .after(GameSet::Actions),
+        );
+    }
+}
+
+/// Camera is framing the opening, exists only while it does
+#[derive(Resource)]
+pub struct FocusPull {
+    /// View of the whole board, restored at the end
+    from: BoardView,
+    to: BoardView,
+    elapsed: f32,
+}
+
+fn start_focus_pull(
+    mut commands: Commands,
+    mut progress: EventReader<BoardProgress>,
+    settings: Res<Settings>,
+    board: Res<Board>,
+    game: Res<Game>,
+    view: Res<BoardView>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let opening = progress
+        .iter()
+        .any(|progress| progress.uncovered >= MIN_OPENING && game.replay.steps.len() == 1);
+    if !opening || settings.reduced_motion {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let revealed = board
+        .cells
+        .iter()
+        .filter(|hex| !board.covered.contains(*hex))
+        .copied();
+    let to = BoardView::fit(revealed, board.config.rules.geometry, window, &settings);
+    // the opening is most of the board anyway
+    if to.scale >= view.scale * 0.9 {
+        return;
+    }
+    commands.insert_resource(FocusPull {
+        from: *view,
+        to,
+        elapsed: 0.0,
+    });
+}
+
+fn animate_focus_pull(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Res<Game>,
+    pull: Option<ResMut<FocusPull>>,
+    mut view: ResMut<BoardView>,
+) {
+    let Some(mut pull) = pull else {
+        return;
+    };
+    // a new game has its own view already
+    if game.replay.is_empty() {
+        commands.remove_resource::<FocusPull>();
+        return;
+    }
+    pull.elapsed += time.delta_seconds();
+    let t = if pull.elapsed < PULL_IN {
+        pull.elapsed / PULL_IN
+    } else if pull.elapsed < PULL_IN + HOLD {
+        1.0
+    } else if pull.elapsed < PULL_IN + HOLD + PULL_OUT {
+        1.0 - (pull.elapsed - PULL_IN - HOLD) / PULL_OUT
+    } else {
+        *view = pull.from;
+        commands.remove_resource::<FocusPull>();
+        return;
+    };
+    // smoothstep, so the camera eases in and out
+    let t = t * t * (3.0 - 2.0 * t);
+    *view = BoardView {
+        center: pull.from.center.lerp(pull.to.center, t),
+        scale: pull.from.scale + (pull.to.scale - pull.from.scale) * t,
+        rotation: pull.to.rotation,
+        flip: pull.to.flip,
+    };
+}
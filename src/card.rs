@@ -0,0 +1,305 @@
+//! Results card: a small PNG picture of a finished game to share, with the silhouette of the board,
+//! the time, 3BV, efficiency, date and the board code.
+//!
+//! The card is laid out as UI above everything else, and once it's drawn, its part of a screenshot
+//! of the window is saved. The board silhouette is a picture drawn on the CPU, as UI nodes can't be round.
+
+use std::sync::{mpsc, Mutex};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        view::screenshot::ScreenshotManager,
+    },
+    text::BreakLineOn,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    any_events,
+    board::Board,
+    clock,
+    game::{Game, Outcome},
+    share::encode_board_code,
+    stats::efficiency,
+    storage,
+    title::TITLE,
+};
+
+const WIDTH: f32 = 480.0;
+const PADDING: f32 = 20.0;
+/// Size of the area the board silhouette is fitted into
+const BOARD_SIZE: UVec2 = UVec2::new(440, 300);
+const BACKGROUND: Color = Color::rgb(0.12, 0.12, 0.14);
+const TEXT: Color = Color::rgb(0.9, 0.9, 0.9);
+const REVEALED: Color = Color::rgb(0.75, 0.75, 0.72);
+const COVERED: Color = Color::rgb(0.35, 0.38, 0.45);
+const FLAG: Color = Color::rgb(0.9, 0.25, 0.2);
+const MINE: Color = Color::rgb(1.0, 0.6, 0.1);
+/// Window surfaces are 8-bit RGBA or BGRA
+const BYTES_PER_PIXEL: u32 = 4;
+
+pub struct CardPlugin;
+
+impl Plugin for CardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportCard>()
+            .add_event::<CardExported>()
+            .add_systems(Update, (spawn_card, capture_card));
+    }
+}
+
+/// Request to save the card of the game that has just ended
+#[derive(Event)]
+pub struct ExportCard;
+
+/// Where the card was saved, or why it wasn't
+#[derive(Event)]
+pub struct CardExported(pub Result<String, String>);
+
+#[derive(Component)]
+struct Card {
+    path: String,
+    /// Result of saving the screenshot, once it's requested
+    saved: Option<Mutex<mpsc::Receiver<Result<(), String>>>>,
+}
+
+fn spawn_card(
+    mut commands: Commands,
+    mut requests: EventReader<ExportCard>,
+    board: Res<Board>,
+    game: Res<Game>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !any_events(&mut requests) {
+        return;
+    }
+    let time = format!("{:.1}s", game.elapsed.as_secs_f32());
+    let headline = match (game.outcome, board.config.rules.zen) {
+        (Some(Outcome::Won), true) => "Cleared".to_string(),
+        (Some(Outcome::Won), false) => format!("Cleared in {time}"),
+        _ => format!(
+            "Boom at {:.0}% revealed after {time}",
+            board.progress() * 100.0
+        ),
+    };
+    let mut details = format!(
+        "3BV {}   Efficiency {:.0}%\n{}",
+        board.min_clicks(),
+        efficiency(&board, &game) * 100.0,
+        clock::format_day(clock::today()),
+    );
+    if let Ok(code) = encode_board_code(&board) {
+        details += &format!("\nCode {code}");
+    }
+    let silhouette = images.add(draw_board(&board));
+
+    let text = |value: String, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size,
+                color: TEXT,
+                ..default()
+            },
+        )
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(0.0),
+                    top: Val::Px(0.0),
+                    width: Val::Px(WIDTH),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(PADDING)),
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                background_color: BACKGROUND.into(),
+                // above the dialogs, which the card is exported from
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            Card {
+                path: storage::export_path(&format!("card-{}.png", clock::unix_time_millis())),
+                saved: None,
+            },
+        ))
+        .with_children(|card| {
+            card.spawn(text(TITLE.to_string(), 30.0));
+            card.spawn(ImageBundle {
+                style: Style {
+                    width: Val::Px(BOARD_SIZE.x as f32),
+                    height: Val::Px(BOARD_SIZE.y as f32),
+                    margin: UiRect::vertical(Val::Px(PADDING / 2.0)),
+                    ..default()
+                },
+                image: UiImage::new(silhouette),
+                ..default()
+            });
+            card.spawn(text(headline, 28.0));
+            // long codes of hand-made boards have no spaces to wrap at
+            let mut details = text(details, 18.0);
+            details.text.linebreak_behavior = BreakLineOn::AnyCharacter;
+            card.spawn(details);
+        });
+}
+
+/// Takes the screenshot once the card is laid out, and removes the card once it's saved
+fn capture_card(
+    mut commands: Commands,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    mut cards: Query<(Entity, &mut Card, &Node, &GlobalTransform)>,
+    mut exported: EventWriter<CardExported>,
+) {
+    for (entity, mut card, node, transform) in &mut cards {
+        let result = match &card.saved {
+            Some(saved) => match saved.lock().map(|saved| saved.try_recv()) {
+                Ok(Err(mpsc::TryRecvError::Empty)) => continue,
+                Ok(Ok(result)) => result.map(|()| card.path.clone()),
+                _ => Err("the screenshot was never taken".to_string()),
+            },
+            None if node.size() == Vec2::ZERO => continue,
+            None => {
+                let Ok((window, window_info)) = windows.get_single() else {
+                    continue;
+                };
+                let scale = window_info.scale_factor() as f32;
+                let min = (transform.translation().truncate() - node.size() / 2.0) * scale;
+                let size = node.size() * scale;
+                let (sender, receiver) = mpsc::channel();
+                let path = card.path.clone();
+                let requested = screenshots.take_screenshot(window, move |image| {
+                    let card = crop(&image, min.round().as_uvec2(), size.round().as_uvec2());
+                    let _ = sender.send(storage::write_png(&path, card));
+                });
+                match requested {
+                    Ok(()) => {
+                        card.saved = Some(Mutex::new(receiver));
+                        continue;
+                    }
+                    Err(err) => Err(err.to_string()),
+                }
+            }
+        };
+        exported.send(CardExported(result));
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Part of a screenshot, in physical pixels
+fn crop(image: &Image, min: UVec2, size: UVec2) -> Image {
+    let full = image.texture_descriptor.size;
+    let max = (min + size).min(UVec2::new(full.width, full.height));
+    let min = min.min(max);
+    let row = |y: u32| {
+        let start = ((y * full.width + min.x) * BYTES_PER_PIXEL) as usize;
+        let end = start + ((max.x - min.x) * BYTES_PER_PIXEL) as usize;
+        image.data[start..end].iter().copied()
+    };
+    Image::new(
+        Extent3d {
+            width: max.x - min.x,
+            height: max.y - min.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        (min.y..max.y).flat_map(row).collect(),
+        image.texture_descriptor.format,
+    )
+}
+
+/// Every cell as a dot in the color of its state, fitted into [`BOARD_SIZE`]
+fn draw_board(board: &Board) -> Image {
+    let mut canvas = Canvas::new(BOARD_SIZE.x, BOARD_SIZE.y);
+    let geometry = board.config.rules.geometry;
+    let half_cell = geometry.half_extent();
+    let (min, max) = board.cells.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), hex| {
+            let pos = geometry.to_world(*hex);
+            (min.min(pos - half_cell), max.max(pos + half_cell))
+        },
+    );
+    if min.x > max.x {
+        return canvas.into_image();
+    }
+    let area = BOARD_SIZE.as_vec2();
+    let size = (max - min).max(Vec2::ONE);
+    let scale = (area.x / size.x).min(area.y / size.y);
+    // centered in the area, with the y axis pointing down like image rows
+    let offset = (area - size * scale) / 2.0;
+    let radius = half_cell.min_element() * scale * 0.9;
+    for hex in &board.cells {
+        let pos = geometry.to_world(*hex);
+        let center = offset + Vec2::new(pos.x - min.x, max.y - pos.y) * scale;
+        let color = if !board.covered.contains(hex) {
+            if board.mines.contains(hex) {
+                MINE
+            } else {
+                REVEALED
+            }
+        } else if board.flagged.contains(hex) {
+            FLAG
+        } else {
+            COVERED
+        };
+        canvas.disc(center, radius, color);
+    }
+    canvas.into_image()
+}
+
+/// RGBA pixels of the board silhouette
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        let pixels = BACKGROUND.as_rgba_u8().repeat((width * height) as usize);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn set(&mut self, x: u32, y: u32, color: Color) {
+        if x < self.width && y < self.height {
+            let index = ((y * self.width + x) * 4) as usize;
+            self.pixels[index..index + 4].copy_from_slice(&color.as_rgba_u8());
+        }
+    }
+
+    fn disc(&mut self, center: Vec2, radius: f32, color: Color) {
+        let radius = radius.max(0.5);
+        let (from, to) = (center - radius, center + radius);
+        for y in from.y.floor().max(0.0) as u32..=to.y.ceil() as u32 {
+            for x in from.x.floor().max(0.0) as u32..=to.x.ceil() as u32 {
+                let pixel = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                if pixel.distance(center) <= radius {
+                    self.set(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn into_image(self) -> Image {
+        Image::new(
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.pixels,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+}
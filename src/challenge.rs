@@ -0,0 +1,86 @@
+//! Weekly challenge: a fixed board with rule modifiers that rotates every week.
+//!
+//! Challenges are plain data in [`CHALLENGES`], so a new one is just a new entry as long as
+//! it's a combination of existing [`Rules`].
+
+use crate::{
+    board::{BoardConfig, Rules},
+    game::NewGame,
+    rng::Rng,
+};
+
+/// Mixed into the week number, so weekly seeds don't collide with seeds of other modes
+const WEEKLY_SALT: u64 = 0x7765_656b_6c79;
+
+pub struct Challenge {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub config: BoardConfig,
+}
+
+pub const CHALLENGES: &[Challenge] = &[
+    Challenge {
+        name: "No flags",
+        description: "Flags are disabled, keep the mines in your head",
+        config: BoardConfig {
+            radius: 12,
+            mine_density: 0.14,
+            rules: Rules {
+                flags: false,
+                ..Rules::CLASSIC
+            },
+        },
+    },
+    Challenge {
+        name: "Wide numbers",
+        description: "Numbers count mines up to two hexes away",
+        config: BoardConfig {
+            radius: 14,
+            mine_density: 0.14,
+            rules: Rules {
+                adjacency: 2,
+                ..Rules::CLASSIC
+            },
+        },
+    },
+    Challenge {
+        name: "Minefield",
+        description: "Dense mines, but one of them can be survived",
+        config: BoardConfig {
+            radius: 14,
+            mine_density: 0.22,
+            rules: Rules {
+                lives: 2,
+                ..Rules::CLASSIC
+            },
+        },
+    },
+    Challenge {
+        name: "Fog of war",
+        description: "Numbers are visible only around the cursor",
+        config: BoardConfig {
+            radius: 14,
+            mine_density: 0.15,
+            rules: Rules {
+                fog: Some(3),
+                ..Rules::CLASSIC
+            },
+        },
+    },
+];
+
+/// Weeks since the UNIX epoch for the given day
+pub fn week(day: u32) -> u32 {
+    day / 7
+}
+
+/// Challenge of the given week and its board
+pub fn weekly_challenge(week: u32) -> (&'static Challenge, NewGame) {
+    let challenge = &CHALLENGES[week as usize % CHALLENGES.len()];
+    let seed = Rng::new(WEEKLY_SALT ^ week as u64).next_u64();
+    let new_game = NewGame {
+        config: challenge.config,
+        seed,
+    };
+    (challenge, new_game)
+}
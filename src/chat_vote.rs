@@ -0,0 +1,281 @@
+//! Chat vote: viewers of a Twitch stream pick the next hex to reveal. A few covered hexes are numbered
+//! on the board, chat votes with messages like `2` or `!2`, and when the timer runs out the hex with
+//! the most votes is revealed. Ties go to the lower number, and a round without votes starts over.
+//!
+//! Candidates are picked only from what the player can see, so they give away nothing about the mines.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::Hex;
+
+use crate::{
+    any_events,
+    board::{sorted_hexes, Board},
+    game::{
+        ActionRequest, Authority, CellsChanged, Game, GameAction, GameSet, NewCustomGame, NewGame,
+    },
+    net::chat::{ChatClient, ChatEvent},
+    rng::Rng,
+    settings::Settings,
+    solver, AppState, HexGrid,
+};
+
+/// Choices for [`ChatVoteSettings::seconds`](crate::settings::ChatVoteSettings::seconds)
+pub const VOTE_SECONDS: [u32; 4] = [10, 20, 30, 60];
+const MAX_CANDIDATES: usize = 5;
+const CANDIDATE_COLOR: Color = Color::rgba(0.6, 0.4, 1.0, 0.45);
+const LABEL_COLOR: Color = Color::WHITE;
+/// Over the ghost and under the sprites and the cursor highlight
+const CANDIDATE_Z: f32 = 0.6;
+/// Over the numbers of the hexes
+const LABEL_Z: f32 = 3.0;
+
+pub struct ChatVotePlugin;
+
+impl Plugin for ChatVotePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup).add_systems(
+            Update,
+            (
+                (read_chat, finish_round)
+                    .chain()
+                    .in_set(GameSet::Input)
+                    .run_if(resource_exists::<ChatVote>()),
+                (pick_candidates, draw_candidates)
+                    .chain()
+                    .after(GameSet::Actions),
+            ),
+        );
+    }
+}
+
+pub enum ChatStatus {
+    Connecting,
+    Connected,
+    Disconnected(String),
+}
+
+/// Ongoing chat vote. The mode is active as long as the resource exists.
+#[derive(Resource)]
+pub struct ChatVote {
+    client: ChatClient,
+    pub channel: String,
+    pub status: ChatStatus,
+    /// Numbered in chat from 1
+    candidates: Vec<Hex>,
+    /// Latest vote of every viewer, as an index into the candidates
+    votes: HashMap<String, usize>,
+    /// Time left in the current round
+    pub left: Duration,
+}
+
+impl ChatVote {
+    pub fn start(channel: &str) -> Self {
+        Self {
+            client: ChatClient::connect(channel),
+            channel: channel.trim().trim_start_matches('#').to_string(),
+            status: ChatStatus::Connecting,
+            candidates: Vec::new(),
+            votes: HashMap::new(),
+            left: Duration::ZERO,
+        }
+    }
+
+    /// Votes for every candidate, in the order of the candidates
+    pub fn tally(&self) -> Vec<u32> {
+        let mut tally = vec![0; self.candidates.len()];
+        for index in self.votes.values() {
+            tally[*index] += 1;
+        }
+        tally
+    }
+
+    pub fn votes_count(&self) -> usize {
+        self.votes.len()
+    }
+
+    /// Candidate with the most votes, if anyone voted
+    fn winner(&self) -> Option<Hex> {
+        let tally = self.tally();
+        let (index, votes) = tally
+            .iter()
+            .enumerate()
+            // `max_by_key` picks the last of equal ones, so the order is reversed for ties to go to the first
+            .rev()
+            .max_by_key(|(_, votes)| **votes)?;
+        (*votes > 0).then(|| self.candidates[index])
+    }
+}
+
+/// Vote for the candidate number `1..=candidates` in a message like "2" or "!2"
+fn parse_vote(text: &str, candidates: usize) -> Option<usize> {
+    let number: usize = text.trim().trim_start_matches('!').parse().ok()?;
+    (1..=candidates).contains(&number).then(|| number - 1)
+}
+
+#[derive(Resource)]
+struct CandidateMaterial(Handle<ColorMaterial>);
+
+/// Marker of the numbered candidates on the board
+#[derive(Component)]
+struct CandidateMark;
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands.insert_resource(CandidateMaterial(materials.add(CANDIDATE_COLOR.into())));
+}
+
+fn read_chat(mut vote: ResMut<ChatVote>) {
+    let events = vote.client.poll();
+    // avoid triggering change detection every frame
+    if events.is_empty() {
+        return;
+    }
+    for event in events {
+        match event {
+            ChatEvent::Connected => vote.status = ChatStatus::Connected,
+            ChatEvent::Disconnected(reason) => {
+                warn!("Chat of {} disconnected: {reason}", vote.channel);
+                vote.status = ChatStatus::Disconnected(reason);
+            }
+            ChatEvent::Message { user, text } => {
+                if let Some(index) = parse_vote(&text, vote.candidates.len()) {
+                    vote.votes.insert(user, index);
+                }
+            }
+        }
+    }
+}
+
+/// Counts the round down while the game is on, and reveals the winner when it's over
+fn finish_round(
+    time: Res<Time>,
+    state: Res<State<AppState>>,
+    authority: Res<Authority>,
+    settings: Res<Settings>,
+    game: Res<Game>,
+    mut vote: ResMut<ChatVote>,
+    mut requests: EventWriter<ActionRequest>,
+) {
+    let running = *state.get() == AppState::Playing
+        && *authority != Authority::Replay
+        && game.outcome.is_none();
+    if !running || vote.candidates.is_empty() {
+        return;
+    }
+    vote.left = vote.left.saturating_sub(time.delta());
+    if !vote.left.is_zero() {
+        return;
+    }
+    vote.left = Duration::from_secs(settings.chat_vote.seconds as u64);
+    if let Some(hex) = vote.winner() {
+        // new candidates are picked once the reveal is applied
+        vote.votes.clear();
+        requests.send(ActionRequest(GameAction::Reveal(hex)));
+    }
+}
+
+/// Picks new candidates whenever the board changes, which also starts a new round
+#[allow(clippy::too_many_arguments)]
+fn pick_candidates(
+    mut cells_changed: EventReader<CellsChanged>,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    settings: Res<Settings>,
+    board: Res<Board>,
+    game: Res<Game>,
+    vote: Option<ResMut<ChatVote>>,
+    mut picked: Local<bool>,
+) {
+    let changed =
+        any_events(&mut cells_changed) | any_events(&mut new_games) | any_events(&mut custom_games);
+    let Some(mut vote) = vote else {
+        *picked = false;
+        return;
+    };
+    // a vote that has just started picks its first candidates right away
+    if !changed && *picked {
+        return;
+    }
+    *picked = true;
+
+    // the same as the player knows: covered hexes next to revealed ones, minus the certain mines
+    let forced = solver::forced_mines(&board);
+    let open = sorted_hexes(
+        board
+            .covered
+            .iter()
+            .filter(|hex| !board.flagged.contains(*hex) && !forced.contains(*hex))
+            .copied(),
+    );
+    let frontier: Vec<Hex> = open
+        .iter()
+        .filter(|hex| {
+            board
+                .counted_by(**hex)
+                .any(|neighbor| !board.covered.contains(&neighbor))
+        })
+        .copied()
+        .collect();
+    let mut candidates = if frontier.is_empty() { open } else { frontier };
+    let mut rng = Rng::new(board.seed ^ game.replay.steps.len() as u64);
+    rng.shuffle(&mut candidates);
+    candidates.truncate(MAX_CANDIDATES);
+
+    vote.candidates = candidates;
+    vote.votes.clear();
+    vote.left = Duration::from_secs(settings.chat_vote.seconds as u64);
+}
+
+/// Tints the candidates and labels them with their numbers and votes
+fn draw_candidates(
+    mut commands: Commands,
+    vote: Option<Res<ChatVote>>,
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    material: Res<CandidateMaterial>,
+    marks: Query<Entity, With<CandidateMark>>,
+    mut drawn: Local<Vec<(Hex, u32)>>,
+) {
+    // the timer changes the vote every frame, so only the candidates and their votes are compared
+    let expected: Vec<(Hex, u32)> = match &vote {
+        Some(vote) => vote.candidates.iter().copied().zip(vote.tally()).collect(),
+        None => Vec::new(),
+    };
+    if *drawn == expected {
+        return;
+    }
+    for entity in &marks {
+        commands.entity(entity).despawn_recursive();
+    }
+    let geometry = board.config.rules.geometry;
+    for (number, (hex, votes)) in expected.iter().enumerate() {
+        let pos = geometry.to_world(*hex);
+        commands
+            .spawn((
+                ColorMesh2dBundle {
+                    mesh: grid.mesh_at(geometry, *hex).into(),
+                    material: material.0.clone(),
+                    transform: Transform::from_translation(pos.extend(CANDIDATE_Z)),
+                    ..default()
+                },
+                CandidateMark,
+            ))
+            .with_children(|mark| {
+                mark.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        format!("{}\n{votes}", number + 1),
+                        TextStyle {
+                            font_size: 14.0,
+                            color: LABEL_COLOR,
+                            ..default()
+                        },
+                    )
+                    .with_alignment(TextAlignment::Center),
+                    transform: Transform::from_xyz(0.0, 0.0, LABEL_Z - CANDIDATE_Z),
+                    ..default()
+                });
+            });
+    }
+    *drawn = expected;
+}
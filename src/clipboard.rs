@@ -0,0 +1,26 @@
+//! System clipboard. The browser clipboard API requires unstable `web-sys` features, so on the web
+//! codes are only shown on screen for manual copying.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn copy(text: &str) -> Result<(), String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn paste() -> Result<String, String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn copy(_text: &str) -> Result<(), String> {
+    Err("clipboard isn't available in the browser".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn paste() -> Result<String, String> {
+    Err("clipboard isn't available in the browser".to_string())
+}
@@ -0,0 +1,56 @@
+//! Wall-clock time, which `std::time::SystemTime` doesn't provide in the browser
+
+pub const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Milliseconds since the UNIX epoch
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_time_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// Milliseconds since the UNIX epoch
+#[cfg(target_arch = "wasm32")]
+pub fn unix_time_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Difference between the local time and UTC in minutes
+#[cfg(not(target_arch = "wasm32"))]
+fn utc_offset_minutes() -> i64 {
+    chrono::Local::now().offset().local_minus_utc() as i64 / 60
+}
+
+/// Difference between the local time and UTC in minutes
+#[cfg(target_arch = "wasm32")]
+fn utc_offset_minutes() -> i64 {
+    // the browser gives it with the opposite sign
+    -(js_sys::Date::new_0().get_timezone_offset() as i64)
+}
+
+/// Hours since the local midnight, with a fraction for minutes and seconds
+pub fn local_hour() -> f32 {
+    let local = unix_time_millis() as i64 + utc_offset_minutes() * 60 * 1000;
+    local.rem_euclid(MILLIS_PER_DAY as i64) as f32 / (60.0 * 60.0 * 1000.0)
+}
+
+/// Days since the UNIX epoch in UTC, so every player shares the same "today"
+pub fn today() -> u32 {
+    (unix_time_millis() / MILLIS_PER_DAY) as u32
+}
+
+/// Calendar date of the day since the UNIX epoch as `YYYY-MM-DD`
+pub fn format_day(day: u32) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{y:04}-{m:02}-{d:02}")
+}
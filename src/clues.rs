@@ -0,0 +1,35 @@
+//! Directional clues variant: instead of a number, every revealed safe hex shows an arrow toward
+//! the nearest mine, so mines are found by triangulation rather than counting
+
+use bevy::utils::{HashMap, HashSet};
+use hexx::Hex;
+
+/// Neighbor offsets that clues point along, a clue is an index into it
+pub fn directions() -> Vec<Hex> {
+    Hex::ZERO.ring(1).collect()
+}
+
+/// Direction of the first step from `from` towards `to`, the first of the directions on ties.
+/// Hexes with the same direction from a hex form its sector.
+pub fn direction(from: Hex, to: Hex) -> u8 {
+    directions()
+        .into_iter()
+        .enumerate()
+        .min_by_key(|(i, offset)| ((from + *offset).unsigned_distance_to(to), *i))
+        .map_or(0, |(i, _)| i as u8)
+}
+
+/// Clue of every safe hex under [`Rules::directional`](crate::board::Rules::directional)
+pub fn compute(cells: &HashSet<Hex>, mines: &HashSet<Hex>) -> HashMap<Hex, u8> {
+    cells
+        .iter()
+        .filter(|hex| !mines.contains(*hex))
+        .filter_map(|hex| {
+            // ties are broken by coordinates, so every board gets the same clues
+            let nearest = mines
+                .iter()
+                .min_by_key(|mine| (hex.unsigned_distance_to(**mine), mine.x, mine.y))?;
+            Some((*hex, direction(*hex, *nearest)))
+        })
+        .collect()
+}
@@ -0,0 +1,41 @@
+//! Colored mines variant: mines are either blue or red, numbers count both colors separately,
+//! and flags have a color too, which has to match the mine to count as correct
+
+use bevy::{
+    prelude::Color,
+    utils::{HashMap, HashSet},
+};
+use hexx::Hex;
+
+use crate::{
+    board::{count_numbers, sorted_hexes, Adjacency},
+    rng::Rng,
+};
+
+/// Part of all mines that are red
+const RED_FRACTION: f32 = 0.5;
+/// Colors use their own random sequence, so they don't change mines of the same seed
+const COLOR_SEED_SALT: u64 = 0xC010_4ED5;
+pub const BLUE: Color = Color::rgb(0.15, 0.35, 0.9);
+pub const RED: Color = Color::rgb(0.85, 0.1, 0.1);
+
+/// Red mines under [`Rules::colored`](crate::board::Rules::colored), the rest are blue
+pub fn pick_red(mines: &HashSet<Hex>, seed: u64) -> HashSet<Hex> {
+    let mut mines = sorted_hexes(mines.iter().copied());
+    Rng::new(seed ^ COLOR_SEED_SALT).shuffle(&mut mines);
+    let count = (mines.len() as f32 * RED_FRACTION).round() as usize;
+    mines.into_iter().take(count).collect()
+}
+
+/// Red mines counted by every number in `numbers`, only for numbers with at least one
+pub fn count_red(
+    cells: &HashSet<Hex>,
+    numbers: &HashMap<Hex, u8>,
+    red_mines: &HashSet<Hex>,
+    adjacency: &Adjacency,
+) -> HashMap<Hex, u8> {
+    let mut red = count_numbers(cells, red_mines, adjacency);
+    // blue mines are not in `red_mines`, so they got counts too
+    red.retain(|hex, _| numbers.contains_key(hex));
+    red
+}
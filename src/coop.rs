@@ -0,0 +1,528 @@
+//! Cooperative mode, where several players reveal and flag the same board in real time.
+//!
+//! The host owns the authoritative board. Clients send their [`ActionRequest`]s to the host, which
+//! applies them as local ones and broadcasts every applied action. Clients get the content of the board
+//! from the host instead of generating it with rule scripts of their own, so replaying the same actions
+//! in the same order keeps all boards in sync.
+//! A client back after a dropped connection gets the whole game again, like a player who joins late.
+
+use std::io;
+
+use bevy::{prelude::*, sprite::Mesh2dHandle, utils::HashMap};
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    any_events,
+    board::{Board, BoardConfig},
+    game::{ActionRequest, Authority, Game, GameAction, GameSet, NewCustomGame, NewGame},
+    net::transport::{PeerId, Transport, TransportEvent, HOST},
+    puzzle::Puzzle,
+    replay::ReplayStep,
+    share, CursorPos, HexGrid,
+};
+
+pub const DEFAULT_PORT: u16 = 7878;
+/// Largest board a client can start for everyone
+const MAX_RADIUS: u32 = 64;
+
+pub const PLAYER_COLORS: [Color; 6] = [
+    Color::rgb(0.95, 0.35, 0.35),
+    Color::rgb(0.35, 0.6, 0.95),
+    Color::rgb(0.4, 0.85, 0.4),
+    Color::rgb(0.95, 0.8, 0.3),
+    Color::rgb(0.75, 0.45, 0.95),
+    Color::rgb(0.35, 0.85, 0.85),
+];
+
+pub fn player_color(player: PeerId) -> Color {
+    PLAYER_COLORS[player as usize % PLAYER_COLORS.len()]
+}
+
+pub struct CoopPlugin;
+
+impl Plugin for CoopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(CoopSessionPlugin).add_systems(
+            Update,
+            (
+                send_cursor
+                    .after(GameSet::Actions)
+                    .run_if(resource_exists::<CoopSession>()),
+                draw_cursors,
+            ),
+        );
+    }
+}
+
+/// Messages of the session without the local player, which is all the dedicated server needs
+pub struct CoopSessionPlugin;
+
+impl Plugin for CoopSessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemoteCursors>().add_systems(
+            Update,
+            (
+                (receive_messages, send_requests)
+                    .chain()
+                    .in_set(GameSet::Route)
+                    .run_if(resource_exists::<CoopSession>()),
+                broadcast_applied
+                    .after(GameSet::Actions)
+                    .run_if(resource_exists::<CoopSession>()),
+            ),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum CoopMessage {
+    /// Client -> host right after connecting, and after reconnecting to catch up again
+    Hello,
+    /// Host -> client, the whole state to catch up with the game in progress
+    Welcome {
+        player: PeerId,
+        config: BoardConfig,
+        seed: u64,
+        layout: Puzzle,
+        actions: Vec<GameAction>,
+    },
+    /// Client -> host, action the client wants to do
+    Request(GameAction),
+    /// Host -> clients, action applied to the authoritative board
+    Applied(GameAction),
+    /// Client -> host, the client started a new game from the menu
+    RequestNewGame { config: BoardConfig },
+    /// Host -> clients, everyone switches to the new board, generated or hand-made
+    NewGame {
+        config: BoardConfig,
+        seed: u64,
+        layout: Puzzle,
+    },
+    /// Hovered hex of the player, relayed to everyone by the host
+    Cursor {
+        player: PeerId,
+        hex: Option<[i32; 2]>,
+    },
+    /// Host -> clients, the player disconnected
+    Left { player: PeerId },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Host,
+    Client,
+}
+
+/// Ongoing co-op game. Removing the resource closes all connections.
+#[derive(Resource)]
+pub struct CoopSession {
+    transport: Transport<CoopMessage>,
+    pub role: Role,
+    /// Id of the local player
+    pub player: PeerId,
+    /// Seed of the last board shared by the host, to tell local new games apart
+    shared_seed: u64,
+    /// Content of the board when the host started sharing it, and the amount of replay steps made before that
+    shared: Option<(Puzzle, usize)>,
+    /// Amount of replay steps already broadcasted by the host
+    broadcasted: usize,
+}
+
+impl CoopSession {
+    pub fn host(port: u16) -> io::Result<Self> {
+        Ok(Self {
+            transport: Transport::host(port)?,
+            role: Role::Host,
+            player: HOST,
+            shared_seed: 0,
+            shared: None,
+            broadcasted: 0,
+        })
+    }
+
+    pub fn join(address: &str) -> io::Result<Self> {
+        let transport = Transport::connect(address)?;
+        transport.send(HOST, &CoopMessage::Hello);
+        Ok(Self {
+            transport,
+            role: Role::Client,
+            // assigned by the host in `Welcome`
+            player: HOST,
+            shared_seed: 0,
+            shared: None,
+            broadcasted: 0,
+        })
+    }
+
+    /// Amount of other players the local one is directly connected to
+    pub fn peers_count(&self) -> usize {
+        self.transport.peers_count()
+    }
+
+    /// The client lost its connection to the host and is trying to get it back
+    pub fn is_reconnecting(&self) -> bool {
+        self.transport.is_reconnecting()
+    }
+
+    pub fn authority(&self) -> Authority {
+        match self.role {
+            Role::Host => Authority::Host,
+            Role::Client => Authority::Remote,
+        }
+    }
+
+    /// Starts sharing the board as it is now. Actions made so far are a part of its content then,
+    /// which only loses flags of a game that was in progress when the session started.
+    fn share(&mut self, board: &Board, game: &Game) {
+        let layout = Puzzle::new(String::new(), None, &board.layout());
+        self.shared = Some((layout, game.replay.steps.len()));
+        self.broadcasted = game.replay.steps.len();
+    }
+}
+
+/// Hexes hovered by other players
+#[derive(Resource, Default)]
+pub struct RemoteCursors {
+    positions: HashMap<PeerId, Option<Hex>>,
+    entities: HashMap<PeerId, Entity>,
+}
+
+impl RemoteCursors {
+    pub fn clear(&mut self) {
+        self.positions.clear();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn receive_messages(
+    mut commands: Commands,
+    mut session: ResMut<CoopSession>,
+    board: Res<Board>,
+    game: Res<Game>,
+    mut authority: ResMut<Authority>,
+    mut new_game: EventWriter<NewGame>,
+    mut custom_games: EventWriter<NewCustomGame>,
+    mut actions: EventWriter<GameAction>,
+    mut cursors: ResMut<RemoteCursors>,
+) {
+    let role = session.role;
+    for event in session.transport.poll() {
+        match (role, event) {
+            (Role::Host, TransportEvent::Message(peer, CoopMessage::Hello)) => {
+                if session.shared.is_none() {
+                    session.share(&board, &game);
+                }
+                let Some((layout, shared_at)) = session.shared.clone() else {
+                    continue;
+                };
+                // only what's already broadcasted, the rest goes with the next broadcast
+                let actions = game.replay.steps[shared_at..session.broadcasted]
+                    .iter()
+                    .map(ReplayStep::action)
+                    .collect();
+                let welcome = CoopMessage::Welcome {
+                    player: peer,
+                    config: board.config,
+                    seed: board.seed,
+                    layout,
+                    actions,
+                };
+                session.transport.send(peer, &welcome);
+            }
+            (Role::Host, TransportEvent::Message(_, CoopMessage::Request(action))) => {
+                actions.send(action);
+            }
+            (Role::Host, TransportEvent::Message(peer, CoopMessage::RequestNewGame { config })) => {
+                // everyone would have to play it, so only boards and rules the local game UI could set up
+                let valid = config.radius <= MAX_RADIUS
+                    && (0.0..1.0).contains(&config.mine_density)
+                    && share::validate_rules(&config.rules).is_ok();
+                if valid {
+                    new_game.send(NewGame::random(config));
+                } else {
+                    warn!("Player {peer} requested a new game with invalid settings");
+                }
+            }
+            (Role::Host, TransportEvent::Message(peer, CoopMessage::Cursor { hex, .. })) => {
+                // clients can't pretend to be someone else
+                let cursor = CoopMessage::Cursor { player: peer, hex };
+                session.transport.broadcast_except(peer, &cursor);
+                cursors
+                    .positions
+                    .insert(peer, hex.map(|[x, y]| Hex::new(x, y)));
+            }
+            // the player comes back with `Hello` to catch up, the cursor is hidden until then
+            (Role::Host, TransportEvent::Reconnecting(peer)) => {
+                let cursor = CoopMessage::Cursor {
+                    player: peer,
+                    hex: None,
+                };
+                session.transport.broadcast_except(peer, &cursor);
+                cursors.positions.insert(peer, None);
+            }
+            (Role::Host, TransportEvent::Disconnected(peer)) => {
+                cursors.positions.remove(&peer);
+                session
+                    .transport
+                    .broadcast(&CoopMessage::Left { player: peer });
+            }
+            (
+                Role::Client,
+                TransportEvent::Message(
+                    _,
+                    CoopMessage::Welcome {
+                        player,
+                        config,
+                        seed,
+                        layout,
+                        actions: applied,
+                    },
+                ),
+            ) => {
+                session.player = player;
+                session.shared_seed = seed;
+                custom_games.send(NewCustomGame {
+                    layout: layout.layout(),
+                    rules: config.rules,
+                    seed,
+                });
+                actions.send_batch(applied);
+            }
+            (Role::Client, TransportEvent::Message(_, CoopMessage::Applied(action))) => {
+                actions.send(action);
+            }
+            (
+                Role::Client,
+                TransportEvent::Message(
+                    _,
+                    CoopMessage::NewGame {
+                        config,
+                        seed,
+                        layout,
+                    },
+                ),
+            ) => {
+                session.shared_seed = seed;
+                custom_games.send(NewCustomGame {
+                    layout: layout.layout(),
+                    rules: config.rules,
+                    seed,
+                });
+            }
+            (Role::Client, TransportEvent::Message(_, CoopMessage::Cursor { player, hex })) => {
+                cursors
+                    .positions
+                    .insert(player, hex.map(|[x, y]| Hex::new(x, y)));
+            }
+            (Role::Client, TransportEvent::Message(_, CoopMessage::Left { player })) => {
+                cursors.positions.remove(&player);
+            }
+            (Role::Client, TransportEvent::Reconnecting(_)) => {
+                warn!("Lost connection to the co-op host, reconnecting");
+            }
+            // actions applied meanwhile are lost, so the whole game is synced again
+            (Role::Client, TransportEvent::Reconnected(_)) => {
+                info!("Reconnected to the co-op host");
+                session.transport.send(HOST, &CoopMessage::Hello);
+            }
+            (Role::Client, TransportEvent::Disconnected(_)) => {
+                warn!("Lost connection to the co-op host, continuing alone");
+                commands.remove_resource::<CoopSession>();
+                *authority = Authority::Local;
+                cursors.clear();
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Clients don't apply their own actions, but wait for the host to confirm them
+fn send_requests(
+    mut session: ResMut<CoopSession>,
+    mut requests: EventReader<ActionRequest>,
+    mut new_games: EventReader<NewGame>,
+) {
+    if session.role != Role::Client {
+        return;
+    }
+    for ActionRequest(action) in requests.iter() {
+        session.transport.send(HOST, &CoopMessage::Request(*action));
+    }
+    // local new game would diverge from the others, so ask the host to start it for everyone instead
+    for new_game in new_games.iter() {
+        if new_game.seed != session.shared_seed {
+            let request = CoopMessage::RequestNewGame {
+                config: new_game.config,
+            };
+            session.transport.send(HOST, &request);
+            session.shared_seed = new_game.seed;
+        }
+    }
+}
+
+fn broadcast_applied(
+    mut session: ResMut<CoopSession>,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    board: Res<Board>,
+    game: Res<Game>,
+) {
+    if session.role != Role::Host {
+        return;
+    }
+    let new_board = any_events(&mut new_games) | any_events(&mut custom_games);
+    if new_board || session.shared.is_none() {
+        session.share(&board, &game);
+        if let Some((layout, _)) = session.shared.clone() {
+            let new_game = CoopMessage::NewGame {
+                config: board.config,
+                seed: board.seed,
+                layout,
+            };
+            session.transport.broadcast(&new_game);
+        }
+    }
+
+    let steps = game
+        .replay
+        .steps
+        .get(session.broadcasted..)
+        .unwrap_or_default();
+    for step in steps {
+        session
+            .transport
+            .broadcast(&CoopMessage::Applied(step.action()));
+    }
+    session.broadcasted = game.replay.steps.len();
+}
+
+fn send_cursor(
+    session: Res<CoopSession>,
+    cursor_pos: Res<CursorPos>,
+    mut prev_pos: Local<CursorPos>,
+) {
+    if *prev_pos == *cursor_pos {
+        return;
+    }
+    *prev_pos = *cursor_pos;
+
+    let cursor = CoopMessage::Cursor {
+        player: session.player,
+        hex: cursor_pos.0.map(|hex| [hex.x, hex.y]),
+    };
+    match session.role {
+        Role::Host => session.transport.broadcast(&cursor),
+        Role::Client => session.transport.send(HOST, &cursor),
+    }
+}
+
+fn draw_cursors(
+    mut commands: Commands,
+    mut cursors: ResMut<RemoteCursors>,
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut transforms: Query<(&mut Transform, &mut Visibility, &mut Mesh2dHandle)>,
+) {
+    if !cursors.is_changed() {
+        return;
+    }
+    let cursors = &mut *cursors;
+
+    cursors.entities.retain(|player, entity| {
+        let keep = cursors.positions.contains_key(player);
+        if !keep {
+            commands.entity(*entity).despawn_recursive();
+        }
+        keep
+    });
+
+    let geometry = board.config.rules.geometry;
+    for (player, hex) in &cursors.positions {
+        let (translation, visibility) = match hex {
+            Some(hex) => {
+                let pos = geometry.to_world(*hex);
+                (Vec3::new(pos.x, pos.y, 1.0), Visibility::Visible)
+            }
+            None => (Vec3::ZERO, Visibility::Hidden),
+        };
+        let mesh = grid.mesh_at(geometry, hex.unwrap_or(Hex::ZERO));
+
+        if let Some(entity) = cursors.entities.get(player) {
+            if let Ok((mut transform, mut cursor_visibility, mut cursor_mesh)) =
+                transforms.get_mut(*entity)
+            {
+                transform.translation = translation;
+                *cursor_visibility = visibility;
+                *cursor_mesh = mesh.into();
+            }
+        } else {
+            let entity = commands
+                .spawn(ColorMesh2dBundle {
+                    transform: Transform::from_translation(translation),
+                    mesh: mesh.into(),
+                    material: materials.add(player_color(*player).with_a(0.35).into()),
+                    visibility,
+                    ..default()
+                })
+                .id();
+            cursors.entities.insert(*player, entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::TcpListener,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::sim::Simulation;
+
+    fn free_port() -> u16 {
+        TcpListener::bind(("127.0.0.1", 0))
+            .and_then(|listener| listener.local_addr())
+            .map(|address| address.port())
+            .expect("free port")
+    }
+
+    #[test]
+    fn new_games_with_invalid_rules_are_dropped() {
+        let port = free_port();
+        let mut sim = Simulation::new(BoardConfig::default(), 42);
+        sim.app_mut()
+            .add_plugins(CoopSessionPlugin)
+            .insert_resource(CoopSession::host(port).expect("port is free"))
+            .insert_resource(Authority::Host);
+        sim.step();
+
+        let client = Transport::<CoopMessage>::connect(&format!("127.0.0.1:{port}"))
+            .expect("host accepts clients");
+        let mut config = BoardConfig::default();
+        config.rules.adjacency = u32::MAX;
+        client.send(HOST, &CoopMessage::RequestNewGame { config });
+        // the host handles messages in order, so the request is done with once the welcome comes
+        client.send(HOST, &CoopMessage::Hello);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut welcomed = false;
+        while !welcomed && Instant::now() < deadline {
+            sim.step();
+            welcomed = client.poll().into_iter().any(|event| {
+                matches!(
+                    event,
+                    TransportEvent::Message(_, CoopMessage::Welcome { .. })
+                )
+            });
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(welcomed, "host didn't answer");
+        sim.step();
+        assert_eq!(sim.board().seed, 42);
+        assert_ne!(sim.board().config.rules.adjacency, u32::MAX);
+    }
+}
@@ -0,0 +1,122 @@
+//! Daily puzzle: the board is derived from the UTC date, so everyone worldwide plays the same one each day.
+//! Wins of today's puzzle extend the personal streak, which is persisted between sessions.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Board, BoardConfig, Rules},
+    clock,
+    game::{Authority, GameOver, GameSet, NewGame, Outcome},
+    rng::Rng,
+    storage,
+};
+
+/// Mixed into the day number, so daily seeds don't collide with seeds of other modes
+const DAILY_SALT: u64 = 0x6461_696c_7921;
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<DailyStreak>(DailyStreak::FILE))
+            // before the results screen is spawned on the next frame
+            .add_systems(
+                Update,
+                track_daily_wins
+                    .after(GameSet::Actions)
+                    .run_if(not(resource_equals(Authority::Replay))),
+            );
+    }
+}
+
+/// Board of the given day. Size and density vary a bit from day to day to keep it interesting.
+pub fn daily_game(day: u32) -> NewGame {
+    let mut rng = Rng::new(DAILY_SALT ^ day as u64);
+    let config = BoardConfig {
+        radius: 10 + rng.below(7) as u32,
+        // in permille, so the density survives a round trip through board codes exactly
+        mine_density: (120 + rng.below(7) * 10) as f32 / 1000.0,
+        rules: Rules::CLASSIC,
+    };
+    NewGame {
+        config,
+        seed: rng.next_u64(),
+    }
+}
+
+/// Whether the current board is the puzzle of the given day
+pub fn is_daily(board: &Board, day: u32) -> bool {
+    let daily = daily_game(day);
+    board.seed == daily.seed && board.config == daily.config
+}
+
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyStreak {
+    /// Last day whose puzzle was won
+    pub last_completed: Option<u32>,
+    /// Consecutive days ending with `last_completed`
+    pub streak: u32,
+    pub best_streak: u32,
+    /// Every day whose puzzle was won, in ascending order
+    pub completed_days: Vec<u32>,
+}
+
+impl DailyStreak {
+    pub const FILE: &str = "daily";
+
+    pub fn is_completed(&self, day: u32) -> bool {
+        self.last_completed == Some(day)
+    }
+
+    /// Streak as of the given day, which is already broken if yesterday's puzzle was missed
+    pub fn current(&self, day: u32) -> u32 {
+        match self.last_completed {
+            Some(last) if last + 1 >= day => self.streak,
+            _ => 0,
+        }
+    }
+
+    /// Combines completions from another profile, the streaks are recounted from the combined days
+    pub fn merge(&mut self, other: DailyStreak) {
+        self.completed_days.extend(other.completed_days);
+        self.completed_days.sort_unstable();
+        self.completed_days.dedup();
+        self.last_completed = self.completed_days.last().copied();
+        self.streak = self
+            .completed_days
+            .iter()
+            .rev()
+            .zip(self.completed_days.iter().rev().skip(1))
+            .take_while(|(day, previous)| **previous + 1 == **day)
+            .count() as u32
+            + u32::from(self.last_completed.is_some());
+        self.best_streak = self.best_streak.max(other.best_streak).max(self.streak);
+    }
+
+    fn complete(&mut self, day: u32) {
+        if self.is_completed(day) {
+            return;
+        }
+        self.streak = self.current(day) + 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        self.last_completed = Some(day);
+        self.completed_days.push(day);
+    }
+}
+
+fn track_daily_wins(
+    mut game_over: EventReader<GameOver>,
+    board: Res<Board>,
+    mut streak: ResMut<DailyStreak>,
+) {
+    for GameOver(outcome) in game_over.iter() {
+        // only today's puzzle counts, the ones from the past can't be replayed for a streak
+        let today = clock::today();
+        if *outcome == Outcome::Won && is_daily(&board, today) && !streak.is_completed(today) {
+            streak.complete(today);
+            storage::save(DailyStreak::FILE, &*streak);
+        }
+    }
+}
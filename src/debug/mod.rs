@@ -0,0 +1,32 @@
+//! Developer tools, only built with the `debug-tools` feature
+
+mod overlay;
+mod panel;
+
+use bevy::{input::common_conditions::input_toggle_active, prelude::*};
+use bevy_inspector_egui::quick::{AssetInspectorPlugin, WorldInspectorPlugin};
+
+use crate::{settings::Settings, style::CellMaterial, HexGrid};
+
+/// Shows and hides the inspectors, they take a lot of space
+const INSPECTOR_KEY: KeyCode = KeyCode::F4;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<HexGrid>()
+            .register_type::<Settings>()
+            .register_asset_reflect::<CellMaterial>()
+            // the panel adds the egui plugin, which inspectors reuse
+            .add_plugins((overlay::OverlayPlugin, panel::PanelPlugin))
+            .add_plugins((
+                WorldInspectorPlugin::new().run_if(input_toggle_active(false, INSPECTOR_KEY)),
+                AssetInspectorPlugin::<CellMaterial>::default()
+                    .run_if(input_toggle_active(false, INSPECTOR_KEY)),
+                // still used by the editor and the chat vote
+                AssetInspectorPlugin::<ColorMaterial>::default()
+                    .run_if(input_toggle_active(false, INSPECTOR_KEY)),
+            ));
+    }
+}
@@ -0,0 +1,86 @@
+//! Overlay that labels every hex with its axial coordinates and internal state, `F3` toggles it
+
+use bevy::prelude::*;
+
+use crate::board::Board;
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .add_systems(Update, (toggle_overlay, redraw_overlay).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+struct DebugOverlay {
+    visible: bool,
+}
+
+#[derive(Component)]
+struct DebugLabel;
+
+fn toggle_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keys.just_pressed(TOGGLE_KEY) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+/// Labels are rebuilt from scratch on every change, which is plenty fast for a debug tool
+fn redraw_overlay(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    board: Res<Board>,
+    labels: Query<Entity, With<DebugLabel>>,
+) {
+    if !overlay.is_changed() && !board.is_changed() {
+        return;
+    }
+    for entity in &labels {
+        commands.entity(entity).despawn();
+    }
+    if !overlay.visible {
+        return;
+    }
+
+    let style = TextStyle {
+        font_size: 9.0,
+        color: Color::YELLOW,
+        ..default()
+    };
+    for hex in &board.cells {
+        let pos = board.config.rules.geometry.to_world(*hex);
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("{},{}\n{}", hex.x, hex.y, cell_state(&board, *hex)),
+                    style.clone(),
+                )
+                .with_alignment(TextAlignment::Center),
+                // above everything, including the board editor
+                transform: Transform::from_xyz(pos.x, pos.y, 10.0),
+                ..default()
+            },
+            DebugLabel,
+        ));
+    }
+}
+
+/// `*` for mines or the number, then `c` if covered and `f` if flagged
+fn cell_state(board: &Board, hex: hexx::Hex) -> String {
+    let mut state = if board.mines.contains(&hex) {
+        "*".to_string()
+    } else {
+        board.numbers.get(&hex).copied().unwrap_or(0).to_string()
+    };
+    if board.covered.contains(&hex) {
+        state.push('c');
+    }
+    if board.flagged.contains(&hex) {
+        state.push('f');
+    }
+    state
+}
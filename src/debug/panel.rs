@@ -0,0 +1,141 @@
+//! egui panel with actions that otherwise need editing constants and recompiling
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+
+use crate::{
+    board::Board,
+    board_text::encode_board_text,
+    clipboard,
+    game::{ActionRequest, CellsChanged, Game, GameOver, GameSet, NewGame, Outcome},
+    rng::{self, Rng},
+    AppState,
+};
+
+pub struct PanelPlugin;
+
+impl Plugin for PanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(EguiPlugin)
+            .init_resource::<PanelState>()
+            .add_systems(
+                Update,
+                (
+                    show_panel.before(GameSet::NewGame),
+                    drop_clicks_on_panel
+                        .after(GameSet::Input)
+                        .before(GameSet::Route),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct PanelState {
+    seed: String,
+    /// Last message, like where the board was dumped
+    status: String,
+}
+
+/// Clicks on the panel would also go to the board below it
+fn drop_clicks_on_panel(mut contexts: EguiContexts, mut requests: ResMut<Events<ActionRequest>>) {
+    if contexts.ctx_mut().is_pointer_over_area() {
+        requests.clear();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn show_panel(
+    mut contexts: EguiContexts,
+    mut state: ResMut<PanelState>,
+    mut board: ResMut<Board>,
+    mut game: ResMut<Game>,
+    mut new_game: EventWriter<NewGame>,
+    mut cells_changed: EventWriter<CellsChanged>,
+    mut game_over: EventWriter<GameOver>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let state = &mut *state;
+    egui::Window::new("Debug").show(contexts.ctx_mut(), |ui| {
+        ui.label(format!("Seed {}, {}", board.seed, board.config));
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut state.seed);
+            if ui.button("Replace seed").clicked() {
+                match state.seed.trim().parse() {
+                    Ok(seed) => new_game.send(NewGame {
+                        config: board.config,
+                        seed,
+                    }),
+                    Err(err) => state.status = format!("Invalid seed: {err}"),
+                }
+            }
+        });
+
+        let mut density = board.config.mine_density;
+        let slider = egui::Slider::new(&mut density, 0.05..=0.5).text("Mine density");
+        if ui.add(slider).changed() {
+            let mut config = board.config;
+            config.mine_density = density;
+            new_game.send(NewGame {
+                config,
+                seed: board.seed,
+            });
+        }
+
+        if ui.button("Reveal all").clicked() {
+            // mines too, the game goes on as nothing is revealed through the game logic
+            board.covered.clear();
+            cells_changed.send(CellsChanged(board.cells.iter().copied().collect()));
+        }
+        if ui.button("Teleport mines").clicked() {
+            teleport_mines(&mut board);
+            cells_changed.send(CellsChanged(board.cells.iter().copied().collect()));
+        }
+
+        ui.horizontal(|ui| {
+            for (label, outcome) in [("Win", Outcome::Won), ("Lose", Outcome::Lost)] {
+                if ui.button(label).clicked() && game.outcome.is_none() {
+                    if outcome == Outcome::Lost {
+                        board.exploded = board.mines.iter().next().copied();
+                    }
+                    game.outcome = Some(outcome);
+                    game_over.send(GameOver(outcome));
+                    next_state.set(AppState::GameOver);
+                }
+            }
+        });
+
+        if ui.button("Dump board as text").clicked() {
+            let text = encode_board_text(&board.layout());
+            info!("Board {} with seed {}:\n{text}", board.config, board.seed);
+            state.status = match clipboard::copy(&text) {
+                Ok(()) => "Copied to the clipboard and the log".to_string(),
+                Err(err) => format!("Dumped to the log, copy failed: {err}"),
+            };
+        }
+        ui.label(state.status.as_str());
+    });
+}
+
+/// Moves every mine to a random covered hex, revealed hexes stay safe
+fn teleport_mines(board: &mut Board) {
+    let mut candidates: Vec<_> = board
+        .covered
+        .iter()
+        .filter(|hex| !board.flagged.contains(hex))
+        .copied()
+        .collect();
+    // hash set order isn't stable, but there is no point in reproducing a debug action
+    Rng::new(rng::random_seed()).shuffle(&mut candidates);
+    let mut layout = board.layout();
+    layout.mines = candidates.into_iter().take(board.mines.len()).collect();
+
+    let (config, seed) = (board.config, board.seed);
+    let (flagged, red_flags) = (board.flagged.clone(), board.red_flags.clone());
+    *board = Board::from_layout(&layout, config.rules);
+    board.config = config;
+    board.seed = seed;
+    board.flagged = flagged;
+    board.red_flags = red_flags;
+}
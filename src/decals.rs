@@ -0,0 +1,52 @@
+//! Purely cosmetic terrain on uncovered empty hexes, so large cleared areas are not a flat grey.
+//! Hexes are grouped into patches of grass, sand and stone around points of a jittered lattice,
+//! picked from the board seed, so the same board always looks the same.
+
+use bevy::math::{IVec2, Vec2};
+use hexx::Hex;
+
+use crate::{board::Board, rng::Rng, HEX_SIZE};
+
+/// Kinds of terrain, indices into `Sprites::decals`
+pub const BIOMES: usize = 3;
+/// Distance between lattice points in hexes, roughly the size of a patch
+const PATCH_SIZE: f32 = 5.0;
+/// Percent of empty hexes with a decal, the rest stay clean
+const DENSITY: u64 = 45;
+/// Decals use their own random sequence, like colored mines
+const DECAL_SEED_SALT: u64 = 0xDECA_1500;
+
+/// Index of the terrain drawn on `hex`, if any. Only empty revealed hexes get one.
+pub fn decal(board: &Board, hex: Hex) -> Option<usize> {
+    let empty = !board.covered.contains(&hex)
+        && !board.numbers.contains_key(&hex)
+        && !board.mines.contains(&hex)
+        && !board.treasures.contains_key(&hex)
+        && !board.clues.contains_key(&hex);
+    if !empty || random(board.seed ^ DECAL_SEED_SALT, hex.x, hex.y).below(100) >= DENSITY {
+        return None;
+    }
+
+    // the closest lattice point decides, every point is somewhere in its own lattice cell
+    let pos = board.config.rules.geometry.to_world(hex) / (HEX_SIZE.x * 2.0 * PATCH_SIZE);
+    let cell = pos.floor().as_ivec2();
+    let (_, biome) = (-1..=1)
+        .flat_map(|dx| (-1..=1).map(move |dy| cell + IVec2::new(dx, dy)))
+        .map(|lattice| {
+            let mut rng = random(board.seed ^ DECAL_SEED_SALT, lattice.x, lattice.y);
+            let jitter = Vec2::new(
+                rng.below(1000) as f32 / 1000.0,
+                rng.below(1000) as f32 / 1000.0,
+            );
+            let distance = (lattice.as_vec2() + jitter).distance_squared(pos);
+            (distance, rng.below(BIOMES as u64) as usize)
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))?;
+    Some(biome)
+}
+
+/// Random sequence of a point, independent from all the others
+fn random(seed: u64, x: i32, y: i32) -> Rng {
+    let point = (x as u32 as u64) << 32 | y as u32 as u64;
+    Rng::new(seed ^ point.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
@@ -0,0 +1,182 @@
+//! Defuse timers variant: some mines start a countdown as soon as any of their neighbors is revealed,
+//! and detonate on their own unless they are flagged before it runs out
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use hexx::Hex;
+
+use crate::{
+    any_events,
+    board::{sorted_hexes, Board},
+    game::{Authority, CellsChanged, Game, GameOver, GameSet, NewCustomGame, NewGame, Outcome},
+    idle::Idle,
+    rng::Rng,
+    AppState,
+};
+
+/// Part of all mines that have a timer
+const TIMED_FRACTION: f32 = 0.25;
+/// Timed mines use their own random sequence, so they don't change mines of the same seed
+const TIMED_SEED_SALT: u64 = 0xDEF0_5E00;
+const COUNTDOWN_COLOR: Color = Color::rgb(0.95, 0.2, 0.1);
+
+pub struct DefusePlugin;
+
+impl Plugin for DefusePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Fuses>().add_systems(
+            Update,
+            (
+                reset_fuses.after(GameSet::NewGame),
+                (
+                    arm_fuses,
+                    // stopped along with the clock, see `idle.rs`
+                    burn_fuses.run_if(not(resource_exists::<Idle>())),
+                )
+                    .chain()
+                    .after(GameSet::Actions)
+                    .run_if(in_state(AppState::Playing))
+                    .run_if(resource_equals(Authority::Local)),
+                draw_countdowns.after(burn_fuses),
+            ),
+        );
+    }
+}
+
+/// Mines that get a timer under [`Rules::defuse_seconds`](crate::board::Rules::defuse_seconds)
+pub fn pick_timed(mines: &HashSet<Hex>, seed: u64) -> HashSet<Hex> {
+    let mut mines = sorted_hexes(mines.iter().copied());
+    Rng::new(seed ^ TIMED_SEED_SALT).shuffle(&mut mines);
+    let count = (mines.len() as f32 * TIMED_FRACTION).round() as usize;
+    mines.into_iter().take(count).collect()
+}
+
+/// Time left on every armed timed mine
+#[derive(Resource, Default)]
+pub struct Fuses(pub HashMap<Hex, Duration>);
+
+/// Countdown over a covered timed mine
+#[derive(Component)]
+struct Countdown(Hex);
+
+fn reset_fuses(
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    mut fuses: ResMut<Fuses>,
+) {
+    if any_events(&mut new_games) | any_events(&mut custom_games) {
+        fuses.0.clear();
+    }
+}
+
+fn arm_fuses(mut board: ResMut<Board>, mut fuses: ResMut<Fuses>) {
+    let seconds = board.config.rules.defuse_seconds;
+    if seconds == 0 || board.timed_mines.is_empty() {
+        return;
+    }
+    // flagging defuses the mine for good
+    let defused: Vec<_> = board
+        .timed_mines
+        .intersection(&board.flagged)
+        .copied()
+        .collect();
+    for hex in defused {
+        board.timed_mines.remove(&hex);
+        fuses.0.remove(&hex);
+    }
+
+    let armed: Vec<_> = board
+        .timed_mines
+        .iter()
+        .filter(|hex| !fuses.0.contains_key(*hex) && board.covered.contains(*hex))
+        // numbers of these neighbors count the mine
+        .filter(|hex| {
+            board
+                .counted_by(**hex)
+                .any(|near| !board.covered.contains(&near))
+        })
+        .copied()
+        .collect();
+    for hex in armed {
+        fuses.0.insert(hex, Duration::from_secs(seconds as u64));
+    }
+}
+
+fn burn_fuses(
+    time: Res<Time>,
+    mut board: ResMut<Board>,
+    mut game: ResMut<Game>,
+    mut fuses: ResMut<Fuses>,
+    mut cells_changed: EventWriter<CellsChanged>,
+    mut game_over: EventWriter<GameOver>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // nothing ends relaxed games
+    let rules = board.config.rules;
+    if game.outcome.is_some() || rules.zen || rules.sandbox {
+        return;
+    }
+    let mut detonated = None;
+    for (hex, left) in &mut fuses.0 {
+        *left = left.saturating_sub(time.delta());
+        if left.is_zero() {
+            detonated = Some(*hex);
+        }
+    }
+    let Some(hex) = detonated else {
+        return;
+    };
+
+    // same as revealing the mine, but with no lives to spare
+    board.covered.remove(&hex);
+    board.exploded = Some(hex);
+    fuses.0.clear();
+    cells_changed.send(CellsChanged(board.mines.iter().copied().collect()));
+    game.outcome = Some(Outcome::Lost);
+    game_over.send(GameOver(Outcome::Lost));
+    next_state.set(AppState::GameOver);
+}
+
+fn draw_countdowns(
+    mut commands: Commands,
+    board: Res<Board>,
+    fuses: Res<Fuses>,
+    mut countdowns: Query<(Entity, &Countdown, &mut Text)>,
+) {
+    let mut drawn = HashSet::new();
+    for (entity, Countdown(hex), mut text) in &mut countdowns {
+        let Some(left) = fuses.0.get(hex) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let label = format!("{}", left.as_secs() + 1);
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+        drawn.insert(*hex);
+    }
+
+    for (hex, left) in fuses.0.iter().filter(|(hex, _)| !drawn.contains(*hex)) {
+        let pos = board.config.rules.geometry.to_world(*hex);
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("{}", left.as_secs() + 1),
+                    TextStyle {
+                        font_size: 18.0,
+                        color: COUNTDOWN_COLOR,
+                        ..default()
+                    },
+                ),
+                // above the cell sprites
+                transform: Transform::from_xyz(pos.x, pos.y, 3.0),
+                ..default()
+            },
+            Countdown(*hex),
+        ));
+    }
+}
@@ -0,0 +1,399 @@
+//! Dual boards: two independent boards side by side that share the clock. A mine on either of them ends the game,
+//! and both have to be cleared to win.
+//!
+//! Both boards are laid into one hand-made [`Board`] far enough apart, so numbers never count hexes of the other one.
+//! Each of them is shown by its own camera in its half of the window with its own [`BoardView`], while the main camera
+//! draws only the UI, and the cursor picks a hex through the view of the half it's over.
+
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    ecs::event::ManualEventReader,
+    prelude::*,
+    render::{camera::Viewport, view::RenderLayers},
+    utils::HashSet,
+    window::PrimaryWindow,
+};
+use hexx::Hex;
+
+use crate::{
+    board::{Board, BoardConfig, Layout, Rules},
+    game::{GameSet, NewCustomGame, NewGame},
+    lod::{LowDetail, DETAIL_LAYER},
+    magnifier::MagnifierCamera,
+    mods::Mods,
+    settings::Settings,
+    theme::Theme,
+    BoardView,
+};
+
+/// Hexes between the two boards at least, more than any neighbors reach
+const GAP: i32 = 4;
+/// The second board uses its own seed, so the two are different
+const SECOND_SEED_SALT: u64 = 0xD0A1_B0A2;
+/// Width of the line between the halves of the window, in pixels
+const DIVIDER_WIDTH: f32 = 2.0;
+
+pub struct DualPlugin;
+
+impl Plugin for DualPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                // `start_new_game` prefers hand-made boards over generated ones of the same frame
+                split_new_games
+                    .before(GameSet::NewGame)
+                    .run_if(resource_exists::<DualBoards>()),
+                (toggle_cameras, fit_views).chain().after(GameSet::NewGame),
+                toggle_divider,
+            ),
+        );
+    }
+}
+
+/// Dual boards are on. The mode is active as long as the resource exists.
+#[derive(Resource, Default)]
+pub struct DualBoards {
+    /// Config of each of the two boards, while the one of the [`Board`] has a radius covering both
+    pub config: BoardConfig,
+    /// Both boards of the last split game, to tell its restarts apart from other hand-made boards
+    layout: Layout,
+}
+
+/// Camera of one half of the window, `0` for the left board and `1` for the right one
+#[derive(Component)]
+pub struct DualCamera(usize);
+
+/// Marks the main camera while it draws only the UI, with the layers it had before
+#[derive(Component)]
+pub struct BoardHidden(RenderLayers);
+
+/// Views of both boards, exists only while dual boards are shown
+#[derive(Resource)]
+pub struct DualViews {
+    views: [BoardView; 2],
+    /// Size of each half of the window, in logical pixels
+    half: Vec2,
+}
+
+impl DualViews {
+    /// World position of a position in the window, seen through the view of the half it's in
+    pub(crate) fn to_world(&self, window_pos: Vec2, window: &Window) -> Vec2 {
+        let side = (window_pos.x >= window.width() / 2.0) as usize;
+        let pos = window_pos - Vec2::X * self.half.x * side as f32;
+        self.views[side].to_world_in(pos, self.half)
+    }
+
+    /// World units per pixel of the board zoomed out the most
+    pub(crate) fn scale(&self) -> f32 {
+        self.views[0].scale.max(self.views[1].scale)
+    }
+}
+
+#[derive(Component)]
+struct Divider;
+
+/// Two boards of the `config` next to each other, left and right of [`Hex::ZERO`]
+fn layout(mods: &Mods, config: BoardConfig, seed: u64) -> Layout {
+    // even, so the triangles of both boards point the same way
+    let shift = (config.radius as i32 + GAP / 2 + 1) & !1;
+    let mut layout = Layout {
+        cells: HashSet::new(),
+        mines: HashSet::new(),
+        revealed: HashSet::new(),
+    };
+    for (shift, seed) in [(-shift, seed), (shift, seed ^ SECOND_SEED_SALT)] {
+        let board = mods.generate(config, seed);
+        let center = Hex::new(shift, 0);
+        layout
+            .cells
+            .extend(board.cells.iter().map(|hex| *hex + center));
+        layout
+            .mines
+            .extend(board.mines.iter().map(|hex| *hex + center));
+    }
+    layout
+}
+
+/// Rules that work with two boards in one: rows of gravity and the light of darkness start in the middle of the board
+fn rules(rules: Rules) -> Rules {
+    Rules {
+        gravity: false,
+        darkness: None,
+        ..rules
+    }
+}
+
+/// Splits generated boards into two, while hand-made ones, like puzzles or boards of a co-op host,
+/// are a single board and end dual boards. Restarting the dual board itself keeps them on.
+fn split_new_games(
+    mut commands: Commands,
+    mut new_games: EventReader<NewGame>,
+    mods: Res<Mods>,
+    mut dual: ResMut<DualBoards>,
+    mut custom_games: ResMut<Events<NewCustomGame>>,
+    mut custom_reader: Local<ManualEventReader<NewCustomGame>>,
+) {
+    // only the last request matters, the same as in `start_new_game`
+    let new_game = new_games.iter().last().copied();
+    // which also prefers hand-made boards
+    if let Some(custom_game) = custom_reader.iter(&custom_games).last() {
+        if custom_game.layout != dual.layout {
+            commands.remove_resource::<DualBoards>();
+        }
+        return;
+    }
+    let Some(new_game) = new_game else {
+        return;
+    };
+    let config = BoardConfig {
+        rules: rules(new_game.config.rules),
+        ..new_game.config
+    };
+    dual.config = config;
+    dual.layout = layout(&mods, config, new_game.seed);
+    custom_games.send(NewCustomGame {
+        layout: dual.layout.clone(),
+        rules: config.rules,
+        seed: new_game.seed,
+    });
+    // not a board from someone else
+    custom_reader.iter(&custom_games).last();
+}
+
+/// Hex of the board shown on the `side`, the left one is left of [`Hex::ZERO`]
+fn on_side(hex: Hex, side: usize) -> bool {
+    (hex.x > 0) as usize == side
+}
+
+/// Makes the main camera draw only the UI and adds a camera for each board while dual boards are on, and undoes that
+#[allow(clippy::type_complexity)]
+fn toggle_cameras(
+    mut commands: Commands,
+    dual: Option<Res<DualBoards>>,
+    low_detail: Option<Res<LowDetail>>,
+    mut main_cameras: Query<
+        (
+            Entity,
+            &mut Camera2d,
+            &mut RenderLayers,
+            Option<&BoardHidden>,
+        ),
+        (Without<MagnifierCamera>, Without<DualCamera>),
+    >,
+    dual_cameras: Query<Entity, With<DualCamera>>,
+) {
+    let Ok((entity, mut camera, mut layers, hidden)) = main_cameras.get_single_mut() else {
+        return;
+    };
+    match (dual.is_some(), hidden) {
+        (true, None) => {
+            commands.entity(entity).insert(BoardHidden(*layers));
+            *layers = RenderLayers::none();
+            // the board cameras are drawn first, and the UI over them
+            camera.clear_color = ClearColorConfig::None;
+            let board_layers = match low_detail {
+                Some(_) => RenderLayers::default(),
+                None => RenderLayers::default().with(DETAIL_LAYER),
+            };
+            for side in 0..2 {
+                commands.spawn((
+                    Camera2dBundle {
+                        camera: Camera {
+                            order: side as isize - 3,
+                            ..default()
+                        },
+                        camera_2d: Camera2d {
+                            // clearing clears the whole window, and not just the viewport
+                            clear_color: match side {
+                                0 => ClearColorConfig::Default,
+                                _ => ClearColorConfig::None,
+                            },
+                        },
+                        ..default()
+                    },
+                    UiCameraConfig { show_ui: false },
+                    board_layers,
+                    DualCamera(side),
+                ));
+            }
+        }
+        (false, Some(hidden)) => {
+            // the detail could have changed since
+            *layers = match low_detail {
+                Some(_) => hidden.0.without(DETAIL_LAYER),
+                None => hidden.0.with(DETAIL_LAYER),
+            };
+            camera.clear_color = ClearColorConfig::Default;
+            commands.entity(entity).remove::<BoardHidden>();
+            commands.remove_resource::<DualViews>();
+            for camera in &dual_cameras {
+                commands.entity(camera).despawn();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Fits each board into its half of the window, and points the cameras at them
+fn fit_views(
+    mut commands: Commands,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    views: Option<ResMut<DualViews>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(
+        &DualCamera,
+        &mut Camera,
+        &mut Transform,
+        &mut OrthographicProjection,
+    )>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    if cameras.is_empty() {
+        return;
+    }
+    let half = Vec2::new(window.width() / 2.0, window.height());
+    let up_to_date = views
+        .as_ref()
+        .is_some_and(|views| views.half == half && !board.is_changed() && !settings.is_changed());
+    if up_to_date {
+        return;
+    }
+    let geometry = board.config.rules.geometry;
+    let fit = |side| {
+        let hexes = board
+            .cells
+            .iter()
+            .copied()
+            .filter(|hex| on_side(*hex, side));
+        BoardView::fit_into(hexes, geometry, half, &settings)
+    };
+    let fitted = DualViews {
+        views: [fit(0), fit(1)],
+        half,
+    };
+
+    let physical_half = UVec2::new(window.physical_width() / 2, window.physical_height());
+    for (DualCamera(side), mut camera, mut transform, mut projection) in &mut cameras {
+        let view = fitted.views[*side];
+        camera.viewport = Some(Viewport {
+            physical_position: UVec2::new(physical_half.x * *side as u32, 0),
+            physical_size: physical_half.max(UVec2::ONE),
+            ..default()
+        });
+        transform.translation.x = view.center.x;
+        transform.translation.y = view.center.y;
+        transform.rotation = Quat::from_rotation_z(view.rotation);
+        transform.scale = view.flip.extend(1.0);
+        projection.scale = view.scale;
+    }
+    match views {
+        Some(mut views) => *views = fitted,
+        None => commands.insert_resource(fitted),
+    }
+}
+
+fn toggle_divider(
+    mut commands: Commands,
+    dual: Option<Res<DualBoards>>,
+    theme: Res<Theme>,
+    mut dividers: Query<(Entity, &mut BackgroundColor), With<Divider>>,
+) {
+    match (dual.is_some(), dividers.get_single_mut()) {
+        (true, Err(_)) => {
+            commands.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(50.0),
+                        width: Val::Px(DIVIDER_WIDTH),
+                        height: Val::Percent(100.0),
+                        margin: UiRect::left(Val::Px(-DIVIDER_WIDTH / 2.0)),
+                        ..default()
+                    },
+                    background_color: theme.outline.into(),
+                    ..default()
+                },
+                Divider,
+            ));
+        }
+        (true, Ok((_, mut color))) if theme.is_changed() => color.0 = theme.outline,
+        (false, Ok((entity, _))) => commands.entity(entity).despawn(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::shapes;
+
+    use super::*;
+    use crate::sim::Simulation;
+
+    fn dual_sim() -> Simulation {
+        let mut sim = Simulation::new(BoardConfig::default(), 42);
+        sim.app_mut().init_resource::<DualBoards>().add_systems(
+            Update,
+            split_new_games
+                .before(GameSet::NewGame)
+                .run_if(resource_exists::<DualBoards>()),
+        );
+        sim.app_mut().world.send_event(NewGame {
+            config: BoardConfig::default(),
+            seed: 7,
+        });
+        sim.step();
+        sim
+    }
+
+    fn is_dual(sim: &mut Simulation) -> bool {
+        sim.app_mut().world.contains_resource::<DualBoards>()
+    }
+
+    #[test]
+    fn restarts_keep_dual_boards() {
+        let mut sim = dual_sim();
+        let layout = sim
+            .board()
+            .layout
+            .clone()
+            .expect("both boards are laid into one");
+        let rules = sim.board().config.rules;
+        sim.app_mut().world.send_event(NewCustomGame {
+            layout,
+            rules,
+            seed: 7,
+        });
+        sim.steps(2);
+        assert!(is_dual(&mut sim));
+    }
+
+    #[test]
+    fn hand_made_boards_end_dual_boards() {
+        let mut sim = dual_sim();
+        let puzzle = Layout {
+            cells: shapes::hexagon(Hex::ZERO, 2).collect(),
+            mines: [Hex::new(1, 0)].into_iter().collect(),
+            revealed: HashSet::new(),
+        };
+        sim.app_mut().world.send_event(NewCustomGame {
+            layout: puzzle,
+            rules: Rules::CLASSIC,
+            seed: 0,
+        });
+        sim.steps(2);
+        assert!(!is_dual(&mut sim));
+        assert_eq!(sim.board().cells.len(), 19);
+
+        // and the next generated board is a single one again
+        sim.app_mut().world.send_event(NewGame {
+            config: BoardConfig::default(),
+            seed: 9,
+        });
+        sim.step();
+        assert!(sim.board().layout.is_none());
+    }
+}
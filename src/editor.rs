@@ -0,0 +1,253 @@
+//! Board editor: paints a [`Layout`] hex by hex on top of the board, which stays hidden under it.
+//! The editor panel with the tools and metadata lives in `ui::editor`.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+    window::PrimaryWindow,
+};
+use hexx::{shapes, Hex};
+
+use crate::{
+    board::{Board, Layout},
+    cursor_hex,
+    geometry::Geometry,
+    settings::Settings,
+    style, AppState, BoardView, GRID_LAYOUT,
+};
+
+/// Hexes that can be painted, the rest of the window is taken by the editor panel
+pub const EDITOR_RADIUS: u32 = 11;
+/// Size of the board in a fresh editor
+const DEFAULT_RADIUS: u32 = 6;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Editor>()
+            .add_systems(Startup, setup_materials)
+            .add_systems(OnEnter(AppState::Editor), spawn_editor_hexes)
+            .add_systems(OnExit(AppState::Editor), despawn_editor_hexes)
+            .add_systems(
+                Update,
+                (paint, redraw_editor_hexes)
+                    .chain()
+                    .run_if(in_state(AppState::Editor)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tool {
+    /// Adds an empty hex to the board
+    Cell,
+    /// Removes the hex from the board
+    Void,
+    Mine,
+    /// Hex that is revealed from the start
+    Revealed,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 4] = [Tool::Cell, Tool::Void, Tool::Mine, Tool::Revealed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Cell => "Cell",
+            Tool::Void => "Void",
+            Tool::Mine => "Mine",
+            Tool::Revealed => "Revealed",
+        }
+    }
+}
+
+/// Puzzle being edited, kept between visits of the editor so it survives test plays
+#[derive(Resource)]
+pub struct Editor {
+    pub layout: Layout,
+    pub tool: Tool,
+    pub name: String,
+    pub par_time: String,
+    /// Safe hexes that can't be reached without guessing, found when the puzzle was last saved
+    pub unreachable: HashSet<Hex>,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self {
+            layout: Layout {
+                cells: shapes::hexagon(Hex::ZERO, DEFAULT_RADIUS).collect(),
+                ..default()
+            },
+            tool: Tool::Mine,
+            name: String::new(),
+            par_time: String::new(),
+            unreachable: HashSet::new(),
+        }
+    }
+}
+
+impl Editor {
+    fn apply(&mut self, tool: Tool, hex: Hex) {
+        let layout = &mut self.layout;
+        match tool {
+            Tool::Cell => {
+                layout.cells.insert(hex);
+                layout.mines.remove(&hex);
+                layout.revealed.remove(&hex);
+            }
+            Tool::Void => {
+                layout.cells.remove(&hex);
+                layout.mines.remove(&hex);
+                layout.revealed.remove(&hex);
+            }
+            Tool::Mine => {
+                layout.cells.insert(hex);
+                layout.mines.insert(hex);
+                layout.revealed.remove(&hex);
+            }
+            Tool::Revealed => {
+                layout.cells.insert(hex);
+                layout.mines.remove(&hex);
+                layout.revealed.insert(hex);
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+struct EditorMaterials {
+    covered: Handle<ColorMaterial>,
+    void: Handle<ColorMaterial>,
+    mine: Handle<ColorMaterial>,
+    revealed: Handle<ColorMaterial>,
+    unreachable: Handle<ColorMaterial>,
+}
+
+#[derive(Component)]
+struct EditorHex(Hex);
+
+fn setup_materials(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands.insert_resource(EditorMaterials {
+        covered: materials.add(Color::rgb(0.25, 0.25, 0.25).into()),
+        void: materials.add(Color::rgb(0.1, 0.1, 0.1).into()),
+        mine: materials.add(Color::rgb(0.8, 0.25, 0.2).into()),
+        revealed: materials.add(Color::rgb(0.6, 0.6, 0.6).into()),
+        unreachable: materials.add(Color::rgb(0.7, 0.6, 0.2).into()),
+    });
+}
+
+fn spawn_editor_hexes(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<EditorMaterials>,
+    mut editor: ResMut<Editor>,
+    mut view: ResMut<BoardView>,
+) {
+    // the editor has its own fixed size, next to the panel
+    *view = BoardView::default();
+    // layouts are always made of hexes, whatever the board under the editor is
+    let mesh = meshes.add(style::face_mesh(&settings.grid_style, Geometry::Hex, false));
+    for hex in shapes::hexagon(Hex::ZERO, EDITOR_RADIUS) {
+        let pos = GRID_LAYOUT.hex_to_world_pos(hex);
+        commands.spawn((
+            ColorMesh2dBundle {
+                // above the board, its hexes and sprites
+                transform: Transform::from_xyz(pos.x, pos.y, 3.0),
+                mesh: mesh.clone().into(),
+                material: materials.covered.clone(),
+                ..default()
+            },
+            EditorHex(hex),
+        ));
+    }
+    // draw everything on the first frame
+    editor.set_changed();
+}
+
+fn despawn_editor_hexes(
+    mut commands: Commands,
+    hexes: Query<Entity, With<EditorHex>>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut view: ResMut<BoardView>,
+) {
+    for entity in &hexes {
+        commands.entity(entity).despawn_recursive();
+    }
+    *view = BoardView::fit(
+        board.cells.iter().copied(),
+        board.config.rules.geometry,
+        windows.single(),
+        &settings,
+    );
+}
+
+/// Left button paints with the current tool, right button erases back to an empty cell
+fn paint(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    view: Res<BoardView>,
+    buttons: Res<Input<MouseButton>>,
+    interactions: Query<&Interaction>,
+    mut editor: ResMut<Editor>,
+) {
+    // clicks on the editor panel are not for the board
+    if interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    let Some(hex) = cursor_hex(windows.single(), &view, Geometry::Hex)
+        .filter(|hex| hex.ulength() <= EDITOR_RADIUS)
+    else {
+        return;
+    };
+    let tool = if buttons.pressed(MouseButton::Left) {
+        editor.tool
+    } else if buttons.pressed(MouseButton::Right) {
+        Tool::Cell
+    } else {
+        return;
+    };
+    // holding the button drags the tool over hexes, but repainting the same hex is not a change
+    let layout = editor.layout.clone();
+    editor.bypass_change_detection().apply(tool, hex);
+    if editor.layout != layout {
+        // the last check is outdated
+        editor.unreachable.clear();
+    }
+}
+
+fn redraw_editor_hexes(
+    editor: Res<Editor>,
+    materials: Res<EditorMaterials>,
+    mut hexes: Query<(&EditorHex, &mut Handle<ColorMaterial>)>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    let layout = &editor.layout;
+    let mut expected = HashMap::new();
+    for hex in &layout.cells {
+        let material = if layout.mines.contains(hex) {
+            &materials.mine
+        } else if layout.revealed.contains(hex) {
+            &materials.revealed
+        } else if editor.unreachable.contains(hex) {
+            &materials.unreachable
+        } else {
+            &materials.covered
+        };
+        expected.insert(*hex, material);
+    }
+    for (EditorHex(hex), mut material) in &mut hexes {
+        let target = expected.get(hex).copied().unwrap_or(&materials.void);
+        if *material != *target {
+            *material = target.clone();
+        }
+    }
+}
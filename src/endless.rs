@@ -0,0 +1,115 @@
+//! Endless run: boards get bigger and denser one after another, the next one starts as soon as
+//! the previous one is cleared, and the run ends on the first mine. The amount of cleared boards is the score.
+//! Stats and history get the whole run as one game, the one lost at its end.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    any_events,
+    board::{BoardConfig, Rules},
+    game::{Game, GameOver, GameSet, NewGame, Outcome},
+    storage, AppState,
+};
+
+const START_RADIUS: u32 = 6;
+const RADIUS_STEP: u32 = 2;
+const MAX_RADIUS: u32 = 24;
+const START_DENSITY: f32 = 0.10;
+const DENSITY_STEP: f32 = 0.01;
+const MAX_DENSITY: f32 = 0.30;
+
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<EndlessRecord>(EndlessRecord::FILE))
+            .add_systems(
+                Update,
+                (
+                    leave_finished_run.after(GameSet::NewGame),
+                    next_stage.after(GameSet::Actions),
+                )
+                    .run_if(resource_exists::<EndlessRun>()),
+            );
+    }
+}
+
+/// Ongoing run. The mode is active as long as the resource exists.
+#[derive(Resource, Default)]
+pub struct EndlessRun {
+    /// Boards cleared so far, which is also the index of the current one
+    pub cleared: u32,
+    /// The run ended with a mine, and the next new game leaves it
+    pub ended: bool,
+}
+
+impl EndlessRun {
+    /// Board of the given stage, starting from 0
+    pub fn stage_game(stage: u32) -> NewGame {
+        NewGame::random(BoardConfig {
+            radius: (START_RADIUS + stage * RADIUS_STEP).min(MAX_RADIUS),
+            mine_density: (START_DENSITY + stage as f32 * DENSITY_STEP).min(MAX_DENSITY),
+            rules: Rules::CLASSIC,
+        })
+    }
+
+    /// Cleared board of a run, which is a stage and not a game of its own
+    pub fn is_stage_clear(run: Option<&Self>, outcome: Outcome) -> bool {
+        run.is_some() && outcome == Outcome::Won
+    }
+}
+
+/// Longest run ever, persisted between sessions
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndlessRecord {
+    pub best: u32,
+}
+
+impl EndlessRecord {
+    pub const FILE: &str = "endless";
+
+    /// Keeps the longer run of the two profiles
+    pub fn merge(&mut self, other: EndlessRecord) {
+        self.best = self.best.max(other.best);
+    }
+}
+
+fn next_stage(
+    mut game_over: EventReader<GameOver>,
+    game: Res<Game>,
+    mut run: ResMut<EndlessRun>,
+    mut record: ResMut<EndlessRecord>,
+    mut new_game: EventWriter<NewGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for GameOver(outcome) in game_over.iter() {
+        match outcome {
+            Outcome::Won => {
+                run.cleared += 1;
+                new_game.send(EndlessRun::stage_game(run.cleared));
+                // straight to the next board without the results screen
+                next_state.set(AppState::Playing);
+            }
+            Outcome::Lost => {
+                run.ended = true;
+                // a rule script can win stages at will
+                if run.cleared > record.best && game.script.is_none() {
+                    record.best = run.cleared;
+                    storage::save(EndlessRecord::FILE, &*record);
+                }
+            }
+        }
+    }
+}
+
+fn leave_finished_run(
+    mut commands: Commands,
+    mut new_games: EventReader<NewGame>,
+    run: Res<EndlessRun>,
+) {
+    if any_events(&mut new_games) && run.ended {
+        commands.remove_resource::<EndlessRun>();
+    }
+}
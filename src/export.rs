@@ -0,0 +1,114 @@
+//! Recent games and statistics as plain tables for spreadsheets and scripts.
+//! CSV files have one row per game or per board config, the JSON file has everything including replays.
+
+use std::{fmt::Write, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    board::BoardConfig,
+    history::{History, HistoryEntry},
+    replay::ActionKind,
+    stats::Stats,
+    storage,
+};
+
+const CONFIG_COLUMNS: &str = "radius,mine_density,rules";
+
+#[derive(Serialize)]
+struct Analysis<'a> {
+    stats: &'a Stats,
+    games: Vec<&'a HistoryEntry>,
+}
+
+/// Writes `<base>-games.csv`, `<base>-stats.csv` and `<base>-games.json` next to `path`,
+/// where `base` is the file name without the extension. Returns paths of the written files.
+pub fn export(path: &str, stats: &Stats, history: &History) -> Result<Vec<String>, String> {
+    let base = Path::new(path).with_extension("").display().to_string();
+    let files = [
+        (format!("{base}-games.csv"), games_csv(history)),
+        (format!("{base}-stats.csv"), stats_csv(stats)),
+        (
+            format!("{base}-games.json"),
+            serde_json::to_string_pretty(&Analysis {
+                stats,
+                // oldest first, the same order as in the CSV
+                games: history.entries.iter().rev().collect(),
+            })
+            .map_err(|err| err.to_string())?,
+        ),
+    ];
+    for (path, text) in &files {
+        storage::write_text(path, text)?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+fn games_csv(history: &History) -> String {
+    let mut csv = format!(
+        "finished_at_ms,{CONFIG_COLUMNS},seed,outcome,time_ms,reveals,flags,chords,assists\n"
+    );
+    for entry in history.entries.iter().rev() {
+        let count = |kind| {
+            let steps = entry.replay.steps.iter();
+            steps.filter(|step| step.kind == kind).count()
+        };
+        let _ = writeln!(
+            csv,
+            "{},{},{},{:?},{},{},{},{},{}",
+            entry.finished_at,
+            config_fields(&entry.config),
+            entry.seed,
+            entry.outcome,
+            entry.time_ms,
+            count(ActionKind::Reveal),
+            count(ActionKind::ToggleFlag),
+            count(ActionKind::Chord),
+            entry.assists.name(),
+        );
+    }
+    csv
+}
+
+/// Totals go into a row of their own with empty config columns, assisted games are marked separately
+fn stats_csv(stats: &Stats) -> String {
+    let mut csv = format!(
+        "assisted,{CONFIG_COLUMNS},played,wins,best_ms,average_ms,best_no_flag_ms,best_efficiency\n"
+    );
+    let _ = writeln!(csv, "false,,,,{},{},,,,", stats.played, stats.wins);
+    let rows = stats
+        .configs
+        .iter()
+        .map(|config| (false, config))
+        .chain(stats.assisted.iter().map(|config| (true, config)));
+    for (assisted, config) in rows {
+        let optional =
+            |value: Option<u32>| value.map(|value| value.to_string()).unwrap_or_default();
+        let _ = writeln!(
+            csv,
+            "{assisted},{},{},{},{},{},{},{}",
+            config_fields(&config.config),
+            config.played,
+            config.wins(),
+            optional(config.best_ms()),
+            optional(config.average_ms()),
+            optional(config.best_no_flag_ms()),
+            config
+                .best_efficiency
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+        );
+    }
+    csv
+}
+
+/// Rules are a JSON object in a single quoted field, there are too many of them for columns
+fn config_fields(config: &BoardConfig) -> String {
+    let rules = serde_json::to_string(&config.rules).unwrap_or_default();
+    format!(
+        "{},{},\"{}\"",
+        config.radius,
+        config.mine_density,
+        rules.replace('"', "\"\"")
+    )
+}
@@ -0,0 +1,36 @@
+//! Font of the numbers on hexes, loaded again whenever [`NumberStyle`](crate::settings::NumberStyle) changes
+
+use bevy::prelude::*;
+
+use crate::settings::{NumberFont, Settings};
+
+pub struct NumberFontPlugin;
+
+impl Plugin for NumberFontPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DigitFont>()
+            .add_systems(PreUpdate, load_digit_font);
+    }
+}
+
+/// Font for text numbers, the default handle is the built-in font
+#[derive(Resource, Default, PartialEq)]
+pub struct DigitFont(pub Handle<Font>);
+
+fn load_digit_font(
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    mut font: ResMut<DigitFont>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let style = &settings.number_style;
+    let handle = match style.font {
+        NumberFont::Custom if !style.custom_path.is_empty() => {
+            asset_server.load(style.custom_path.as_str())
+        }
+        NumberFont::Pictures | NumberFont::BuiltIn | NumberFont::Custom => Handle::default(),
+    };
+    font.set_if_neq(DigitFont(handle));
+}
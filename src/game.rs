@@ -0,0 +1,360 @@
+//! Game flow: applies player actions to the [`Board`], tracks the time and detects the end of the game
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assist::AssistPreset,
+    board::{hex_serde, Board, BoardConfig, Layout, Rules},
+    gravity,
+    idle::Idle,
+    mods::{Mods, RuleScript, ScriptId},
+    replay::Replay,
+    rng,
+    treasure::Treasure,
+    AppState,
+};
+
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Board::generate(BoardConfig::default(), 0))
+            .init_resource::<Game>()
+            .init_resource::<Authority>()
+            .add_event::<NewGame>()
+            .add_event::<NewCustomGame>()
+            .add_event::<ActionRequest>()
+            .add_event::<GameAction>()
+            .add_event::<CellsChanged>()
+            .add_event::<BoardProgress>()
+            .add_event::<TreasureFound>()
+            .add_event::<RowsCollapsed>()
+            .add_event::<GameOver>()
+            .add_systems(Startup, |mut new_game: EventWriter<NewGame>| {
+                new_game.send(NewGame::random(BoardConfig::default()));
+            })
+            .add_systems(
+                Update,
+                (
+                    forward_requests.in_set(GameSet::Route).run_if(
+                        resource_equals(Authority::Local).or_else(resource_equals(Authority::Host)),
+                    ),
+                    start_new_game.in_set(GameSet::NewGame),
+                    (
+                        // the player stepped away, see `idle.rs`
+                        tick_clock.run_if(not(resource_exists::<Idle>())),
+                        apply_actions,
+                    )
+                        .chain()
+                        .in_set(GameSet::Actions)
+                        // shared game goes on even if one of the players opened a menu
+                        .run_if(
+                            in_state(AppState::Playing)
+                                .or_else(resource_equals(Authority::Host))
+                                .or_else(resource_equals(Authority::Remote)),
+                        ),
+                ),
+            )
+            .configure_sets(
+                Update,
+                (
+                    GameSet::Input,
+                    GameSet::Route,
+                    GameSet::NewGame,
+                    GameSet::Actions,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Input produces [`ActionRequest`]s, which are routed to [`GameAction`]s by whoever owns the board
+/// and applied in the `Actions` set after a possible `NewGame`
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    Input,
+    Route,
+    NewGame,
+    Actions,
+}
+
+/// Who decides which actions are applied to the board
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Authority {
+    /// Single player game, local requests are applied as is
+    #[default]
+    Local,
+    /// Local board is the source of truth for other players
+    Host,
+    /// Local requests are sent to the host, and only actions confirmed by it are applied
+    Remote,
+    /// Actions come from a recorded replay, local requests are ignored
+    Replay,
+}
+
+/// Request to replace the current board with a fresh one
+#[derive(Event, Clone, Copy)]
+pub struct NewGame {
+    pub config: BoardConfig,
+    pub seed: u64,
+}
+
+impl NewGame {
+    pub fn random(config: BoardConfig) -> Self {
+        Self {
+            config,
+            seed: rng::random_seed(),
+        }
+    }
+}
+
+/// Request to replace the current board with a hand-made one
+#[derive(Event, Clone)]
+pub struct NewCustomGame {
+    pub layout: Layout,
+    pub rules: Rules,
+    /// Seed of what the layout doesn't hold, see [`Board::from_seeded_layout`], `0` for boards made by hand
+    pub seed: u64,
+}
+
+/// Everything a player can do with the board
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameAction {
+    Reveal(#[serde(with = "hex_serde")] Hex),
+    ToggleFlag(#[serde(with = "hex_serde")] Hex),
+    /// Reveals the neighbors of a revealed number that has all its flags, see [`Board::chord`]
+    Chord(#[serde(with = "hex_serde")] Hex),
+}
+
+impl GameAction {
+    /// Hex the action was done on
+    pub fn hex(self) -> Hex {
+        match self {
+            Self::Reveal(hex) | Self::ToggleFlag(hex) | Self::Chord(hex) => hex,
+        }
+    }
+}
+
+/// Action the local player wants to do, which isn't necessarily applied
+#[derive(Event, Clone, Copy)]
+pub struct ActionRequest(pub GameAction);
+
+/// Hexes whose state changed and have to be redrawn
+#[derive(Event)]
+pub struct CellsChanged(pub Vec<Hex>);
+
+/// Sent after every reveal that uncovered safe hexes
+#[derive(Event, Clone, Copy)]
+pub struct BoardProgress {
+    /// Fraction of revealed safe hexes, see [`Board::progress`]
+    pub revealed: f32,
+    /// Hexes uncovered by the reveal, more than one for cascades
+    pub uncovered: usize,
+    /// Game time of the reveal
+    pub elapsed: Duration,
+}
+
+/// Revealed hex had a treasure, whose effect is applied by `treasure.rs`
+#[derive(Event, Clone, Copy)]
+pub struct TreasureFound {
+    pub hex: Hex,
+    pub treasure: Treasure,
+}
+
+/// Bottom rows collapsed under [`Rules::gravity`] and the rest of the board moved down by this many rows
+#[derive(Event, Clone, Copy)]
+pub struct RowsCollapsed(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Won,
+    Lost,
+}
+
+#[derive(Event)]
+pub struct GameOver(pub Outcome);
+
+/// State of the current game besides the board itself
+#[derive(Resource, Default)]
+pub struct Game {
+    /// Time spent in the game, starts with the first action and stops when the game ends or is paused
+    pub elapsed: Duration,
+    pub replay: Replay,
+    pub outcome: Option<Outcome>,
+    /// Some moves were made by an assist, see `assist.rs`
+    pub assisted: bool,
+    /// The loosest assists enabled at any point of the game
+    pub assists: AssistPreset,
+    /// Rule script active when the game started, see `mods.rs`
+    pub script: Option<ScriptId>,
+}
+
+impl Game {
+    /// Whether the game can be compared with other games of the config, see [`Rules::is_ranked`].
+    /// A rule script can change the rules at will, so scripted games never are.
+    pub fn is_ranked(&self, board: &Board) -> bool {
+        self.script.is_none() && board.config.rules.is_ranked()
+    }
+}
+
+fn start_new_game(
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    mods: Res<Mods>,
+    mut board: ResMut<Board>,
+    mut game: ResMut<Game>,
+    mut cells_changed: EventWriter<CellsChanged>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    // only the last request matters if there are several, and hand-made boards are rare enough to win
+    let generated = new_games.iter().last();
+    if let Some(custom_game) = custom_games.iter().last() {
+        *board =
+            Board::from_seeded_layout(&custom_game.layout, custom_game.rules, custom_game.seed);
+        // pre-revealed hexes have to be drawn right away
+        cells_changed.send(CellsChanged(
+            custom_game.layout.revealed.iter().copied().collect(),
+        ));
+    } else if let Some(new_game) = generated {
+        *board = mods.generate(new_game.config, new_game.seed);
+    } else {
+        return;
+    }
+    if board.config.rules.sandbox {
+        // mines are visible from the start
+        cells_changed.send(CellsChanged(board.mines.iter().copied().collect()));
+    }
+    *game = Game {
+        script: mods.active().map(RuleScript::id),
+        ..default()
+    };
+    next_state.set(AppState::Playing);
+}
+
+fn forward_requests(
+    mut requests: EventReader<ActionRequest>,
+    mut actions: EventWriter<GameAction>,
+) {
+    actions.send_batch(requests.iter().map(|ActionRequest(action)| *action));
+}
+
+fn tick_clock(time: Res<Time>, board: Res<Board>, mut game: ResMut<Game>) {
+    // there is no rush in zen mode
+    if board.config.rules.zen {
+        return;
+    }
+    if !game.replay.is_empty() && game.outcome.is_none() {
+        game.elapsed += time.delta();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_actions(
+    mut actions: EventReader<GameAction>,
+    mods: Res<Mods>,
+    mut board: ResMut<Board>,
+    mut game: ResMut<Game>,
+    mut cells_changed: EventWriter<CellsChanged>,
+    mut progress: EventWriter<BoardProgress>,
+    mut treasures: EventWriter<TreasureFound>,
+    mut collapsed: EventWriter<RowsCollapsed>,
+    mut game_over: EventWriter<GameOver>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for action in actions.iter() {
+        if game.outcome.is_some() {
+            break;
+        }
+
+        let changed = match *action {
+            GameAction::Reveal(hex) => {
+                let mut changed = board.reveal(hex);
+                if !changed.is_empty() {
+                    for extra in mods.on_reveal(hex) {
+                        changed.extend(board.reveal(extra));
+                    }
+                }
+                changed
+            }
+            GameAction::ToggleFlag(hex) if board.toggle_flag(hex) => vec![hex],
+            GameAction::ToggleFlag(_) => Vec::new(),
+            GameAction::Chord(hex) => board.chord(hex),
+        };
+        if changed.is_empty() {
+            continue;
+        }
+        let elapsed = game.elapsed;
+        game.replay.push(elapsed, *action);
+        treasures.send_batch(changed.iter().filter_map(|hex| {
+            let treasure = *board.treasures.get(hex)?;
+            Some(TreasureFound {
+                hex: *hex,
+                treasure,
+            })
+        }));
+        let uncovered = match action {
+            GameAction::Reveal(_) | GameAction::Chord(_) => changed
+                .iter()
+                .filter(|hex| !board.mines.contains(hex))
+                .count(),
+            GameAction::ToggleFlag(_) => 0,
+        };
+        cells_changed.send(CellsChanged(changed));
+        if uncovered > 0 {
+            progress.send(BoardProgress {
+                revealed: board.progress(),
+                uncovered,
+                elapsed,
+            });
+        }
+
+        let moves_every = board.config.rules.moving_mines;
+        if uncovered > 0 && moves_every > 0 && board.exploded.is_none() {
+            let reveals = game
+                .replay
+                .steps
+                .iter()
+                .filter(|step| {
+                    matches!(step.action(), GameAction::Reveal(_) | GameAction::Chord(_))
+                })
+                .count() as u32;
+            if reveals % moves_every == 0 {
+                // seeded by the reveal count, so replays move the same mines
+                let seed = board.seed ^ reveals as u64;
+                cells_changed.send(CellsChanged(board.move_mine(seed)));
+            }
+        }
+
+        if board.config.rules.gravity && board.exploded.is_none() {
+            let mut rows = 0;
+            while gravity::is_bottom_resolved(&board) {
+                gravity::collapse(&mut board);
+                rows += 2;
+            }
+            if rows > 0 {
+                collapsed.send(RowsCollapsed(rows));
+                cells_changed.send(CellsChanged(board.cells.iter().copied().collect()));
+            }
+        }
+
+        let outcome = if board.exploded.is_some() {
+            // show where all the mines were
+            cells_changed.send(CellsChanged(board.mines.iter().copied().collect()));
+            Outcome::Lost
+        } else if !board.config.rules.gravity && mods.is_won(&board) {
+            // gravity games are endless
+            Outcome::Won
+        } else {
+            continue;
+        };
+        game.outcome = Some(outcome);
+        // wrong flags get crossed out
+        cells_changed.send(CellsChanged(board.flagged.iter().copied().collect()));
+        game_over.send(GameOver(outcome));
+        next_state.set(AppState::GameOver);
+    }
+}
@@ -0,0 +1,98 @@
+//! Log of everything that happened in the current game, for bug reports.
+//! Every entry is also logged with `debug!` and structured fields, visible with `RUST_LOG=hex_minesweeper=debug`.
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    board::BoardConfig,
+    game::{Game, GameAction, GameOver, GameSet, NewCustomGame, NewGame, Outcome},
+    storage, AppState,
+};
+
+const FILE_NAME: &str = "game-log.json";
+
+pub struct GameLogPlugin;
+
+impl Plugin for GameLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameLog>()
+            .add_systems(Update, record_events.after(GameSet::Actions));
+    }
+}
+
+#[derive(Resource, Default, Serialize)]
+pub struct GameLog {
+    entries: Vec<LogEntry>,
+}
+
+#[derive(Serialize)]
+struct LogEntry {
+    /// Game time, which stands still until the first action
+    at_ms: u32,
+    event: LogEvent,
+}
+
+#[derive(Debug, Serialize)]
+enum LogEvent {
+    Generated { config: BoardConfig, seed: u64 },
+    HandMade { cells: usize, mines: usize },
+    Action(GameAction),
+    State(String),
+    GameOver(Outcome),
+}
+
+impl GameLog {
+    /// Writes the log next to exported profiles, returning the path
+    pub fn save(&self) -> Result<String, String> {
+        let path = storage::export_path(FILE_NAME);
+        storage::write_file(&path, self).map(|_| path)
+    }
+
+    fn push(&mut self, at_ms: u32, event: LogEvent) {
+        debug!(at_ms, ?event, "Game event");
+        self.entries.push(LogEntry { at_ms, event });
+    }
+}
+
+fn record_events(
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    mut game_over: EventReader<GameOver>,
+    state: Res<State<AppState>>,
+    game: Res<Game>,
+    mut logged_steps: Local<usize>,
+    mut log: ResMut<GameLog>,
+) {
+    // the same request that `start_new_game` picked, a new game starts a new log
+    let new_game = match (custom_games.iter().last(), new_games.iter().last()) {
+        (Some(custom), _) => Some(LogEvent::HandMade {
+            cells: custom.layout.cells.len(),
+            mines: custom.layout.mines.len(),
+        }),
+        (None, Some(new_game)) => Some(LogEvent::Generated {
+            config: new_game.config,
+            seed: new_game.seed,
+        }),
+        (None, None) => None,
+    };
+    let now_ms = game.elapsed.as_millis() as u32;
+    if let Some(event) = new_game {
+        log.entries.clear();
+        *logged_steps = 0;
+        log.push(now_ms, event);
+    }
+
+    // the replay has only the actions that changed something
+    for step in game.replay.steps.iter().skip(*logged_steps) {
+        log.push(step.at_ms, LogEvent::Action(step.action()));
+    }
+    *logged_steps = game.replay.steps.len();
+
+    for GameOver(outcome) in game_over.iter() {
+        log.push(now_ms, LogEvent::GameOver(*outcome));
+    }
+    if state.is_changed() {
+        log.push(now_ms, LogEvent::State(format!("{:?}", state.get())));
+    }
+}
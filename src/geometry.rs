@@ -0,0 +1,323 @@
+//! Shapes of the cells a board is made of. Boards always keep cells as [`Hex`] coordinates,
+//! and the geometry decides which cells touch, where they are drawn and which one is under the cursor.
+//! Square cells use the two axes of the coordinates as columns and rows, and so do triangles,
+//! which point left and right in turns, so a cell and the one to the right of it share an edge.
+//! Organic cells are the Voronoi cells of hex centers moved by a random bit, which is the same for a hex on every board.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::Vec2;
+use hexx::{shapes, Hex};
+use serde::{Deserialize, Serialize};
+
+use crate::{board::sorted_hexes, rng::Rng, GRID_LAYOUT, HEX_SIZE};
+
+/// Distance between the centers of neighbor squares, the same as the width of a hex
+const SQUARE_SIZE: f32 = HEX_SIZE.x * 1.732_050_8;
+/// Side of a triangle whose inscribed circle is the same as the one of a hex, so numbers fit the same way
+const TRIANGLE_SIDE: f32 = HEX_SIZE.x * 3.0;
+/// Distance between the columns of triangles, which is the height of a triangle
+const TRIANGLE_HEIGHT: f32 = TRIANGLE_SIDE * 0.866_025_4;
+/// How far organic cells move from the centers of their hexes, relative to the hex size.
+/// Neighbors stay within two rings of hexes around.
+const ORGANIC_JITTER: f32 = 0.4;
+const ORGANIC_SEED_SALT: u64 = 0x0E6A_41C5;
+/// Edges of organic cells shorter than this are only touching corners
+const MIN_EDGE: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Geometry {
+    #[default]
+    Hex,
+    /// Classic board, where numbers count eight neighbors
+    Square,
+    /// Numbers count twelve neighbors: the three behind the edges and the nine that only share a corner
+    Triangle,
+    /// Irregular cells, where numbers count the cells sharing an edge, see [`Self::edges`]
+    Organic,
+}
+
+impl Geometry {
+    pub const ALL: [Self; 4] = [Self::Hex, Self::Square, Self::Triangle, Self::Organic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hex => "hexes",
+            Self::Square => "squares",
+            Self::Triangle => "triangles",
+            Self::Organic => "organic",
+        }
+    }
+
+    /// Offsets of all cells within `distance` steps, including the center.
+    /// Offsets are the same for every cell, except for [`Self::is_flipped`] ones, see [`Self::neighbor`].
+    /// Organic cells have no common offsets and use the ones of hexes, while [`Self::around`] follows their edges.
+    pub fn range(self, distance: u32) -> Vec<Hex> {
+        match self {
+            Self::Hex | Self::Organic => Hex::ZERO.range(distance).collect(),
+            Self::Square | Self::Triangle => {
+                // a step goes up to two rows of triangles up or down
+                let (columns, rows) = match self {
+                    Self::Square => (distance as i32, distance as i32),
+                    _ => (distance as i32, 2 * distance as i32),
+                };
+                (-rows..=rows)
+                    .flat_map(|y| (-columns..=columns).map(move |x| Hex::new(x, y)))
+                    .filter(|offset| self.distance(Hex::ZERO, *offset) <= distance)
+                    .collect()
+            }
+        }
+    }
+
+    /// All cells within `distance` steps from `hex`, including it
+    pub fn around(self, hex: Hex, distance: u32) -> Vec<Hex> {
+        if self != Self::Organic {
+            return self
+                .range(distance)
+                .into_iter()
+                .map(|offset| self.neighbor(hex, offset))
+                .collect();
+        }
+        let mut found = vec![hex];
+        let mut last = vec![hex];
+        for _ in 0..distance {
+            let mut next = sorted_hexes(
+                last.iter()
+                    .flat_map(|cell| self.edges(*cell))
+                    .filter(|cell| !found.contains(cell)),
+            );
+            next.dedup();
+            found.extend(next.iter().copied());
+            last = next;
+        }
+        found
+    }
+
+    /// Steps between two cells, where a step goes to any cell touching the current one
+    pub fn distance(self, a: Hex, b: Hex) -> u32 {
+        let offset = b - a;
+        match self {
+            // organic cells are never far from their hexes
+            Self::Hex | Self::Organic => offset.ulength(),
+            Self::Square => offset.x.unsigned_abs().max(offset.y.unsigned_abs()),
+            Self::Triangle => {
+                // columns towards the flat edge of `a` take a step less to get up and down
+                let x = self.orient(a, offset).x;
+                let climb = if x > 0 { x - 1 } else { -x };
+                let rows = (offset.y.unsigned_abs() + climb as u32 + 1) / 2;
+                x.unsigned_abs().max(rows)
+            }
+        }
+    }
+
+    /// Triangles that point right, which are mirrored triangles pointing left.
+    /// Hexes and squares look the same mirrored, so none of them are flipped.
+    pub fn is_flipped(self, hex: Hex) -> bool {
+        self == Self::Triangle && (hex.x + hex.y).rem_euclid(2) == 1
+    }
+
+    /// Cell at `offset` from `hex`, where offsets of flipped cells are mirrored too
+    pub fn neighbor(self, hex: Hex, offset: Hex) -> Hex {
+        hex + self.orient(hex, offset)
+    }
+
+    /// Cell that has `hex` at `offset`, the opposite of [`Self::neighbor`]
+    pub fn counted_by(self, hex: Hex, offset: Hex) -> Hex {
+        // mirroring keeps the parity of the offset, so the cell is flipped the same either way
+        hex - self.orient(hex - offset, offset)
+    }
+
+    fn orient(self, hex: Hex, offset: Hex) -> Hex {
+        match self.is_flipped(hex) {
+            true => Hex::new(-offset.x, offset.y),
+            false => offset,
+        }
+    }
+
+    /// Organic cells sharing an edge with `hex`, ordered by their hexes
+    pub fn edges(self, hex: Hex) -> Vec<Hex> {
+        if self != Self::Organic {
+            return Vec::new();
+        }
+        sorted_hexes(
+            self.voronoi(hex)
+                .into_iter()
+                .filter_map(|(_, neighbor)| neighbor)
+                // a corner shared by four cells might be off by a rounding error on one side only
+                .filter(|neighbor| {
+                    self.voronoi(*neighbor)
+                        .iter()
+                        .any(|(_, back)| *back == Some(hex))
+                }),
+        )
+    }
+
+    /// Corners of the organic cell at `hex` around [`Self::to_world`], counter-clockwise
+    pub fn corners(self, hex: Hex) -> Vec<Vec2> {
+        self.voronoi(hex)
+            .into_iter()
+            .map(|(corner, _)| corner)
+            .collect()
+    }
+
+    /// Voronoi cell of the organic `hex`: every corner with the cell behind the edge after it, if the edge is long enough.
+    /// A big hexagon around is cut by the halfway lines to the centers of two rings of cells around.
+    fn voronoi(self, hex: Hex) -> Vec<(Vec2, Option<Hex>)> {
+        let center = self.to_world(hex);
+        let mut polygon: Vec<(Vec2, Option<Hex>)> = (0..6)
+            .map(|i| {
+                (
+                    Vec2::from_angle(i as f32 * PI / 3.0) * HEX_SIZE.x * 4.0,
+                    None,
+                )
+            })
+            .collect();
+        for other in hex.range(2).filter(|other| *other != hex) {
+            let toward = self.to_world(other) - center;
+            let halfway = toward.length_squared() / 2.0;
+            let mut clipped = Vec::with_capacity(polygon.len() + 1);
+            for (i, &(a, edge)) in polygon.iter().enumerate() {
+                let b = polygon[(i + 1) % polygon.len()].0;
+                let (da, db) = (a.dot(toward) - halfway, b.dot(toward) - halfway);
+                if da <= 0.0 {
+                    clipped.push((a, edge));
+                }
+                if (da <= 0.0) != (db <= 0.0) {
+                    let crossing = a + (b - a) * (da / (da - db));
+                    // leaving goes along the halfway line to `other`, entering goes on along the edge
+                    clipped.push((crossing, if da <= 0.0 { Some(other) } else { edge }));
+                }
+            }
+            polygon = clipped;
+        }
+        (0..polygon.len())
+            .map(|i| {
+                let (corner, edge) = polygon[i];
+                let length = corner.distance(polygon[(i + 1) % polygon.len()].0);
+                (corner, edge.filter(|_| length >= MIN_EDGE))
+            })
+            .collect()
+    }
+
+    /// Cells of a generated board of the given radius, in a stable order so shuffles are reproducible
+    pub fn shape(self, radius: u32) -> Vec<Hex> {
+        match self {
+            Self::Hex | Self::Organic => shapes::hexagon(Hex::ZERO, radius).collect(),
+            Self::Square | Self::Triangle => self.range(radius),
+        }
+    }
+
+    /// Rows above and below the center of a generated board of the given radius,
+    /// a step goes up to two rows of triangles
+    pub fn rows(self, radius: u32) -> u32 {
+        match self {
+            Self::Triangle => 2 * radius,
+            _ => radius,
+        }
+    }
+
+    pub fn to_world(self, hex: Hex) -> Vec2 {
+        match self {
+            Self::Hex => GRID_LAYOUT.hex_to_world_pos(hex),
+            Self::Organic => {
+                let seed = (hex.x as u32 as u64) << 32 | hex.y as u32 as u64;
+                let mut rng = Rng::new(seed ^ ORGANIC_SEED_SALT);
+                let mut unit = || rng.below(1 << 24) as f32 / (1 << 24) as f32;
+                // uniform within a circle
+                let (angle, distance) = (unit() * 2.0 * PI, unit().sqrt());
+                GRID_LAYOUT.hex_to_world_pos(hex)
+                    + Vec2::from_angle(angle) * distance * ORGANIC_JITTER * HEX_SIZE.x
+            }
+            Self::Square => Vec2::new(hex.x as f32, hex.y as f32) * SQUARE_SIZE,
+            Self::Triangle => {
+                // the center is a third of the height away from the flat edge, which is on the right unless flipped
+                let shift = match self.is_flipped(hex) {
+                    true => -TRIANGLE_HEIGHT / 6.0,
+                    false => TRIANGLE_HEIGHT / 6.0,
+                };
+                Vec2::new(
+                    hex.x as f32 * TRIANGLE_HEIGHT + shift,
+                    hex.y as f32 * TRIANGLE_SIDE / 2.0,
+                )
+            }
+        }
+    }
+
+    pub fn from_world(self, pos: Vec2) -> Hex {
+        match self {
+            Self::Hex => GRID_LAYOUT.world_pos_to_hex(pos),
+            // Voronoi cells are made of points closest to their centers
+            Self::Organic => GRID_LAYOUT
+                .world_pos_to_hex(pos)
+                .range(1)
+                .min_by(|a, b| {
+                    let (a, b) = (self.to_world(*a), self.to_world(*b));
+                    a.distance_squared(pos).total_cmp(&b.distance_squared(pos))
+                })
+                .expect("ranges are not empty"),
+            Self::Square => {
+                let cell = (pos / SQUARE_SIZE).round();
+                Hex::new(cell.x as i32, cell.y as i32)
+            }
+            Self::Triangle => {
+                // columns are straight, but within one the point might be in any of the rows around it
+                let column = (pos.x / TRIANGLE_HEIGHT).round() as i32;
+                let row = (pos.y / (TRIANGLE_SIDE / 2.0)).round() as i32;
+                (row - 1..=row + 1)
+                    .map(|row| Hex::new(column, row))
+                    .min_by(|a, b| {
+                        self.edge_distance(*a, pos)
+                            .total_cmp(&self.edge_distance(*b, pos))
+                    })
+                    .expect("rows are not empty")
+            }
+        }
+    }
+
+    /// How far `pos` is beyond the farthest edge of the triangle at `hex`, negative inside of it
+    fn edge_distance(self, hex: Hex, pos: Vec2) -> f32 {
+        let mut offset = pos - self.to_world(hex);
+        if self.is_flipped(hex) {
+            offset.x = -offset.x;
+        }
+        // edges face right, up left and down left
+        [0.0, 2.0 * PI / 3.0, 4.0 * PI / 3.0]
+            .map(|angle| offset.dot(Vec2::from_angle(angle)))
+            .into_iter()
+            .fold(f32::MIN, f32::max)
+            - TRIANGLE_HEIGHT / 3.0
+    }
+
+    /// Half of the width and the height of a cell
+    pub fn half_extent(self) -> Vec2 {
+        match self {
+            // pointy hexes are narrower than they are tall
+            Self::Hex => Vec2::new(HEX_SIZE.x * 3f32.sqrt() / 2.0, HEX_SIZE.y),
+            Self::Square => Vec2::splat(SQUARE_SIZE / 2.0),
+            // from the center, the corner is farther than the flat edge
+            Self::Triangle => Vec2::new(TRIANGLE_HEIGHT * 2.0 / 3.0, TRIANGLE_SIDE / 2.0),
+            Self::Organic => Vec2::splat(HEX_SIZE.x * (1.0 + ORGANIC_JITTER)),
+        }
+    }
+
+    /// Cells are regular polygons with an edge facing right, or left when [`Self::is_flipped`].
+    /// Zero for organic cells, which all differ, see [`Self::corners`].
+    pub fn sides(self) -> u32 {
+        match self {
+            Self::Hex => 6,
+            Self::Square => 4,
+            Self::Triangle => 3,
+            Self::Organic => 0,
+        }
+    }
+
+    /// Distance from the center to the corners of a cell that touches its neighbors
+    pub fn cell_radius(self) -> f32 {
+        match self {
+            Self::Hex => HEX_SIZE.x,
+            Self::Square => SQUARE_SIZE / 2f32.sqrt(),
+            Self::Triangle => TRIANGLE_HEIGHT * 2.0 / 3.0,
+            Self::Organic => HEX_SIZE.x * (1.0 + ORGANIC_JITTER),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Ghost racing: on a board that was won before, the best previous run plays along on a copy of the board,
+//! and the hexes it has revealed but the player hasn't yet are tinted. Its clock starts with the first action
+//! of the player, so both runs are timed the same way.
+//!
+//! The copy gets the same actions with the same rule scripts, so it stays exactly like the original run.
+//! Boards that change by themselves over time, like with moving mines or gravity, have no ghost.
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use hexx::Hex;
+
+use crate::{
+    any_events,
+    board::Board,
+    game::{CellsChanged, Game, GameAction, GameSet, NewCustomGame, NewGame, Outcome},
+    history::History,
+    mods::Mods,
+    replay::ReplayStep,
+    settings::Settings,
+    HexGrid,
+};
+
+const GHOST_COLOR: Color = Color::rgba(0.6, 0.75, 1.0, 0.35);
+/// Over covered cells, under the sprites and the cursor highlight
+const GHOST_Z: f32 = 0.5;
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostCells>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    // on the fresh board, before any action of the player
+                    summon_ghost
+                        .after(GameSet::NewGame)
+                        .before(GameSet::Actions),
+                    (advance_ghost, draw_ghost).chain().after(GameSet::Actions),
+                ),
+            );
+    }
+}
+
+/// Best previous run on the current board, played on its own copy of the board
+#[derive(Resource)]
+pub struct Ghost {
+    board: Board,
+    steps: Vec<ReplayStep>,
+    next: usize,
+    /// Time of the whole run, to compare with
+    pub time: Duration,
+}
+
+impl Ghost {
+    /// Fraction of safe hexes the ghost has revealed by now
+    pub fn progress(&self) -> f32 {
+        self.board.progress()
+    }
+}
+
+#[derive(Resource)]
+struct GhostMaterial(Handle<ColorMaterial>);
+
+/// Tinted hexes, one entity per hex revealed only by the ghost
+#[derive(Resource, Default)]
+struct GhostCells(HashMap<Hex, Entity>);
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands.insert_resource(GhostMaterial(materials.add(GHOST_COLOR.into())));
+}
+
+fn summon_ghost(
+    mut commands: Commands,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    settings: Res<Settings>,
+    board: Res<Board>,
+    game: Res<Game>,
+    history: Res<History>,
+) {
+    if !(any_events(&mut new_games) | any_events(&mut custom_games)) {
+        return;
+    }
+    commands.remove_resource::<Ghost>();
+    let rules = &board.config.rules;
+    // hand-made boards can't be told apart by their seed
+    if !settings.ghost || board.layout.is_some() || rules.moving_mines > 0 || rules.gravity {
+        return;
+    }
+    let best = history
+        .entries
+        .iter()
+        .filter(|entry| {
+            entry.outcome == Outcome::Won
+                && entry.seed == board.seed
+                && entry.config == board.config
+                && entry.script == game.script
+        })
+        .min_by_key(|entry| entry.time_ms);
+    if let Some(best) = best {
+        commands.insert_resource(Ghost {
+            board: board.clone(),
+            steps: best.replay.steps.clone(),
+            next: 0,
+            time: Duration::from_millis(best.time_ms as u64),
+        });
+    }
+}
+
+/// Plays the steps of the ghost that are due by the clock of the player
+fn advance_ghost(game: Res<Game>, mods: Res<Mods>, ghost: Option<ResMut<Ghost>>) {
+    let Some(mut ghost) = ghost else {
+        return;
+    };
+    // the clock of the player starts with the first action, the same as it did for the ghost
+    if game.replay.is_empty() {
+        return;
+    }
+    let now = game.elapsed.as_millis() as u32;
+    let is_due = |ghost: &Ghost| {
+        ghost
+            .steps
+            .get(ghost.next)
+            .is_some_and(|step| step.at_ms <= now)
+    };
+    // mutable access marks the ghost changed, which redraws it
+    if !is_due(&*ghost) {
+        return;
+    }
+    let ghost = &mut *ghost;
+    while let Some(step) = ghost.steps.get(ghost.next).filter(|step| step.at_ms <= now) {
+        match step.action() {
+            // the same as in `apply_actions`
+            GameAction::Reveal(hex) => {
+                if !ghost.board.reveal(hex).is_empty() {
+                    for extra in mods.on_reveal(hex) {
+                        ghost.board.reveal(extra);
+                    }
+                }
+            }
+            GameAction::ToggleFlag(hex) => {
+                ghost.board.toggle_flag(hex);
+            }
+            GameAction::Chord(hex) => {
+                ghost.board.chord(hex);
+            }
+        }
+        ghost.next += 1;
+    }
+}
+
+fn draw_ghost(
+    mut commands: Commands,
+    mut cells_changed: EventReader<CellsChanged>,
+    ghost: Option<Res<Ghost>>,
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    material: Res<GhostMaterial>,
+    mut cells: ResMut<GhostCells>,
+) {
+    let player_moved = any_events(&mut cells_changed);
+    let ghost_moved = ghost.as_ref().is_some_and(|ghost| ghost.is_changed());
+    let ghost_gone = ghost.is_none() && !cells.0.is_empty();
+    if !player_moved && !ghost_moved && !ghost_gone {
+        return;
+    }
+    let shown: HashSet<Hex> = match &ghost {
+        Some(ghost) => ghost
+            .board
+            .cells
+            .iter()
+            .filter(|hex| !ghost.board.covered.contains(hex) && board.covered.contains(hex))
+            .copied()
+            .collect(),
+        None => HashSet::new(),
+    };
+
+    cells.0.retain(|hex, entity| {
+        let keep = shown.contains(hex);
+        if !keep {
+            commands.entity(*entity).despawn();
+        }
+        keep
+    });
+    let geometry = board.config.rules.geometry;
+    for hex in shown {
+        cells.0.entry(hex).or_insert_with(|| {
+            let pos = geometry.to_world(hex);
+            commands
+                .spawn(ColorMesh2dBundle {
+                    mesh: grid.mesh_at(geometry, hex).into(),
+                    material: material.0.clone(),
+                    transform: Transform::from_translation(pos.extend(GHOST_Z)),
+                    ..default()
+                })
+                .id()
+        });
+    }
+}
@@ -0,0 +1,80 @@
+//! Density gradients: mines are spread unevenly over the board, so a single board ramps up in difficulty
+//! as it's cleared in the direction of the gradient. The total amount of mines stays the same.
+
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{geometry::Geometry, rng::Rng};
+
+/// Weight of the sparsest part of the board, the densest one has 1
+const MIN_WEIGHT: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Gradient {
+    #[default]
+    Uniform,
+    /// Sparse at the rim and dense at the center, so the board gets harder when pushing inward
+    Center,
+    /// Dense at the rim and sparse at the center
+    Rim,
+    /// Sparse at the bottom row and dense at the top one
+    Linear,
+}
+
+impl Gradient {
+    pub const ALL: [Self; 4] = [Self::Uniform, Self::Center, Self::Rim, Self::Linear];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Uniform => "uniform",
+            Self::Center => "dense center",
+            Self::Rim => "dense rim",
+            Self::Linear => "bottom to top",
+        }
+    }
+
+    /// Relative chance of a mine on the cell of a board with the given geometry and radius
+    pub fn weight(self, hex: Hex, geometry: Geometry, radius: u32) -> f32 {
+        let distance = geometry.distance(Hex::ZERO, hex) as f32;
+        let rows = geometry.rows(radius).max(1) as f32;
+        let radius = radius.max(1) as f32;
+        let ramp = match self {
+            Self::Uniform => return 1.0,
+            Self::Center => 1.0 - distance / radius,
+            Self::Rim => distance / radius,
+            // rows go up with `y`, see `gravity::FALL`
+            Self::Linear => (hex.y as f32 + rows) / (2.0 * rows),
+        };
+        MIN_WEIGHT + (1.0 - MIN_WEIGHT) * ramp.clamp(0.0, 1.0)
+    }
+}
+
+/// Picks `count` mines out of `hexes`, weighted by the gradient. Uniform boards are the first hexes
+/// of a shuffle, which keeps them the same as before gradients were added.
+pub fn pick_mines(
+    hexes: &mut [Hex],
+    count: usize,
+    gradient: Gradient,
+    geometry: Geometry,
+    radius: u32,
+    rng: &mut Rng,
+) -> Vec<Hex> {
+    rng.shuffle(hexes);
+    if gradient != Gradient::Uniform {
+        // weighted sampling without replacement: every hex gets an exponential key with its weight as the rate,
+        // and the smallest keys win
+        let mut keyed: Vec<_> = hexes
+            .iter()
+            .map(|hex| {
+                let uniform = ((rng.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64;
+                let key = -uniform.ln() / gradient.weight(*hex, geometry, radius) as f64;
+                (key, *hex)
+            })
+            .collect();
+        keyed.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        for (hex, (_, keyed)) in hexes.iter_mut().zip(keyed) {
+            *hex = keyed;
+        }
+    }
+    hexes.iter().take(count).copied().collect()
+}
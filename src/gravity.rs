@@ -0,0 +1,96 @@
+//! Experimental gravity variant: an endless rectangular board, where resolved rows at the bottom collapse
+//! and fresh rows with more and more mines slide in from the top, until a mine is hit
+
+use bevy::utils::HashSet;
+use hexx::Hex;
+
+use crate::{
+    board::{sorted_hexes, Board},
+    colors,
+    rng::Rng,
+};
+
+/// Content falls straight down by two rows, the smallest translation that keeps hex columns in place
+const FALL: Hex = Hex::new(1, -2);
+/// Density added to the config one with every collapse
+const DENSITY_STEP: f32 = 0.01;
+const MAX_DENSITY: f32 = 0.35;
+/// Fresh rows use their own random sequence, so they don't repeat the initial mines
+const GRAVITY_SEED_SALT: u64 = 0x6752_A717;
+
+/// Rectangle of `radius` columns and twice as many rows in a stable order, so shuffles are reproducible.
+/// Columns are kept in world space, so translating by [`FALL`] keeps the shape.
+pub fn shape(radius: u32) -> Vec<Hex> {
+    let (columns, rows) = (radius as i32, 2 * radius as i32);
+    (-rows / 2..rows - rows / 2)
+        .flat_map(|row| {
+            (-columns / 2..columns - columns / 2).map(move |column| {
+                // odd rows are shifted by half a hex
+                Hex::new(column - row.div_euclid(2), row)
+            })
+        })
+        .collect()
+}
+
+/// The bottom two rows have no covered safe hexes left
+pub fn is_bottom_resolved(board: &Board) -> bool {
+    let Some(bottom) = board.cells.iter().map(|hex| hex.y).min() else {
+        return false;
+    };
+    board
+        .cells
+        .iter()
+        .filter(|hex| hex.y < bottom + 2)
+        .all(|hex| board.mines.contains(hex) || !board.covered.contains(hex))
+}
+
+/// Drops the bottom two rows and moves everything else down, so the top two rows are fresh and covered.
+/// The cells stay the same, so only the content of hexes changes.
+pub fn collapse(board: &mut Board) {
+    let (Some(bottom), Some(top)) = (
+        board.cells.iter().map(|hex| hex.y).min(),
+        board.cells.iter().map(|hex| hex.y).max(),
+    ) else {
+        return;
+    };
+    let fall = |hexes: &HashSet<Hex>| -> HashSet<Hex> {
+        hexes
+            .iter()
+            .filter(|hex| hex.y >= bottom + 2)
+            .map(|hex| *hex + FALL)
+            .collect()
+    };
+    board.mines = fall(&board.mines);
+    board.covered = fall(&board.covered);
+    board.flagged = fall(&board.flagged);
+    board.lit = fall(&board.lit);
+    board.timed_mines = fall(&board.timed_mines);
+    board.scanned = fall(&board.scanned);
+    board.red_mines = fall(&board.red_mines);
+    board.red_flags = fall(&board.red_flags);
+    board.treasures = board
+        .treasures
+        .iter()
+        .filter(|(hex, _)| hex.y >= bottom + 2)
+        .map(|(hex, treasure)| (*hex + FALL, *treasure))
+        .collect();
+
+    let fresh = sorted_hexes(board.cells.iter().filter(|hex| hex.y > top - 2).copied());
+    board.covered.extend(fresh.iter().copied());
+
+    board.collapsed += 1;
+    let density =
+        (board.config.mine_density + board.collapsed as f32 * DENSITY_STEP).min(MAX_DENSITY);
+    let mut rng = Rng::new(board.seed ^ GRAVITY_SEED_SALT ^ board.collapsed as u64);
+    rng.shuffle(&mut fresh);
+    let count = (fresh.len() as f32 * density).round() as usize;
+    let fresh_mines: HashSet<_> = fresh.into_iter().take(count).collect();
+    if board.config.rules.colored {
+        let red = colors::pick_red(&fresh_mines, board.seed ^ board.collapsed as u64);
+        board.red_mines.extend(red);
+    }
+    board.mines.extend(fresh_mines);
+
+    board.recount_numbers();
+    board.recount_variants();
+}
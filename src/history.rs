@@ -0,0 +1,256 @@
+//! Rolling log of the recently finished games with their replays, persisted between sessions
+
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::Hex;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{
+    assist::AssistPreset,
+    board::{Board, BoardConfig},
+    clock,
+    endless::EndlessRun,
+    game::{Authority, Game, GameOver, GameSet, NewCustomGame, NewGame, Outcome},
+    mods::ScriptId,
+    puzzle::Puzzle,
+    replay::{ActionKind, Replay},
+    storage,
+};
+
+/// Older games are dropped, replays are small but not free
+const MAX_ENTRIES: usize = 100;
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<History>(History::FILE))
+            .add_systems(
+                Update,
+                record_games
+                    .after(GameSet::Actions)
+                    .run_if(not(resource_equals(Authority::Replay))),
+            );
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the game ended, in milliseconds since the UNIX epoch
+    pub finished_at: u64,
+    pub config: BoardConfig,
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub time_ms: u32,
+    pub replay: Replay,
+    /// Missing for games recorded before assist presets were added
+    #[serde(default)]
+    pub assists: AssistPreset,
+    /// Rule script of the game, which doesn't make it a classic game of the config
+    #[serde(default)]
+    pub script: Option<ScriptId>,
+    /// Hand-made boards can't be generated from the seed
+    #[serde(default)]
+    pub layout: Option<Puzzle>,
+    /// Mine that ended a lost game as an `[x, y]` pair, which isn't always the last reveal:
+    /// chords open several hexes at once, and timed mines detonate on their own.
+    /// Missing for games recorded before it was kept, which get the last reveal or chord instead, see [`History`]
+    #[serde(default)]
+    pub exploded: Option<[i32; 2]>,
+}
+
+impl HistoryEntry {
+    /// Starts the board of the game again
+    pub fn start(
+        &self,
+        new_game: &mut EventWriter<NewGame>,
+        custom_game: &mut EventWriter<NewCustomGame>,
+    ) {
+        match &self.layout {
+            Some(puzzle) => custom_game.send(NewCustomGame {
+                layout: puzzle.layout(),
+                rules: self.config.rules,
+                seed: self.seed,
+            }),
+            None => new_game.send(NewGame {
+                config: self.config,
+                seed: self.seed,
+            }),
+        }
+    }
+}
+
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    /// The most recent game is the first one
+    #[serde(deserialize_with = "entries_with_exploded")]
+    pub entries: VecDeque<HistoryEntry>,
+}
+
+/// Lost games recorded before [`HistoryEntry::exploded`] was kept get the last hex the player opened,
+/// which is the mine unless a chord or a timed mine ended the game
+fn entries_with_exploded<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<VecDeque<HistoryEntry>, D::Error> {
+    let mut entries = VecDeque::<HistoryEntry>::deserialize(deserializer)?;
+    for entry in &mut entries {
+        if entry.outcome == Outcome::Lost && entry.exploded.is_none() {
+            entry.exploded = entry
+                .replay
+                .steps
+                .iter()
+                .rev()
+                .find(|step| matches!(step.kind, ActionKind::Reveal | ActionKind::Chord))
+                .map(|step| [step.x, step.y]);
+        }
+    }
+    Ok(entries)
+}
+
+impl History {
+    pub const FILE: &str = "history";
+
+    /// Combines games from another profile, keeping the most recent ones
+    pub fn merge(&mut self, other: History) {
+        for entry in other.entries {
+            // the same game can't finish twice at the same millisecond
+            let known = self
+                .entries
+                .iter()
+                .any(|known| known.finished_at == entry.finished_at && known.seed == entry.seed);
+            if !known {
+                self.entries.push_back(entry);
+            }
+        }
+        self.entries
+            .make_contiguous()
+            .sort_by_key(|entry| std::cmp::Reverse(entry.finished_at));
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    /// How many times every hex was the first reveal of a game
+    pub fn first_clicks(&self) -> HashMap<Hex, u32> {
+        count_hexes(self.entries.iter().filter_map(|entry| {
+            entry
+                .replay
+                .steps
+                .iter()
+                .find(|step| step.kind == ActionKind::Reveal)
+                .map(|step| [step.x, step.y])
+        }))
+    }
+
+    /// How many times every hex was the mine that ended a game
+    pub fn fatal_clicks(&self) -> HashMap<Hex, u32> {
+        count_hexes(self.entries.iter().filter_map(|entry| entry.exploded))
+    }
+}
+
+fn count_hexes(hexes: impl Iterator<Item = [i32; 2]>) -> HashMap<Hex, u32> {
+    hexes.fold(HashMap::new(), |mut counts, [x, y]| {
+        *counts.entry(Hex::new(x, y)).or_default() += 1;
+        counts
+    })
+}
+
+fn record_games(
+    mut game_over: EventReader<GameOver>,
+    board: Res<Board>,
+    game: Res<Game>,
+    endless: Option<Res<EndlessRun>>,
+    mut history: ResMut<History>,
+) {
+    for GameOver(outcome) in game_over.iter() {
+        if EndlessRun::is_stage_clear(endless.as_deref(), *outcome) {
+            continue;
+        }
+        history.entries.push_front(HistoryEntry {
+            finished_at: clock::unix_time_millis(),
+            config: board.config,
+            seed: board.seed,
+            outcome: *outcome,
+            time_ms: game.elapsed.as_millis() as u32,
+            replay: game.replay.clone(),
+            assists: game.assists,
+            script: game.script.clone(),
+            layout: board
+                .layout
+                .as_ref()
+                .map(|layout| Puzzle::new(String::new(), None, layout)),
+            exploded: board.exploded.map(|hex| [hex.x, hex.y]),
+        });
+        history.entries.truncate(MAX_ENTRIES);
+        storage::save(History::FILE, &*history);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::game::GameAction;
+
+    fn entry(outcome: Outcome) -> HistoryEntry {
+        let mut replay = Replay::default();
+        for (at, action) in [
+            GameAction::Reveal(Hex::ZERO),
+            GameAction::Reveal(Hex::new(2, -1)),
+            GameAction::ToggleFlag(Hex::new(1, 0)),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            replay.push(Duration::from_secs(at as u64), action);
+        }
+        HistoryEntry {
+            finished_at: 0,
+            config: BoardConfig::default(),
+            seed: 42,
+            outcome,
+            time_ms: 3000,
+            replay,
+            assists: AssistPreset::default(),
+            script: None,
+            layout: None,
+            exploded: None,
+        }
+    }
+
+    /// History as it was saved before the mine that ended a game was kept
+    fn old_history(entries: &[HistoryEntry]) -> History {
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                let mut value = serde_json::to_value(entry).expect("entries serialize");
+                value
+                    .as_object_mut()
+                    .expect("entries are objects")
+                    .remove("exploded");
+                value
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({ "entries": entries })).expect("old format")
+    }
+
+    #[test]
+    fn old_lost_games_exploded_on_the_last_reveal() {
+        let history = old_history(&[entry(Outcome::Lost), entry(Outcome::Won)]);
+        assert_eq!(history.entries[0].exploded, Some([2, -1]));
+        assert_eq!(history.entries[1].exploded, None);
+    }
+
+    #[test]
+    fn kept_mines_are_not_replaced() {
+        let mut lost = entry(Outcome::Lost);
+        lost.exploded = Some([3, 0]);
+        let json = serde_json::to_string(&History {
+            entries: [lost].into(),
+        })
+        .expect("history serializes");
+        let history: History = serde_json::from_str(&json).expect("current format");
+        assert_eq!(history.entries[0].exploded, Some([3, 0]));
+    }
+}
@@ -0,0 +1,116 @@
+//! Hot-seat duel: two players take turns on the same board and the same mouse.
+//!
+//! Every reveal passes the turn to the other player, flags don't. Whoever reveals a mine loses the point,
+//! and whoever clears the board wins it. The match goes on over several boards until someone gets
+//! [`POINTS_TO_WIN`] points.
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::Hex;
+
+use crate::{
+    board::Board,
+    coop::player_color,
+    game::{Game, GameSet, NewGame},
+    replay::ActionKind,
+    FlagSprite,
+};
+
+pub const POINTS_TO_WIN: u32 = 3;
+
+pub struct HotSeatPlugin;
+
+impl Plugin for HotSeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (reset_round, track_turns)
+                    .chain()
+                    .after(GameSet::Actions)
+                    .run_if(resource_exists::<HotSeat>()),
+                // flags are redrawn by the board renderer, which runs after the actions as well
+                color_flags
+                    .after(track_turns)
+                    .run_if(resource_exists::<HotSeat>()),
+            ),
+        );
+    }
+}
+
+/// Ongoing duel. The mode is active as long as the resource exists.
+#[derive(Resource, Default)]
+pub struct HotSeat {
+    /// Player whose turn it is, `0` or `1`
+    pub current: usize,
+    pub points: [u32; 2],
+    /// Who placed each flag
+    pub flag_owners: HashMap<Hex, usize>,
+    /// Replay steps of the current board that are already accounted for
+    processed: usize,
+}
+
+impl HotSeat {
+    /// Winner of the whole match, if it's over
+    pub fn match_winner(&self) -> Option<usize> {
+        self.points
+            .iter()
+            .position(|points| *points >= POINTS_TO_WIN)
+    }
+
+    pub fn color(player: usize) -> Color {
+        player_color(player as u32)
+    }
+}
+
+fn reset_round(mut new_games: EventReader<NewGame>, mut hot_seat: ResMut<HotSeat>) {
+    if new_games.iter().last().is_none() {
+        return;
+    }
+    if hot_seat.match_winner().is_some() {
+        *hot_seat = HotSeat::default();
+    }
+    hot_seat.flag_owners.clear();
+    hot_seat.processed = 0;
+}
+
+fn track_turns(board: Res<Board>, game: Res<Game>, mut hot_seat: ResMut<HotSeat>) {
+    let steps = &game.replay.steps;
+    if steps.len() <= hot_seat.processed {
+        return;
+    }
+    for step in &steps[hot_seat.processed..] {
+        let hex = Hex::new(step.x, step.y);
+        let current = hot_seat.current;
+        match step.kind {
+            ActionKind::ToggleFlag if board.flagged.contains(&hex) => {
+                hot_seat.flag_owners.insert(hex, current);
+            }
+            ActionKind::ToggleFlag => {
+                hot_seat.flag_owners.remove(&hex);
+            }
+            ActionKind::Reveal | ActionKind::Chord => hot_seat.current = 1 - current,
+        }
+    }
+    hot_seat.processed = steps.len();
+
+    // the last step is the one that ended the game, so the player who made it is the previous one
+    if board.exploded.is_some() {
+        let other = hot_seat.current;
+        hot_seat.points[other] += 1;
+    } else if board.is_cleared() {
+        let last = 1 - hot_seat.current;
+        hot_seat.points[last] += 1;
+    }
+}
+
+/// Tints flags with the color of the player who placed them
+fn color_flags(
+    hot_seat: Res<HotSeat>,
+    mut flags: Query<(&FlagSprite, &mut Sprite), Added<FlagSprite>>,
+) {
+    for (FlagSprite(hex), mut sprite) in &mut flags {
+        if let Some(owner) = hot_seat.flag_owners.get(hex) {
+            sprite.color = HotSeat::color(*owner);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Idle pause: when nothing is pressed or moved for [`Settings::idle_pause_seconds`] during a game,
+//! the clock stops until the player is back, so stepping away doesn't ruin a timed run.
+//! The input that wakes the game up does nothing else, and with [`Settings::idle_veil`]
+//! the board is hidden under a veil while idle.
+
+use std::time::Duration;
+
+use bevy::{
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+};
+
+use crate::{
+    any_events,
+    game::{Authority, Game, GameSet},
+    settings::Settings,
+    ui::text_style,
+    AppState,
+};
+
+const VEIL_COLOR: Color = Color::rgba(0.1, 0.1, 0.12, 0.92);
+
+pub struct IdlePlugin;
+
+impl Plugin for IdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_veil)
+            .add_systems(Update, (track_activity.in_set(GameSet::Input), update_veil));
+    }
+}
+
+/// Player is away and the clock is stopped, exists only while it is
+#[derive(Resource)]
+pub struct Idle;
+
+#[derive(Component)]
+struct Veil;
+
+fn spawn_veil(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: VEIL_COLOR.into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            Veil,
+        ))
+        .with_children(|veil| {
+            veil.spawn(TextBundle::from_section(
+                "Paused while you were away, click or press a key to go on",
+                text_style(24.0),
+            ));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn track_activity(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut since_active: Local<Duration>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    settings: Res<Settings>,
+    state: Res<State<AppState>>,
+    authority: Res<Authority>,
+    game: Res<Game>,
+    idle: Option<Res<Idle>>,
+) {
+    let moved = any_events(&mut motion) | any_events(&mut wheel);
+    // waking up takes a deliberate press, a bumped mouse only restarts the countdown
+    let pressed = keys.get_just_pressed().len() + buttons.get_just_pressed().len() > 0;
+    if moved || pressed {
+        *since_active = Duration::ZERO;
+    } else {
+        *since_active += time.delta();
+    }
+
+    // shared games can't wait for one player, and there is no clock to stop outside a running game
+    let running = *state.get() == AppState::Playing
+        && *authority == Authority::Local
+        && !game.replay.is_empty()
+        && game.outcome.is_none();
+    let limit = Duration::from_secs(settings.idle_pause_seconds as u64);
+    match idle {
+        Some(_) if pressed || !running => commands.remove_resource::<Idle>(),
+        None if running && !limit.is_zero() && *since_active >= limit => {
+            commands.insert_resource(Idle);
+        }
+        _ => {}
+    }
+}
+
+fn update_veil(
+    settings: Res<Settings>,
+    idle: Option<Res<Idle>>,
+    mut veils: Query<&mut Visibility, With<Veil>>,
+) {
+    let expected = if idle.is_some() && settings.idle_veil {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+    for mut visibility in &mut veils {
+        visibility.set_if_neq(expected);
+    }
+}
@@ -0,0 +1,92 @@
+//! Neighbor kernels: which hexes around a number count its mines.
+//!
+//! A kernel is a set of offsets within [`MAX_DISTANCE`] of the center, stored as a bit mask
+//! so it fits into [`Rules`](crate::board::Rules) and share codes. Besides the built-in ones,
+//! custom kernels are loaded from `kernels/*.json` in the data directory, e.g. `{"offsets": [[1, 0], [0, 1]]}`.
+
+use hexx::{shapes, Hex};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+/// Farthest offset a kernel can have, so all of them fit into 64 bits
+pub const MAX_DISTANCE: u32 = 3;
+const KERNELS_DIR: &str = "kernels";
+
+/// Bit mask over [`Kernel::slots`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Kernel(pub u64);
+
+impl Kernel {
+    /// All hexes within `distance`, which is the classic neighborhood for 1
+    pub fn range(distance: u32) -> Self {
+        Self::from_offsets(Hex::ZERO.range(distance.min(MAX_DISTANCE)))
+    }
+
+    /// Every other direction of the first ring, so numbers count only three neighbors
+    pub fn alternating() -> Self {
+        Self::from_offsets(Hex::ZERO.ring(1).step_by(2))
+    }
+
+    /// Offsets out of reach and the center itself are ignored
+    pub fn from_offsets(offsets: impl IntoIterator<Item = Hex>) -> Self {
+        let slots = Self::slots();
+        Self(
+            offsets
+                .into_iter()
+                .filter_map(|offset| slots.iter().position(|slot| *slot == offset))
+                .fold(0, |mask, bit| mask | 1 << bit),
+        )
+    }
+
+    pub fn offsets(self) -> Vec<Hex> {
+        Self::slots()
+            .into_iter()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & 1 << bit != 0)
+            .map(|(_, offset)| offset)
+            .collect()
+    }
+
+    /// Counts nothing, which makes every hex an opening
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Hexes that a bit of the mask stands for, in a stable order so masks mean the same everywhere
+    fn slots() -> Vec<Hex> {
+        shapes::hexagon(Hex::ZERO, MAX_DISTANCE)
+            .filter(|hex| *hex != Hex::ZERO)
+            .collect()
+    }
+}
+
+/// Custom kernel file
+#[derive(Deserialize)]
+struct KernelFile {
+    offsets: Vec<[i32; 2]>,
+}
+
+/// Kernels to pick from for a new game: the built-in ones followed by custom ones from files.
+/// Classic rules are not in the list, as they have no kernel at all.
+pub fn available() -> Vec<(String, Kernel)> {
+    let mut kernels = vec![
+        ("two rings".to_string(), Kernel::range(2)),
+        ("alternating".to_string(), Kernel::alternating()),
+    ];
+    for (name, text) in storage::read_all_text_in(KERNELS_DIR, "json") {
+        match serde_json::from_str::<KernelFile>(&text) {
+            Ok(file) => {
+                let kernel =
+                    Kernel::from_offsets(file.offsets.iter().map(|[x, y]| Hex::new(*x, *y)));
+                if kernel.is_empty() {
+                    bevy::log::warn!("Skipping kernel {name}: no offsets within {MAX_DISTANCE}");
+                } else {
+                    kernels.push((name, kernel));
+                }
+            }
+            Err(err) => bevy::log::warn!("Skipping kernel {name}: {err}"),
+        }
+    }
+    kernels
+}
@@ -0,0 +1,138 @@
+//! Finding multiplayer games on the local network, see [`crate::net::discovery`].
+//! Hosts announce themselves all the time, while the list of games is collected only on the multiplayer screens.
+
+use std::{io, net::SocketAddr, time::Duration};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    coop::{self, CoopSession},
+    net::discovery::{Announcement, Beacon, GameMode, Listener},
+    settings::Settings,
+    versus::{self, VersusSession},
+    AppState,
+};
+
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+/// Games that weren't announced for this long are considered closed
+const GAME_TIMEOUT: Duration = Duration::from_secs(4);
+
+pub struct LanPlugin;
+
+impl Plugin for LanPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LanGames>()
+            .add_systems(
+                Update,
+                (
+                    announce_games,
+                    discover_games
+                        .run_if(in_state(AppState::Coop).or_else(in_state(AppState::Versus))),
+                ),
+            )
+            .add_systems(OnExit(AppState::Coop), stop_discovery)
+            .add_systems(OnExit(AppState::Versus), stop_discovery);
+    }
+}
+
+/// Games announced on the local network
+#[derive(Resource, Default)]
+pub struct LanGames {
+    /// Opened lazily, only while somebody looks for games. Kept even if failed, so it's not retried every frame.
+    listener: Option<io::Result<Listener>>,
+    games: HashMap<SocketAddr, (Announcement, Duration)>,
+}
+
+impl LanGames {
+    /// Games of the given mode with their addresses, in a stable order
+    pub fn games(&self, mode: GameMode) -> Vec<(SocketAddr, &Announcement)> {
+        let mut games: Vec<_> = self
+            .games
+            .iter()
+            .filter(|(_, (announcement, _))| announcement.mode == mode)
+            .map(|(address, (announcement, _))| (*address, announcement))
+            .collect();
+        games.sort_by_key(|(address, _)| *address);
+        games
+    }
+
+    /// Why games can't be discovered, e.g. the port is taken by another instance on the same machine
+    pub fn error(&self) -> Option<String> {
+        match &self.listener {
+            Some(Err(err)) => Some(err.to_string()),
+            _ => None,
+        }
+    }
+}
+
+fn announce_games(
+    time: Res<Time>,
+    mut since_announced: Local<Duration>,
+    mut beacon: Local<Option<Beacon>>,
+    settings: Res<Settings>,
+    coop: Option<Res<CoopSession>>,
+    versus: Option<Res<VersusSession>>,
+) {
+    *since_announced += time.delta();
+    if *since_announced < ANNOUNCE_INTERVAL {
+        return;
+    }
+    *since_announced = Duration::ZERO;
+
+    let hosted = [
+        coop.filter(|session| session.role == coop::Role::Host)
+            .map(|session| (GameMode::Coop, coop::DEFAULT_PORT, session.peers_count())),
+        versus
+            .filter(|session| session.role == versus::Role::Host)
+            .map(|session| {
+                (
+                    GameMode::Versus,
+                    versus::DEFAULT_PORT,
+                    session.peers_count(),
+                )
+            }),
+    ];
+    for (mode, port, peers) in hosted.into_iter().flatten() {
+        if beacon.is_none() {
+            *beacon = Beacon::new()
+                .map_err(|err| warn!("LAN announcements are unavailable: {err}"))
+                .ok();
+        }
+        let Some(beacon) = beacon.as_ref() else {
+            return;
+        };
+        let announcement = Announcement {
+            mode,
+            port,
+            host_name: match settings.player_name.as_str() {
+                "" => "anonymous".to_string(),
+                name => name.to_string(),
+            },
+            // the host plays too
+            players: peers as u32 + 1,
+        };
+        if let Err(err) = beacon.announce(&announcement) {
+            debug!("Failed to announce the game: {err}");
+        }
+    }
+}
+
+fn discover_games(time: Res<Time>, mut lan_games: ResMut<LanGames>) {
+    let listener = lan_games.listener.get_or_insert_with(Listener::new);
+    let received = match listener {
+        Ok(listener) => listener.poll(),
+        Err(_) => return,
+    };
+    let now = time.elapsed();
+    for (address, announcement) in received {
+        lan_games.games.insert(address, (announcement, now));
+    }
+    lan_games
+        .games
+        .retain(|_, (_, last_seen)| now - *last_seen < GAME_TIMEOUT);
+}
+
+/// Frees the port, so other instances on the same machine can look for games
+fn stop_discovery(mut lan_games: ResMut<LanGames>) {
+    *lan_games = LanGames::default();
+}
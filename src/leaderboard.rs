@@ -0,0 +1,190 @@
+//! Opt-in online leaderboard.
+//!
+//! Protocol is plain JSON over HTTP relative to the configured endpoint:
+//! - `POST {endpoint}/scores` with a [`Submission`] after every win
+//! - `GET {endpoint}/rankings?period=global` or `?period=daily&day=N` answered with [`Rankings`],
+//!   optionally followed by `&assists=purist` and so on to rank only the results of one [`AssistPreset`]
+//!
+//! 64-bit values are sent as hex strings, because JSON numbers lose precision above 2^53 in many parsers.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assist::AssistPreset,
+    board::{Board, BoardConfig},
+    clock,
+    game::{Authority, Game, GameOver, Outcome},
+    net::{http, Pending},
+    score::{track_score, Score},
+    settings::Settings,
+};
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Leaderboard>().add_systems(
+            Update,
+            (
+                // replays of past games are not new results
+                submit_wins
+                    .after(track_score)
+                    .run_if(not(resource_equals(Authority::Replay))),
+                poll_requests,
+            ),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Submission {
+    pub player: String,
+    pub time_ms: u64,
+    /// See [`Score`]
+    pub score: u32,
+    pub config: BoardConfig,
+    pub seed: String,
+    pub replay_hash: String,
+    /// Days since the UNIX epoch, used for the daily rankings
+    pub day: u32,
+    pub assists: AssistPreset,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RankingEntry {
+    pub rank: u32,
+    pub player: String,
+    pub time_ms: u64,
+    /// Missing for results submitted before scoring was added
+    #[serde(default)]
+    pub score: u32,
+    pub config: BoardConfig,
+    /// Missing for results submitted before assist presets were added
+    #[serde(default)]
+    pub assists: AssistPreset,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rankings {
+    pub entries: Vec<RankingEntry>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Global,
+    Daily,
+}
+
+/// State of a request, shown as is in the UI
+#[derive(Clone, Default)]
+pub enum Status<T> {
+    #[default]
+    Idle,
+    Pending,
+    Done(T),
+    Failed(String),
+}
+
+#[derive(Resource, Default)]
+pub struct Leaderboard {
+    pub submission: Status<()>,
+    pub rankings: Status<Rankings>,
+    pending_submission: Option<Pending<Result<(), String>>>,
+    pending_rankings: Option<Pending<Result<Rankings, String>>>,
+}
+
+impl Leaderboard {
+    /// Rankings of the `period`, of all results or only of the ones with the `assists`
+    pub fn fetch_rankings(
+        &mut self,
+        endpoint: &str,
+        period: Period,
+        assists: Option<AssistPreset>,
+    ) {
+        let mut url = match period {
+            Period::Global => format!("{endpoint}/rankings?period=global"),
+            Period::Daily => format!("{endpoint}/rankings?period=daily&day={}", clock::today()),
+        };
+        if let Some(assists) = assists {
+            url += &format!("&assists={}", assists.name());
+        }
+        self.rankings = Status::Pending;
+        self.pending_rankings = Some(Pending::spawn(move || {
+            let body = http::get(&url)?;
+            serde_json::from_slice(&body).map_err(|err| format!("malformed rankings: {err}"))
+        }));
+    }
+}
+
+fn submit_wins(
+    mut game_over: EventReader<GameOver>,
+    settings: Res<Settings>,
+    board: Res<Board>,
+    game: Res<Game>,
+    score: Res<Score>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    for GameOver(outcome) in game_over.iter() {
+        leaderboard.submission = Status::Idle;
+        // relaxed and scripted games have no time to compete with, while assisted ones are told apart by their preset.
+        // Hand-made boards can't be checked against the seed.
+        if *outcome != Outcome::Won
+            || !settings.leaderboard.enabled
+            || !game.is_ranked(&board)
+            || board.layout.is_some()
+        {
+            continue;
+        }
+
+        let submission = Submission {
+            player: match settings.player_name.as_str() {
+                "" => "anonymous".to_string(),
+                name => name.to_string(),
+            },
+            time_ms: game.elapsed.as_millis() as u64,
+            score: score.points,
+            config: board.config,
+            seed: format!("{:016x}", board.seed),
+            replay_hash: format!("{:016x}", game.replay.hash()),
+            day: clock::today(),
+            assists: game.assists,
+        };
+        let url = format!("{}/scores", settings.leaderboard.endpoint);
+        leaderboard.submission = Status::Pending;
+        leaderboard.pending_submission = Some(Pending::spawn(move || {
+            let body = serde_json::to_vec(&submission).map_err(|err| err.to_string())?;
+            http::post_json(&url, &body).map(|_| ())
+        }));
+    }
+}
+
+fn poll_requests(mut leaderboard: ResMut<Leaderboard>) {
+    fn finish<T>(pending: &mut Option<Pending<Result<T, String>>>, status: &mut Status<T>)
+    where
+        T: Send + 'static,
+    {
+        let Some(result) = pending.as_ref().and_then(Pending::poll) else {
+            return;
+        };
+        *pending = None;
+        *status = match result.and_then(|result| result) {
+            Ok(value) => Status::Done(value),
+            Err(err) => {
+                warn!("Leaderboard request failed: {err}");
+                Status::Failed(err)
+            }
+        };
+    }
+
+    // avoid triggering change detection every frame
+    if leaderboard.pending_submission.is_none() && leaderboard.pending_rankings.is_none() {
+        return;
+    }
+    let leaderboard = &mut *leaderboard;
+    finish(
+        &mut leaderboard.pending_submission,
+        &mut leaderboard.submission,
+    );
+    finish(&mut leaderboard.pending_rankings, &mut leaderboard.rankings);
+}
@@ -0,0 +1,1235 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::{HashMap, HashSet},
+    window::{PrimaryWindow, WindowResized},
+};
+use hexx::{Hex, HexLayout, HexOrientation};
+
+mod assist;
+mod autosave;
+mod background;
+mod blind;
+pub mod board;
+pub mod board_text;
+mod camera;
+mod card;
+mod challenge;
+mod chat_vote;
+mod clipboard;
+mod clock;
+mod clues;
+mod colors;
+mod coop;
+mod daily;
+#[cfg(feature = "debug-tools")]
+mod debug;
+mod decals;
+mod defuse;
+mod dual;
+mod editor;
+mod endless;
+mod export;
+mod fonts;
+pub mod game;
+mod gamelog;
+mod geometry;
+mod ghost;
+mod gradient;
+mod gravity;
+mod history;
+mod hotseat;
+mod idle;
+mod kernel;
+mod lan;
+mod leaderboard;
+mod lobby;
+mod lod;
+mod magnifier;
+mod mask;
+mod mbf;
+mod mini;
+mod mods;
+mod net;
+mod notes;
+mod picture;
+mod placement;
+mod playback;
+mod powerups;
+mod profile;
+mod puzzle;
+pub mod replay;
+mod result_text;
+mod rng;
+mod score;
+pub mod server;
+mod settings;
+mod share;
+pub mod sim;
+mod sketch;
+mod solver;
+mod speedrun;
+mod stats;
+mod storage;
+mod streamer;
+mod style;
+mod theme;
+mod title;
+mod tournament;
+mod treasure;
+mod tutorial;
+mod ui;
+mod versus;
+
+use board::Board;
+use fonts::DigitFont;
+use game::{
+    ActionRequest, CellsChanged, Game, GameAction, GameSet, NewCustomGame, NewGame, RowsCollapsed,
+};
+use geometry::Geometry;
+use settings::{NumberFont, NumberStyle, Settings};
+use style::CellMaterial;
+use theme::Theme;
+
+const TEXTURE_SIZE: Vec2 = Vec2::splat(26.0);
+const HEX_SIZE: Vec2 = Vec2::splat(16.0);
+/// Vertical distance between rows of pointy hexes
+const ROW_HEIGHT: f32 = HEX_SIZE.y * 1.5;
+/// In pixels per second
+const SLIDE_SPEED: f32 = 240.0;
+/// Refused chords press the hexes around without opening them
+const PRESS_TIME: f32 = 0.15;
+const PRESSED_SCALE: f32 = 0.8;
+/// Mines are shown through the cover in sandbox mode
+const COVERED_MINE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.35);
+/// Free space around a fitted board, in pixels
+const VIEW_MARGIN: f32 = 16.0;
+/// Dot on the hex of the last action
+const LAST_MOVE_COLOR: Color = Color::rgba(1.0, 0.8, 0.2, 0.8);
+/// Cross over flags that turned out wrong when the game is over
+const WRONG_FLAG_COLOR: Color = Color::rgb(0.95, 0.1, 0.1);
+/// Layout of hexes in the world. Boards are shown turned and mirrored by the [`BoardView`] as the player likes,
+/// so board codes, replays and pictures of boards don't depend on it.
+const GRID_LAYOUT: HexLayout = HexLayout {
+    orientation: HexOrientation::Pointy,
+    hex_size: HEX_SIZE,
+    origin: Vec2::ZERO,
+    invert_x: false,
+    invert_y: false,
+};
+
+/// Runs the game, see `main.rs`
+pub fn run() {
+    let default_plugins = DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: title::TITLE.to_string(),
+            // any size works, boards are fitted into the window, see `BoardView::fit`
+            resolution: (916.0, 800.0).into(),
+            ..default()
+        }),
+        ..default()
+    });
+    // sprites are reloaded as soon as they change on disk, handy when working on a custom skin
+    #[cfg(feature = "hot-reload")]
+    let default_plugins = default_plugins.set(AssetPlugin {
+        watch_for_changes: bevy::asset::ChangeWatcher::with_delay(
+            std::time::Duration::from_millis(200),
+        ),
+        ..default()
+    });
+
+    let mut app = App::new();
+    app.add_plugins(default_plugins)
+        .add_state::<AppState>()
+        .add_plugins((
+            settings::SettingsPlugin,
+            theme::ThemePlugin,
+            mods::ModsPlugin,
+            game::GamePlugin,
+            gamelog::GameLogPlugin,
+            leaderboard::LeaderboardPlugin,
+            coop::CoopPlugin,
+            daily::DailyPlugin,
+            stats::StatsPlugin,
+            history::HistoryPlugin,
+            playback::PlaybackPlugin,
+            editor::EditorPlugin,
+            versus::VersusPlugin,
+            lan::LanPlugin,
+            hotseat::HotSeatPlugin,
+        ))
+        .add_plugins((
+            assist::AssistPlugin,
+            autosave::AutosavePlugin,
+            blind::BlindPlugin,
+            defuse::DefusePlugin,
+            dual::DualPlugin,
+            endless::EndlessPlugin,
+            ghost::GhostPlugin,
+            idle::IdlePlugin,
+            powerups::PowerUpsPlugin,
+            score::ScorePlugin,
+            speedrun::SpeedrunPlugin,
+            tournament::TournamentPlugin,
+            treasure::TreasurePlugin,
+            tutorial::TutorialPlugin,
+            ui::ScreensPlugin,
+        ))
+        .add_plugins((
+            card::CardPlugin,
+            chat_vote::ChatVotePlugin,
+            lobby::LobbyPlugin,
+        ))
+        // presentation only, none of these change the game
+        .add_plugins((
+            background::BackgroundPlugin,
+            camera::CameraPlugin,
+            fonts::NumberFontPlugin,
+            lod::LodPlugin,
+            magnifier::MagnifierPlugin,
+            mini::MiniModePlugin,
+            notes::NotesPlugin,
+            picture::PicturePlugin,
+            placement::WindowPlacementPlugin,
+            sketch::SketchPlugin,
+            streamer::StreamerPlugin,
+            style::GridStylePlugin,
+            title::TitlePlugin,
+        ))
+        .add_systems(PreStartup, load_sprites)
+        .add_systems(Startup, setup)
+        .init_resource::<CursorPos>()
+        .init_resource::<Slide>()
+        .init_resource::<InputQueue>()
+        .init_resource::<BoardView>()
+        .add_systems(
+            Update,
+            rotate_board
+                .run_if(in_state(AppState::Playing))
+                .before(refit_view),
+        )
+        .add_systems(
+            Update,
+            (
+                update_cursor_pos,
+                highlight_cursor_pos,
+                // dragging draws sketches instead, and the input that ends an idle pause is spent on it
+                handle_input.run_if(
+                    not(resource_exists::<sketch::Sketching>())
+                        .and_then(not(resource_exists::<idle::Idle>())),
+                ),
+                flush_input,
+            )
+                .chain()
+                .in_set(GameSet::Input)
+                .run_if(in_state(AppState::Playing))
+                .run_if(not(resource_exists::<camera::FocusPull>())),
+        )
+        .add_systems(
+            Update,
+            (
+                spawn_cells,
+                refit_view,
+                redraw_cells,
+                keep_upright,
+                apply_fog,
+                apply_darkness,
+                slide_cells,
+                press_cells,
+                mark_last_move,
+                apply_view,
+            )
+                .chain()
+                .after(GameSet::Actions),
+        );
+    #[cfg(feature = "debug-tools")]
+    app.add_plugins(debug::DebugPlugin);
+    app.run();
+}
+
+/// Top-level state of the app. Board input is processed only while `Playing`, all other states are screens
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum AppState {
+    #[default]
+    Playing,
+    Paused,
+    NewGameSetup,
+    GameOver,
+    Leaderboard,
+    Coop,
+    Versus,
+    PlayFromCode,
+    Stats,
+    History,
+    Profile,
+    Editor,
+    Puzzles,
+    Help,
+    Settings,
+    Tournament,
+    ChatVote,
+    ThemeEditor,
+    Lobby,
+}
+
+/// Rendering side of the board, one entity per hex
+#[derive(Resource)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect), reflect(Resource))]
+struct HexGrid {
+    #[cfg_attr(feature = "debug-tools", reflect(ignore))]
+    entities: HashMap<Hex, Entity>,
+    /// Plain hex for overlays, styled by `style.rs` like the cells
+    mesh: Handle<Mesh>,
+    /// Hex of the cells themselves, with outlines and shadows when they are enabled
+    cell_mesh: Handle<Mesh>,
+    /// Mirrored meshes for [`Geometry::is_flipped`] cells, see [`Self::mesh_at`]
+    flipped_mesh: Handle<Mesh>,
+    flipped_cell_mesh: Handle<Mesh>,
+    /// Face and cell meshes of every cell of an organic board, which all have their own shapes
+    #[cfg_attr(feature = "debug-tools", reflect(ignore))]
+    organic_meshes: HashMap<Hex, (Handle<Mesh>, Handle<Mesh>)>,
+    covered_material: Handle<CellMaterial>,
+    uncovered_material: Handle<CellMaterial>,
+    /// Covered hexes while they are [`Pressed`]
+    pressed_material: Handle<CellMaterial>,
+    /// Tinted cells of a zoomed out board, by [`lod::Summary`]
+    summary_materials: [Handle<CellMaterial>; 3],
+    /// Clue under [`Rules::directional`](board::Rules::directional), pointing right before it's rotated
+    arrow_mesh: Handle<Mesh>,
+    arrow_material: Handle<ColorMaterial>,
+    /// Overlay of the hex under the cursor, colored by the theme
+    highlight_material: Handle<ColorMaterial>,
+}
+
+impl HexGrid {
+    /// Overlay mesh for `hex` of the board
+    fn mesh_at(&self, geometry: Geometry, hex: Hex) -> Handle<Mesh> {
+        if let Some((face, _)) = self.organic_meshes.get(&hex) {
+            return face.clone();
+        }
+        match geometry.is_flipped(hex) {
+            true => self.flipped_mesh.clone(),
+            false => self.mesh.clone(),
+        }
+    }
+
+    fn cell_mesh_at(&self, geometry: Geometry, hex: Hex) -> Handle<Mesh> {
+        if let Some((_, cell)) = self.organic_meshes.get(&hex) {
+            return cell.clone();
+        }
+        match geometry.is_flipped(hex) {
+            true => self.flipped_cell_mesh.clone(),
+            false => self.cell_mesh.clone(),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Sprites {
+    /// Textures to display numbers. Number 1 lives under index 0 and so on.
+    numbers: [SpriteBundle; 6],
+    mine: SpriteBundle,
+    sign: SpriteBundle,
+    treasure: SpriteBundle,
+    /// Terrain of empty hexes, indexed by [`decals::decal`]
+    decals: [SpriteBundle; decals::BIOMES],
+}
+
+fn load_sprites(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let load_sprite = |path: &str| SpriteBundle {
+        texture: asset_server.load(path),
+        sprite: Sprite {
+            custom_size: Some(TEXTURE_SIZE),
+            ..default()
+        },
+        transform: Transform::from_xyz(0.0, 0.0, 2.0),
+        ..default()
+    };
+
+    commands.insert_resource(Sprites {
+        numbers: (1..=6).map(|i| format!("{i}.png")).enumerate().fold(
+            Default::default(),
+            |mut acc, (i, path)| {
+                acc[i] = load_sprite(&path);
+                acc
+            },
+        ),
+        mine: load_sprite("mine.png"),
+        sign: load_sprite("sign.png"),
+        treasure: load_sprite("treasure.png"),
+        decals: ["grass", "sand", "stone"].map(|name| {
+            let mut decal = load_sprite(&format!("decals/{name}.png"));
+            // under numbers and everything else on the hex
+            decal.transform.translation.z = 1.0;
+            decal
+        }),
+    });
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut cell_materials: ResMut<Assets<CellMaterial>>,
+    settings: Res<Settings>,
+    theme: Res<Theme>,
+    board: Res<Board>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // meshes
+    let (style, geometry) = (&settings.grid_style, board.config.rules.geometry);
+    let mesh_handle = meshes.add(style::face_mesh(style, geometry, false));
+    let highlight_material = materials.add(theme.highlight.into());
+
+    commands.insert_resource(HexGrid {
+        entities: HashMap::new(),
+        mesh: mesh_handle.clone(),
+        cell_mesh: meshes.add(style::cell_mesh(style, geometry, false)),
+        flipped_mesh: meshes.add(style::face_mesh(style, geometry, true)),
+        flipped_cell_mesh: meshes.add(style::cell_mesh(style, geometry, true)),
+        organic_meshes: HashMap::new(),
+        covered_material: cell_materials.add(CellMaterial::covered(style, &theme, geometry)),
+        uncovered_material: cell_materials.add(CellMaterial::uncovered(style, &theme, geometry)),
+        pressed_material: cell_materials.add(CellMaterial::pressed(style, &theme, geometry)),
+        summary_materials: lod::Summary::ALL.map(|summary| {
+            cell_materials.add(CellMaterial::summary(summary, style, &theme, geometry))
+        }),
+        arrow_mesh: meshes.add(arrow()),
+        arrow_material: materials.add(Color::BLACK.into()),
+        highlight_material: highlight_material.clone(),
+    });
+
+    // Use a separate entity to highlight hex under the cursor
+    commands
+        .spawn(ColorMesh2dBundle {
+            mesh: mesh_handle.clone().into(),
+            material: highlight_material,
+            // default visibility is hidden
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(HighlightHex);
+
+    commands.spawn((
+        ColorMesh2dBundle {
+            transform: Transform::from_scale(Vec3::splat(0.25)),
+            mesh: mesh_handle.into(),
+            material: materials.add(LAST_MOVE_COLOR.into()),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        LastMove,
+    ));
+}
+
+/// Part of the world shown in the window, so boards of any size and shape fit into it
+#[derive(Resource, Clone, Copy, PartialEq)]
+struct BoardView {
+    center: Vec2,
+    /// World units per pixel
+    scale: f32,
+    /// Counterclockwise turn of the board on the screen in radians, see [`Settings::board_rotation`]
+    rotation: f32,
+    /// `-1` on the axes of the screen the board is mirrored along, applied before the rotation
+    flip: Vec2,
+}
+
+impl Default for BoardView {
+    fn default() -> Self {
+        Self {
+            center: Vec2::ZERO,
+            scale: 1.0,
+            rotation: 0.0,
+            flip: Vec2::ONE,
+        }
+    }
+}
+
+impl BoardView {
+    /// Centers the `hexes` and zooms out until they fit into the window with [`VIEW_MARGIN`] around,
+    /// leaving out the webcam strip of the streamer mode, see [`streamer::webcam_margin`].
+    /// Small boards are zoomed in no further than [`GridStyle::max_zoom`](settings::GridStyle::max_zoom).
+    fn fit(
+        hexes: impl IntoIterator<Item = Hex>,
+        geometry: Geometry,
+        window: &Window,
+        settings: &Settings,
+    ) -> Self {
+        let webcam = streamer::webcam_margin(settings, window);
+        let area = Vec2::new(window.width() - webcam, window.height());
+        let mut view = Self::fit_into(hexes, geometry, area, settings);
+        // the board is centered in the window without the webcam strip
+        view.center +=
+            Vec2::from_angle(view.rotation).rotate(Vec2::X * view.flip * webcam / 2.0 * view.scale);
+        view
+    }
+
+    /// Same as [`Self::fit`], but into an `area` of the given size in logical pixels, such as a viewport
+    fn fit_into(
+        hexes: impl IntoIterator<Item = Hex>,
+        geometry: Geometry,
+        area: Vec2,
+        settings: &Settings,
+    ) -> Self {
+        let (rotation, flip) = Self::orientation(settings);
+        // the board is measured as it's turned on the screen, mirroring doesn't change the size
+        let to_screen = Vec2::from_angle(-rotation);
+        let extent = geometry.half_extent();
+        let half_cell = Vec2::new(
+            to_screen.x.abs() * extent.x + to_screen.y.abs() * extent.y,
+            to_screen.y.abs() * extent.x + to_screen.x.abs() * extent.y,
+        );
+        let (min, max) = hexes.into_iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), hex| {
+                let pos = to_screen.rotate(geometry.to_world(hex)) * flip;
+                (min.min(pos - half_cell), max.max(pos + half_cell))
+            },
+        );
+        if min.x > max.x {
+            return Self::default();
+        }
+        let size = max - min;
+        let available = (area - 2.0 * VIEW_MARGIN).max(Vec2::ONE);
+        let scale = (size.x / available.x)
+            .max(size.y / available.y)
+            .max(1.0 / settings.grid_style.max_zoom);
+        let center = (min + max) / 2.0;
+        Self {
+            center: Vec2::from_angle(rotation).rotate(center * flip),
+            scale,
+            rotation,
+            flip,
+        }
+    }
+
+    /// Rotation and mirroring of the board on the screen. Flat-topped hexes are pointy ones turned by 30°.
+    fn orientation(settings: &Settings) -> (f32, Vec2) {
+        let style = &settings.grid_style;
+        let mut rotation = settings.board_rotation as f32 * std::f32::consts::FRAC_PI_3;
+        if style.flat_top {
+            rotation += std::f32::consts::FRAC_PI_6;
+        }
+        let flip = Vec2::new(
+            if style.invert_x { -1.0 } else { 1.0 },
+            if style.invert_y { -1.0 } else { 1.0 },
+        );
+        (rotation, flip)
+    }
+
+    /// Turns and mirrors the world the way the board is shown, for things that have to stay upright on the screen
+    fn upright(self) -> Mat4 {
+        Mat4::from_rotation_z(self.rotation) * Mat4::from_scale(self.flip.extend(1.0))
+    }
+
+    /// World position of a position in the window, with (0,0) in the top left corner
+    fn to_world(self, window_pos: Vec2, window: &Window) -> Vec2 {
+        self.to_world_in(window_pos, Vec2::new(window.width(), window.height()))
+    }
+
+    /// World position of a position in an area of the given size shown by this view, such as a viewport
+    fn to_world_in(self, pos: Vec2, area: Vec2) -> Vec2 {
+        let from_center = Vec2::new(pos.x - area.x / 2.0, area.y / 2.0 - pos.y);
+        self.center + Vec2::from_angle(self.rotation).rotate(from_center * self.flip * self.scale)
+    }
+}
+
+fn apply_view(
+    view: Res<BoardView>,
+    mut cameras: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (
+            With<Camera2d>,
+            Without<magnifier::MagnifierCamera>,
+            Without<dual::DualCamera>,
+        ),
+    >,
+) {
+    if !view.is_changed() {
+        return;
+    }
+    for (mut transform, mut projection) in &mut cameras {
+        transform.translation.x = view.center.x;
+        transform.translation.y = view.center.y;
+        transform.rotation = Quat::from_rotation_z(view.rotation);
+        transform.scale = view.flip.extend(1.0);
+        projection.scale = view.scale;
+    }
+}
+
+/// Replaces all hex entities when a new board is generated
+#[allow(clippy::too_many_arguments)]
+fn spawn_cells(
+    mut commands: Commands,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    mut grid: ResMut<HexGrid>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut view: ResMut<BoardView>,
+    mut slide: ResMut<Slide>,
+    mut queue: ResMut<InputQueue>,
+) {
+    if !(any_events(&mut new_games) | any_events(&mut custom_games)) {
+        return;
+    }
+
+    // the slide and the clicks held back during it were about the previous board
+    slide.0 = 0.0;
+    queue.0.clear();
+    for entity in grid.entities.values() {
+        commands.entity(*entity).despawn_recursive();
+    }
+    let geometry = board.config.rules.geometry;
+    grid.organic_meshes = match geometry {
+        Geometry::Organic => board
+            .cells
+            .iter()
+            .map(|hex| {
+                let (face, cell) = style::organic_meshes(&settings.grid_style, *hex);
+                (*hex, (meshes.add(face), meshes.add(cell)))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    };
+    let material = grid.covered_material.clone();
+    grid.entities = board
+        .cells
+        .iter()
+        .map(|&hex| {
+            let pos = geometry.to_world(hex);
+            let id = commands
+                .spawn(MaterialMesh2dBundle {
+                    transform: Transform::from_xyz(pos.x, pos.y, 0.0),
+                    mesh: grid.cell_mesh_at(geometry, hex).into(),
+                    material: material.clone(),
+                    ..default()
+                })
+                .id();
+            (hex, id)
+        })
+        .collect();
+    view.set_if_neq(BoardView::fit(
+        board.cells.iter().copied(),
+        board.config.rules.geometry,
+        windows.single(),
+        &settings,
+    ));
+}
+
+/// `[` and `]` turn the board a sixth of a turn counterclockwise and clockwise
+fn rotate_board(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    let turn = keys.just_pressed(KeyCode::BracketLeft) as i32
+        - keys.just_pressed(KeyCode::BracketRight) as i32;
+    if turn != 0 {
+        settings.board_rotation = (settings.board_rotation as i32 + turn).rem_euclid(6) as u8;
+    }
+}
+
+/// Keeps the board fitted into the window when it's resized or the max zoom, webcam strip, rotation or mirroring changes.
+/// The editor has its own fixed view.
+fn refit_view(
+    mut resized: EventReader<WindowResized>,
+    state: Res<State<AppState>>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    mut fitted_with: Local<Option<(f32, f32, (f32, Vec2))>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut view: ResMut<BoardView>,
+) {
+    let window = windows.single();
+    let fitting = (
+        settings.grid_style.max_zoom,
+        streamer::webcam_margin(&settings, window),
+        BoardView::orientation(&settings),
+    );
+    let fitting_changed = fitted_with
+        .replace(fitting)
+        .is_some_and(|fitted| fitted != fitting);
+    if (!any_events(&mut resized) && !fitting_changed) || *state.get() == AppState::Editor {
+        return;
+    }
+    view.set_if_neq(BoardView::fit(
+        board.cells.iter().copied(),
+        board.config.rules.geometry,
+        window,
+        &settings,
+    ));
+}
+
+/// Syncs hex entities with the board state
+#[allow(clippy::too_many_arguments)]
+fn redraw_cells(
+    mut commands: Commands,
+    mut cells_changed: EventReader<CellsChanged>,
+    board: Res<Board>,
+    game: Res<Game>,
+    settings: Res<Settings>,
+    grid: Res<HexGrid>,
+    textures: Res<Sprites>,
+    font: Res<DigitFont>,
+    theme: Res<Theme>,
+    mut drawn_with: Local<Option<(bool, NumberStyle, Color, [Color; 6])>>,
+) {
+    let effective = settings.effective_numbers;
+    let style = &settings.number_style;
+    let mut changed: HashSet<Hex> = cells_changed
+        .iter()
+        .flat_map(|CellsChanged(hexes)| hexes)
+        .copied()
+        .collect();
+    // numbers look different with other settings, or in a font that is loaded just now
+    let look = (effective, style.clone(), theme.flag, theme.numbers);
+    if drawn_with.as_ref() != Some(&look) || font.is_changed() {
+        *drawn_with = Some(look);
+        changed.extend(board.cells.iter().copied());
+    } else if effective {
+        // flags change the numbers around them
+        let around: Vec<_> = changed
+            .iter()
+            .flat_map(|hex| board.counted_by(*hex))
+            .collect();
+        changed.extend(around);
+    }
+    for hex in &changed {
+        let Some(&entity) = grid.entities.get(hex) else {
+            continue;
+        };
+        let mut cell = commands.entity(entity);
+        cell.despawn_descendants();
+
+        let covered = board.covered.contains(hex);
+        let flagged = covered && board.flagged.contains(hex);
+        let is_mine = board.mines.contains(hex);
+        let colored = board.config.rules.colored;
+        let sprite = if flagged {
+            let mut sign = textures.sign.clone();
+            sign.sprite.color = theme.flag;
+            if colored {
+                sign.sprite.color = match board.red_flags.contains(hex) {
+                    true => colors::RED,
+                    false => colors::BLUE,
+                };
+            }
+            Some(sign)
+        } else if is_mine && (!covered || board.exploded.is_some()) {
+            let mut mine = textures.mine.clone();
+            if colored {
+                mine.sprite.color = match board.red_mines.contains(hex) {
+                    true => colors::RED,
+                    false => colors::BLUE,
+                };
+            }
+            Some(mine)
+        } else if !covered && board.treasures.contains_key(hex) {
+            Some(textures.treasure.clone())
+        } else if is_mine && (board.config.rules.sandbox || board.scanned.contains(hex)) {
+            // known but still covered
+            let mut mine = textures.mine.clone();
+            mine.sprite.color = COVERED_MINE_COLOR;
+            Some(mine)
+        } else {
+            None
+        };
+        let number = board.numbers.get(hex).filter(|_| !covered).copied();
+        let red = board.red_numbers.get(hex).copied().unwrap_or(0);
+        // mines left to flag instead of all mines around
+        let (number, red) = match number {
+            Some(number) if effective => {
+                let flags: Vec<_> = board
+                    .neighbors(*hex)
+                    .filter(|hex| board.flagged.contains(hex))
+                    .collect();
+                let red_flags = flags
+                    .iter()
+                    .filter(|hex| board.red_flags.contains(*hex))
+                    .count() as u8;
+                let blue_flags = flags.len() as u8 - red_flags;
+                let red_left = red.saturating_sub(red_flags);
+                let blue_left = (number - red).saturating_sub(blue_flags);
+                (
+                    Some(blue_left + red_left).filter(|left| *left > 0),
+                    red_left,
+                )
+            }
+            number => (number, red),
+        };
+        let clue = board.clues.get(hex).filter(|_| !covered).map(|clue| {
+            let direction =
+                (board.config.rules.geometry).to_world(clues::directions()[*clue as usize]);
+            Quat::from_rotation_z(direction.y.atan2(direction.x))
+        });
+
+        cell.insert(if covered {
+            grid.covered_material.clone()
+        } else {
+            grid.uncovered_material.clone()
+        });
+        let decal = decals::decal(&board, *hex);
+        cell.with_children(|parent| {
+            if let Some(decal) = decal {
+                parent.spawn(textures.decals[decal].clone());
+            }
+            match sprite {
+                Some(sprite) if flagged => {
+                    parent.spawn((sprite, FlagSprite(*hex)));
+                    if game.outcome.is_some() && !board.is_correct_flag(*hex) {
+                        spawn_cross(parent);
+                    }
+                }
+                Some(sprite) => {
+                    parent.spawn(sprite);
+                }
+                None => {}
+            }
+            // arrows replace numbers, so they are hidden the same way
+            if let Some(rotation) = clue {
+                parent.spawn((
+                    ColorMesh2dBundle {
+                        mesh: grid.arrow_mesh.clone().into(),
+                        material: grid.arrow_material.clone(),
+                        transform: Transform::from_xyz(0.0, 0.0, 2.0).with_rotation(rotation),
+                        ..default()
+                    },
+                    CellNumber(*hex),
+                ));
+                return;
+            }
+            match number {
+                // blue and red counts side by side
+                Some(number) if colored => {
+                    for (count, color, x) in
+                        [(number - red, colors::BLUE, -5.0), (red, colors::RED, 5.0)]
+                    {
+                        parent.spawn((
+                            Text2dBundle {
+                                text: Text::from_section(
+                                    count.to_string(),
+                                    TextStyle {
+                                        font: font.0.clone(),
+                                        font_size: TEXTURE_SIZE.y * 0.6 * style.size,
+                                        color,
+                                    },
+                                ),
+                                transform: Transform::from_xyz(x, 0.0, 2.0),
+                                ..default()
+                            },
+                            CellNumber(*hex),
+                        ));
+                    }
+                }
+                Some(number @ 1..=6) if style.font == NumberFont::Pictures => {
+                    let mut sprite = textures.numbers[number as usize - 1].clone();
+                    sprite.sprite.color = theme.numbers[number as usize - 1];
+                    sprite.transform.scale = Vec3::splat(style.size);
+                    parent.spawn((sprite, CellNumber(*hex)));
+                }
+                // only extended adjacency and large kernels produce numbers without textures
+                Some(number) => {
+                    parent.spawn((
+                        Text2dBundle {
+                            text: Text::from_section(
+                                number.to_string(),
+                                TextStyle {
+                                    font: font.0.clone(),
+                                    font_size: TEXTURE_SIZE.y * style.size,
+                                    color: theme.digit(number),
+                                },
+                            ),
+                            transform: Transform::from_xyz(0.0, 0.0, 2.0),
+                            ..default()
+                        },
+                        CellNumber(*hex),
+                    ));
+                }
+                None => {}
+            }
+        });
+    }
+}
+
+/// Turns and mirrors pictures and numbers on the cells back against the [`BoardView`], so they stay upright
+/// however the board is shown. Clue arrows are meshes, they point along the board and turn with it.
+fn keep_upright(
+    view: Res<BoardView>,
+    mut applied: Local<Mat4>,
+    cells: Query<(), With<Handle<CellMaterial>>>,
+    mut pieces: ParamSet<(
+        Query<(&Parent, &mut Transform), (Or<(With<Sprite>, With<Text>)>, Without<Node>)>,
+        Query<
+            (&Parent, &mut Transform),
+            (
+                Or<(With<Sprite>, With<Text>)>,
+                Without<Node>,
+                Added<Transform>,
+            ),
+        >,
+    )>,
+) {
+    let upright = view.upright();
+    let change = upright * applied.inverse();
+    let changed = upright != *applied;
+    *applied = upright;
+    // around the cell center, so side by side numbers of colored mines stay side by side
+    let turn = |parent: &Parent, transform: &mut Transform, by: Mat4| {
+        if cells.contains(parent.get()) {
+            *transform = Transform::from_matrix(by * transform.compute_matrix());
+        }
+    };
+    if changed {
+        for (parent, mut transform) in &mut pieces.p0() {
+            let by = match transform.is_added() {
+                true => upright,
+                false => change,
+            };
+            turn(parent, &mut transform, by);
+        }
+    } else if upright != Mat4::IDENTITY {
+        for (parent, mut transform) in &mut pieces.p1() {
+            turn(parent, &mut transform, upright);
+        }
+    }
+}
+
+/// Two bars crossing out a wrong flag
+fn spawn_cross(parent: &mut ChildBuilder) {
+    for angle in [std::f32::consts::FRAC_PI_4, -std::f32::consts::FRAC_PI_4] {
+        parent.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: WRONG_FLAG_COLOR,
+                custom_size: Some(Vec2::new(TEXTURE_SIZE.x, 3.0)),
+                ..default()
+            },
+            // above the flag
+            transform: Transform::from_xyz(0.0, 0.0, 3.0)
+                .with_rotation(Quat::from_rotation_z(angle)),
+            ..default()
+        });
+    }
+}
+
+/// Flag on a covered hex
+#[derive(Component)]
+struct FlagSprite(Hex);
+
+/// Number on an uncovered hex, hidden outside of the visible area under [`Rules::fog`](board::Rules::fog)
+/// and once it's forgotten under [`Rules::blind`](board::Rules::blind)
+#[derive(Component)]
+struct CellNumber(Hex);
+
+fn apply_fog(
+    board: Res<Board>,
+    cursor_pos: Res<CursorPos>,
+    memory: Res<blind::Memory>,
+    mut numbers: Query<(&CellNumber, &mut Visibility)>,
+) {
+    for (CellNumber(hex), mut visibility) in &mut numbers {
+        let visible = match (board.config.rules.fog, cursor_pos.0) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(radius), Some(cursor)) => {
+                board.config.rules.geometry.distance(*hex, cursor) <= radius
+            }
+        } && (!board.config.rules.blind || memory.is_shown(*hex));
+        let expected = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != expected {
+            *visibility = expected;
+        }
+    }
+}
+
+/// Hexes outside of the lit area under [`Rules::darkness`](board::Rules::darkness) are not drawn at all
+fn apply_darkness(board: Res<Board>, grid: Res<HexGrid>, mut cells: Query<&mut Visibility>) {
+    for (hex, entity) in &grid.entities {
+        let Ok(mut visibility) = cells.get_mut(*entity) else {
+            continue;
+        };
+        let expected = if board.is_lit(*hex) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != expected {
+            *visibility = expected;
+        }
+    }
+}
+
+/// Distance the cells still have to slide down after rows collapsed under [`Rules::gravity`](board::Rules::gravity).
+/// Cells are redrawn with the new content right away, so they start above their place and slide into it.
+#[derive(Resource, Default)]
+struct Slide(f32);
+
+fn slide_cells(
+    time: Res<Time>,
+    mut collapsed: EventReader<RowsCollapsed>,
+    mut slide: ResMut<Slide>,
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    mut cells: Query<&mut Transform>,
+) {
+    for RowsCollapsed(rows) in collapsed.iter() {
+        slide.0 += *rows as f32 * ROW_HEIGHT;
+    }
+    if slide.0 <= 0.0 {
+        return;
+    }
+    slide.0 = (slide.0 - SLIDE_SPEED * time.delta_seconds()).max(0.0);
+    for (hex, entity) in &grid.entities {
+        if let Ok(mut transform) = cells.get_mut(*entity) {
+            transform.translation.y = board.config.rules.geometry.to_world(*hex).y + slide.0;
+        }
+    }
+}
+
+/// Whether any events came since the last run. Reads all of them, unlike `iter().any()` that stops at the first
+/// one and leaves the rest for the next run, so readers are combined with `|` rather than `||`.
+fn any_events<E: Event>(events: &mut EventReader<E>) -> bool {
+    events.iter().count() > 0
+}
+
+/// Current cursor position in within hex grid
+#[derive(Copy, Clone, Default, PartialEq, Resource)]
+struct CursorPos(Option<Hex>);
+
+/// Hex under the cursor, whether it's on the board or not
+fn cursor_hex(window: &Window, view: &BoardView, geometry: Geometry) -> Option<Hex> {
+    let cursor_pos = view.to_world(window.cursor_position()?, window);
+    Some(geometry.from_world(cursor_pos))
+}
+
+fn update_cursor_pos(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    view: Res<BoardView>,
+    dual: Option<Res<dual::DualViews>>,
+    slide: Res<Slide>,
+    board: Res<Board>,
+    mut cursor_pos: ResMut<CursorPos>,
+) {
+    let window = windows.single();
+    // sliding cells are drawn above their place on the board, and the click is meant for the drawn one
+    cursor_pos.0 = window
+        .cursor_position()
+        .map(|pos| match &dual {
+            Some(dual) => dual.to_world(pos, window),
+            None => view.to_world(pos, window),
+        })
+        .map(|pos| pos - Vec2::new(0.0, slide.0))
+        .map(|pos| board.config.rules.geometry.from_world(pos))
+        .filter(|hex| board.contains(*hex));
+}
+
+/// Actions of the local player in the order they were made, held back while the board is animated
+/// so they are applied to the settled board instead of the one that is still moving
+#[derive(Resource, Default)]
+struct InputQueue(Vec<GameAction>);
+
+fn flush_input(
+    slide: Res<Slide>,
+    mut queue: ResMut<InputQueue>,
+    mut actions: EventWriter<ActionRequest>,
+) {
+    if slide.0 > 0.0 || queue.0.is_empty() {
+        return;
+    }
+    actions.send_batch(queue.0.drain(..).map(ActionRequest));
+}
+
+#[derive(Component)]
+struct HighlightHex;
+
+/// Marker on the hex of the last action, which stays on the exploded mine after a loss
+#[derive(Component)]
+struct LastMove;
+
+fn mark_last_move(
+    board: Res<Board>,
+    game: Res<Game>,
+    mut markers: Query<(&mut Transform, &mut Visibility), With<LastMove>>,
+) {
+    if !board.is_changed() && !game.is_changed() {
+        return;
+    }
+    let last = board
+        .exploded
+        .or_else(|| game.replay.steps.last().map(|step| step.action().hex()));
+    for (mut transform, mut visibility) in &mut markers {
+        let Some(hex) = last.filter(|hex| board.contains(*hex)) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Visible;
+        let geometry = board.config.rules.geometry;
+        let pos = geometry.to_world(hex);
+        // at the top of the hex, where it doesn't cover numbers, and above the sprites
+        let top = geometry.half_extent().y * 0.6;
+        transform.translation = Vec3::new(pos.x, pos.y + top, 4.0);
+    }
+}
+
+fn highlight_cursor_pos(
+    cursor_pos: Res<CursorPos>,
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    mut prev_pos: Local<CursorPos>,
+    mut highlight_hex: Query<
+        (&mut Transform, &mut Visibility, &mut Mesh2dHandle),
+        With<HighlightHex>,
+    >,
+) {
+    if *prev_pos == *cursor_pos {
+        return;
+    }
+    *prev_pos = *cursor_pos;
+
+    let geometry = board.config.rules.geometry;
+    for (mut transform, mut visibility, mut mesh) in highlight_hex.iter_mut() {
+        if let Some(cursor_pos) = cursor_pos.0 {
+            *visibility = Visibility::Visible;
+            let pos = geometry.to_world(cursor_pos);
+            // over the pieces of a revealed picture, but under the numbers
+            transform.translation = Vec3::new(pos.x, pos.y, 1.8);
+            *mesh = grid.mesh_at(geometry, cursor_pos).into();
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+/// Hexes already revealed by the current drag under [`Settings::drag_sweep`]
+#[derive(Default)]
+struct Sweep {
+    last: Option<Hex>,
+    swept: HashSet<Hex>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_input(
+    mut commands: Commands,
+    cursor_pos: Res<CursorPos>,
+    buttons: Res<Input<MouseButton>>,
+    board: Res<Board>,
+    settings: Res<Settings>,
+    grid: Res<HexGrid>,
+    mut sweep: Local<Sweep>,
+    mut queue: ResMut<InputQueue>,
+    mut notes: ResMut<notes::FlagNotes>,
+) {
+    let Some(curr_hex) = cursor_pos.0 else {
+        sweep.last = None;
+        return;
+    };
+
+    if buttons.just_pressed(MouseButton::Right) {
+        // flags go through their note colors before they are removed
+        let recolored = board.flagged.contains(&curr_hex)
+            && !board.config.rules.colored
+            && notes.cycle(curr_hex, settings.flag_colors);
+        if !recolored {
+            queue.0.push(GameAction::ToggleFlag(curr_hex));
+        }
+    }
+    if settings.drag_sweep && buttons.pressed(MouseButton::Left) {
+        if buttons.just_pressed(MouseButton::Left) {
+            *sweep = Sweep::default();
+            sweep.swept.insert(curr_hex);
+        }
+        // fast drags skip hexes between frames, so the whole line since the last frame is swept
+        let from = sweep.last.unwrap_or(curr_hex);
+        for hex in from.line_to(curr_hex) {
+            let sweepable = board.covered.contains(&hex) && !board.flagged.contains(&hex);
+            if sweepable && sweep.swept.insert(hex) {
+                queue.0.push(GameAction::Reveal(hex));
+            }
+        }
+        sweep.last = Some(curr_hex);
+    }
+    if buttons.just_pressed(MouseButton::Left) {
+        // clicks on revealed numbers chord
+        let action = match board.covered.contains(&curr_hex) {
+            true => GameAction::Reveal(curr_hex),
+            false if settings.safe_chord && !board.is_safe_chord(curr_hex) => {
+                press(&mut commands, &grid, &board, curr_hex);
+                return;
+            }
+            false => GameAction::Chord(curr_hex),
+        };
+        queue.0.push(action);
+    }
+}
+
+/// Cell that is briefly pushed in, with the time left
+#[derive(Component)]
+struct Pressed(f32);
+
+/// Presses the covered hexes around a refused chord, or the number itself if there are none
+fn press(commands: &mut Commands, grid: &HexGrid, board: &Board, hex: Hex) {
+    let mut pressed: Vec<_> = board
+        .neighbors(hex)
+        .filter(|hex| board.covered.contains(hex) && !board.flagged.contains(hex))
+        .collect();
+    if pressed.is_empty() {
+        pressed.push(hex);
+    }
+    for hex in pressed {
+        if let Some(entity) = grid.entities.get(&hex) {
+            commands.entity(*entity).insert(Pressed(PRESS_TIME));
+        }
+    }
+}
+
+fn press_cells(
+    mut commands: Commands,
+    time: Res<Time>,
+    grid: Res<HexGrid>,
+    mut cells: Query<(
+        Entity,
+        &mut Pressed,
+        &mut Transform,
+        &mut Handle<CellMaterial>,
+    )>,
+) {
+    for (entity, mut pressed, mut transform, mut material) in &mut cells {
+        pressed.0 -= time.delta_seconds();
+        if pressed.0 <= 0.0 {
+            transform.scale = Vec3::ONE;
+            // the hex could have been revealed meanwhile, then it has the uncovered material already
+            if *material == grid.pressed_material {
+                *material = grid.covered_material.clone();
+            }
+            commands.entity(entity).remove::<Pressed>();
+            continue;
+        }
+        // only covered hexes have a bevel to sink
+        if *material == grid.covered_material {
+            *material = grid.pressed_material.clone();
+        }
+        // down and back up again
+        let t = 1.0 - (pressed.0 / PRESS_TIME * 2.0 - 1.0).abs();
+        transform.scale = Vec3::splat(1.0 - (1.0 - PRESSED_SCALE) * t);
+    }
+}
+
+/// Triangle pointing along the X axis, sized to fit into a hex
+fn arrow() -> Mesh {
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        vec![[8.0, 0.0, 0.0], [-6.0, 6.0, 0.0], [-6.0, -6.0, 0.0]],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 3]);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        vec![[1.0, 0.5], [0.0, 1.0], [0.0, 0.0]],
+    );
+    mesh.set_indices(Some(Indices::U16(vec![0, 1, 2])));
+    mesh
+}
@@ -0,0 +1,464 @@
+//! Lobby before a multiplayer game: one player creates a room and tells the others its code, everyone picks
+//! a color and gets ready while the board is agreed on, and a countdown starts the game for everyone at once.
+//!
+//! The room is hosted like the games themselves, see [`crate::net::transport`], and the code is just
+//! the address of the host on the local network. Any player can change the board, which unreadies
+//! everyone, so nobody is started into a game they didn't agree to. Once the countdown is over,
+//! the host starts a co-op or versus session and the others join it on their own.
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, UdpSocket},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::BoardConfig,
+    coop::{self, CoopSession},
+    game::{Authority, NewGame},
+    net::{
+        discovery::GameMode,
+        transport::{PeerId, Transport, TransportEvent, HOST},
+    },
+    versus::{self, VersusSession},
+    AppState,
+};
+
+pub const DEFAULT_PORT: u16 = 7881;
+/// Players a room needs before the countdown can start
+pub const MIN_PLAYERS: usize = 2;
+const COUNTDOWN: Duration = Duration::from_secs(3);
+/// How long the host of a race waits for everyone to join before it starts without them
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Largest board anyone in the room can propose
+const MAX_RADIUS: u32 = 64;
+/// Letters of room codes, without the ones that are easy to confuse like `O` and `0`
+const CODE_ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// Enough 5-bit letters for an IPv4 address
+const CODE_LENGTH: usize = 7;
+
+pub struct LobbyPlugin;
+
+impl Plugin for LobbyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LobbyStatus>().add_systems(
+            Update,
+            (
+                (receive_messages, run_countdown)
+                    .chain()
+                    .run_if(resource_exists::<Lobby>()),
+                start_race.run_if(resource_exists::<PendingRace>()),
+            ),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum LobbyMessage {
+    /// Client -> host right after connecting
+    Join { name: String, color: u8 },
+    /// Host -> client, the id the player has in the room
+    Welcome { player: PeerId },
+    /// Client -> host, the player picked another color
+    SetColor(u8),
+    /// Client -> host
+    SetReady(bool),
+    /// Client -> host, board the player wants everyone to play
+    Propose { mode: GameMode, config: BoardConfig },
+    /// Host -> clients, the whole room after every change
+    Room(Room),
+    /// Host -> clients, the game is hosted on the port and everyone joins it
+    Start { port: u16 },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LobbyPlayer {
+    pub id: PeerId,
+    pub name: String,
+    /// Index into [`coop::PLAYER_COLORS`], no two players have the same one
+    pub color: u8,
+    pub ready: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Room {
+    pub players: Vec<LobbyPlayer>,
+    pub mode: GameMode,
+    pub config: BoardConfig,
+    /// Everyone is ready and the game is about to start
+    pub counting_down: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Host,
+    Client,
+}
+
+/// Room the local player is in. Removing the resource leaves it.
+#[derive(Resource)]
+pub struct Lobby {
+    transport: Transport<LobbyMessage>,
+    pub role: Role,
+    /// Id of the local player, known to clients once the host welcomes them
+    pub player: Option<PeerId>,
+    pub room: Room,
+    /// Code the others join the room with
+    pub code: String,
+    /// Time left until the game starts
+    pub countdown: Option<Duration>,
+    /// Where clients join the game once it starts
+    host: IpAddr,
+}
+
+/// Last error or why the room was left, shown until the next attempt
+#[derive(Resource, Default)]
+pub struct LobbyStatus(pub Option<String>);
+
+/// Race the host started, which waits for the players of the room to join it
+#[derive(Resource)]
+struct PendingRace {
+    config: BoardConfig,
+    players: usize,
+    waited: Duration,
+}
+
+impl Lobby {
+    pub fn create(name: &str, color: u8, config: BoardConfig) -> io::Result<Self> {
+        let host = local_ip();
+        let mut lobby = Self {
+            transport: Transport::host(DEFAULT_PORT)?,
+            role: Role::Host,
+            player: Some(HOST),
+            room: Room {
+                players: Vec::new(),
+                mode: GameMode::Coop,
+                config,
+                counting_down: false,
+            },
+            code: encode_code(host),
+            countdown: None,
+            host,
+        };
+        lobby.apply(
+            HOST,
+            LobbyMessage::Join {
+                name: name.to_string(),
+                color,
+            },
+        );
+        Ok(lobby)
+    }
+
+    pub fn join(code: &str, name: &str, color: u8) -> io::Result<Self> {
+        let host = decode_code(code)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a valid room code"))?;
+        let transport = Transport::connect(&format!("{host}:{DEFAULT_PORT}"))?;
+        transport.send(
+            HOST,
+            &LobbyMessage::Join {
+                name: name.to_string(),
+                color,
+            },
+        );
+        Ok(Self {
+            transport,
+            role: Role::Client,
+            player: None,
+            room: Room {
+                players: Vec::new(),
+                mode: GameMode::Coop,
+                config: BoardConfig::default(),
+                counting_down: false,
+            },
+            code: encode_code(host),
+            countdown: None,
+            host,
+        })
+    }
+
+    pub fn local_player(&self) -> Option<&LobbyPlayer> {
+        let id = self.player?;
+        self.room.players.iter().find(|player| player.id == id)
+    }
+
+    /// The lost connection to the host is being restored
+    pub fn is_reconnecting(&self) -> bool {
+        self.transport.is_reconnecting()
+    }
+
+    /// Change of the local player, made right away by the host and asked for by clients
+    pub fn request(&mut self, message: LobbyMessage) {
+        match self.role {
+            Role::Host => self.apply(HOST, message),
+            Role::Client => self.transport.send(HOST, &message),
+        }
+    }
+
+    /// Applies a change of the player to the room on the host and shares the result
+    fn apply(&mut self, player: PeerId, message: LobbyMessage) {
+        let room = &mut self.room;
+        match message {
+            LobbyMessage::Join { name, color } => {
+                if room.players.iter().any(|known| known.id == player) {
+                    return;
+                }
+                let name = match name.trim() {
+                    "" => format!("Player {}", player + 1),
+                    name => name.to_string(),
+                };
+                let color = free_color(room, player, color);
+                if player != HOST {
+                    self.transport
+                        .send(player, &LobbyMessage::Welcome { player });
+                }
+                room.players.push(LobbyPlayer {
+                    id: player,
+                    name,
+                    color,
+                    ready: false,
+                });
+            }
+            LobbyMessage::SetColor(color) => {
+                let color = free_color(room, player, color);
+                if let Some(known) = room.players.iter_mut().find(|known| known.id == player) {
+                    known.color = color;
+                }
+            }
+            LobbyMessage::SetReady(ready) => {
+                if let Some(known) = room.players.iter_mut().find(|known| known.id == player) {
+                    known.ready = ready;
+                }
+            }
+            LobbyMessage::Propose { mode, config } => {
+                let valid = config.radius > 0
+                    && config.radius <= MAX_RADIUS
+                    && (0.0..1.0).contains(&config.mine_density);
+                if !valid || (room.mode == mode && room.config == config) {
+                    return;
+                }
+                room.mode = mode;
+                room.config = config;
+                room.players
+                    .iter_mut()
+                    .for_each(|known| known.ready = false);
+            }
+            LobbyMessage::Welcome { .. } | LobbyMessage::Room(_) | LobbyMessage::Start { .. } => {
+                return
+            }
+        }
+        self.share_room();
+    }
+
+    /// Starts or stops the countdown on the host and sends the room to everyone
+    fn share_room(&mut self) {
+        let players = &self.room.players;
+        let everyone_ready =
+            players.len() >= MIN_PLAYERS && players.iter().all(|player| player.ready);
+        self.countdown = match (everyone_ready, self.countdown) {
+            (true, None) => Some(COUNTDOWN),
+            (true, countdown) => countdown,
+            (false, _) => None,
+        };
+        self.room.counting_down = self.countdown.is_some();
+        self.transport
+            .broadcast(&LobbyMessage::Room(self.room.clone()));
+    }
+}
+
+/// The wanted color if nobody else has it, otherwise the first free one
+fn free_color(room: &Room, player: PeerId, wanted: u8) -> u8 {
+    let colors = coop::PLAYER_COLORS.len() as u8;
+    let taken = |color: u8| {
+        room.players
+            .iter()
+            .any(|other| other.id != player && other.color == color)
+    };
+    (0..colors)
+        .map(|offset| (wanted % colors + offset) % colors)
+        .find(|color| !taken(*color))
+        .unwrap_or(wanted % colors)
+}
+
+fn receive_messages(
+    mut commands: Commands,
+    mut lobby: ResMut<Lobby>,
+    mut status: ResMut<LobbyStatus>,
+    mut authority: ResMut<Authority>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for event in lobby.transport.poll() {
+        match (lobby.role, event) {
+            (Role::Host, TransportEvent::Message(peer, message)) => lobby.apply(peer, message),
+            (Role::Host, TransportEvent::Disconnected(peer)) => {
+                lobby.room.players.retain(|player| player.id != peer);
+                lobby.share_room();
+            }
+            // a client back from a drop might have missed changes
+            (Role::Host, TransportEvent::Reconnected(_)) => lobby.share_room(),
+            (Role::Client, TransportEvent::Message(_, LobbyMessage::Welcome { player })) => {
+                lobby.player = Some(player);
+            }
+            (Role::Client, TransportEvent::Message(_, LobbyMessage::Room(room))) => {
+                lobby.countdown = match (room.counting_down, lobby.countdown) {
+                    (true, None) => Some(COUNTDOWN),
+                    (true, countdown) => countdown,
+                    (false, _) => None,
+                };
+                lobby.room = room;
+            }
+            (Role::Client, TransportEvent::Message(_, LobbyMessage::Start { port })) => {
+                let address = format!("{}:{port}", lobby.host);
+                let joined = match lobby.room.mode {
+                    GameMode::Coop => CoopSession::join(&address).map(|session| {
+                        *authority = session.authority();
+                        commands.insert_resource(session);
+                    }),
+                    GameMode::Versus => VersusSession::join(&address)
+                        .map(|session| commands.insert_resource(session)),
+                };
+                status.0 = joined
+                    .err()
+                    .map(|err| format!("Failed to join the game: {err}"));
+                commands.remove_resource::<Lobby>();
+                next_state.set(AppState::Playing);
+                // whatever comes after is the room closing
+                return;
+            }
+            (Role::Client, TransportEvent::Disconnected(_)) => {
+                status.0 = Some("The room was closed".to_string());
+                commands.remove_resource::<Lobby>();
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Counts down on every side, but only the host starts the game once it's over
+fn run_countdown(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lobby: ResMut<Lobby>,
+    mut status: ResMut<LobbyStatus>,
+    mut authority: ResMut<Authority>,
+    mut new_game: EventWriter<NewGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(countdown) = lobby.countdown else {
+        return;
+    };
+    let countdown = countdown.saturating_sub(time.delta());
+    lobby.countdown = Some(countdown);
+    if !countdown.is_zero() || lobby.role != Role::Host {
+        return;
+    }
+
+    let config = lobby.room.config;
+    let (port, hosted) = match lobby.room.mode {
+        GameMode::Coop => (
+            coop::DEFAULT_PORT,
+            CoopSession::host(coop::DEFAULT_PORT).map(|session| {
+                *authority = session.authority();
+                commands.insert_resource(session);
+                new_game.send(NewGame::random(config));
+            }),
+        ),
+        GameMode::Versus => (
+            versus::DEFAULT_PORT,
+            VersusSession::host(versus::DEFAULT_PORT).map(|session| {
+                commands.insert_resource(session);
+                commands.insert_resource(PendingRace {
+                    config,
+                    players: lobby.room.players.len() - 1,
+                    waited: Duration::ZERO,
+                });
+            }),
+        ),
+    };
+    match hosted {
+        Ok(()) => {
+            lobby.transport.broadcast(&LobbyMessage::Start { port });
+            commands.remove_resource::<Lobby>();
+            status.0 = None;
+            next_state.set(AppState::Playing);
+        }
+        Err(err) => {
+            // everyone stays in the room and can try again
+            status.0 = Some(format!("Failed to host the game: {err}"));
+            lobby
+                .room
+                .players
+                .iter_mut()
+                .for_each(|player| player.ready = false);
+            lobby.share_room();
+        }
+    }
+}
+
+fn start_race(
+    mut commands: Commands,
+    time: Res<Time>,
+    session: Option<ResMut<VersusSession>>,
+    mut pending: ResMut<PendingRace>,
+    mut new_game: EventWriter<NewGame>,
+) {
+    let Some(mut session) = session else {
+        commands.remove_resource::<PendingRace>();
+        return;
+    };
+    pending.waited += time.delta();
+    if session.peers_count() < pending.players && pending.waited < JOIN_TIMEOUT {
+        return;
+    }
+    let race = NewGame::random(pending.config);
+    session.start(race);
+    new_game.send(race);
+    commands.remove_resource::<PendingRace>();
+}
+
+/// Address of this machine on the local network, as the other players see it
+fn local_ip() -> IpAddr {
+    // connecting a UDP socket sends nothing, it only picks the interface that leads outside
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9))?;
+            socket.local_addr()
+        })
+        .map(|address| address.ip())
+        .ok()
+        .filter(|ip| ip.is_ipv4() && !ip.is_unspecified())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+fn encode_code(host: IpAddr) -> String {
+    let IpAddr::V4(host) = host else {
+        return String::new();
+    };
+    let value = u32::from(host) as u64;
+    (0..CODE_LENGTH)
+        .rev()
+        .map(|letter| CODE_ALPHABET[(value >> (letter * 5)) as usize & 31] as char)
+        .collect()
+}
+
+/// Room codes are forgiving of case, spaces and dashes
+fn decode_code(code: &str) -> Option<IpAddr> {
+    let letters: Vec<u8> = code
+        .bytes()
+        .filter(|letter| !matches!(letter, b' ' | b'-'))
+        .map(|letter| letter.to_ascii_uppercase())
+        .collect();
+    if letters.len() != CODE_LENGTH {
+        return None;
+    }
+    let value = letters.iter().try_fold(0u64, |value, letter| {
+        let index = CODE_ALPHABET.iter().position(|known| known == letter)?;
+        Some(value << 5 | index as u64)
+    })?;
+    let value = u32::try_from(value).ok()?;
+    Some(IpAddr::V4(Ipv4Addr::from(value)))
+}
@@ -0,0 +1,184 @@
+//! Level of detail for huge boards: once cells get too small on the screen for their numbers and flags to be read,
+//! those are hidden and the cells themselves are tinted by what's on them instead. Zooming back in brings
+//! the full detail back, with a gap between both sizes so the board doesn't flicker around one of them.
+//!
+//! Everything on the cells is drawn on its own render layer, so hiding it is a matter of the camera
+//! and not of every entity. The magnifier sees that layer always.
+
+use bevy::{prelude::*, render::view::RenderLayers, utils::HashMap};
+use hexx::Hex;
+
+use crate::{
+    board::Board,
+    dual::{BoardHidden, DualViews},
+    magnifier::MagnifierCamera,
+    style::CellMaterial,
+    theme::Theme,
+    BoardView, HexGrid,
+};
+
+/// Layer of everything drawn on the cells
+pub const DETAIL_LAYER: u8 = 2;
+/// Cell radius on the screen below which details are hidden, and above which they come back, in pixels
+const LOW_DETAIL_RADIUS: f32 = 6.0;
+const FULL_DETAIL_RADIUS: f32 = 7.5;
+/// Colors the cells are tinted with, and how much
+const FLAG_TINT: (Color, f32) = (Color::rgb(0.85, 0.2, 0.15), 0.7);
+const MINE_TINT: (Color, f32) = (Color::rgb(0.1, 0.1, 0.1), 0.8);
+const NUMBER_TINT: (Color, f32) = (Color::rgb(0.3, 0.45, 0.85), 0.35);
+
+pub struct LodPlugin;
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        // after the commands of `Update`, so new details are never drawn on the wrong layer
+        app.add_systems(
+            PostUpdate,
+            (tag_details, switch_detail, apply_deferred, tint_cells).chain(),
+        );
+    }
+}
+
+/// Board is zoomed out too far for details, exists only while it is
+#[derive(Resource)]
+pub struct LowDetail;
+
+/// What a cell is tinted by when zoomed out
+#[derive(Clone, Copy)]
+pub enum Summary {
+    Flagged,
+    Mine,
+    Numbered,
+}
+
+impl Summary {
+    pub const ALL: [Self; 3] = [Self::Flagged, Self::Mine, Self::Numbered];
+
+    /// Flags are on covered cells, the rest is on uncovered ones
+    pub fn color(self, theme: &Theme) -> Color {
+        let (base, (tint, amount)) = match self {
+            Self::Flagged => (theme.covered, FLAG_TINT),
+            Self::Mine => (theme.uncovered, MINE_TINT),
+            Self::Numbered => (theme.uncovered, NUMBER_TINT),
+        };
+        let base = Vec4::from(base.as_linear_rgba_f32());
+        let tint = Vec4::from(tint.as_linear_rgba_f32());
+        let [r, g, b, _] = base.lerp(tint, amount).to_array();
+        Color::rgba_linear(r, g, b, base.w)
+    }
+
+    fn of(board: &Board, hex: Hex) -> Option<Self> {
+        // numbers that aren't always shown shouldn't be given away by the tint
+        let rules = &board.config.rules;
+        let hidden = rules.fog.is_some() || rules.blind;
+        let covered = board.covered.contains(&hex);
+        if covered && board.flagged.contains(&hex) {
+            Some(Self::Flagged)
+        } else if board.mines.contains(&hex) && (!covered || board.exploded.is_some()) {
+            Some(Self::Mine)
+        } else if !covered && board.numbers.get(&hex).is_some_and(|number| *number > 0) && !hidden {
+            Some(Self::Numbered)
+        } else {
+            None
+        }
+    }
+}
+
+fn tag_details(
+    mut commands: Commands,
+    cells: Query<(), With<Handle<CellMaterial>>>,
+    details: Query<(Entity, &Parent), (Added<Parent>, Without<RenderLayers>)>,
+) {
+    for (entity, parent) in &details {
+        if cells.contains(parent.get()) {
+            commands
+                .entity(entity)
+                .insert(RenderLayers::layer(DETAIL_LAYER));
+        }
+    }
+}
+
+fn switch_detail(
+    mut commands: Commands,
+    view: Res<BoardView>,
+    dual: Option<Res<DualViews>>,
+    board: Res<Board>,
+    low_detail: Option<Res<LowDetail>>,
+    // the main camera shows no cells while dual boards are on
+    mut cameras: Query<
+        &mut RenderLayers,
+        (
+            With<Camera2d>,
+            Without<MagnifierCamera>,
+            Without<BoardHidden>,
+        ),
+    >,
+    mut applied: Local<Option<bool>>,
+) {
+    let scale = dual.map_or(view.scale, |dual| dual.scale());
+    let radius = board.config.rules.geometry.cell_radius() / scale;
+    let low = match low_detail {
+        Some(_) => radius < FULL_DETAIL_RADIUS,
+        None => radius < LOW_DETAIL_RADIUS,
+    };
+    if *applied == Some(low) {
+        return;
+    }
+    *applied = Some(low);
+    match low {
+        true => commands.insert_resource(LowDetail),
+        false => commands.remove_resource::<LowDetail>(),
+    }
+    for mut layers in &mut cameras {
+        *layers = match low {
+            true => layers.without(DETAIL_LAYER),
+            false => layers.with(DETAIL_LAYER),
+        };
+    }
+}
+
+/// Swaps materials of the cells that were redrawn, or of all of them when the detail changes
+fn tint_cells(
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    low_detail: Option<Res<LowDetail>>,
+    mut cells: ParamSet<(
+        Query<&mut Handle<CellMaterial>>,
+        Query<Entity, Changed<Handle<CellMaterial>>>,
+    )>,
+    mut hexes: Local<HashMap<Entity, Hex>>,
+    mut was_low: Local<bool>,
+) {
+    if grid.is_changed() {
+        *hexes = grid
+            .entities
+            .iter()
+            .map(|(hex, entity)| (*entity, *hex))
+            .collect();
+    }
+    let low = low_detail.is_some();
+    let entities: Vec<_> = if low != *was_low {
+        *was_low = low;
+        hexes.keys().copied().collect()
+    } else if low {
+        cells.p1().iter().collect()
+    } else {
+        return;
+    };
+    let mut cells = cells.p0();
+    for entity in entities {
+        let (Some(hex), Ok(mut material)) = (hexes.get(&entity), cells.get_mut(entity)) else {
+            continue;
+        };
+        let summary = Summary::of(&board, *hex).filter(|_| low);
+        let expected = match summary {
+            Some(summary) => grid.summary_materials[summary as usize].clone(),
+            None if board.covered.contains(hex) => grid.covered_material.clone(),
+            None => grid.uncovered_material.clone(),
+        };
+        // pressed cells go back to covered on their own
+        if summary.is_some() || grid.summary_materials.contains(&*material) {
+            material.set_if_neq(expected);
+        }
+    }
+}
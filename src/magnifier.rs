@@ -0,0 +1,183 @@
+//! Round lens next to the cursor with an enlarged view of the hexes around it, for boards zoomed out
+//! so much that numbers are hard to read. A second camera renders the area under the cursor into a texture,
+//! which is drawn on a disc only the main camera sees. Toggled with `F5` or in the settings.
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+    window::PrimaryWindow,
+};
+
+use crate::{lod, settings::Settings, AppState, BoardView, CursorPos};
+
+/// Diameter of the lens in pixels, which is also the size of its texture
+const LENS_SIZE: u32 = 200;
+/// How much larger things look through the lens
+const ZOOM: f32 = 2.5;
+/// Lens is drawn up and to the right of the cursor, so it doesn't hide what's being clicked
+const LENS_OFFSET: Vec2 = Vec2::new(130.0, 130.0);
+const RIM_WIDTH: f32 = 3.0;
+const RIM_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
+/// Layer seen only by the main camera, so the lens doesn't show itself
+const LENS_LAYER: u8 = 1;
+/// Above the board and everything on it
+const LENS_Z: f32 = 10.0;
+
+pub struct MagnifierPlugin;
+
+impl Plugin for MagnifierPlugin {
+    fn build(&self, app: &mut App) {
+        // after the main camera is spawned
+        app.add_systems(PostStartup, spawn_magnifier)
+            .add_systems(Update, (toggle_magnifier, update_magnifier).chain());
+    }
+}
+
+/// Camera rendering into the lens texture, the board view doesn't apply to it
+#[derive(Component)]
+pub struct MagnifierCamera;
+
+#[derive(Component)]
+struct Lens;
+
+fn spawn_magnifier(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    main_cameras: Query<Entity, (With<Camera2d>, Without<MagnifierCamera>)>,
+) {
+    let size = Extent3d {
+        width: LENS_SIZE,
+        height: LENS_SIZE,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    // fills the texture with zeroes
+    image.resize(size);
+    let image = images.add(image);
+
+    // the main camera sees the lens along with the board
+    for camera in &main_cameras {
+        commands
+            .entity(camera)
+            .insert(RenderLayers::default().with(LENS_LAYER));
+    }
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                // before the main camera, so the texture is ready when the lens is drawn
+                order: -1,
+                target: RenderTarget::Image(image.clone()),
+                is_active: false,
+                ..default()
+            },
+            ..default()
+        },
+        UiCameraConfig { show_ui: false },
+        // the lens shows details however far the board is zoomed out
+        RenderLayers::default().with(lod::DETAIL_LAYER),
+        MagnifierCamera,
+    ));
+
+    let radius = LENS_SIZE as f32 / 2.0;
+    commands
+        .spawn((
+            ColorMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+                material: materials.add(ColorMaterial {
+                    color: Color::WHITE,
+                    texture: Some(image),
+                }),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            RenderLayers::layer(LENS_LAYER),
+            Lens,
+        ))
+        .with_children(|lens| {
+            lens.spawn((
+                ColorMesh2dBundle {
+                    mesh: meshes
+                        .add(shape::Circle::new(radius + RIM_WIDTH).into())
+                        .into(),
+                    material: materials.add(RIM_COLOR.into()),
+                    transform: Transform::from_xyz(0.0, 0.0, -0.01),
+                    ..default()
+                },
+                RenderLayers::layer(LENS_LAYER),
+            ));
+        });
+}
+
+fn toggle_magnifier(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keys.just_pressed(KeyCode::F5) {
+        settings.magnifier = !settings.magnifier;
+    }
+}
+
+fn update_magnifier(
+    settings: Res<Settings>,
+    state: Res<State<AppState>>,
+    cursor_pos: Res<CursorPos>,
+    view: Res<BoardView>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<
+        (&mut Camera, &mut Transform, &mut OrthographicProjection),
+        With<MagnifierCamera>,
+    >,
+    mut lenses: Query<(&mut Transform, &mut Visibility), (With<Lens>, Without<MagnifierCamera>)>,
+) {
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(|window| Some(view.to_world(window.cursor_position()?, window)));
+    // only over the board while playing
+    let target = cursor.filter(|_| {
+        settings.magnifier && *state.get() == AppState::Playing && cursor_pos.0.is_some()
+    });
+
+    for (mut camera, mut transform, mut projection) in &mut cameras {
+        camera.is_active = target.is_some();
+        if let Some(target) = target {
+            transform.translation.x = target.x;
+            transform.translation.y = target.y;
+            transform.rotation = Quat::from_rotation_z(view.rotation);
+            transform.scale = view.flip.extend(1.0);
+            projection.scale = view.scale / ZOOM;
+        }
+    }
+    for (mut transform, mut visibility) in &mut lenses {
+        let Some(target) = target else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Visible;
+        // same size on the screen whatever the zoom of the board
+        // the lens is turned and mirrored with the board, so it looks the same on the screen
+        let offset = Vec2::from_angle(view.rotation).rotate(LENS_OFFSET * view.flip * view.scale);
+        transform.translation = (target + offset).extend(LENS_Z);
+        transform.rotation = Quat::from_rotation_z(view.rotation);
+        transform.scale = (view.flip * view.scale).extend(1.0);
+    }
+}
@@ -0,0 +1,123 @@
+//! Board shapes from image masks: small black-and-white PNG files in `masks` within the data directory,
+//! where every dark pixel becomes a hex of the board. Pixel rows become hex rows, with every other row
+//! shifted by half a hex, so shapes look roughly the same as in the image.
+
+use bevy::{
+    render::texture::{CompressedImageFormats, Image, ImageType},
+    utils::HashSet,
+};
+use hexx::Hex;
+
+use crate::{
+    board::{sorted_hexes, BoardConfig, Layout},
+    gradient,
+    rng::Rng,
+    storage,
+};
+
+const MASKS_DIR: &str = "masks";
+/// Larger images make boards that don't fit into share codes, see `share::MAX_RADIUS`
+const MAX_SIZE: u32 = 48;
+/// Pixels darker than this are hexes
+const DARK: u8 = 128;
+
+/// Playable area loaded from an image
+pub struct Mask {
+    pub name: String,
+    pub cells: HashSet<Hex>,
+}
+
+impl Mask {
+    /// Board of the mask shape with mines picked by the seed, the radius of the config is not used
+    pub fn layout(&self, config: &BoardConfig, seed: u64) -> Layout {
+        let mut hexes = sorted_hexes(self.cells.iter().copied());
+        let count = (hexes.len() as f32 * config.mine_density).round() as usize;
+        let radius = self.radius();
+        let mines = gradient::pick_mines(
+            &mut hexes,
+            count,
+            config.rules.gradient,
+            config.rules.geometry,
+            radius,
+            &mut Rng::new(seed),
+        );
+        Layout {
+            cells: self.cells.clone(),
+            mines: mines.into_iter().collect(),
+            revealed: HashSet::new(),
+        }
+    }
+
+    pub fn radius(&self) -> u32 {
+        self.cells
+            .iter()
+            .map(|hex| hex.ulength())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// All masks that could be loaded, ordered by file names. Broken ones are skipped with a warning.
+pub fn available() -> Vec<Mask> {
+    storage::read_all_in(MASKS_DIR, "png")
+        .into_iter()
+        .filter_map(|(name, bytes)| match rasterize(&bytes) {
+            Ok(cells) => Some(Mask { name, cells }),
+            Err(err) => {
+                bevy::log::warn!("Skipping mask {name}: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Hexes of the dark pixels of an 8-bit PNG, centered around [`Hex::ZERO`]
+fn rasterize(bytes: &[u8]) -> Result<HashSet<Hex>, String> {
+    let image = Image::from_buffer(
+        bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        false,
+    )
+    .map_err(|err| err.to_string())?;
+    let (width, height) = (
+        image.texture_descriptor.size.width,
+        image.texture_descriptor.size.height,
+    );
+    if width > MAX_SIZE || height > MAX_SIZE {
+        return Err(format!("larger than {MAX_SIZE}x{MAX_SIZE} pixels"));
+    }
+    // grayscale images keep a single channel, the rest are converted to RGBA
+    let pixel_size = image.data.len() / (width * height).max(1) as usize;
+    let is_dark = |x: u32, y: u32| {
+        let pixel = &image.data[(y * width + x) as usize * pixel_size..][..pixel_size];
+        let alpha = match pixel_size {
+            2 | 4 => pixel[pixel_size - 1],
+            _ => u8::MAX,
+        };
+        pixel[0] < DARK && alpha >= DARK
+    };
+    // pixel rows go down, while hex rows go up with `y`
+    let to_hex = |x: u32, y: u32| {
+        let (column, row) = (x as i32, -(y as i32));
+        Hex::new(column - row.div_euclid(2), row)
+    };
+
+    let dark: Vec<_> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|(x, y)| is_dark(*x, *y))
+        .collect();
+    let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) = (
+        dark.iter().map(|(x, _)| *x).min(),
+        dark.iter().map(|(x, _)| *x).max(),
+        dark.iter().map(|(_, y)| *y).min(),
+        dark.iter().map(|(_, y)| *y).max(),
+    ) else {
+        return Err("no dark pixels".to_string());
+    };
+    let center = to_hex((min_x + max_x) / 2, (min_y + max_y) / 2);
+    Ok(dark
+        .into_iter()
+        .map(|(x, y)| to_hex(x, y) - center)
+        .collect())
+}
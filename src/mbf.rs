@@ -0,0 +1,159 @@
+//! Classic square boards in the `.mbf` format of Minesweeper Arbiter, from `boards` within the data directory.
+//!
+//! A file is the width and the height in a byte each, the mine count as a big-endian `u16`,
+//! and then a column and a row byte for every mine. Squares can be played on hexes or as they are, see [`Mapping`].
+
+use bevy::utils::HashSet;
+use hexx::Hex;
+
+use crate::{
+    board::{Layout, Rules},
+    geometry::Geometry,
+    storage,
+};
+
+const BOARDS_DIR: &str = "boards";
+const HEADER_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mapping {
+    /// Rows of hexes shifted by half a hex every other row, like `mask.rs` does, with the classic rules.
+    /// The board looks like the original one, but numbers count six neighbors and differ from it.
+    Hex,
+    /// The original squares with [`Geometry::Square`], so every number is the same as on the original board
+    Square,
+}
+
+/// Board loaded from a `.mbf` file
+pub struct SquareBoard {
+    pub name: String,
+    pub width: u8,
+    pub height: u8,
+    /// Columns and rows, starting from the top left corner
+    pub mines: Vec<(u8, u8)>,
+}
+
+impl SquareBoard {
+    pub fn decode(name: String, bytes: &[u8]) -> Result<Self, String> {
+        let [width, height, count_high, count_low] = *bytes
+            .get(..HEADER_SIZE)
+            .and_then(|header| <[u8; HEADER_SIZE]>::try_from(header).ok())
+            .ok_or("too short for a header")?;
+        if width == 0 || height == 0 {
+            return Err("board has no squares".to_string());
+        }
+        let count = u16::from_be_bytes([count_high, count_low]) as usize;
+        let positions = &bytes[HEADER_SIZE..];
+        if positions.len() < count * 2 {
+            return Err(format!("{count} mines expected, the file ends earlier"));
+        }
+        let mut mines = Vec::with_capacity(count);
+        for pair in positions[..count * 2].chunks_exact(2) {
+            let (column, row) = (pair[0], pair[1]);
+            if column >= width || row >= height {
+                return Err(format!(
+                    "mine at {column},{row} is outside of {width}x{height}"
+                ));
+            }
+            mines.push((column, row));
+        }
+        Ok(Self {
+            name,
+            width,
+            height,
+            mines,
+        })
+    }
+
+    pub fn layout(&self, mapping: Mapping) -> Layout {
+        // rows go down, while hex rows go up with `y`
+        let to_hex = |column: u8, row: u8| {
+            let (column, row) = (column as i32, -(row as i32));
+            match mapping {
+                Mapping::Hex => Hex::new(column - row.div_euclid(2), row),
+                Mapping::Square => Hex::new(column, row),
+            }
+        };
+        let cells: HashSet<_> = (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |column| to_hex(column, row)))
+            .collect();
+        // centered like the rest of the boards, the middle square might be between two hexes
+        let center = to_hex(self.width / 2, self.height / 2);
+        let shift = |hexes: HashSet<Hex>| hexes.into_iter().map(|hex| hex - center).collect();
+        Layout {
+            cells: shift(cells),
+            mines: shift(
+                self.mines
+                    .iter()
+                    .map(|(column, row)| to_hex(*column, *row))
+                    .collect(),
+            ),
+            revealed: HashSet::new(),
+        }
+    }
+
+    pub fn rules(mapping: Mapping) -> Rules {
+        match mapping {
+            Mapping::Hex => Rules::CLASSIC,
+            Mapping::Square => Rules {
+                geometry: Geometry::Square,
+                ..Rules::CLASSIC
+            },
+        }
+    }
+}
+
+/// All boards that could be loaded, ordered by file names. Broken ones are skipped with a warning.
+pub fn available() -> Vec<SquareBoard> {
+    storage::read_all_in(BOARDS_DIR, "mbf")
+        .into_iter()
+        .filter_map(|(name, bytes)| {
+            let board = SquareBoard::decode(name.clone(), &bytes);
+            board
+                .map_err(|err| bevy::log::warn!("Skipping board {name}: {err}"))
+                .ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn board_is_decoded() {
+        let bytes = [3, 2, 0, 2, 0, 0, 2, 1];
+        let board = SquareBoard::decode("test".to_string(), &bytes).expect("valid board");
+        assert_eq!((board.width, board.height), (3, 2));
+        assert_eq!(board.mines, vec![(0, 0), (2, 1)]);
+
+        for mapping in [Mapping::Hex, Mapping::Square] {
+            let layout = board.layout(mapping);
+            assert_eq!(layout.cells.len(), 6);
+            assert_eq!(layout.mines.len(), 2);
+            assert!(layout.mines.is_subset(&layout.cells));
+        }
+    }
+
+    #[test]
+    fn broken_boards_are_rejected() {
+        let cases: [&[u8]; 5] = [
+            // shorter than the header
+            &[3, 2, 0],
+            // no squares at all
+            &[0, 2, 0, 0],
+            // two mines promised, one given
+            &[3, 2, 0, 2, 0, 0],
+            // a column past the width
+            &[3, 2, 0, 1, 3, 0],
+            // a row past the height
+            &[3, 2, 0, 1, 0, 2],
+        ];
+        for bytes in cases {
+            assert!(
+                SquareBoard::decode("test".to_string(), bytes).is_err(),
+                "{bytes:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Mini mode for a casual game next to other work: a small borderless window that stays on top of others,
+//! with only the time and the mines left in the HUD. Toggled with `F7`, the board is refitted to the new size
+//! like after any resize. Without a title bar, the window is moved by dragging it anywhere off the board.
+
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowLevel, WindowMode, WindowPosition},
+    winit::WinitWindows,
+};
+
+use crate::{AppState, CursorPos};
+
+/// Logical size of the mini window
+const MINI_SIZE: Vec2 = Vec2::new(360.0, 320.0);
+
+pub struct MiniModePlugin;
+
+impl Plugin for MiniModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_mini_mode,
+                drag_window
+                    .run_if(resource_exists::<MiniMode>())
+                    .run_if(in_state(AppState::Playing)),
+            ),
+        );
+    }
+}
+
+/// Active while the resource exists, keeps the normal window to go back to
+#[derive(Resource)]
+pub struct MiniMode {
+    size: Vec2,
+    position: WindowPosition,
+    mode: WindowMode,
+}
+
+fn toggle_mini_mode(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mini: Option<Res<MiniMode>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !keys.just_pressed(KeyCode::F7) {
+        return;
+    }
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    match mini {
+        Some(normal) => {
+            window.mode = normal.mode;
+            window.resolution.set(normal.size.x, normal.size.y);
+            window.position = normal.position;
+            window.decorations = true;
+            window.window_level = WindowLevel::Normal;
+            commands.remove_resource::<MiniMode>();
+        }
+        None => {
+            commands.insert_resource(MiniMode {
+                size: Vec2::new(window.width(), window.height()),
+                position: window.position,
+                mode: window.mode,
+            });
+            window.mode = WindowMode::Windowed;
+            window.resolution.set(MINI_SIZE.x, MINI_SIZE.y);
+            window.decorations = false;
+            window.window_level = WindowLevel::AlwaysOnTop;
+        }
+    }
+}
+
+/// Pressing the left button off the board hands the window to the system to move it around
+fn drag_window(
+    buttons: Res<Input<MouseButton>>,
+    cursor_pos: Res<CursorPos>,
+    winit_windows: NonSend<WinitWindows>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) || cursor_pos.0.is_some() {
+        return;
+    }
+    for entity in &windows {
+        if let Some(native) = winit_windows.get_window(entity) {
+            if let Err(err) = native.drag_window() {
+                warn!("Failed to move the window: {err}");
+            }
+        }
+    }
+}
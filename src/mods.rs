@@ -0,0 +1,257 @@
+//! Rule variants written as [rhai](https://rhai.rs) scripts in the `mods` directory of the game data.
+//!
+//! Every hook is optional, and missing or failing hooks fall back to the classic rules:
+//!
+//! ```rhai
+//! // mines of a new board as `[x, y]` pairs, `hexagon(radius, seed)` lists all hexes in a random order
+//! fn generate(radius, density, seed) { hexagon(radius, seed).extract(0, 10) }
+//! // number shown on a safe hex, where `mines` is the classic number
+//! fn number(x, y, mines) { mines * 2 }
+//! // extra hexes to reveal after the player revealed a hex
+//! fn on_reveal(x, y) { [[x + 1, y]] }
+//! // whether the game is won, checked after every action
+//! fn won(remaining_safe, mines_left) { remaining_safe == 0 }
+//! ```
+
+use std::{hash::Hasher, sync::Arc, time::Duration};
+
+use bevy::prelude::*;
+use hexx::{shapes, Hex};
+use rhai::{Array, Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Board, BoardConfig, Layout},
+    replay::Fnv1a,
+    rng::Rng,
+    storage, AppState,
+};
+
+const MODS_DIR: &str = "mods";
+/// How often the mods directory is checked for changed scripts
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+/// Keeps a runaway script from freezing the game
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+pub struct ModsPlugin;
+
+impl Plugin for ModsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Mods::load()).add_systems(
+            Update,
+            // swapping rules in the middle of a game would break it
+            reload_changed.run_if(not(in_state(AppState::Playing))),
+        );
+    }
+}
+
+/// Loaded rule scripts, and the one used for new games if any
+#[derive(Resource)]
+pub struct Mods {
+    /// Shared with the number hooks of the boards, see [`Board::number_hook`]
+    engine: Arc<Engine>,
+    pub scripts: Vec<RuleScript>,
+    pub active: Option<usize>,
+    /// Names and sources of the script files as they were loaded, to notice changes
+    sources: Vec<(String, String)>,
+}
+
+pub struct RuleScript {
+    pub name: String,
+    /// Hash of the source, as a script can be edited without renaming it
+    pub hash: u64,
+    ast: AST,
+}
+
+impl RuleScript {
+    pub fn id(&self) -> ScriptId {
+        ScriptId {
+            name: self.name.clone(),
+            hash: self.hash,
+        }
+    }
+}
+
+/// Rule script a game was played with, which makes the game unranked as the script can change the rules at will
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptId {
+    pub name: String,
+    pub hash: u64,
+}
+
+impl Mods {
+    /// Classic rules only, nothing is loaded from the disk
+    pub fn empty() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.register_fn("hexagon", |radius: i64, seed: i64| {
+            let mut hexes: Vec<_> = shapes::hexagon(Hex::ZERO, radius.max(0) as u32).collect();
+            Rng::new(seed as u64).shuffle(&mut hexes);
+            hexes.into_iter().map(hex_to_dynamic).collect::<Array>()
+        });
+        Self {
+            engine: Arc::new(engine),
+            scripts: Vec::new(),
+            active: None,
+            sources: Vec::new(),
+        }
+    }
+
+    fn load() -> Self {
+        let mut mods = Self::empty();
+        mods.compile(storage::read_all_text_in(MODS_DIR, "rhai"));
+        mods
+    }
+
+    /// Replaces all scripts with the compiled `sources`, keeping the active script by its name
+    fn compile(&mut self, sources: Vec<(String, String)>) {
+        let active = self.active().map(|script| script.name.clone());
+        self.scripts = sources
+            .iter()
+            .filter_map(|(name, text)| match self.engine.compile(text) {
+                Ok(ast) => Some(RuleScript {
+                    name: name.clone(),
+                    hash: source_hash(text),
+                    ast,
+                }),
+                Err(err) => {
+                    warn!("Skipping mod {name}: {err}");
+                    None
+                }
+            })
+            .collect();
+        self.active =
+            active.and_then(|name| self.scripts.iter().position(|script| script.name == name));
+        self.sources = sources;
+    }
+
+    pub fn active(&self) -> Option<&RuleScript> {
+        self.active.and_then(|index| self.scripts.get(index))
+    }
+
+    /// Board for a new game, generated by the active script if it has the `generate` hook
+    pub fn generate(&self, config: BoardConfig, seed: u64) -> Board {
+        let Some(script) = self.active() else {
+            return Board::generate(config, seed);
+        };
+        let args = (
+            config.radius as i64,
+            config.mine_density as f64,
+            seed as i64,
+        );
+        let Some(mines) = self.call::<Array>(script, "generate", args) else {
+            let mut board = Board::generate(config, seed);
+            self.renumber(script, &mut board);
+            return board;
+        };
+        let cells: bevy::utils::HashSet<_> = config.shape().into_iter().collect();
+        let layout = Layout {
+            mines: hexes_from_array(mines)
+                .filter(|hex| cells.contains(hex))
+                .collect(),
+            cells,
+            ..default()
+        };
+        let mut board = Board::from_seeded_layout(&layout, config.rules, seed);
+        // the config that was asked for, while `Game::script` keeps the game apart from classic ones
+        board.config = config;
+        self.renumber(script, &mut board);
+        board
+    }
+
+    /// Extra hexes the active script reveals after `hex` was revealed
+    pub fn on_reveal(&self, hex: Hex) -> Vec<Hex> {
+        self.active()
+            .and_then(|script| {
+                self.call::<Array>(script, "on_reveal", (hex.x as i64, hex.y as i64))
+            })
+            .map(|hexes| hexes_from_array(hexes).collect())
+            .unwrap_or_default()
+    }
+
+    /// Win condition of the active script, all safe hexes have to be revealed by default
+    pub fn is_won(&self, board: &Board) -> bool {
+        let args = (board.remaining_safe() as i64, board.mines_left() as i64);
+        self.active()
+            .and_then(|script| self.call::<bool>(script, "won", args))
+            .unwrap_or_else(|| board.is_cleared())
+    }
+
+    /// Hands the `number` hook of the script to the board, so it applies to every recount of the game
+    fn renumber(&self, script: &RuleScript, board: &mut Board) {
+        if !has_fn(script, "number", 3) {
+            return;
+        }
+        let (engine, ast, name) = (self.engine.clone(), script.ast.clone(), script.name.clone());
+        board.number_hook = Some(Arc::new(move |hex: Hex, mines: u8| {
+            let args = (hex.x as i64, hex.y as i64, mines as i64);
+            engine
+                .call_fn::<i64>(&mut Scope::new(), &ast, "number", args)
+                .map_err(|err| warn!("Mod {name}: `number` failed: {err}"))
+                .map_or(mines, |number| number.clamp(0, u8::MAX as i64) as u8)
+        }));
+        board.recount_numbers();
+    }
+
+    /// Calls the hook if the script has it, logging script errors
+    fn call<T: rhai::Variant + Clone>(
+        &self,
+        script: &RuleScript,
+        name: &str,
+        args: impl rhai::FuncArgs,
+    ) -> Option<T> {
+        let mut values = Vec::new();
+        args.parse(&mut values);
+        if !has_fn(script, name, values.len()) {
+            return None;
+        }
+        self.engine
+            .call_fn::<T>(&mut Scope::new(), &script.ast, name, values)
+            .map_err(|err| warn!("Mod {}: `{name}` failed: {err}", script.name))
+            .ok()
+    }
+}
+
+fn source_hash(text: &str) -> u64 {
+    let mut hasher = Fnv1a::default();
+    hasher.write(text.as_bytes());
+    hasher.finish()
+}
+
+fn has_fn(script: &RuleScript, name: &str, params: usize) -> bool {
+    script
+        .ast
+        .iter_functions()
+        .any(|function| function.name == name && function.params.len() == params)
+}
+
+fn hex_to_dynamic(hex: Hex) -> Dynamic {
+    let pair: Array = vec![(hex.x as i64).into(), (hex.y as i64).into()];
+    pair.into()
+}
+
+/// Takes `[x, y]` pairs, silently skipping anything else
+fn hexes_from_array(array: Array) -> impl Iterator<Item = Hex> {
+    array.into_iter().filter_map(|item| {
+        let pair = item.try_cast::<Array>()?;
+        match pair.as_slice() {
+            [x, y] => Some(Hex::new(x.as_int().ok()? as i32, y.as_int().ok()? as i32)),
+            _ => None,
+        }
+    })
+}
+
+fn reload_changed(time: Res<Time>, mut since_checked: Local<Duration>, mut mods: ResMut<Mods>) {
+    *since_checked += time.delta();
+    if *since_checked < RELOAD_INTERVAL {
+        return;
+    }
+    *since_checked = Duration::ZERO;
+
+    let sources = storage::read_all_text_in(MODS_DIR, "rhai");
+    // `bypass_change_detection` keeps screens showing mods from refreshing every second
+    if sources != mods.bypass_change_detection().sources {
+        info!("Reloading mods");
+        mods.compile(sources);
+    }
+}
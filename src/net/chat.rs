@@ -0,0 +1,148 @@
+//! Read-only client of the Twitch chat, which is plain IRC over TCP.
+//!
+//! Twitch lets anyone read a channel anonymously with a `justinfan` nick, so no account or token is needed.
+//! The connection runs on its own thread and forwards chat messages into a channel polled from systems.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{mpsc, Arc, Mutex},
+};
+
+const ADDRESS: &str = "irc.chat.twitch.tv:6667";
+/// Any `justinfan` nick logs in anonymously
+const NICK: &str = "justinfan31337";
+
+pub enum ChatEvent {
+    Connected,
+    Message { user: String, text: String },
+    Disconnected(String),
+}
+
+pub struct ChatClient {
+    stream: Arc<Mutex<Option<TcpStream>>>,
+    events: Mutex<mpsc::Receiver<ChatEvent>>,
+}
+
+impl ChatClient {
+    /// Starts reading the chat of `channel` in the background
+    pub fn connect(channel: &str) -> Self {
+        let channel = channel.trim().trim_start_matches('#').to_lowercase();
+        let stream = Arc::new(Mutex::new(None));
+        let (sender, receiver) = mpsc::channel();
+
+        let shared = stream.clone();
+        let run = move || {
+            let reason = match read_chat(&channel, &shared, &sender) {
+                Ok(()) => "connection closed".to_string(),
+                Err(err) => err.to_string(),
+            };
+            let _ = sender.send(ChatEvent::Disconnected(reason));
+        };
+        // the same as in `Pending::spawn`, connecting fails right away in the browser
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(run);
+        #[cfg(target_arch = "wasm32")]
+        run();
+
+        Self {
+            stream,
+            events: Mutex::new(receiver),
+        }
+    }
+
+    /// Takes all events received since the last call
+    pub fn poll(&self) -> Vec<ChatEvent> {
+        self.events
+            .lock()
+            .map(|events| events.try_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for ChatClient {
+    fn drop(&mut self) {
+        if let Ok(Some(stream)) = self.stream.lock().as_deref() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+    }
+}
+
+fn read_chat(
+    channel: &str,
+    shared: &Mutex<Option<TcpStream>>,
+    sender: &mpsc::Sender<ChatEvent>,
+) -> io::Result<()> {
+    let mut stream = TcpStream::connect(ADDRESS)?;
+    write!(stream, "NICK {NICK}\r\nJOIN #{channel}\r\n")?;
+    let reader = BufReader::new(stream.try_clone()?);
+    if let Ok(mut shared) = shared.lock() {
+        *shared = Some(stream.try_clone()?);
+    }
+    let _ = sender.send(ChatEvent::Connected);
+
+    for line in reader.lines() {
+        let line = line?;
+        // the server drops clients that don't answer its pings
+        if let Some(payload) = line.strip_prefix("PING") {
+            write!(stream, "PONG{payload}\r\n")?;
+            continue;
+        }
+        if let Some(message) = parse_message(&line) {
+            if sender.send(message).is_err() {
+                // nobody listens anymore
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Chat message from a line like ":user!user@user.tmi.twitch.tv PRIVMSG #channel :text"
+fn parse_message(line: &str) -> Option<ChatEvent> {
+    let rest = line.strip_prefix(':')?;
+    let (prefix, rest) = rest.split_once(' ')?;
+    let (command, rest) = rest.split_once(' ')?;
+    if command != "PRIVMSG" {
+        return None;
+    }
+    let (_channel, text) = rest.split_once(" :")?;
+    let user = prefix.split('!').next()?;
+    Some(ChatEvent::Message {
+        user: user.to_string(),
+        text: text.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_messages_are_parsed() {
+        let line = ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :reveal 3 -2";
+        assert!(matches!(
+            parse_message(line),
+            Some(ChatEvent::Message { user, text }) if user == "viewer" && text == "reveal 3 -2"
+        ));
+        // only the first ` :` separates the text
+        let line = ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :a :b";
+        assert!(matches!(
+            parse_message(line),
+            Some(ChatEvent::Message { text, .. }) if text == "a :b"
+        ));
+    }
+
+    #[test]
+    fn other_lines_are_skipped() {
+        for line in [
+            ":tmi.twitch.tv 001 justinfan31337 :Welcome, GLHF!",
+            ":justinfan31337!justinfan31337@justinfan31337.tmi.twitch.tv JOIN #channel",
+            "PING :tmi.twitch.tv",
+            ":viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel",
+            "",
+        ] {
+            assert!(parse_message(line).is_none(), "{line}");
+        }
+    }
+}
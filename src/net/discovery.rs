@@ -0,0 +1,76 @@
+//! LAN game discovery over UDP broadcast.
+//!
+//! Hosts periodically broadcast an [`Announcement`] to [`DISCOVERY_PORT`], and players looking for a game
+//! listen on that port. There is no request/response, so listeners simply collect whatever they hear.
+
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+};
+
+use serde::{Deserialize, Serialize};
+
+pub const DISCOVERY_PORT: u16 = 7880;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameMode {
+    Coop,
+    Versus,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Announcement {
+    pub mode: GameMode,
+    /// Port of the game itself, the address is taken from the datagram
+    pub port: u16,
+    pub host_name: String,
+    pub players: u32,
+}
+
+pub struct Beacon(UdpSocket);
+
+impl Beacon {
+    pub fn new() -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_broadcast(true)?;
+        Ok(Self(socket))
+    }
+
+    pub fn announce(&self, announcement: &Announcement) -> io::Result<()> {
+        let bytes = serde_json::to_vec(announcement)?;
+        self.0
+            .send_to(&bytes, (Ipv4Addr::BROADCAST, DISCOVERY_PORT))
+            .map(|_| ())
+    }
+}
+
+pub struct Listener(UdpSocket);
+
+impl Listener {
+    pub fn new() -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self(socket))
+    }
+
+    /// Announcements received since the last call, with the address to connect to
+    pub fn poll(&self) -> Vec<(SocketAddr, Announcement)> {
+        let mut received = Vec::new();
+        let mut buffer = [0; 1024];
+        loop {
+            match self.0.recv_from(&mut buffer) {
+                Ok((len, mut address)) => {
+                    // junk from other apps on the same port is silently ignored
+                    if let Ok(announcement) = serde_json::from_slice::<Announcement>(&buffer[..len])
+                    {
+                        address.set_port(announcement.port);
+                        received.push((address, announcement));
+                    }
+                }
+                // `WouldBlock` means everything is read, other errors are not worth retrying
+                Err(_) => break,
+            }
+        }
+        received
+    }
+}
@@ -0,0 +1,70 @@
+//! Minimal HTTP/1.0 client on top of `std::net`, enough to exchange JSON with a simple REST endpoint.
+//! HTTP/1.0 is used on purpose: servers answer it without chunked encoding and close the connection,
+//! so the whole response is just read till the end.
+
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn get(url: &str) -> Result<Vec<u8>, String> {
+    request("GET", url, &[])
+}
+
+pub fn post_json(url: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+    request("POST", url, body)
+}
+
+fn request(method: &str, url: &str, body: &[u8]) -> Result<Vec<u8>, String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("only plain http:// endpoints are supported, got '{url}'"))?;
+    let (authority, path) = match rest.split_once('/') {
+        Some((authority, path)) => (authority, format!("/{path}")),
+        None => (rest, "/".to_string()),
+    };
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{authority}:80")
+    };
+
+    let io_err = |err: std::io::Error| format!("{authority}: {err}");
+    let socket_addr = address
+        .to_socket_addrs()
+        .map_err(io_err)?
+        .next()
+        .ok_or_else(|| format!("{authority}: can't resolve the address"))?;
+    let mut stream = TcpStream::connect_timeout(&socket_addr, TIMEOUT).map_err(io_err)?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(io_err)?;
+    stream.set_write_timeout(Some(TIMEOUT)).map_err(io_err)?;
+
+    let head = format!(
+        "{method} {path} HTTP/1.0\r\nHost: {authority}\r\nAccept: application/json\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).map_err(io_err)?;
+    stream.write_all(body).map_err(io_err)?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).map_err(io_err)?;
+
+    let header_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| format!("{authority}: malformed response"))?;
+    // "HTTP/1.1 200 OK"
+    let status = std::str::from_utf8(&response[..header_end])
+        .ok()
+        .and_then(|head| head.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("{authority}: malformed status line"))?;
+    if !(200..300).contains(&status) {
+        return Err(format!("{authority}: server responded with {status}"));
+    }
+    Ok(response.split_off(header_end + 4))
+}
@@ -0,0 +1,43 @@
+//! Networking building blocks. Everything here is blocking by nature, so it's run off the main thread
+//! with [`Pending`] and polled from systems.
+
+pub mod chat;
+pub mod discovery;
+pub mod http;
+pub mod protocol;
+pub mod transport;
+
+use std::sync::{mpsc, Mutex};
+
+/// Result of a background job, which can be polled every frame until it's ready
+pub struct Pending<T>(Mutex<mpsc::Receiver<T>>);
+
+impl<T: Send + 'static> Pending<T> {
+    /// Runs `job` on a separate thread. There are no threads in the browser, so there it runs inline,
+    /// which is fine as networking isn't available there and fails immediately.
+    pub fn spawn(job: impl FnOnce() -> T + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let run = move || {
+            // receiver might be dropped if nobody waits for the result anymore
+            let _ = sender.send(job());
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(run);
+        #[cfg(target_arch = "wasm32")]
+        run();
+        Self(Mutex::new(receiver))
+    }
+
+    /// Takes the result if the job is done, or the error if it stopped without one, like after a panic
+    pub fn poll(&self) -> Option<Result<T, String>> {
+        let failed = || "the job stopped without a result".to_string();
+        let Ok(receiver) = self.0.lock() else {
+            return Some(Err(failed()));
+        };
+        match receiver.try_recv() {
+            Ok(result) => Some(Ok(result)),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(failed())),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Framing of the multiplayer messages on the wire, shared by all modes.
+//!
+//! Every connection starts with a handshake: the client sends [`Frame::Hello`] with its protocol version
+//! and capabilities, and the host answers with the version and capabilities both sides have, or rejects
+//! the client with a reason to show. The client checks that the answer is something it offered.
+//! Mode messages go in [`Frame::Message`] after that.
+
+use serde::{Deserialize, Serialize};
+
+use super::transport::PeerId;
+
+/// Bumped on every change of the frames or of the messages of any mode
+pub const VERSION: u32 = 1;
+/// Oldest version this one still talks to
+pub const MIN_VERSION: u32 = 1;
+
+/// Both sides send [`Frame::Heartbeat`] and drop connections that went silent
+pub const HEARTBEAT: &str = "heartbeat";
+/// Clients come back after a drop as the same peer, see [`Resume`]
+pub const RESUME: &str = "resume";
+/// Capabilities of this build, unknown ones of newer builds are ignored
+pub const CAPABILITIES: [&str; 2] = [HEARTBEAT, RESUME];
+
+#[derive(Serialize, Deserialize)]
+pub enum Frame<M> {
+    /// Client -> host, the first frame of every connection
+    Hello {
+        version: u32,
+        capabilities: Vec<String>,
+        /// Peer the client was before the connection dropped
+        resume: Option<Resume>,
+    },
+    /// Host -> client, the connection is accepted with what both sides support
+    Accepted {
+        version: u32,
+        capabilities: Vec<String>,
+        /// To come back as the same peer, only with [`RESUME`]
+        resume: Option<Resume>,
+    },
+    /// Host -> client right before closing the connection
+    Rejected {
+        reason: String,
+    },
+    /// Keeps the connection alive while there is nothing else to send
+    Heartbeat,
+    /// The connection is closed on purpose, there is nothing to come back to
+    Closed,
+    Message(M),
+}
+
+/// Id of the peer and the secret it was given by the host, so nobody else can take its place
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Resume {
+    pub peer: PeerId,
+    pub token: u64,
+}
+
+impl<M> Frame<M> {
+    pub fn hello(resume: Option<Resume>) -> Self {
+        Self::Hello {
+            version: VERSION,
+            capabilities: CAPABILITIES.map(String::from).to_vec(),
+            resume,
+        }
+    }
+}
+
+/// What both sides of a connection use
+pub struct Agreement {
+    pub version: u32,
+    pub capabilities: Vec<String>,
+}
+
+/// Host side: the newest version and the capabilities both sides have, or why they can't talk
+pub fn negotiate(version: u32, capabilities: &[String]) -> Result<Agreement, String> {
+    // a newer client talks the older version of the host, if it still can
+    let agreed = version.min(VERSION);
+    if agreed < MIN_VERSION {
+        return Err(format!(
+            "Protocol version {version} is too old, update the game to at least version {MIN_VERSION}"
+        ));
+    }
+    Ok(Agreement {
+        version: agreed,
+        capabilities: capabilities
+            .iter()
+            .filter(|capability| CAPABILITIES.contains(&capability.as_str()))
+            .cloned()
+            .collect(),
+    })
+}
+
+/// Client side: the answer of the host has to be a version and capabilities the client offered
+pub fn confirm(version: u32, capabilities: Vec<String>) -> Result<Agreement, String> {
+    if !(MIN_VERSION..=VERSION).contains(&version) {
+        return Err(format!(
+            "The host talks protocol version {version}, this game supports {MIN_VERSION} to {VERSION}"
+        ));
+    }
+    if let Some(unknown) = capabilities
+        .iter()
+        .find(|capability| !CAPABILITIES.contains(&capability.as_str()))
+    {
+        return Err(format!("The host agreed on unknown capability {unknown}"));
+    }
+    Ok(Agreement {
+        version,
+        capabilities,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capabilities(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn same_versions_agree_on_everything() {
+        let agreement = negotiate(VERSION, &capabilities(&CAPABILITIES)).expect("same version");
+        assert_eq!(agreement.version, VERSION);
+        assert_eq!(agreement.capabilities, capabilities(&CAPABILITIES));
+
+        let agreement =
+            confirm(agreement.version, agreement.capabilities).expect("what was offered");
+        assert_eq!(agreement.version, VERSION);
+        assert_eq!(agreement.capabilities, capabilities(&CAPABILITIES));
+    }
+
+    #[test]
+    fn peers_older_than_the_min_version_are_rejected() {
+        assert!(negotiate(MIN_VERSION - 1, &capabilities(&CAPABILITIES)).is_err());
+        assert!(confirm(MIN_VERSION - 1, capabilities(&CAPABILITIES)).is_err());
+    }
+
+    #[test]
+    fn peers_from_the_future_talk_the_older_version() {
+        // a newer client is answered with the version of the host
+        let agreement = negotiate(VERSION + 1, &capabilities(&CAPABILITIES)).expect("newer client");
+        assert_eq!(agreement.version, VERSION);
+        // while a newer host can't pick a version the client never offered
+        assert!(confirm(VERSION + 1, capabilities(&CAPABILITIES)).is_err());
+    }
+
+    #[test]
+    fn only_capabilities_of_both_sides_are_agreed_on() {
+        let offered = capabilities(&[HEARTBEAT, "teleport"]);
+        let agreement = negotiate(VERSION, &offered).expect("same version");
+        assert_eq!(agreement.capabilities, capabilities(&[HEARTBEAT]));
+
+        let agreement = negotiate(VERSION, &capabilities(&[RESUME])).expect("same version");
+        assert_eq!(agreement.capabilities, capabilities(&[RESUME]));
+        assert!(negotiate(VERSION, &[])
+            .expect("same version")
+            .capabilities
+            .is_empty());
+
+        assert!(confirm(VERSION, capabilities(&[RESUME])).is_ok());
+        assert!(confirm(VERSION, capabilities(&[HEARTBEAT, "teleport"])).is_err());
+    }
+}
@@ -0,0 +1,506 @@
+//! Message transport over TCP, one JSON [`Frame`] per line.
+//!
+//! Every connection gets a reader thread, which decodes messages and forwards them into a channel
+//! that is polled from systems. Messages are small, so writes happen directly from the caller.
+//!
+//! Heartbeats drop connections that went silent, like after a Wi-Fi blip. A client that lost its connection
+//! keeps reconnecting in the background for a while, and the host holds its place meanwhile,
+//! so the client comes back as the same peer and modes only have to catch it up.
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    marker::PhantomData,
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Serialize,
+};
+
+use super::protocol::{confirm, negotiate, Frame, Resume, HEARTBEAT, RESUME};
+use crate::rng;
+
+/// Host is always `0`, clients are numbered from `1` in the order they connect
+pub type PeerId = u32;
+
+pub const HOST: PeerId = 0;
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+/// Connections without a single frame for this long are dropped
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Peers that don't take a frame for this long are dropped, as frames are written from the game loop
+/// with all peers locked
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+/// How long the host holds the place of a dropped client, and the client keeps trying to come back
+const RESUME_TIMEOUT: Duration = Duration::from_secs(30);
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+pub enum TransportEvent<M> {
+    Connected(PeerId),
+    Message(PeerId, M),
+    /// Connection dropped, but the client is expected to come back
+    Reconnecting(PeerId),
+    /// Client is back as the same peer, and might have missed some messages
+    Reconnected(PeerId),
+    Disconnected(PeerId),
+}
+
+struct Connection {
+    stream: TcpStream,
+    /// Tells connections of the same peer apart, so the reader of a replaced one leaves quietly
+    id: u64,
+    heartbeat: bool,
+}
+
+/// Connection right after the handshake
+struct Link {
+    stream: TcpStream,
+    /// Might have read past the handshake already, so it's kept for the reader thread
+    reader: BufReader<TcpStream>,
+    heartbeat: bool,
+}
+
+/// Where and as whom the client reconnects
+#[derive(Clone)]
+struct Reconnect {
+    address: String,
+    resume: Resume,
+}
+
+/// State shared with the background threads
+#[derive(Default)]
+struct Shared {
+    peers: Mutex<HashMap<PeerId, Connection>>,
+    /// Secrets of the clients that can resume, on the host
+    tokens: Mutex<HashMap<PeerId, u64>>,
+    /// Dropped clients the host holds the place of, with the time they dropped
+    away: Mutex<HashMap<PeerId, Instant>>,
+    next_connection: AtomicU64,
+    reconnecting: AtomicBool,
+    closed: AtomicBool,
+}
+
+type Events<M> = mpsc::Sender<TransportEvent<M>>;
+
+pub struct Transport<M> {
+    shared: Arc<Shared>,
+    events: Mutex<mpsc::Receiver<TransportEvent<M>>>,
+    _message: PhantomData<fn(M)>,
+}
+
+impl<M: Serialize + DeserializeOwned + Send + 'static> Transport<M> {
+    /// Starts accepting clients on `port` in the background
+    pub fn host(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        // non-blocking accept lets the thread notice that the transport is dropped and free the port
+        listener.set_nonblocking(true)?;
+        let shared = Arc::new(Shared::default());
+        let (sender, receiver) = mpsc::channel();
+
+        let accept_shared = shared.clone();
+        std::thread::spawn(move || {
+            let next_id = Arc::new(AtomicU32::new(HOST + 1));
+            while !accept_shared.closed.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _address)) => {
+                        let (shared, sender, next_id) =
+                            (accept_shared.clone(), sender.clone(), next_id.clone());
+                        // a slow handshake shouldn't hold up other clients
+                        std::thread::spawn(move || {
+                            if let Err(err) = accept_client(stream, &shared, sender, &next_id) {
+                                bevy::log::warn!("Failed to accept a client: {err}");
+                            }
+                        });
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        forget_away(&accept_shared, &sender);
+                        std::thread::sleep(Duration::from_millis(50));
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        spawn_heartbeat(&shared);
+
+        Ok(Self {
+            shared,
+            events: Mutex::new(receiver),
+            _message: PhantomData,
+        })
+    }
+
+    /// Connects to the host at `address` like "192.168.0.2:7878".
+    /// Fails if the host can't talk to this version of the game.
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let (link, resume) = handshake(address, None)?;
+        let shared = Arc::new(Shared::default());
+        let (sender, receiver) = mpsc::channel();
+        let reconnect = resume.map(|resume| Reconnect {
+            address: address.to_string(),
+            resume,
+        });
+        add_peer(&shared, HOST, link, sender.clone(), reconnect)?;
+        let _ = sender.send(TransportEvent::Connected(HOST));
+        spawn_heartbeat(&shared);
+
+        Ok(Self {
+            shared,
+            events: Mutex::new(receiver),
+            _message: PhantomData,
+        })
+    }
+
+    pub fn send(&self, peer: PeerId, message: &M) {
+        self.send_filtered(message, |id| id == peer);
+    }
+
+    pub fn broadcast(&self, message: &M) {
+        self.send_filtered(message, |_| true);
+    }
+
+    pub fn broadcast_except(&self, except: PeerId, message: &M) {
+        self.send_filtered(message, |id| id != except);
+    }
+
+    pub fn peers_count(&self) -> usize {
+        self.shared.peers.lock().map_or(0, |peers| peers.len())
+    }
+
+    /// The client lost its connection and is trying to get it back
+    pub fn is_reconnecting(&self) -> bool {
+        self.shared.reconnecting.load(Ordering::Relaxed)
+    }
+
+    /// Takes all events received since the last call
+    pub fn poll(&self) -> Vec<TransportEvent<M>> {
+        self.events
+            .lock()
+            .map(|events| events.try_iter().collect())
+            .unwrap_or_default()
+    }
+
+    fn send_filtered(&self, message: &M, filter: impl Fn(PeerId) -> bool) {
+        send_frame(&self.shared, &Frame::Message(message), |id, _| filter(id));
+    }
+}
+
+impl<M> Drop for Transport<M> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Relaxed);
+        send_frame(&self.shared, &Frame::<()>::Closed, |_, _| true);
+        if let Ok(peers) = self.shared.peers.lock() {
+            for connection in peers.values() {
+                let _ = connection.stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+/// Writes `frame` to every peer that passes the `filter`
+fn send_frame(
+    shared: &Shared,
+    frame: &impl Serialize,
+    filter: impl Fn(PeerId, &Connection) -> bool,
+) {
+    let Ok(mut line) = serde_json::to_vec(frame) else {
+        return;
+    };
+    line.push(b'\n');
+
+    let Ok(mut peers) = shared.peers.lock() else {
+        return;
+    };
+    for (id, connection) in peers.iter_mut() {
+        if filter(*id, connection) && connection.stream.write_all(&line).is_err() {
+            // reader thread notices the broken connection and reports it
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn spawn_heartbeat(shared: &Arc<Shared>) {
+    let shared = shared.clone();
+    std::thread::spawn(move || {
+        while !shared.closed.load(Ordering::Relaxed) {
+            std::thread::sleep(HEARTBEAT_INTERVAL);
+            send_frame(&shared, &Frame::<()>::Heartbeat, |_, connection| {
+                connection.heartbeat
+            });
+        }
+    });
+}
+
+/// Host side of the handshake, the client becomes a new peer or takes its place back
+fn accept_client<M: DeserializeOwned + Send + 'static>(
+    stream: TcpStream,
+    shared: &Arc<Shared>,
+    events: Events<M>,
+    next_id: &AtomicU32,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Frame::Hello {
+        version,
+        capabilities,
+        resume,
+    } = read_frame(&mut reader)?
+    else {
+        return Err(invalid("expected a hello"));
+    };
+    let agreement = match negotiate(version, &capabilities) {
+        Ok(agreement) => agreement,
+        Err(reason) => {
+            write_frame(
+                &stream,
+                &Frame::<()>::Rejected {
+                    reason: reason.clone(),
+                },
+            )?;
+            return Err(invalid(reason));
+        }
+    };
+    let resumable = agreement
+        .capabilities
+        .iter()
+        .any(|capability| capability == RESUME);
+    let heartbeat = agreement
+        .capabilities
+        .iter()
+        .any(|capability| capability == HEARTBEAT);
+
+    // the same peer again with the right secret, a new one otherwise
+    let resumed = resume.filter(|resume| {
+        let tokens = shared.tokens.lock();
+        resumable && tokens.is_ok_and(|tokens| tokens.get(&resume.peer) == Some(&resume.token))
+    });
+    let (peer, token) = match resumed {
+        Some(resume) => (resume.peer, resume.token),
+        None => (next_id.fetch_add(1, Ordering::Relaxed), rng::random_seed()),
+    };
+    if resumable {
+        if let Ok(mut tokens) = shared.tokens.lock() {
+            tokens.insert(peer, token);
+        }
+    }
+    write_frame(
+        &stream,
+        &Frame::<()>::Accepted {
+            version: agreement.version,
+            capabilities: agreement.capabilities,
+            resume: resumable.then_some(Resume { peer, token }),
+        },
+    )?;
+
+    let link = Link {
+        stream,
+        reader,
+        heartbeat,
+    };
+    add_peer(shared, peer, link, events.clone(), None)?;
+    let event = match resumed {
+        Some(_) => {
+            if let Ok(mut away) = shared.away.lock() {
+                away.remove(&peer);
+            }
+            TransportEvent::Reconnected(peer)
+        }
+        None => TransportEvent::Connected(peer),
+    };
+    let _ = events.send(event);
+    Ok(())
+}
+
+/// Client side of the handshake, returns the place to come back to after a drop if the host holds them
+fn handshake(address: &str, resume: Option<Resume>) -> io::Result<(Link, Option<Resume>)> {
+    let stream = TcpStream::connect(address)?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    write_frame(&stream, &Frame::<()>::hello(resume))?;
+    match read_frame(&mut reader)? {
+        Frame::Accepted {
+            version,
+            capabilities,
+            resume,
+        } => {
+            let agreement = confirm(version, capabilities).map_err(invalid)?;
+            let heartbeat = agreement
+                .capabilities
+                .iter()
+                .any(|capability| capability == HEARTBEAT);
+            let link = Link {
+                stream,
+                reader,
+                heartbeat,
+            };
+            Ok((link, resume))
+        }
+        Frame::Rejected { reason } => Err(invalid(reason)),
+        _ => Err(invalid("expected an answer to the hello")),
+    }
+}
+
+fn add_peer<M: DeserializeOwned + Send + 'static>(
+    shared: &Arc<Shared>,
+    peer: PeerId,
+    link: Link,
+    events: Events<M>,
+    reconnect: Option<Reconnect>,
+) -> io::Result<()> {
+    let Link {
+        stream,
+        mut reader,
+        heartbeat,
+    } = link;
+    stream.set_nodelay(true)?;
+    // a silent connection is a dropped one, but only if the other side promises to keep talking
+    stream.set_read_timeout(heartbeat.then_some(HEARTBEAT_TIMEOUT))?;
+    // a peer that stopped reading would otherwise block every other one once its buffers are full
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let id = shared.next_connection.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut peers) = shared.peers.lock() {
+        let connection = Connection {
+            stream,
+            id,
+            heartbeat,
+        };
+        // the host might not have noticed the drop of a client that is back already
+        if let Some(replaced) = peers.insert(peer, connection) {
+            let _ = replaced.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    let shared = shared.clone();
+    std::thread::spawn(move || {
+        let mut farewell = false;
+        for line in (&mut reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match serde_json::from_str(&line) {
+                Ok(Frame::Message(message)) => {
+                    if events.send(TransportEvent::Message(peer, message)).is_err() {
+                        return;
+                    }
+                }
+                Ok(Frame::Closed) => {
+                    farewell = true;
+                    break;
+                }
+                Ok(_) => {}
+                Err(err) => bevy::log::warn!("Malformed message from peer {peer}: {err}"),
+            }
+        }
+        if let Ok(mut peers) = shared.peers.lock() {
+            if peers.get(&peer).map(|connection| connection.id) != Some(id) {
+                return;
+            }
+            if let Some(connection) = peers.remove(&peer) {
+                let _ = connection.stream.shutdown(Shutdown::Both);
+            }
+        }
+        if shared.closed.load(Ordering::Relaxed) {
+            return;
+        }
+        // there is no place to hold for a peer that left on purpose
+        let held = match shared.tokens.lock() {
+            Ok(mut tokens) if farewell => {
+                tokens.remove(&peer);
+                false
+            }
+            Ok(tokens) => tokens.contains_key(&peer),
+            Err(_) => false,
+        };
+        match reconnect {
+            Some(reconnect) if !farewell => {
+                let _ = events.send(TransportEvent::Reconnecting(peer));
+                resume_connection(&shared, reconnect, events);
+            }
+            _ if held => {
+                if let Ok(mut away) = shared.away.lock() {
+                    away.insert(peer, Instant::now());
+                }
+                let _ = events.send(TransportEvent::Reconnecting(peer));
+            }
+            _ => {
+                let _ = events.send(TransportEvent::Disconnected(peer));
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Keeps reconnecting to the host until it takes the client back, or for [`RESUME_TIMEOUT`] at most
+fn resume_connection<M: DeserializeOwned + Send + 'static>(
+    shared: &Arc<Shared>,
+    reconnect: Reconnect,
+    events: Events<M>,
+) {
+    shared.reconnecting.store(true, Ordering::Relaxed);
+    let started = Instant::now();
+    while started.elapsed() < RESUME_TIMEOUT && !shared.closed.load(Ordering::Relaxed) {
+        match handshake(&reconnect.address, Some(reconnect.resume)) {
+            Ok((link, resume)) if resume == Some(reconnect.resume) => {
+                shared.reconnecting.store(false, Ordering::Relaxed);
+                let added = add_peer(shared, HOST, link, events.clone(), Some(reconnect));
+                let event = match added {
+                    Ok(()) => TransportEvent::Reconnected(HOST),
+                    Err(_) => TransportEvent::Disconnected(HOST),
+                };
+                let _ = events.send(event);
+                return;
+            }
+            // the host doesn't hold the place anymore, like after a restart
+            Ok(_) => break,
+            Err(err) => {
+                bevy::log::debug!("Failed to reconnect to {}: {err}", reconnect.address);
+                std::thread::sleep(RECONNECT_DELAY);
+            }
+        }
+    }
+    shared.reconnecting.store(false, Ordering::Relaxed);
+    let _ = events.send(TransportEvent::Disconnected(HOST));
+}
+
+/// Gives up on the clients that didn't come back in time
+fn forget_away<M>(shared: &Shared, events: &Events<M>) {
+    let Ok(mut away) = shared.away.lock() else {
+        return;
+    };
+    away.retain(|peer, since| {
+        if since.elapsed() < RESUME_TIMEOUT {
+            return true;
+        }
+        if let Ok(mut tokens) = shared.tokens.lock() {
+            tokens.remove(peer);
+        }
+        let _ = events.send(TransportEvent::Disconnected(*peer));
+        false
+    });
+}
+
+fn read_frame(reader: &mut BufReader<TcpStream>) -> io::Result<Frame<IgnoredAny>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    serde_json::from_str(&line).map_err(|err| invalid(err.to_string()))
+}
+
+fn write_frame(mut stream: &TcpStream, frame: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_vec(frame)?;
+    line.push(b'\n');
+    stream.write_all(&line)
+}
+
+fn invalid(reason: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.into())
+}
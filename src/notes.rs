@@ -0,0 +1,88 @@
+//! Flag colors for note-taking: right-clicking a flag moves it through [`Settings::flag_colors`] colors
+//! before it's removed. Colors mean whatever the player wants, like "certain" or "one of these two",
+//! so they are kept apart from the board and never change the game.
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::Hex;
+
+use crate::{
+    any_events,
+    board::Board,
+    game::{GameSet, NewCustomGame, NewGame},
+    FlagSprite,
+};
+
+/// Tints of flags in the order they are cycled, the first one leaves the sprite as is
+pub const NOTE_COLORS: [Color; 4] = [
+    Color::WHITE,
+    Color::rgb(1.0, 0.85, 0.2),
+    Color::rgb(0.3, 0.6, 1.0),
+    Color::rgb(0.35, 0.85, 0.35),
+];
+
+pub struct NotesPlugin;
+
+impl Plugin for NotesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlagNotes>().add_systems(
+            Update,
+            // flags are redrawn by the board renderer, which runs after the actions as well
+            (forget_notes, color_flags).chain().after(GameSet::Actions),
+        );
+    }
+}
+
+/// Color of every flag that isn't of the first one, as an index into [`NOTE_COLORS`]
+#[derive(Resource, Default)]
+pub struct FlagNotes(HashMap<Hex, usize>);
+
+impl FlagNotes {
+    /// Moves the flag on `hex` to its next color, `false` once it went through all `colors`
+    /// and should be removed
+    pub fn cycle(&mut self, hex: Hex, colors: u8) -> bool {
+        let colors = (colors as usize).clamp(1, NOTE_COLORS.len());
+        let next = self.0.get(&hex).map_or(1, |note| note + 1);
+        if next < colors {
+            self.0.insert(hex, next);
+            true
+        } else {
+            self.0.remove(&hex);
+            false
+        }
+    }
+}
+
+fn forget_notes(
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    board: Res<Board>,
+    mut notes: ResMut<FlagNotes>,
+) {
+    if any_events(&mut new_games) | any_events(&mut custom_games) {
+        notes.0.clear();
+        return;
+    }
+    // flags can be removed by other players or power-ups too
+    if notes.0.keys().any(|hex| !board.flagged.contains(hex)) {
+        notes.0.retain(|hex, _| board.flagged.contains(hex));
+    }
+}
+
+fn color_flags(
+    board: Res<Board>,
+    notes: Res<FlagNotes>,
+    mut flags: Query<(Ref<FlagSprite>, &mut Sprite)>,
+) {
+    // colored mines give flags colors of their own
+    if board.config.rules.colored {
+        return;
+    }
+    for (flag, mut sprite) in &mut flags {
+        if !notes.is_changed() && !flag.is_added() {
+            continue;
+        }
+        if let Some(note) = notes.0.get(&flag.0) {
+            sprite.color = NOTE_COLORS[*note];
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Picture reveal: a casual look where every uncovered hex shows its part of a hidden picture,
+//! so clearing the board slowly uncovers the whole image. Pictures come from `assets/pictures`,
+//! and PNG files in `pictures` within the data directory are added to the gallery after them.
+//!
+//! Only the look changes, the game itself is the same.
+
+use bevy::{
+    asset::HandleId,
+    prelude::*,
+    render::texture::{CompressedImageFormats, ImageType},
+    utils::HashMap,
+};
+use hexx::Hex;
+
+use crate::{
+    any_events,
+    board::Board,
+    game::{CellsChanged, GameSet, RowsCollapsed},
+    settings::Settings,
+    storage, HexGrid,
+};
+
+const PICTURES_DIR: &str = "pictures";
+/// Names of the pictures in `assets/pictures`
+const BUNDLED: [&str; 3] = ["sunset", "mountains", "flower"];
+/// Above the decals and below the numbers and sprites of the hex
+const PIECE_Z: f32 = 1.5;
+
+pub struct PicturePlugin;
+
+impl Plugin for PicturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pieces>()
+            .add_systems(Startup, load_gallery)
+            .add_systems(Update, reveal_pieces.after(GameSet::Actions));
+    }
+}
+
+pub struct Picture {
+    pub name: String,
+    image: Handle<Image>,
+}
+
+/// Pictures to pick from, with the one shown under the board, if any
+#[derive(Resource)]
+pub struct Gallery {
+    pictures: Vec<Picture>,
+    selected: Option<usize>,
+}
+
+impl Gallery {
+    pub fn selected(&self) -> Option<&Picture> {
+        self.selected.and_then(|index| self.pictures.get(index))
+    }
+
+    /// Switches to the next picture, and then back to none after the last one
+    pub fn cycle(&mut self) {
+        self.selected = match self.selected {
+            None if !self.pictures.is_empty() => Some(0),
+            Some(index) if index + 1 < self.pictures.len() => Some(index + 1),
+            _ => None,
+        };
+    }
+
+    pub fn label(&self) -> String {
+        match self.selected() {
+            Some(picture) => format!("Picture: {}", picture.name),
+            None => "Picture: off".to_string(),
+        }
+    }
+}
+
+/// Shown parts of the picture, one entity per uncovered hex
+#[derive(Resource, Default)]
+struct Pieces(HashMap<Hex, Entity>);
+
+fn load_gallery(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let bundled = BUNDLED.map(|name| Picture {
+        name: name.to_string(),
+        image: asset_server.load(format!("pictures/{name}.png")),
+    });
+    let user = user_images().into_iter().map(|(name, image)| Picture {
+        name,
+        image: images.add(image),
+    });
+    commands.insert_resource(Gallery {
+        pictures: bundled.into_iter().chain(user).collect(),
+        selected: None,
+    });
+}
+
+/// Images from the data directory, ordered by file names. Broken ones are skipped with a warning.
+fn user_images() -> Vec<(String, Image)> {
+    storage::read_all_in(PICTURES_DIR, "png")
+        .into_iter()
+        .filter_map(|(name, bytes)| {
+            let image = Image::from_buffer(
+                &bytes,
+                ImageType::Extension("png"),
+                CompressedImageFormats::NONE,
+                true,
+            );
+            match image {
+                Ok(image) => Some((name, image)),
+                Err(err) => {
+                    warn!("Skipping picture {name}: {err}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn reveal_pieces(
+    mut commands: Commands,
+    mut cells_changed: EventReader<CellsChanged>,
+    mut collapsed: EventReader<RowsCollapsed>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    gallery: Res<Gallery>,
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    settings: Res<Settings>,
+    mut pieces: ResMut<Pieces>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    images: Res<Assets<Image>>,
+    mut material: Local<Option<(HandleId, Handle<ColorMaterial>)>>,
+) {
+    let picture = gallery.selected();
+    let image_loaded = image_events.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+            picture.is_some_and(|picture| picture.image == *handle)
+        }
+        AssetEvent::Removed { .. } => false,
+    });
+    // pieces depend on the bounds of the board and the shape of its cells
+    let redraw_all = any_events(&mut collapsed)
+        || gallery.is_changed()
+        || grid.is_changed()
+        || settings.is_changed()
+        || image_loaded;
+    let mut changed: Vec<Hex> = cells_changed
+        .iter()
+        .flat_map(|CellsChanged(hexes)| hexes)
+        .copied()
+        .collect();
+    if redraw_all {
+        for entity in pieces.0.values() {
+            commands.entity(*entity).despawn();
+        }
+        pieces.0.clear();
+        changed = board.cells.iter().copied().collect();
+    }
+
+    let Some(picture) = picture else {
+        return;
+    };
+    // waits for the bundled pictures to load, as their size is needed to fit them
+    let Some(image) = images.get(&picture.image) else {
+        return;
+    };
+    let material = match &*material {
+        Some((id, material)) if *id == picture.image.id() => material.clone(),
+        _ => {
+            let handle = materials.add(picture.image.clone().into());
+            *material = Some((picture.image.id(), handle.clone()));
+            handle
+        }
+    };
+
+    let geometry = board.config.rules.geometry;
+    let to_uv = picture_uv(&board, image.size());
+    for hex in changed {
+        let shown = board.contains(hex) && !board.covered.contains(&hex);
+        match (shown, pieces.0.get(&hex).copied()) {
+            (true, None) => {
+                let Some(mut mesh) = meshes.get(&grid.mesh_at(geometry, hex)).cloned() else {
+                    continue;
+                };
+                let center = geometry.to_world(hex);
+                let uvs: Option<Vec<[f32; 2]>> = mesh
+                    .attribute(Mesh::ATTRIBUTE_POSITION)
+                    .and_then(|positions| positions.as_float3())
+                    .map(|positions| {
+                        positions
+                            .iter()
+                            .map(|[x, y, _]| to_uv(center + Vec2::new(*x, *y)).to_array())
+                            .collect()
+                    });
+                let Some(uvs) = uvs else {
+                    continue;
+                };
+                mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+                let entity = commands
+                    .spawn(ColorMesh2dBundle {
+                        mesh: meshes.add(mesh).into(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(center.extend(PIECE_Z)),
+                        ..default()
+                    })
+                    .id();
+                pieces.0.insert(hex, entity);
+            }
+            (false, Some(entity)) => {
+                commands.entity(entity).despawn();
+                pieces.0.remove(&hex);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Position within the picture of a world position over the board. The picture covers the whole board,
+/// cropped to keep its proportions.
+fn picture_uv(board: &Board, image_size: Vec2) -> impl Fn(Vec2) -> Vec2 {
+    let geometry = board.config.rules.geometry;
+    let half_cell = geometry.half_extent();
+    let (min, max) = board.cells.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), hex| {
+            let pos = geometry.to_world(*hex);
+            (min.min(pos - half_cell), max.max(pos + half_cell))
+        },
+    );
+    let size = (max - min).max(Vec2::ONE);
+    // part of the picture on each axis, which is less than all of it on the longer side of the picture
+    let (board_aspect, image_aspect) = (size.x / size.y, image_size.x / image_size.y.max(1.0));
+    let part = if image_aspect > board_aspect {
+        Vec2::new(board_aspect / image_aspect, 1.0)
+    } else {
+        Vec2::new(1.0, image_aspect / board_aspect)
+    };
+    move |pos| {
+        // image rows go down
+        let uv = Vec2::new((pos.x - min.x) / size.x, (max.y - pos.y) / size.y);
+        (uv - 0.5) * part + 0.5
+    }
+}
@@ -0,0 +1,181 @@
+//! Keeps the window where it was left: its size, position, monitor and fullscreen state are saved in the settings
+//! and restored at startup. `F11` toggles fullscreen.
+//!
+//! Monitors come and go between runs, so a saved position is used only when it's still on a connected monitor,
+//! otherwise the window opens centered on the primary one. The size is shrunk to fit the monitor too.
+
+use bevy::{
+    prelude::*,
+    window::{MonitorSelection, PrimaryWindow, WindowMode, WindowPosition},
+    winit::WinitWindows,
+};
+
+use crate::{
+    mini::MiniMode,
+    settings::{Settings, WindowPlacement},
+};
+
+/// Changes are saved once the window stays put for this long, not on every step of a drag
+const SETTLE_SECONDS: f32 = 0.5;
+/// Part of the window next to its top left corner that has to be on a monitor to be reachable, in physical pixels
+const MIN_VISIBLE: i32 = 64;
+/// Smallest restored size, in logical pixels
+const MIN_SIZE: f32 = 320.0;
+
+pub struct WindowPlacementPlugin;
+
+impl Plugin for WindowPlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                restore_placement,
+                toggle_fullscreen,
+                // the mini window is not the one to come back to
+                track_placement.run_if(not(resource_exists::<MiniMode>())),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Connected monitor, in physical pixels of the desktop
+struct Monitor {
+    name: String,
+    position: IVec2,
+    size: IVec2,
+    scale: f32,
+}
+
+impl Monitor {
+    fn shows(&self, position: IVec2) -> bool {
+        let corner = position + IVec2::splat(MIN_VISIBLE) - self.position;
+        corner.cmpge(IVec2::ZERO).all() && corner.cmplt(self.size).all()
+    }
+}
+
+fn restore_placement(
+    mut restored: Local<bool>,
+    settings: Res<Settings>,
+    winit_windows: NonSend<WinitWindows>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+) {
+    if *restored {
+        return;
+    }
+    let Ok((entity, mut window)) = windows.get_single_mut() else {
+        return;
+    };
+    // the native window is created a frame after the entity, and only it knows the monitors
+    let Some(native) = winit_windows.get_window(entity) else {
+        return;
+    };
+    *restored = true;
+
+    let monitors: Vec<_> = native
+        .available_monitors()
+        .map(|monitor| Monitor {
+            name: monitor.name().unwrap_or_default(),
+            position: IVec2::new(monitor.position().x, monitor.position().y),
+            size: IVec2::new(monitor.size().width as i32, monitor.size().height as i32),
+            scale: monitor.scale_factor() as f32,
+        })
+        .collect();
+    // the browser has no monitors to tell, and the page decides the size there
+    if monitors.is_empty() {
+        return;
+    }
+
+    let saved = &settings.window;
+    // the same monitor first, in case monitors overlap after being rearranged
+    let on_monitor = saved.position.and_then(|[x, y]| {
+        let position = IVec2::new(x, y);
+        monitors
+            .iter()
+            .filter(|monitor| monitor.shows(position))
+            .max_by_key(|monitor| monitor.name == saved.monitor)
+            .map(|monitor| (position, monitor))
+    });
+    match on_monitor {
+        Some((position, _)) => window.position = WindowPosition::At(position),
+        None if saved.position.is_some() => {
+            info!("Monitor of the saved window position is gone, centering the window");
+            window.position = WindowPosition::Centered(MonitorSelection::Primary);
+        }
+        None => {}
+    }
+    if let Some([width, height]) = saved.size {
+        let monitor = on_monitor
+            .map(|(_, monitor)| monitor)
+            .unwrap_or(&monitors[0]);
+        let max = monitor.size.as_vec2() / monitor.scale;
+        window.resolution.set(
+            width.clamp(MIN_SIZE, max.x.max(MIN_SIZE)),
+            height.clamp(MIN_SIZE, max.y.max(MIN_SIZE)),
+        );
+    }
+    if saved.fullscreen {
+        window.mode = WindowMode::BorderlessFullscreen;
+    }
+}
+
+fn toggle_fullscreen(
+    keys: Res<Input<KeyCode>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !keys.just_pressed(KeyCode::F11) {
+        return;
+    }
+    for mut window in &mut windows {
+        window.mode = match window.mode {
+            WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+            _ => WindowMode::Windowed,
+        };
+    }
+}
+
+fn track_placement(
+    time: Res<Time>,
+    mut settings: ResMut<Settings>,
+    winit_windows: NonSend<WinitWindows>,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+    mut pending: Local<Option<(WindowPlacement, f32)>>,
+) {
+    let Ok((entity, window)) = windows.get_single() else {
+        return;
+    };
+    // nothing to track before the saved placement is restored
+    let Some(native) = winit_windows.get_window(entity) else {
+        return;
+    };
+    let fullscreen = window.mode != WindowMode::Windowed;
+    // size and position of a fullscreen window are the monitor's, so the windowed ones are kept
+    let mut placement = WindowPlacement {
+        fullscreen,
+        ..settings.window.clone()
+    };
+    if !fullscreen {
+        placement.size = Some([window.width(), window.height()]);
+        if let WindowPosition::At(position) = window.position {
+            placement.position = Some(position.to_array());
+        }
+        if let Some(name) = native.current_monitor().and_then(|monitor| monitor.name()) {
+            placement.monitor = name;
+        }
+    }
+
+    if placement == settings.window {
+        *pending = None;
+        return;
+    }
+    match pending.as_mut() {
+        Some((waiting, since)) if *waiting == placement => {
+            *since += time.delta_seconds();
+            if *since >= SETTLE_SECONDS {
+                settings.window = placement;
+                *pending = None;
+            }
+        }
+        _ => *pending = Some((placement, 0.0)),
+    }
+}
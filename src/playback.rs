@@ -0,0 +1,97 @@
+//! Playback of recorded games: replays the steps on the same board with the original timing.
+//! While it's active, [`Authority::Replay`] keeps the local input away from the board.
+
+use bevy::prelude::*;
+
+use crate::{
+    board::{BoardConfig, Layout},
+    game::{Authority, Game, GameAction, GameSet, NewCustomGame, NewGame},
+    history::HistoryEntry,
+    replay::ReplayStep,
+    AppState,
+};
+
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                feed_actions
+                    .in_set(GameSet::Route)
+                    .run_if(resource_exists::<Playback>().and_then(in_state(AppState::Playing))),
+                stop_on_other_games
+                    .in_set(GameSet::NewGame)
+                    .run_if(resource_exists::<Playback>()),
+            ),
+        );
+    }
+}
+
+/// Replay that is being played. Use [`Playback::start`] to make sure the board matches the replay.
+#[derive(Resource)]
+pub struct Playback {
+    config: BoardConfig,
+    seed: u64,
+    /// Content of hand-made boards, see [`HistoryEntry::layout`]
+    layout: Option<Layout>,
+    steps: Vec<ReplayStep>,
+    next: usize,
+}
+
+impl Playback {
+    pub fn start(
+        commands: &mut Commands,
+        new_game: &mut EventWriter<NewGame>,
+        custom_game: &mut EventWriter<NewCustomGame>,
+        entry: &HistoryEntry,
+    ) {
+        commands.insert_resource(Playback {
+            config: entry.config,
+            seed: entry.seed,
+            layout: entry.layout.as_ref().map(|puzzle| puzzle.layout()),
+            steps: entry.replay.steps.clone(),
+            next: 0,
+        });
+        commands.insert_resource(Authority::Replay);
+        entry.start(new_game, custom_game);
+    }
+}
+
+/// Sends the steps that are due, the game clock starts with the first one just like in the original game
+fn feed_actions(
+    game: Res<Game>,
+    mut playback: ResMut<Playback>,
+    mut actions: EventWriter<GameAction>,
+) {
+    let elapsed_ms = game.elapsed.as_millis() as u32;
+    while let Some(step) = playback.steps.get(playback.next) {
+        if step.at_ms > elapsed_ms {
+            break;
+        }
+        actions.send(step.action());
+        playback.next += 1;
+    }
+}
+
+/// Playback ends as soon as any other board is started
+fn stop_on_other_games(
+    mut commands: Commands,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    playback: Res<Playback>,
+) {
+    let generated = new_games.iter().any(|new_game| {
+        playback.layout.is_some()
+            || new_game.seed != playback.seed
+            || new_game.config != playback.config
+    });
+    let custom = custom_games.iter().any(|custom_game| {
+        playback.layout.as_ref() != Some(&custom_game.layout) || custom_game.seed != playback.seed
+    });
+    if generated || custom {
+        commands.remove_resource::<Playback>();
+        commands.insert_resource(Authority::Local);
+    }
+}
@@ -0,0 +1,186 @@
+//! Power-ups mode: consumables earned by revealing hexes and used with hotkeys.
+//!
+//! - `1` radar: click a hex to show mines among it and its neighbors
+//! - `2` shield: absorbs the next mine hit
+//! - `3` mass flag: flags all mines that can be deduced from the revealed hexes
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    any_events,
+    board::Board,
+    cursor_hex,
+    game::{
+        ActionRequest, BoardProgress, CellsChanged, GameAction, GameSet, NewCustomGame, NewGame,
+    },
+    solver::forced_mines,
+    AppState, BoardView,
+};
+
+/// Revealed hexes needed to earn the next power-up
+const EARN_EVERY: u32 = 40;
+const RADAR_RADIUS: u32 = 1;
+
+pub struct PowerUpsPlugin;
+
+impl Plugin for PowerUpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inventory>().add_systems(
+            Update,
+            (
+                reset_inventory.after(GameSet::NewGame),
+                earn_power_ups.after(GameSet::Actions),
+                (use_power_ups, target_radar)
+                    .chain()
+                    // the radar click must not reach the board
+                    .before(GameSet::Input)
+                    .run_if(in_state(AppState::Playing)),
+            ),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUp {
+    Radar,
+    Shield,
+    MassFlag,
+}
+
+impl PowerUp {
+    pub const ALL: [Self; 3] = [Self::Radar, Self::Shield, Self::MassFlag];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Radar => "Radar",
+            Self::Shield => "Shield",
+            Self::MassFlag => "Mass flag",
+        }
+    }
+
+    pub fn hotkey(self) -> KeyCode {
+        match self {
+            Self::Radar => KeyCode::Key1,
+            Self::Shield => KeyCode::Key2,
+            Self::MassFlag => KeyCode::Key3,
+        }
+    }
+}
+
+/// Power-ups of the current game, under [`Rules::powerups`](crate::board::Rules::powerups)
+#[derive(Resource, Default)]
+pub struct Inventory {
+    /// Amount of every power-up, in the order of [`PowerUp::ALL`]
+    pub counts: [u32; 3],
+    /// The radar is used on the next clicked hex
+    pub targeting: bool,
+    /// Power-ups earned so far, which decides the next one
+    earned: u32,
+    /// Revealed hexes towards the next power-up
+    progress: u32,
+}
+
+impl Inventory {
+    pub fn count(&self, power_up: PowerUp) -> u32 {
+        self.counts[power_up as usize]
+    }
+
+    /// Takes one power-up if there is any left
+    fn take(&mut self, power_up: PowerUp) -> bool {
+        let count = &mut self.counts[power_up as usize];
+        if *count == 0 {
+            return false;
+        }
+        *count -= 1;
+        true
+    }
+}
+
+fn reset_inventory(
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    mut inventory: ResMut<Inventory>,
+) {
+    if any_events(&mut new_games) | any_events(&mut custom_games) {
+        *inventory = Inventory::default();
+    }
+}
+
+fn earn_power_ups(
+    mut progress: EventReader<BoardProgress>,
+    board: Res<Board>,
+    mut inventory: ResMut<Inventory>,
+) {
+    for BoardProgress { uncovered, .. } in progress.iter() {
+        if !board.config.rules.powerups {
+            continue;
+        }
+        inventory.progress += *uncovered as u32;
+        while inventory.progress >= EARN_EVERY {
+            inventory.progress -= EARN_EVERY;
+            // power-ups come in turns, so every game gets the same ones
+            let next = PowerUp::ALL[inventory.earned as usize % PowerUp::ALL.len()];
+            inventory.counts[next as usize] += 1;
+            inventory.earned += 1;
+        }
+    }
+}
+
+fn use_power_ups(
+    keys: Res<Input<KeyCode>>,
+    mut board: ResMut<Board>,
+    mut inventory: ResMut<Inventory>,
+    mut requests: EventWriter<ActionRequest>,
+) {
+    if !board.config.rules.powerups {
+        return;
+    }
+    if keys.just_pressed(PowerUp::Radar.hotkey()) && inventory.count(PowerUp::Radar) > 0 {
+        inventory.targeting = !inventory.targeting;
+    }
+    if keys.just_pressed(PowerUp::Shield.hotkey()) && inventory.take(PowerUp::Shield) {
+        // the same as an extra life, the hit mine stays revealed
+        board.lives += 1;
+    }
+    if keys.just_pressed(PowerUp::MassFlag.hotkey()) && inventory.take(PowerUp::MassFlag) {
+        // flags go through the usual requests, so they are recorded in the replay
+        let flags = forced_mines(&board)
+            .into_iter()
+            .filter(|hex| !board.flagged.contains(hex))
+            .map(|hex| ActionRequest(GameAction::ToggleFlag(hex)));
+        requests.send_batch(flags);
+    }
+}
+
+fn target_radar(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    view: Res<BoardView>,
+    mut buttons: ResMut<Input<MouseButton>>,
+    mut board: ResMut<Board>,
+    mut inventory: ResMut<Inventory>,
+    mut cells_changed: EventWriter<CellsChanged>,
+) {
+    if !inventory.targeting || !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(target) = cursor_hex(windows.single(), &view, board.config.rules.geometry)
+        .filter(|hex| board.contains(*hex))
+    else {
+        return;
+    };
+    buttons.clear_just_pressed(MouseButton::Left);
+    inventory.targeting = false;
+    if !inventory.take(PowerUp::Radar) {
+        return;
+    }
+    let found: Vec<_> = board
+        .config
+        .rules
+        .geometry
+        .around(target, RADAR_RADIUS)
+        .into_iter()
+        .filter(|hex| board.mines.contains(hex) && board.covered.contains(hex))
+        .collect();
+    board.scanned.extend(found.iter().copied());
+    cells_changed.send(CellsChanged(found));
+}
@@ -0,0 +1,57 @@
+//! Everything the player accumulated, bundled into a single file to move it to another machine
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    daily::DailyStreak, endless::EndlessRecord, history::History, settings::Settings,
+    speedrun::BestRuns, stats::Stats, storage,
+};
+
+/// Bumped on incompatible changes of the format. Newer profiles are refused instead of being half-read.
+pub const PROFILE_VERSION: u32 = 1;
+
+pub const DEFAULT_FILE_NAME: &str = "profile.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct Profile {
+    pub version: u32,
+    pub settings: Settings,
+    pub stats: Stats,
+    pub daily: DailyStreak,
+    pub history: History,
+    /// Missing in profiles exported before speedrun splits were kept
+    #[serde(default)]
+    pub splits: BestRuns,
+    /// Missing in profiles exported before endless runs were added
+    #[serde(default)]
+    pub endless: EndlessRecord,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Adds imported games and stats to the local ones, keeping local settings
+    Merge,
+    /// Drops everything local in favor of the imported profile
+    Replace,
+}
+
+pub fn export(path: &str, profile: &Profile) -> Result<(), String> {
+    storage::write_file(path, profile)
+}
+
+pub fn import(path: &str) -> Result<Profile, String> {
+    let value: serde_json::Value = storage::read_file(path)?;
+    let header = Header::deserialize(&value).map_err(|err| format!("not a profile: {err}"))?;
+    if header.version > PROFILE_VERSION {
+        return Err(format!(
+            "profile version {} is newer than supported {PROFILE_VERSION}, update the game first",
+            header.version
+        ));
+    }
+    Profile::deserialize(value).map_err(|err| format!("broken profile: {err}"))
+}
@@ -0,0 +1,93 @@
+//! Hand-made puzzles. They are stored as JSON files in the `puzzles` directory of the game data,
+//! and the campaign is simply all of them in the order of their file names.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Board, Layout, Rules},
+    solver::{solve, Solution},
+    storage,
+};
+
+const PUZZLES_DIR: &str = "puzzles";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Puzzle {
+    pub name: String,
+    /// Time to beat, set by the author
+    pub par_time_ms: Option<u32>,
+    /// Hexes as `[x, y]` pairs
+    pub cells: Vec<[i32; 2]>,
+    pub mines: Vec<[i32; 2]>,
+    pub revealed: Vec<[i32; 2]>,
+}
+
+impl Puzzle {
+    pub fn new(name: String, par_time_ms: Option<u32>, layout: &Layout) -> Self {
+        let to_pairs = |hexes: &bevy::utils::HashSet<hexx::Hex>| {
+            let mut pairs: Vec<_> = hexes.iter().map(|hex| [hex.x, hex.y]).collect();
+            // stable files are nicer to diff
+            pairs.sort_unstable();
+            pairs
+        };
+        Self {
+            name,
+            par_time_ms,
+            cells: to_pairs(&layout.cells),
+            mines: to_pairs(&layout.mines),
+            revealed: to_pairs(&layout.revealed),
+        }
+    }
+
+    pub fn layout(&self) -> Layout {
+        let to_hexes =
+            |pairs: &[[i32; 2]]| pairs.iter().map(|[x, y]| hexx::Hex::new(*x, *y)).collect();
+        Layout {
+            cells: to_hexes(&self.cells),
+            mines: to_hexes(&self.mines),
+            revealed: to_hexes(&self.revealed),
+        }
+    }
+
+    /// Saves the puzzle under its name, replacing a puzzle with the same name. Returns the file path.
+    pub fn save(&self) -> Result<String, String> {
+        let file_name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        if file_name.is_empty() {
+            return Err("puzzle needs a name".to_string());
+        }
+        storage::save_in(PUZZLES_DIR, &file_name, self)
+    }
+}
+
+/// Refuses broken puzzles, otherwise tells whether the puzzle can be solved without guessing
+pub fn validate(layout: &Layout) -> Result<Solution, String> {
+    if layout.mines.is_empty() {
+        return Err("place at least one mine".to_string());
+    }
+    if layout.mines.len() >= layout.cells.len() {
+        return Err("there are no safe hexes".to_string());
+    }
+    if layout.revealed.is_empty() {
+        return Err("reveal at least one hex as the start".to_string());
+    }
+    if layout.cells.len() == layout.mines.len() + layout.revealed.len() {
+        return Err("leave some hexes to reveal".to_string());
+    }
+    Ok(solve(&Board::from_layout(layout, Rules::CLASSIC)))
+}
+
+/// All saved puzzles in the campaign order
+pub fn load_campaign() -> Vec<Puzzle> {
+    storage::load_all_in(PUZZLES_DIR)
+}
@@ -0,0 +1,93 @@
+//! Recording of player actions, which together with the seed and config reproduces a whole game
+
+use std::{hash::Hasher, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::GameAction;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActionKind {
+    Reveal,
+    ToggleFlag,
+    Chord,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayStep {
+    /// Time since the first action
+    pub at_ms: u32,
+    pub kind: ActionKind,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl ReplayStep {
+    pub fn action(&self) -> GameAction {
+        let hex = hexx::Hex::new(self.x, self.y);
+        match self.kind {
+            ActionKind::Reveal => GameAction::Reveal(hex),
+            ActionKind::ToggleFlag => GameAction::ToggleFlag(hex),
+            ActionKind::Chord => GameAction::Chord(hex),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub steps: Vec<ReplayStep>,
+}
+
+impl Replay {
+    pub fn push(&mut self, at: Duration, action: GameAction) {
+        let (kind, hex) = match action {
+            GameAction::Reveal(hex) => (ActionKind::Reveal, hex),
+            GameAction::ToggleFlag(hex) => (ActionKind::ToggleFlag, hex),
+            GameAction::Chord(hex) => (ActionKind::Chord, hex),
+        };
+        self.steps.push(ReplayStep {
+            at_ms: at.as_millis() as u32,
+            kind,
+            x: hex.x,
+            y: hex.y,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Stable fingerprint of the replay, used to match leaderboard entries with their replays
+    pub fn hash(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        for step in &self.steps {
+            hasher.write_u32(step.at_ms);
+            hasher.write_u8(step.kind as u8);
+            hasher.write_i32(step.x);
+            hasher.write_i32(step.y);
+        }
+        hasher.finish()
+    }
+}
+
+/// FNV-1a, unlike the std and bevy hashers it is guaranteed to stay the same across versions
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
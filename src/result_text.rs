@@ -0,0 +1,81 @@
+//! Spoiler-free summary of a finished game to paste into a chat, in the spirit of Wordle results.
+//!
+//! The board is drawn with emoji: revealed hexes are green, flags stay flags, everything still covered is black
+//! and revealed mines are explosions. Covered mines are never told apart from other covered hexes,
+//! so the grid shows how the game went without giving the board away. The board code below lets others try it.
+
+use hexx::Hex;
+
+use crate::{
+    board::Board,
+    clock,
+    daily::is_daily,
+    game::{Game, Outcome},
+    share::encode_board_code,
+    title::TITLE,
+};
+
+const REVEALED: &str = "🟩";
+const COVERED: &str = "⬛";
+const FLAG: &str = "🚩";
+const MINE: &str = "💥";
+
+pub fn encode_result_text(board: &Board, game: &Game) -> String {
+    let today = clock::today();
+    let mut text = if is_daily(board, today) {
+        format!("{TITLE}, daily puzzle of {}\n", clock::format_day(today))
+    } else {
+        format!("{TITLE}, {}\n", board.config)
+    };
+    text += &match (game.outcome, board.config.rules.zen) {
+        (Some(Outcome::Won), true) => "Cleared".to_string(),
+        (Some(Outcome::Won), false) => format!("Cleared in {:.1}s", game.elapsed.as_secs_f32()),
+        _ => format!("Boom at {:.0}% revealed", board.progress() * 100.0),
+    };
+    text += "\n\n";
+    text += &encode_grid(board);
+    // boards too large for a code are still worth bragging about
+    if let Ok(code) = encode_board_code(board) {
+        text += &format!("\nBoard code: {code}\n");
+    }
+    text
+}
+
+/// Rows from the top of the board, shifted against each other like on screen, see `board_text.rs`
+fn encode_grid(board: &Board) -> String {
+    // doubled coordinates, where the column of a hex is `2 * x + y`
+    let column = |hex: &Hex| 2 * hex.x + hex.y;
+    let (Some(min_column), Some(min_row), Some(max_row)) = (
+        board.cells.iter().map(column).min(),
+        board.cells.iter().map(|hex| hex.y).min(),
+        board.cells.iter().map(|hex| hex.y).max(),
+    ) else {
+        return String::new();
+    };
+
+    let mut text = String::new();
+    // y axis points up on screen, so the top row has the largest `y`
+    for y in (min_row..=max_row).rev() {
+        let mut hexes: Vec<_> = board.cells.iter().filter(|hex| hex.y == y).collect();
+        hexes.sort_unstable_by_key(|hex| column(hex));
+        // a space is about half the width of an emoji, which is the shift between rows
+        let mut offset = min_column;
+        for hex in hexes {
+            text.extend(std::iter::repeat(' ').take((column(hex) - offset) as usize));
+            offset = column(hex) + 2;
+            text += if !board.covered.contains(hex) {
+                if board.mines.contains(hex) {
+                    MINE
+                } else {
+                    REVEALED
+                }
+            } else if board.flagged.contains(hex) {
+                FLAG
+            } else {
+                COVERED
+            };
+        }
+        text.push('\n');
+    }
+    text
+}
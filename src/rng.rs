@@ -0,0 +1,35 @@
+/// Tiny deterministic PRNG (SplitMix64), so the same seed produces the same board on every platform
+#[derive(Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform number in `0..bound`, `bound` must be non-zero
+    pub fn below(&mut self, bound: u64) -> u64 {
+        // widening multiplication instead of modulo, the leftover bias is negligible for board-sized bounds
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Fresh seed for a new random game
+pub fn random_seed() -> u64 {
+    Rng::new(crate::clock::unix_time_millis()).next_u64()
+}
@@ -0,0 +1,108 @@
+//! Arcade-style score: points for revealed hexes, multiplied for large cascades and combos of quick reveals,
+//! with penalties for hints and wrong flags
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    any_events,
+    board::Board,
+    game::{BoardProgress, GameOver, GameSet, NewCustomGame, NewGame, TreasureFound},
+    treasure::Treasure,
+};
+
+const POINTS_PER_HEX: u32 = 10;
+/// Every this many hexes uncovered at once add one to the cascade multiplier
+const CASCADE_STEP: u32 = 10;
+const MAX_CASCADE_MULTIPLIER: u32 = 5;
+/// Reveals within this time after the previous one continue the combo
+pub const COMBO_WINDOW: Duration = Duration::from_millis(1500);
+const MAX_COMBO_MULTIPLIER: u32 = 8;
+const TREASURE_POINTS: u32 = 500;
+const HINT_PENALTY: u32 = 100;
+/// For every flag on a safe hex or of the wrong color when the game ends
+const WRONG_FLAG_PENALTY: u32 = 50;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .add_event::<HintUsed>()
+            .add_systems(Update, track_score.after(GameSet::Actions));
+    }
+}
+
+/// Sent by anything that helps the player to solve the board
+#[derive(Event)]
+pub struct HintUsed;
+
+#[derive(Resource, Default)]
+pub struct Score {
+    pub points: u32,
+    /// Consecutive reveals within [`COMBO_WINDOW`] of each other, zero for a single reveal
+    pub combo: u32,
+    /// Game time of the previous reveal
+    pub last_reveal: Option<Duration>,
+}
+
+impl Score {
+    pub fn combo_multiplier(&self) -> u32 {
+        (1 + self.combo).min(MAX_COMBO_MULTIPLIER)
+    }
+
+    /// Time left to continue the combo, if there is one going on
+    pub fn combo_left(&self, elapsed: Duration) -> Option<Duration> {
+        let last = self.last_reveal.filter(|_| self.combo > 0)?;
+        COMBO_WINDOW.checked_sub(elapsed.saturating_sub(last))
+    }
+}
+
+/// Scores the current game, so the leaderboard submission has to run after it
+#[allow(clippy::too_many_arguments)]
+pub fn track_score(
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    mut progress: EventReader<BoardProgress>,
+    mut hints: EventReader<HintUsed>,
+    mut treasures: EventReader<TreasureFound>,
+    mut game_over: EventReader<GameOver>,
+    board: Res<Board>,
+    mut score: ResMut<Score>,
+) {
+    if any_events(&mut new_games) | any_events(&mut custom_games) {
+        *score = Score::default();
+    }
+
+    for progress in progress.iter() {
+        let uncovered = progress.uncovered as u32;
+        let cascade = (1 + uncovered / CASCADE_STEP).min(MAX_CASCADE_MULTIPLIER);
+        // the clock doesn't run in zen mode, so there are no combos either
+        let in_window = |last: Duration| progress.elapsed.saturating_sub(last) < COMBO_WINDOW;
+        score.combo = match score.last_reveal {
+            Some(last) if in_window(last) && !board.config.rules.zen => score.combo + 1,
+            _ => 0,
+        };
+        score.points += uncovered * POINTS_PER_HEX * cascade * score.combo_multiplier();
+        score.last_reveal = Some(progress.elapsed);
+    }
+
+    for found in treasures.iter() {
+        if found.treasure == Treasure::Score {
+            score.points += TREASURE_POINTS;
+        }
+    }
+
+    let hints = hints.iter().count() as u32;
+    if hints > 0 {
+        score.points = score.points.saturating_sub(hints * HINT_PENALTY);
+    }
+
+    if any_events(&mut game_over) {
+        let wrong_flags = board.wrong_flags().count() as u32;
+        score.points = score
+            .points
+            .saturating_sub(wrong_flags * WRONG_FLAG_PENALTY);
+    }
+}
@@ -0,0 +1,223 @@
+//! Dedicated server for the multiplayer modes: a headless host that doesn't play itself, so a game doesn't
+//! depend on the machine of one of the players. Clients join it like any other host.
+//!
+//! It runs the same game plugin as the players with no rule scripts, so in co-op only actions
+//! that are valid under the core rules are applied and relayed. Versus boards are played by every player
+//! on their own, and the server starts races once enough players are in and relays their progress,
+//! which it works out from the actions of the players played on its own copies of their boards.
+//!
+//! ```text
+//! hex-minesweeper-server [coop|versus] [--port PORT] [--radius RADIUS] [--density DENSITY]
+//! ```
+
+use std::time::Duration;
+
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*};
+
+use crate::{
+    board::BoardConfig,
+    coop::{self, CoopSession, CoopSessionPlugin},
+    game::{Authority, Game, GamePlugin, NewGame},
+    mods::Mods,
+    net::discovery::{Announcement, Beacon, GameMode},
+    versus::{self, VersusSession, VersusSessionPlugin},
+    AppState,
+};
+
+/// Frames per second, the server only waits for messages
+const TICK: Duration = Duration::from_millis(1000 / 30);
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+/// A co-op board nobody played for this long is replaced, longer than dropped players have to come back
+const EMPTY_TIMEOUT: Duration = Duration::from_secs(60);
+/// Players a race needs, and how long they wait for more before it starts
+const MIN_RACERS: usize = 2;
+const RACE_DELAY: Duration = Duration::from_secs(5);
+const USAGE: &str =
+    "Usage: hex-minesweeper-server [coop|versus] [--port PORT] [--radius RADIUS] [--density DENSITY]";
+
+/// What the server hosts, from the command line
+#[derive(Resource, Clone, Copy)]
+struct ServerConfig {
+    mode: GameMode,
+    port: u16,
+    board: BoardConfig,
+}
+
+impl ServerConfig {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = Self {
+            mode: GameMode::Coop,
+            port: coop::DEFAULT_PORT,
+            board: BoardConfig::default(),
+        };
+        let mut port = None;
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} needs a value"));
+            match arg.as_str() {
+                "coop" => config.mode = GameMode::Coop,
+                "versus" => config.mode = GameMode::Versus,
+                "--port" => port = Some(parse(&arg, &value(&arg)?)?),
+                "--radius" => config.board.radius = parse(&arg, &value(&arg)?)?,
+                "--density" => config.board.mine_density = parse(&arg, &value(&arg)?)?,
+                _ => return Err(format!("Unknown argument {arg}")),
+            }
+        }
+        config.port = port.unwrap_or(match config.mode {
+            GameMode::Coop => coop::DEFAULT_PORT,
+            GameMode::Versus => versus::DEFAULT_PORT,
+        });
+        if config.board.radius == 0 || !(0.0..1.0).contains(&config.board.mine_density) {
+            return Err("The board needs a positive radius and a density below 1".to_string());
+        }
+        Ok(config)
+    }
+}
+
+fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {value} of {name}"))
+}
+
+pub fn run() {
+    let config = match ServerConfig::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(TICK)))
+        .add_plugins(LogPlugin::default())
+        .add_state::<AppState>()
+        // players' rule scripts are theirs, the server sticks to the core rules
+        .insert_resource(Mods::empty())
+        .insert_resource(config)
+        .add_plugins((GamePlugin, CoopSessionPlugin, VersusSessionPlugin))
+        .insert_resource(Authority::Host)
+        .add_systems(PostStartup, start_session)
+        .add_systems(
+            Update,
+            (
+                announce_game,
+                replace_abandoned_board.run_if(resource_exists::<CoopSession>()),
+                start_races.run_if(resource_exists::<VersusSession>()),
+            ),
+        );
+    app.run();
+}
+
+fn start_session(
+    mut commands: Commands,
+    config: Res<ServerConfig>,
+    mut new_game: EventWriter<NewGame>,
+) {
+    let hosted = match config.mode {
+        GameMode::Coop => CoopSession::host(config.port).map(|session| {
+            // after the random game of the game plugin, so this one wins
+            new_game.send(NewGame::random(config.board));
+            commands.insert_resource(session);
+        }),
+        GameMode::Versus => {
+            VersusSession::host(config.port).map(|session| commands.insert_resource(session))
+        }
+    };
+    match hosted {
+        Ok(()) => info!(
+            "Hosting {:?} on port {}, boards of {}",
+            config.mode, config.port, config.board
+        ),
+        Err(err) => {
+            error!("Failed to host on port {}: {err}", config.port);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn announce_game(
+    time: Res<Time>,
+    config: Res<ServerConfig>,
+    coop: Option<Res<CoopSession>>,
+    versus: Option<Res<VersusSession>>,
+    mut since_announced: Local<Duration>,
+    mut beacon: Local<Option<Beacon>>,
+) {
+    *since_announced += time.delta();
+    if *since_announced < ANNOUNCE_INTERVAL {
+        return;
+    }
+    *since_announced = Duration::ZERO;
+
+    if beacon.is_none() {
+        *beacon = Beacon::new()
+            .map_err(|err| warn!("LAN announcements are unavailable: {err}"))
+            .ok();
+    }
+    let Some(beacon) = beacon.as_ref() else {
+        return;
+    };
+    let players = match (coop, versus) {
+        (Some(session), _) => session.peers_count(),
+        (_, Some(session)) => session.peers_count(),
+        _ => 0,
+    };
+    let announcement = Announcement {
+        mode: config.mode,
+        port: config.port,
+        host_name: "Dedicated server".to_string(),
+        players: players as u32,
+    };
+    if let Err(err) = beacon.announce(&announcement) {
+        debug!("Failed to announce the game: {err}");
+    }
+}
+
+/// Players who come to an empty server get a fresh board, not the leftovers of the previous ones
+fn replace_abandoned_board(
+    time: Res<Time>,
+    config: Res<ServerConfig>,
+    session: Res<CoopSession>,
+    game: Res<Game>,
+    mut new_game: EventWriter<NewGame>,
+    mut empty_for: Local<Duration>,
+) {
+    if session.peers_count() > 0 || game.replay.is_empty() {
+        *empty_for = Duration::ZERO;
+        return;
+    }
+    *empty_for += time.delta();
+    if *empty_for >= EMPTY_TIMEOUT {
+        info!("Everyone left, starting a fresh board");
+        new_game.send(NewGame::random(config.board));
+        *empty_for = Duration::ZERO;
+    }
+}
+
+/// Next race starts a moment after enough players are in, and those who raced the previous one are done.
+/// Players who joined during a race, or never made a move in it, don't hold up the next one.
+fn start_races(
+    time: Res<Time>,
+    config: Res<ServerConfig>,
+    mut session: ResMut<VersusSession>,
+    mut waiting: Local<Duration>,
+) {
+    let mut racers = session
+        .opponents
+        .values()
+        .filter(|progress| progress.revealed > 0.0 || progress.outcome.is_some())
+        .peekable();
+    let finished = !session.is_racing()
+        || (racers.peek().is_some() && racers.all(|progress| progress.outcome.is_some()));
+    if session.opponents.len() < MIN_RACERS || !finished {
+        *waiting = Duration::ZERO;
+        return;
+    }
+    *waiting += time.delta();
+    if *waiting >= RACE_DELAY {
+        info!("Starting a race of {} players", session.opponents.len());
+        session.start(NewGame::random(config.board));
+        *waiting = Duration::ZERO;
+    }
+}
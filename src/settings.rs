@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{storage, theme::Theme};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<Settings>(Settings::FILE))
+            .add_systems(Last, save_settings);
+    }
+}
+
+/// User preferences, persisted between sessions.
+/// Missing fields fall back to defaults, so older files keep working when new settings are added.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect), reflect(Resource))]
+#[serde(default)]
+pub struct Settings {
+    pub player_name: String,
+    /// Color picked in multiplayer lobbies, index into `coop::PLAYER_COLORS`
+    pub player_color: u8,
+    pub leaderboard: LeaderboardSettings,
+    /// Millisecond timer with splits in the HUD, toggled with `F2`
+    pub speedrun_timer: bool,
+    /// Chording does nothing but a press animation when a flag around looks misplaced, see [`Board::is_safe_chord`](crate::board::Board::is_safe_chord)
+    pub safe_chord: bool,
+    /// Assist that reveals hexes around numbers with all their flags, see `assist.rs`
+    pub auto_open: bool,
+    /// Assist that flags hexes a number needs all of, independent from [`Self::auto_open`]
+    pub auto_flag: bool,
+    /// Holding the left button and dragging reveals every covered hex the cursor passes over
+    pub drag_sweep: bool,
+    /// Boards won before show the best previous run as a ghost, see `ghost.rs`
+    pub ghost: bool,
+    /// Seconds without input after which a running game stops its clock, see `idle.rs`. 0 disables it.
+    pub idle_pause_seconds: u32,
+    /// Board is hidden while the game is paused for being idle
+    pub idle_veil: bool,
+    /// Numbers show the mines around that are not flagged yet instead of all of them
+    pub effective_numbers: bool,
+    pub grid_style: GridStyle,
+    /// Sixths of a turn the board is turned counterclockwise on the screen, changed with `[` and `]`
+    pub board_rotation: u8,
+    /// Decorative animations, like the background, stand still
+    pub reduced_motion: bool,
+    /// Lens with an enlarged view around the cursor, toggled with `F5`
+    pub magnifier: bool,
+    /// Presentation for recording or streaming, toggled with `F6`, see `streamer.rs`
+    pub streamer_mode: bool,
+    /// Streamer mode keeps a strip on the right free of the board for a webcam
+    pub webcam_margin: bool,
+    pub chat_vote: ChatVoteSettings,
+    /// Colors a flag goes through before it's removed, see `notes.rs`. `0` and `1` both mean plain flags.
+    pub flag_colors: u8,
+    /// Screenshots taken with `F12` include the sketches over the board, see `sketch.rs`
+    pub sketches_in_screenshots: bool,
+    pub theme: ThemeMode,
+    /// Blend between the light theme at 0 and the dark one at 1 under [`ThemeMode::Manual`]
+    pub darkness: f32,
+    /// Colors under [`ThemeMode::Custom`], picked in the theme editor
+    pub custom_theme: Theme,
+    /// Under which [`Self::custom_theme`] was saved or loaded last
+    pub custom_theme_name: String,
+    pub number_style: NumberStyle,
+    pub window: WindowPlacement,
+}
+
+impl Settings {
+    pub const FILE: &str = "settings";
+    /// Step of the darkness slider in the settings screen
+    pub const DARKNESS_STEP: f32 = 0.25;
+    /// Choices for [`Self::idle_pause_seconds`] in the settings screen
+    pub const IDLE_PAUSE_SECONDS: [u32; 5] = [0, 30, 60, 120, 300];
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+#[serde(default)]
+pub struct LeaderboardSettings {
+    /// Wins are submitted only if the player explicitly opted in
+    pub enabled: bool,
+    pub endpoint: String,
+}
+
+impl Default for LeaderboardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://localhost:8080/api/v1".to_string(),
+        }
+    }
+}
+
+/// How the theme is picked, see `theme.rs`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+pub enum ThemeMode {
+    /// Set by [`Settings::darkness`]
+    #[default]
+    Manual,
+    /// Follows the local time of day, dark at night
+    DayNight,
+    /// [`Settings::custom_theme`]
+    Custom,
+}
+
+/// Where the window was left, restored at startup by `placement.rs`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+#[serde(default)]
+pub struct WindowPlacement {
+    /// Logical size of the window when it's not fullscreen
+    pub size: Option<[f32; 2]>,
+    /// Top left corner on the desktop in physical pixels, unknown until the window is moved
+    pub position: Option<[i32; 2]>,
+    /// Name of the monitor the window was on, to tell overlapping monitors apart
+    pub monitor: String,
+    pub fullscreen: bool,
+}
+
+/// Chat vote on the next reveal, see `chat_vote.rs`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+#[serde(default)]
+pub struct ChatVoteSettings {
+    /// Twitch channel to read the votes from
+    pub channel: String,
+    /// Length of a round of voting
+    pub seconds: u32,
+}
+
+impl Default for ChatVoteSettings {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            seconds: 20,
+        }
+    }
+}
+
+/// Look of the numbers on hexes, applied by `fonts.rs`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+#[serde(default)]
+pub struct NumberStyle {
+    pub font: NumberFont,
+    /// TTF file for [`NumberFont::Custom`], relative to `assets` or absolute
+    pub custom_path: String,
+    /// Digit size relative to the default one, which fits into a hex
+    pub size: f32,
+}
+
+impl NumberStyle {
+    pub const SIZE_STEP: f32 = 0.1;
+    pub const SIZE_RANGE: (f32, f32) = (0.6, 1.4);
+}
+
+impl Default for NumberStyle {
+    fn default() -> Self {
+        Self {
+            font: NumberFont::default(),
+            custom_path: String::new(),
+            size: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+pub enum NumberFont {
+    /// Pictures of the digits from `assets`, numbers above 6 are text in the built-in font anyway
+    #[default]
+    Pictures,
+    /// Font embedded into the game
+    BuiltIn,
+    /// Font from [`NumberStyle::custom_path`]
+    Custom,
+}
+
+/// Cosmetics of the hex grid, applied by `style.rs`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+#[serde(default)]
+pub struct GridStyle {
+    /// How far small boards are zoomed in at most, relative to [`HEX_SIZE`](crate::HEX_SIZE) in pixels.
+    /// Boards are always shrunk to fit the window, so larger ones aren't affected, see `BoardView::fit`.
+    #[serde(alias = "hex_size")]
+    pub max_zoom: f32,
+    /// Empty space between hexes as a fraction of the hex size
+    pub gap: f32,
+    pub outlines: bool,
+    pub rounded: bool,
+    pub shadows: bool,
+    /// Hexes with a flat edge on top instead of a corner, the board is turned by 30° for that
+    pub flat_top: bool,
+    /// Board is mirrored left to right on the screen
+    pub invert_x: bool,
+    /// Board is mirrored upside down on the screen
+    pub invert_y: bool,
+}
+
+impl GridStyle {
+    pub const GAP_STEP: f32 = 0.05;
+    pub const MAX_GAP: f32 = 0.25;
+    pub const MAX_ZOOM_STEP: f32 = 0.25;
+    pub const MAX_ZOOM_RANGE: (f32, f32) = (0.5, 2.0);
+}
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        Self {
+            max_zoom: 1.0,
+            gap: 0.1,
+            outlines: false,
+            rounded: false,
+            shadows: false,
+            flat_top: false,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        storage::save(Settings::FILE, &*settings);
+    }
+}
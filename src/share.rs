@@ -0,0 +1,697 @@
+//! Compact codes to share boards between players.
+//!
+//! A board code packs the config and the seed into a few bytes, prefixed with a format version,
+//! and encodes them with URL-safe base64 so they can be pasted anywhere, e.g. "ARAAoADvzauJZ0UjAQ".
+//! Classic boards use the shorter version 1, boards with rule modifiers need version 2.
+//! Hand-made boards can't be reproduced from a seed, so version 3 packs the whole layout instead.
+//! Newer rules don't fit into the rule bytes, so versions 4 and 5 are versions 2 and 3 with 8 more bytes of them.
+
+use hexx::{shapes, Hex};
+
+use crate::{
+    board::{Board, BoardConfig, Layout, Rules},
+    geometry::Geometry,
+    gradient::Gradient,
+    kernel::Kernel,
+};
+
+const SEED_CODE_VERSION: u8 = 1;
+const RULES_CODE_VERSION: u8 = 2;
+const LAYOUT_CODE_VERSION: u8 = 3;
+const EXTENDED_SEED_CODE_VERSION: u8 = 4;
+const EXTENDED_LAYOUT_CODE_VERSION: u8 = 5;
+/// Extra rule bytes start with the kernel mask over 36 hexes within `kernel::MAX_DISTANCE`, 0 is no kernel
+const KERNEL_BITS: u64 = (1 << 36) - 1;
+const COLORED_SHIFT: u32 = 36;
+const BLIND_PINGS_SHIFT: u32 = 37;
+const MAX_BLIND_PINGS: u32 = 31;
+const DIRECTIONAL_SHIFT: u32 = 42;
+/// Index into [`Gradient::ALL`]
+const GRADIENT_SHIFT: u32 = 43;
+/// Index into [`Geometry::ALL`]
+const GEOMETRY_SHIFT: u32 = 45;
+/// Codes from before the flag have blind numbers whenever they have pings
+const BLIND_SHIFT: u32 = 47;
+/// Codes are typed by hand, so reject boards that would take forever to generate
+const MAX_RADIUS: u32 = 64;
+const MAX_ADJACENCY: u32 = 3;
+/// Treasures share a byte with rule flags
+const MAX_TREASURES: u32 = 15;
+/// Fog and darkness radii share a byte
+const MAX_FOG: u32 = 14;
+/// Lives and the defuse countdown share a byte
+const MAX_LIVES: u32 = 15;
+const DEFUSE_UNIT: u32 = 5;
+/// Defuse countdown in units of [`DEFUSE_UNIT`], up to 75 seconds
+const MAX_DEFUSE_UNITS: u32 = 15;
+/// Moving mines share a byte with the adjacency
+const MAX_MOVING_MINES: u32 = 31;
+/// Longest run of hexes in one byte of a layout code
+const MAX_RUN: u8 = 64;
+
+/// Code that reproduces the exact same board with [`decode_seed_code`], fails for rules that don't fit into it
+pub fn encode_seed_code(config: &BoardConfig, seed: u64) -> Result<String, String> {
+    let classic = config.rules == Rules::CLASSIC;
+    let version = if classic {
+        SEED_CODE_VERSION
+    } else if encode_extension(&config.rules).is_some() {
+        EXTENDED_SEED_CODE_VERSION
+    } else {
+        RULES_CODE_VERSION
+    };
+    let mut bytes = vec![version];
+    bytes.extend_from_slice(&(config.radius as u16).to_le_bytes());
+    bytes.extend_from_slice(&density_to_permille(config.mine_density).to_le_bytes());
+    if !classic {
+        bytes.extend_from_slice(&encode_rules(&config.rules)?);
+    }
+    if let Some(extension) = encode_extension(&config.rules) {
+        bytes.extend_from_slice(&extension.to_le_bytes());
+    }
+    bytes.extend_from_slice(&seed.to_le_bytes());
+    Ok(base64_encode(&bytes))
+}
+
+pub fn decode_seed_code(code: &str) -> Result<(BoardConfig, u64), String> {
+    let bytes = base64_decode(code.trim())?;
+    let (rules, payload) = match bytes.split_first() {
+        Some((&SEED_CODE_VERSION, payload)) if payload.len() == 12 => (Rules::CLASSIC, payload),
+        Some((&RULES_CODE_VERSION, payload)) if payload.len() == 16 => {
+            (decode_rules(&payload[4..8]), payload)
+        }
+        Some((&EXTENDED_SEED_CODE_VERSION, payload)) if payload.len() == 24 => {
+            (decode_extended_rules(&payload[4..16]), payload)
+        }
+        Some((&(SEED_CODE_VERSION | RULES_CODE_VERSION | EXTENDED_SEED_CODE_VERSION), _)) => {
+            return Err("code has a wrong length".to_string())
+        }
+        Some((version, _)) => return Err(format!("unknown code version {version}")),
+        None => return Err("code is empty".to_string()),
+    };
+
+    let radius = u16::from_le_bytes([payload[0], payload[1]]) as u32;
+    let permille = u16::from_le_bytes([payload[2], payload[3]]);
+    let seed = u64::from_le_bytes(
+        payload[payload.len() - 8..]
+            .try_into()
+            .expect("length is checked"),
+    );
+    if radius == 0 || radius > MAX_RADIUS || permille >= 1000 {
+        return Err("code describes an invalid board".to_string());
+    }
+    validate_rules(&rules)?;
+    let config = BoardConfig {
+        radius,
+        mine_density: permille as f32 / 1000.0,
+        rules,
+    };
+    Ok((config, seed))
+}
+
+/// Board decoded from any kind of code
+pub enum SharedBoard {
+    Seed { config: BoardConfig, seed: u64 },
+    Layout { layout: Layout, rules: Rules },
+}
+
+/// Shortest code that reproduces the board, hand-made boards get a layout code
+pub fn encode_board_code(board: &Board) -> Result<String, String> {
+    match &board.layout {
+        Some(layout) => encode_layout_code(layout, &board.config.rules),
+        None => encode_seed_code(&board.config, board.seed),
+    }
+}
+
+pub fn decode_board_code(code: &str) -> Result<SharedBoard, String> {
+    let version = base64_decode(code.trim())?.first().copied();
+    if matches!(
+        version,
+        Some(LAYOUT_CODE_VERSION | EXTENDED_LAYOUT_CODE_VERSION)
+    ) {
+        let (layout, rules) = decode_layout_code(code)?;
+        Ok(SharedBoard::Layout { layout, rules })
+    } else {
+        let (config, seed) = decode_seed_code(code)?;
+        Ok(SharedBoard::Seed { config, seed })
+    }
+}
+
+/// Hexes of the layout within the smallest hexagon around it, one 2-bit state per hex.
+/// Boards are mostly long runs of the same state, so states are run-length encoded,
+/// one byte per run with the state in the top 2 bits and the run length in the rest.
+/// Fails for layouts larger than [`decode_layout_code`] accepts.
+pub fn encode_layout_code(layout: &Layout, rules: &Rules) -> Result<String, String> {
+    let radius = layout
+        .cells
+        .iter()
+        .map(|hex| hex.ulength())
+        .max()
+        .unwrap_or(0);
+    if radius > MAX_RADIUS {
+        return Err(format!(
+            "board is too large to share, {radius} hexes from the center"
+        ));
+    }
+    let extension = encode_extension(rules);
+    let version = match extension {
+        Some(_) => EXTENDED_LAYOUT_CODE_VERSION,
+        None => LAYOUT_CODE_VERSION,
+    };
+    let mut bytes = vec![version, radius as u8];
+    bytes.extend_from_slice(&encode_rules(rules)?);
+    if let Some(extension) = extension {
+        bytes.extend_from_slice(&extension.to_le_bytes());
+    }
+
+    let mut run: Option<(u8, u8)> = None;
+    for hex in shapes::hexagon(Hex::ZERO, radius) {
+        let state = if !layout.cells.contains(&hex) {
+            HexState::Void
+        } else if layout.mines.contains(&hex) {
+            HexState::Mine
+        } else if layout.revealed.contains(&hex) {
+            HexState::Revealed
+        } else {
+            HexState::Cell
+        } as u8;
+        run = match run {
+            Some((run_state, length)) if run_state == state && length < MAX_RUN => {
+                Some((state, length + 1))
+            }
+            Some((run_state, length)) => {
+                bytes.push(run_state << 6 | (length - 1));
+                Some((state, 1))
+            }
+            None => Some((state, 1)),
+        };
+    }
+    if let Some((state, length)) = run {
+        bytes.push(state << 6 | (length - 1));
+    }
+    Ok(base64_encode(&bytes))
+}
+
+pub fn decode_layout_code(code: &str) -> Result<(Layout, Rules), String> {
+    let bytes = base64_decode(code.trim())?;
+    let (radius, rules, runs) = match bytes.as_slice() {
+        [LAYOUT_CODE_VERSION, radius, rest @ ..] if rest.len() > 4 => {
+            (*radius as u32, decode_rules(&rest[..4]), &rest[4..])
+        }
+        [EXTENDED_LAYOUT_CODE_VERSION, radius, rest @ ..] if rest.len() > 12 => (
+            *radius as u32,
+            decode_extended_rules(&rest[..12]),
+            &rest[12..],
+        ),
+        [LAYOUT_CODE_VERSION | EXTENDED_LAYOUT_CODE_VERSION, ..] => {
+            return Err("code has a wrong length".to_string())
+        }
+        [version, ..] => return Err(format!("unknown code version {version}")),
+        [] => return Err("code is empty".to_string()),
+    };
+    if radius > MAX_RADIUS {
+        return Err("code describes an invalid board".to_string());
+    }
+    validate_rules(&rules)?;
+
+    let states = runs
+        .iter()
+        .flat_map(|run| std::iter::repeat(run >> 6).take((run & 0x3f) as usize + 1));
+    let mut hexes = shapes::hexagon(Hex::ZERO, radius);
+    let mut layout = Layout::default();
+    for state in states {
+        let hex = hexes
+            .next()
+            .ok_or_else(|| "code has a wrong length".to_string())?;
+        if state != HexState::Void as u8 {
+            layout.cells.insert(hex);
+        }
+        if state == HexState::Mine as u8 {
+            layout.mines.insert(hex);
+        } else if state == HexState::Revealed as u8 {
+            layout.revealed.insert(hex);
+        }
+    }
+    if hexes.next().is_some() {
+        return Err("code has a wrong length".to_string());
+    }
+    if layout.cells.is_empty() {
+        return Err("code describes an invalid board".to_string());
+    }
+    Ok((layout, rules))
+}
+
+#[derive(Clone, Copy)]
+enum HexState {
+    Void = 0,
+    Cell = 1,
+    Mine = 2,
+    Revealed = 3,
+}
+
+/// Fails for rules that don't fit, see [`validate_rules`]
+fn encode_rules(rules: &Rules) -> Result<[u8; 4], String> {
+    validate_rules(rules)?;
+    Ok([
+        // bit flags with the treasure percent in the middle, codes from before zen mode have only the first flag
+        u8::from(!rules.flags)
+            | u8::from(rules.zen) << 1
+            | u8::from(rules.sandbox) << 2
+            | (rules.treasures as u8) << 3
+            | u8::from(rules.gravity) << 7,
+        // adjacency in the low 2 bits, masked in case the bounds above ever change
+        (rules.adjacency as u8 & 3)
+            | (rules.moving_mines as u8) << 2
+            | u8::from(rules.powerups) << 7,
+        // lives in the low nibble and the defuse countdown in units of `DEFUSE_UNIT` in the high one
+        rules.lives as u8 | ((rules.defuse_seconds / DEFUSE_UNIT) as u8) << 4,
+        // radius + 1 in a nibble each, 0 is no fog and no darkness
+        rules.fog.map_or(0, |radius| radius as u8 + 1)
+            | rules.darkness.map_or(0, |radius| radius as u8 + 1) << 4,
+    ])
+}
+
+fn decode_rules(bytes: &[u8]) -> Rules {
+    Rules {
+        flags: bytes[0] & 1 == 0,
+        adjacency: (bytes[1] & 3) as u32,
+        lives: (bytes[2] & 0xf) as u32,
+        fog: (bytes[3] & 0xf).checked_sub(1).map(u32::from),
+        zen: bytes[0] & 2 != 0,
+        sandbox: bytes[0] & 4 != 0,
+        treasures: (bytes[0] >> 3 & 0xf) as u32,
+        moving_mines: (bytes[1] >> 2 & 0x1f) as u32,
+        darkness: (bytes[3] >> 4).checked_sub(1).map(u32::from),
+        gravity: bytes[0] & 0x80 != 0,
+        defuse_seconds: (bytes[2] >> 4) as u32 * DEFUSE_UNIT,
+        powerups: bytes[1] & 0x80 != 0,
+        kernel: None,
+        colored: false,
+        directional: false,
+        gradient: Gradient::Uniform,
+        blind: false,
+        blind_pings: 0,
+        geometry: Geometry::Hex,
+    }
+}
+
+/// Rules that don't fit into [`encode_rules`], `None` if all of them are off
+fn encode_extension(rules: &Rules) -> Option<u64> {
+    let extension = rules.kernel.map_or(0, |kernel| kernel.0 & KERNEL_BITS)
+        | u64::from(rules.colored) << COLORED_SHIFT
+        | (rules.blind_pings as u64) << BLIND_PINGS_SHIFT
+        | u64::from(rules.directional) << DIRECTIONAL_SHIFT
+        | (rules.gradient as u64) << GRADIENT_SHIFT
+        | (rules.geometry as u64) << GEOMETRY_SHIFT
+        | u64::from(rules.blind) << BLIND_SHIFT;
+    (extension != 0).then_some(extension)
+}
+
+/// Rule bytes followed by the extension of [`encode_extension`]
+fn decode_extended_rules(bytes: &[u8]) -> Rules {
+    let extension = u64::from_le_bytes(bytes[4..12].try_into().expect("length is checked"));
+    let kernel = extension & KERNEL_BITS;
+    let blind_pings = (extension >> BLIND_PINGS_SHIFT) as u32 & MAX_BLIND_PINGS;
+    Rules {
+        kernel: (kernel != 0).then_some(Kernel(kernel)),
+        colored: extension >> COLORED_SHIFT & 1 != 0,
+        directional: extension >> DIRECTIONAL_SHIFT & 1 != 0,
+        gradient: Gradient::ALL[(extension >> GRADIENT_SHIFT) as usize & 3],
+        blind: extension >> BLIND_SHIFT & 1 != 0 || blind_pings > 0,
+        blind_pings,
+        geometry: Geometry::ALL
+            .get((extension >> GEOMETRY_SHIFT) as usize & 3)
+            .copied()
+            .unwrap_or_default(),
+        ..decode_rules(&bytes[..4])
+    }
+}
+
+/// Refuses rules that can't be played or don't fit into the rule bytes,
+/// so a code always reproduces the exact same game
+pub(crate) fn validate_rules(rules: &Rules) -> Result<(), String> {
+    if !(1..=MAX_ADJACENCY).contains(&rules.adjacency) {
+        return Err(format!("adjacency has to be from 1 to {MAX_ADJACENCY}"));
+    }
+    if rules.lives == 0 {
+        return Err("code describes invalid rules".to_string());
+    }
+    if rules.lives > MAX_LIVES {
+        return Err(format!("at most {MAX_LIVES} lives fit into a code"));
+    }
+    if rules.defuse_seconds % DEFUSE_UNIT != 0
+        || rules.defuse_seconds / DEFUSE_UNIT > MAX_DEFUSE_UNITS
+    {
+        return Err(format!(
+            "defuse countdowns have to be multiples of {DEFUSE_UNIT}s up to {}s to fit into a code",
+            MAX_DEFUSE_UNITS * DEFUSE_UNIT
+        ));
+    }
+    if rules.treasures > MAX_TREASURES {
+        return Err(format!(
+            "at most {MAX_TREASURES}% treasures fit into a code"
+        ));
+    }
+    if rules.fog.is_some_and(|radius| radius > MAX_FOG)
+        || rules.darkness.is_some_and(|radius| radius > MAX_FOG)
+    {
+        return Err(format!(
+            "fog and darkness wider than {MAX_FOG} hexes don't fit into a code"
+        ));
+    }
+    if rules.blind_pings > MAX_BLIND_PINGS {
+        return Err(format!("at most {MAX_BLIND_PINGS} pings fit into a code"));
+    }
+    if rules.moving_mines > MAX_MOVING_MINES {
+        return Err(format!(
+            "mines moving less often than every {MAX_MOVING_MINES} reveals don't fit into a code"
+        ));
+    }
+    Ok(())
+}
+
+fn density_to_permille(density: f32) -> u16 {
+    (density.clamp(0.0, 1.0) * 1000.0).round() as u16
+}
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// URL-safe base64 without padding
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 4 / 3 + 2);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |acc, (i, byte)| {
+            acc | ((*byte as u32) << (16 - 8 * i))
+        });
+        // 1 byte makes 2 chars, 2 bytes make 3 chars and 3 bytes make 4 chars
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+pub fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    let values = text
+        .bytes()
+        .map(|c| {
+            ALPHABET
+                .iter()
+                .position(|a| *a == c)
+                .map(|value| value as u32)
+                .ok_or_else(|| format!("unexpected character '{}'", c as char))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut out = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        if chunk.len() == 1 {
+            return Err("code is truncated".to_string());
+        }
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, value)| acc | (value << (18 - 6 * i)));
+        for i in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+
+    use super::*;
+
+    fn version(code: &str) -> u8 {
+        base64_decode(code).expect("valid base64")[0]
+    }
+
+    /// Rules that fit into the rule bytes, with every field away from the classic value
+    fn packed_rules() -> Rules {
+        Rules {
+            flags: false,
+            adjacency: 2,
+            lives: 3,
+            fog: Some(2),
+            zen: true,
+            treasures: 4,
+            moving_mines: 5,
+            darkness: Some(1),
+            defuse_seconds: 10,
+            powerups: true,
+            ..Rules::CLASSIC
+        }
+    }
+
+    /// Rules that need the extension bytes on top of [`packed_rules`]
+    fn extended_rules() -> Rules {
+        Rules {
+            kernel: Some(Kernel(0b1011_0110)),
+            colored: true,
+            directional: true,
+            gradient: Gradient::Rim,
+            blind: true,
+            blind_pings: 7,
+            geometry: Geometry::Square,
+            ..packed_rules()
+        }
+    }
+
+    fn layout() -> Layout {
+        let cells: HashSet<_> = shapes::hexagon(Hex::ZERO, 3)
+            .filter(|hex| *hex != Hex::new(1, 1))
+            .collect();
+        Layout {
+            cells,
+            mines: [Hex::new(0, 2), Hex::new(-3, 1), Hex::new(2, -2)]
+                .into_iter()
+                .collect(),
+            revealed: [Hex::ZERO, Hex::new(0, -1)].into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn seed_codes_round_trip() {
+        let cases = [
+            (Rules::CLASSIC, SEED_CODE_VERSION),
+            (packed_rules(), RULES_CODE_VERSION),
+            (extended_rules(), EXTENDED_SEED_CODE_VERSION),
+        ];
+        for (rules, expected_version) in cases {
+            let config = BoardConfig {
+                radius: 17,
+                mine_density: 0.16,
+                rules,
+            };
+            let seed = 0x0123_4567_89ab_cdef;
+            let code = encode_seed_code(&config, seed).expect("rules fit");
+            assert_eq!(version(&code), expected_version, "{code}");
+            assert_eq!(decode_seed_code(&code), Ok((config, seed)), "{code}");
+        }
+    }
+
+    #[test]
+    fn layout_codes_round_trip() {
+        for (rules, expected_version) in [
+            (packed_rules(), LAYOUT_CODE_VERSION),
+            (extended_rules(), EXTENDED_LAYOUT_CODE_VERSION),
+        ] {
+            let code = encode_layout_code(&layout(), &rules).expect("layout fits");
+            assert_eq!(version(&code), expected_version, "{code}");
+            assert_eq!(decode_layout_code(&code), Ok((layout(), rules)), "{code}");
+            assert!(matches!(
+                decode_board_code(&code),
+                Ok(SharedBoard::Layout { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn long_runs_are_split() {
+        // one state over more hexes than a run byte holds, then runs of every length around its limit
+        let full = Layout {
+            cells: shapes::hexagon(Hex::ZERO, 6).collect(),
+            ..Layout::default()
+        };
+        let code = encode_layout_code(&full, &Rules::CLASSIC).expect("layout fits");
+        let runs = &base64_decode(&code).expect("valid base64")[6..];
+        assert_eq!(runs.len(), 2, "127 hexes take a full run and one more");
+        assert_eq!(
+            decode_layout_code(&code),
+            Ok((full.clone(), Rules::CLASSIC))
+        );
+
+        let hexes: Vec<_> = shapes::hexagon(Hex::ZERO, 6).collect();
+        for length in [1, 2, 63, 64, 65, 126] {
+            let layout = Layout {
+                mines: hexes[..length].iter().copied().collect(),
+                ..full.clone()
+            };
+            let code = encode_layout_code(&layout, &Rules::CLASSIC).expect("layout fits");
+            assert_eq!(
+                decode_layout_code(&code),
+                Ok((layout, Rules::CLASSIC)),
+                "{length} mines"
+            );
+        }
+    }
+
+    #[test]
+    fn layouts_up_to_the_largest_radius_are_shared() {
+        let ring = |radius: u32| Layout {
+            cells: shapes::hexagon(Hex::ZERO, radius)
+                .filter(|hex| hex.ulength() == radius)
+                .collect(),
+            mines: [Hex::new(radius as i32, 0)].into_iter().collect(),
+            ..Layout::default()
+        };
+        let largest = ring(MAX_RADIUS);
+        let code = encode_layout_code(&largest, &Rules::CLASSIC).expect("layout fits");
+        assert_eq!(decode_layout_code(&code), Ok((largest, Rules::CLASSIC)));
+        assert!(encode_layout_code(&ring(MAX_RADIUS + 1), &Rules::CLASSIC).is_err());
+        // past a byte, which used to wrap around
+        assert!(encode_layout_code(&ring(256), &Rules::CLASSIC).is_err());
+    }
+
+    #[test]
+    fn rules_that_do_not_fit_are_refused() {
+        let config = |rules: Rules| BoardConfig {
+            rules,
+            ..BoardConfig::default()
+        };
+        let fitting = Rules {
+            treasures: MAX_TREASURES,
+            ..Rules::CLASSIC
+        };
+        let code = encode_seed_code(&config(fitting), 42).expect("rules fit");
+        assert_eq!(decode_seed_code(&code), Ok((config(fitting), 42)));
+
+        let too_many_treasures = Rules {
+            treasures: MAX_TREASURES + 1,
+            ..Rules::CLASSIC
+        };
+        assert!(encode_seed_code(&config(too_many_treasures), 42).is_err());
+        assert!(encode_layout_code(&layout(), &too_many_treasures).is_err());
+
+        // adjacency used to spill into the moving mines bits
+        for rules in [
+            Rules {
+                adjacency: MAX_ADJACENCY + 1,
+                ..Rules::CLASSIC
+            },
+            Rules {
+                moving_mines: MAX_MOVING_MINES + 1,
+                ..Rules::CLASSIC
+            },
+            Rules {
+                fog: Some(MAX_FOG + 1),
+                ..Rules::CLASSIC
+            },
+            Rules {
+                darkness: Some(MAX_FOG + 1),
+                ..Rules::CLASSIC
+            },
+            Rules {
+                lives: MAX_LIVES + 1,
+                ..Rules::CLASSIC
+            },
+            // used to be shared as 10 seconds
+            Rules {
+                defuse_seconds: 12,
+                ..Rules::CLASSIC
+            },
+            Rules {
+                defuse_seconds: (MAX_DEFUSE_UNITS + 1) * DEFUSE_UNIT,
+                ..Rules::CLASSIC
+            },
+            Rules {
+                blind: true,
+                blind_pings: MAX_BLIND_PINGS + 1,
+                ..Rules::CLASSIC
+            },
+        ] {
+            assert!(encode_seed_code(&config(rules), 42).is_err(), "{rules:?}");
+        }
+    }
+
+    #[test]
+    fn layout_code_with_extra_runs_is_rejected() {
+        let code = encode_layout_code(&layout(), &Rules::CLASSIC).expect("layout fits");
+        let mut bytes = base64_decode(&code).expect("valid base64");
+        bytes.push((HexState::Cell as u8) << 6);
+        assert!(decode_layout_code(&base64_encode(&bytes)).is_err());
+    }
+
+    #[test]
+    fn base64_known_values() {
+        // the standard alphabet with `-` and `_` instead of `+` and `/`, and without the `=` padding
+        let cases: [(&[u8], &str); 6] = [
+            (b"", ""),
+            (b"M", "TQ"),
+            (b"Ma", "TWE"),
+            (b"Man", "TWFu"),
+            (b"Many", "TWFueQ"),
+            (&[0xfb, 0xff], "-_8"),
+        ];
+        for (bytes, text) in cases {
+            assert_eq!(base64_encode(bytes), text);
+            assert_eq!(base64_decode(text).as_deref(), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn base64_round_trips_every_length() {
+        let bytes: Vec<u8> = (0..=255).rev().collect();
+        for length in 0..=bytes.len() {
+            let text = base64_encode(&bytes[..length]);
+            assert_eq!(text.len(), (length * 8 + 5) / 6, "{length} bytes");
+            assert_eq!(base64_decode(&text).as_deref(), Ok(&bytes[..length]));
+        }
+    }
+
+    #[test]
+    fn base64_rejects_bad_input() {
+        // one char is 6 bits, which is not a byte, and padding or the standard alphabet are not expected
+        for text in ["A", "TWFuT", "TQ==", "+/8", "TW u"] {
+            assert!(base64_decode(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn truncated_codes_are_rejected() {
+        let config = BoardConfig {
+            rules: extended_rules(),
+            ..BoardConfig::default()
+        };
+        let codes = [
+            encode_seed_code(&BoardConfig::default(), 42).expect("rules fit"),
+            encode_seed_code(&config, 42).expect("rules fit"),
+            encode_layout_code(&layout(), &Rules::CLASSIC).expect("layout fits"),
+            encode_layout_code(&layout(), &extended_rules()).expect("layout fits"),
+        ];
+        for code in codes {
+            for length in 0..code.len() {
+                assert!(
+                    decode_board_code(&code[..length]).is_err(),
+                    "{} of {code}",
+                    &code[..length]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        for code in ["", "not a code!", "ZZZZZZZZZZZZZZZZ", "AQAAAAAAAAAAAAAAAA"] {
+            assert!(decode_seed_code(code).is_err(), "{code}");
+            assert!(decode_board_code(code).is_err(), "{code}");
+        }
+        // a radius of 0 and a density of 100%
+        let mut bytes = vec![SEED_CODE_VERSION, 0, 0];
+        bytes.extend_from_slice(&1000u16.to_le_bytes());
+        bytes.extend_from_slice(&42u64.to_le_bytes());
+        assert!(decode_seed_code(&base64_encode(&bytes)).is_err());
+    }
+}
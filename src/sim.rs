@@ -0,0 +1,112 @@
+//! Headless game for integration tests and for the versus host checking the boards of clients:
+//! no window, no rendering and no input devices.
+//! Actions are injected as if the player made them, and every [`Simulation::step`] runs one frame.
+//!
+//! ```no_run
+//! use hex_minesweeper::{board::BoardConfig, game::Outcome, sim::Simulation};
+//! use hexx::Hex;
+//!
+//! let mut sim = Simulation::new(BoardConfig::default(), 42);
+//! sim.reveal(Hex::ZERO).step();
+//! assert!(!sim.board().covered.contains(&Hex::ZERO));
+//! assert_ne!(sim.game().outcome, Some(Outcome::Won));
+//! ```
+
+use bevy::prelude::*;
+use hexx::Hex;
+
+use crate::{
+    board::{Board, BoardConfig, Layout, Rules},
+    game::{ActionRequest, Game, GameAction, GamePlugin, NewCustomGame, NewGame},
+    mods::Mods,
+    AppState,
+};
+
+pub struct Simulation {
+    app: App,
+}
+
+impl Simulation {
+    /// Game on the board generated from the `seed`, exactly as in the real game
+    pub fn new(config: BoardConfig, seed: u64) -> Self {
+        let mut sim = Self::empty();
+        sim.app.world.send_event(NewGame { config, seed });
+        sim.step();
+        sim
+    }
+
+    /// Game on a hand-made board
+    pub fn from_layout(layout: Layout, rules: Rules) -> Self {
+        let mut sim = Self::empty();
+        sim.app.world.send_event(NewCustomGame {
+            layout,
+            rules,
+            seed: 0,
+        });
+        sim.step();
+        sim
+    }
+
+    fn empty() -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_state::<AppState>()
+            // rule scripts of whoever runs the tests must not affect them
+            .insert_resource(Mods::empty())
+            .add_plugins(GamePlugin);
+        // the first frame starts a random game on startup, which is replaced right after
+        app.update();
+        Self { app }
+    }
+
+    /// Requests to reveal the hex on the next step
+    pub fn reveal(&mut self, hex: Hex) -> &mut Self {
+        self.request(GameAction::Reveal(hex))
+    }
+
+    /// Requests to flag or unflag the hex on the next step
+    pub fn toggle_flag(&mut self, hex: Hex) -> &mut Self {
+        self.request(GameAction::ToggleFlag(hex))
+    }
+
+    /// Requests to chord the revealed number on the next step
+    pub fn chord(&mut self, hex: Hex) -> &mut Self {
+        self.request(GameAction::Chord(hex))
+    }
+
+    /// Requests the action on the next step, as if the player made it
+    pub fn request(&mut self, action: GameAction) -> &mut Self {
+        self.app.world.send_event(ActionRequest(action));
+        self
+    }
+
+    /// Runs one frame, applying all requested actions
+    pub fn step(&mut self) -> &mut Self {
+        self.app.update();
+        self
+    }
+
+    pub fn steps(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.step();
+        }
+        self
+    }
+
+    pub fn board(&self) -> &Board {
+        self.app.world.resource::<Board>()
+    }
+
+    pub fn game(&self) -> &Game {
+        self.app.world.resource::<Game>()
+    }
+
+    pub fn state(&self) -> AppState {
+        *self.app.world.resource::<State<AppState>>().get()
+    }
+
+    /// Escape hatch for everything else
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+}
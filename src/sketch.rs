@@ -0,0 +1,175 @@
+//! Freehand sketches over the board, to mark groups of hexes while reasoning about them.
+//!
+//! `D` toggles the sketching mode, in which dragging with the left button draws instead of revealing,
+//! and `C` clears everything drawn. Strokes stay in place on the board and are gone with the next game.
+//! `F12` saves a screenshot, with or without the sketches depending on [`Settings::sketches_in_screenshots`].
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+
+use crate::{
+    any_events, clock,
+    game::{GameSet, NewCustomGame, NewGame},
+    settings::Settings,
+    AppState, BoardView,
+};
+
+const STROKE_COLOR: Color = Color::rgba(1.0, 0.85, 0.1, 0.85);
+/// In pixels, so strokes look the same on boards of any size
+const STROKE_WIDTH: f32 = 3.0;
+/// Shortest segment in pixels, shorter mouse moves are merged into the next one
+const MIN_SEGMENT: f32 = 4.0;
+/// Above the hexes and everything on them
+const SKETCH_Z: f32 = 5.0;
+
+pub struct SketchPlugin;
+
+impl Plugin for SketchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_layer).add_systems(
+            Update,
+            (
+                (toggle_sketching, draw_strokes, clear_sketches)
+                    .chain()
+                    .in_set(GameSet::Input)
+                    .run_if(in_state(AppState::Playing)),
+                clear_on_new_game.after(GameSet::NewGame),
+                take_screenshot,
+            ),
+        );
+    }
+}
+
+/// Dragging draws instead of playing, exists only while the mode is on
+#[derive(Resource, Default)]
+pub struct Sketching {
+    /// End of the stroke being drawn
+    last: Option<Vec2>,
+}
+
+/// Parent of all stroke segments
+#[derive(Component)]
+struct SketchLayer;
+
+fn spawn_layer(mut commands: Commands) {
+    commands.spawn((SpatialBundle::default(), SketchLayer));
+}
+
+fn toggle_sketching(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    sketching: Option<Res<Sketching>>,
+) {
+    if !keys.just_pressed(KeyCode::D) {
+        return;
+    }
+    match sketching {
+        Some(_) => commands.remove_resource::<Sketching>(),
+        None => commands.init_resource::<Sketching>(),
+    }
+}
+
+fn draw_strokes(
+    mut commands: Commands,
+    buttons: Res<Input<MouseButton>>,
+    view: Res<BoardView>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    sketching: Option<ResMut<Sketching>>,
+    layers: Query<Entity, With<SketchLayer>>,
+) {
+    let Some(mut sketching) = sketching else {
+        return;
+    };
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(|window| Some(view.to_world(window.cursor_position()?, window)));
+    let (Some(cursor), true) = (cursor, buttons.pressed(MouseButton::Left)) else {
+        sketching.last = None;
+        return;
+    };
+    let Some(last) = sketching.last else {
+        sketching.last = Some(cursor);
+        return;
+    };
+    let length = last.distance(cursor);
+    if length < MIN_SEGMENT * view.scale {
+        return;
+    }
+    sketching.last = Some(cursor);
+
+    let width = STROKE_WIDTH * view.scale;
+    let direction = cursor - last;
+    let segment = SpriteBundle {
+        sprite: Sprite {
+            color: STROKE_COLOR,
+            // a bit longer, so segments overlap at the joints
+            custom_size: Some(Vec2::new(length + width, width)),
+            ..default()
+        },
+        transform: Transform::from_translation(((last + cursor) / 2.0).extend(SKETCH_Z))
+            .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x))),
+        ..default()
+    };
+    for layer in &layers {
+        commands.entity(layer).with_children(|layer| {
+            layer.spawn(segment.clone());
+        });
+    }
+}
+
+fn clear_sketches(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    layers: Query<Entity, With<SketchLayer>>,
+) {
+    if keys.just_pressed(KeyCode::C) {
+        for layer in &layers {
+            commands.entity(layer).despawn_descendants();
+        }
+    }
+}
+
+fn clear_on_new_game(
+    mut commands: Commands,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    layers: Query<Entity, With<SketchLayer>>,
+) {
+    if !(any_events(&mut new_games) | any_events(&mut custom_games)) {
+        return;
+    }
+    for layer in &layers {
+        commands.entity(layer).despawn_descendants();
+    }
+}
+
+/// Saves a screenshot on `F12`. Sketches are hidden for the frame it's taken in, if they should be left out.
+fn take_screenshot(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    mut layers: Query<&mut Visibility, With<SketchLayer>>,
+) {
+    let taking = keys.just_pressed(KeyCode::F12);
+    let hidden = taking && !settings.sketches_in_screenshots;
+    for mut visibility in &mut layers {
+        // shown again on the next frame
+        let expected = if hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        visibility.set_if_neq(expected);
+    }
+    if !taking {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let path = format!("screenshot-{}.png", clock::unix_time_millis());
+    if let Err(err) = screenshots.save_screenshot_to_disk(window, path) {
+        warn!("Failed to take a screenshot: {err}");
+    }
+}
@@ -0,0 +1,203 @@
+//! Solver that only makes moves that are certain, like a careful player would.
+//! Used to check that hand-made puzzles can be cleared without guessing, and by the mass-flag power-up.
+//! Directional clues give much less to go on, so the solver rarely gets far on such boards.
+
+use bevy::{log::debug, utils::HashSet};
+use hexx::Hex;
+
+use crate::{board::Board, clues};
+
+pub struct Solution {
+    /// Safe hexes that stay covered when the solver runs out of certain moves
+    pub unreachable: HashSet<Hex>,
+}
+
+impl Solution {
+    pub fn is_complete(&self) -> bool {
+        self.unreachable.is_empty()
+    }
+}
+
+/// Covered hexes around a revealed number and how many mines are among them
+struct Constraint {
+    hexes: HashSet<Hex>,
+    mines: usize,
+}
+
+/// Solves the board starting from the hexes that are already revealed
+pub fn solve(board: &Board) -> Solution {
+    let mut board = board.clone();
+    board.flagged.clear();
+    let mut known_mines = HashSet::new();
+    loop {
+        let (safe, mines) = certain_moves(&board, &known_mines);
+        debug!(safe = ?safe, mines = ?mines, "Solver found certain moves");
+        if safe.is_empty() && mines.is_empty() {
+            break;
+        }
+        known_mines.extend(mines);
+        for hex in safe {
+            board.reveal(hex);
+        }
+    }
+    Solution {
+        unreachable: board.covered.difference(&board.mines).copied().collect(),
+    }
+}
+
+/// Covered mines that can be deduced from the hexes revealed so far, without revealing anything else
+pub fn forced_mines(board: &Board) -> HashSet<Hex> {
+    let mut known_mines = HashSet::new();
+    loop {
+        let (_, mines) = certain_moves(board, &known_mines);
+        let before = known_mines.len();
+        known_mines.extend(mines);
+        if known_mines.len() == before {
+            return known_mines;
+        }
+    }
+}
+
+/// Hexes that are certainly safe and certainly mines
+fn certain_moves(board: &Board, known_mines: &HashSet<Hex>) -> (HashSet<Hex>, HashSet<Hex>) {
+    let unknown = |hex: &Hex| board.covered.contains(hex) && !known_mines.contains(hex);
+    // mines revealed with spare lives, a shield or in zen mode are known just as well
+    let is_known = |hex: &Hex| {
+        known_mines.contains(hex) || (board.mines.contains(hex) && !board.covered.contains(hex))
+    };
+    let mut safe = HashSet::new();
+    let mut mines = HashSet::new();
+
+    // the total amount of mines is shown to the player, so once all of them are known the rest is safe
+    if board.mines.iter().all(is_known) {
+        safe.extend(board.covered.iter().filter(|hex| unknown(hex)));
+        return (safe, mines);
+    }
+
+    if board.config.rules.directional {
+        mines.extend(clue_mines(board, &unknown));
+        return (safe, mines);
+    }
+
+    let constraints: Vec<_> = board
+        .cells
+        .iter()
+        .filter(|hex| !board.covered.contains(hex) && !board.mines.contains(hex))
+        .filter_map(|hex| {
+            let around: Vec<_> = board.neighbors(*hex).collect();
+            let hexes: HashSet<_> = around.iter().copied().filter(unknown).collect();
+            if hexes.is_empty() {
+                return None;
+            }
+            let known = around.iter().filter(|n| is_known(*n)).count();
+            let number = board.numbers.get(hex).copied().unwrap_or(0) as usize;
+            Some(Constraint {
+                hexes,
+                mines: number.saturating_sub(known),
+            })
+        })
+        .collect();
+
+    for constraint in &constraints {
+        if constraint.mines == 0 {
+            safe.extend(&constraint.hexes);
+        } else if constraint.mines == constraint.hexes.len() {
+            mines.extend(&constraint.hexes);
+        }
+    }
+    if !safe.is_empty() || !mines.is_empty() {
+        return (safe, mines);
+    }
+
+    // a number whose hexes are all around another number tells how many mines are in the difference
+    for smaller in &constraints {
+        for larger in &constraints {
+            if smaller.hexes.len() >= larger.hexes.len()
+                || smaller.mines > larger.mines
+                || !smaller.hexes.is_subset(&larger.hexes)
+            {
+                continue;
+            }
+            let rest: Vec<_> = larger.hexes.difference(&smaller.hexes).copied().collect();
+            let rest_mines = larger.mines - smaller.mines;
+            if rest_mines == 0 {
+                safe.extend(rest);
+            } else if rest_mines == rest.len() {
+                mines.extend(rest);
+            }
+        }
+    }
+    (safe, mines)
+}
+
+/// Numbers are not shown with directional clues, only arrows toward the nearest mine. The nearest mine
+/// is somewhere in the sector of the arrow, so it's certain once a sector has a single covered hex left.
+fn clue_mines(board: &Board, unknown: &impl Fn(&Hex) -> bool) -> HashSet<Hex> {
+    board
+        .clues
+        .iter()
+        .filter(|(hex, _)| !board.covered.contains(*hex))
+        .filter_map(|(hex, clue)| {
+            // mines revealed with extra lives can be the nearest ones too
+            let mut sector = board
+                .covered
+                .union(&board.mines)
+                .filter(|candidate| clues::direction(*hex, **candidate) == *clue);
+            match (sector.next(), sector.next()) {
+                (Some(only), None) if unknown(only) => Some(*only),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Layout, Rules};
+
+    fn layout_board(cells: &[Hex], mines: &[Hex], revealed: &[Hex]) -> Board {
+        let layout = Layout {
+            cells: cells.iter().copied().collect(),
+            mines: mines.iter().copied().collect(),
+            revealed: revealed.iter().copied().collect(),
+        };
+        Board::from_layout(&layout, Rules::CLASSIC)
+    }
+
+    #[test]
+    fn row_without_guesses_is_solved() {
+        // the revealed 0 opens the next hex, whose 1 can only mean the last one
+        let row = [Hex::new(0, 0), Hex::new(1, 0), Hex::new(2, 0)];
+        let board = layout_board(&row, &[row[2]], &[row[0]]);
+        assert!(solve(&board).is_complete());
+    }
+
+    #[test]
+    fn one_mine_between_two_hexes_needs_a_guess() {
+        let row = [Hex::new(-1, 0), Hex::new(0, 0), Hex::new(1, 0)];
+        let board = layout_board(&row, &[row[2]], &[row[1]]);
+        let solution = solve(&board);
+        assert_eq!(
+            solution.unreachable,
+            [row[0]].into_iter().collect::<HashSet<_>>()
+        );
+        assert!(forced_mines(&board).is_empty());
+    }
+
+    #[test]
+    fn revealed_mines_count_as_known() {
+        // a 2 next to a mine revealed with a spare life, with one mine among the two covered hexes
+        let (center, revealed_mine, mine, safe) =
+            (Hex::ZERO, Hex::new(1, 0), Hex::new(-1, 0), Hex::new(0, 1));
+        let cells = [center, revealed_mine, mine, safe];
+        let board = layout_board(&cells, &[revealed_mine, mine], &[center, revealed_mine]);
+        assert!(forced_mines(&board).is_empty());
+
+        // the 1 is already explained by the revealed mine, so the last hex is safe
+        let cells = [center, revealed_mine, safe];
+        let board = layout_board(&cells, &[revealed_mine], &[center, revealed_mine]);
+        assert!(forced_mines(&board).is_empty());
+        assert!(solve(&board).is_complete());
+    }
+}
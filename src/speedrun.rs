@@ -0,0 +1,158 @@
+//! Speedrun timer: splits at fixed fractions of the board, compared with the best run on the same config
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    any_events,
+    autosave::Recovering,
+    board::{Board, BoardConfig},
+    game::{Authority, BoardProgress, Game, GameOver, GameSet, NewCustomGame, NewGame, Outcome},
+    settings::Settings,
+    storage,
+};
+
+/// Fractions of revealed safe hexes at which splits are taken
+pub const SPLITS: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+
+pub struct SpeedrunPlugin;
+
+impl Plugin for SpeedrunPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<BestRuns>(BestRuns::FILE))
+            .init_resource::<Splits>()
+            .add_systems(
+                Update,
+                (
+                    toggle_timer,
+                    track_splits
+                        .after(GameSet::Actions)
+                        .run_if(resource_equals(Authority::Local)),
+                ),
+            );
+    }
+}
+
+/// Splits of the current run
+#[derive(Resource, Default)]
+pub struct Splits {
+    /// Game time when each of [`SPLITS`] was reached, in order
+    pub times_ms: Vec<u32>,
+    /// Splits of the personal best run to compare with
+    pub best_ms: Option<Vec<u32>>,
+    /// Recovered, hand-made and relaxed games have no comparable times
+    pub ranked: bool,
+}
+
+impl Splits {
+    /// Difference to the personal best at the given split, negative if the current run is ahead
+    pub fn delta_ms(&self, split: usize) -> Option<i64> {
+        let time = *self.times_ms.get(split)?;
+        let best = *self.best_ms.as_ref()?.get(split)?;
+        Some(time as i64 - best as i64)
+    }
+}
+
+/// Personal best run for every board config
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BestRuns {
+    pub runs: Vec<BestRun>,
+}
+
+impl BestRuns {
+    pub const FILE: &str = "splits";
+
+    /// Keeps the faster run of every config from the two profiles
+    pub fn merge(&mut self, other: BestRuns) {
+        for run in other.runs {
+            match self.runs.iter_mut().find(|best| best.config == run.config) {
+                Some(best) if best.splits_ms.last() <= run.splits_ms.last() => {}
+                Some(best) => *best = run,
+                None => self.runs.push(run),
+            }
+        }
+    }
+
+    fn get(&self, config: &BoardConfig) -> Option<&BestRun> {
+        self.runs.iter().find(|run| run.config == *config)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BestRun {
+    pub config: BoardConfig,
+    /// Times of all [`SPLITS`], the last one is the final time
+    pub splits_ms: Vec<u32>,
+}
+
+fn toggle_timer(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keys.just_pressed(KeyCode::F2) {
+        settings.speedrun_timer = !settings.speedrun_timer;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn track_splits(
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    mut progress: EventReader<BoardProgress>,
+    mut game_over: EventReader<GameOver>,
+    recovering: Option<Res<Recovering>>,
+    board: Res<Board>,
+    game: Res<Game>,
+    mut best_runs: ResMut<BestRuns>,
+    mut splits: ResMut<Splits>,
+) {
+    let started = any_events(&mut new_games) | any_events(&mut custom_games);
+    if started {
+        *splits = Splits {
+            times_ms: Vec::new(),
+            best_ms: best_runs
+                .get(&board.config)
+                .map(|run| run.splits_ms.clone()),
+            ranked: board.layout.is_none() && game.is_ranked(&board),
+        };
+    }
+    // replayed actions of a recovered game all happen at the same time
+    if recovering.is_some() || game.assisted {
+        splits.ranked = false;
+    }
+
+    for BoardProgress {
+        revealed, elapsed, ..
+    } in progress.iter()
+    {
+        while let Some(fraction) = SPLITS.get(splits.times_ms.len()) {
+            if revealed < fraction {
+                break;
+            }
+            splits.times_ms.push(elapsed.as_millis() as u32);
+        }
+    }
+
+    for GameOver(outcome) in game_over.iter() {
+        let complete = splits.times_ms.len() == SPLITS.len();
+        if *outcome != Outcome::Won || !splits.ranked || !complete {
+            continue;
+        }
+        let time_ms = game.elapsed.as_millis() as u32;
+        let previous = splits.best_ms.as_ref().and_then(|best| best.last());
+        if previous.is_some_and(|best| *best <= time_ms) {
+            continue;
+        }
+        let run = BestRun {
+            config: board.config,
+            splits_ms: splits.times_ms.clone(),
+        };
+        match best_runs
+            .runs
+            .iter_mut()
+            .find(|best| best.config == board.config)
+        {
+            Some(best) => *best = run,
+            None => best_runs.runs.push(run),
+        }
+        storage::save(BestRuns::FILE, &*best_runs);
+    }
+}
@@ -0,0 +1,221 @@
+//! Aggregate statistics over all games ever played, persisted between sessions
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{Board, BoardConfig},
+    endless::EndlessRun,
+    game::{Authority, Game, GameAction, GameOver, GameSet, Outcome},
+    storage,
+};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<Stats>(Stats::FILE))
+            .add_systems(
+                Update,
+                record_games
+                    .after(GameSet::Actions)
+                    .run_if(not(resource_equals(Authority::Replay))),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    /// Time spent in all games, including lost ones
+    pub total_time_ms: u64,
+    /// Consecutive wins up to the last game
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Breakdown by board config, as every config is a difficulty of its own
+    pub configs: Vec<ConfigStats>,
+    /// Games where an assist made some of the moves, by board config. They don't count anywhere else.
+    pub assisted: Vec<ConfigStats>,
+    /// Zen, sandbox and scripted games, see [`Game::is_ranked`], by board config. Their times can't be compared,
+    /// so they don't count anywhere else either.
+    pub unranked: Vec<ConfigStats>,
+}
+
+impl Stats {
+    pub const FILE: &str = "stats";
+
+    pub fn config(&self, config: &BoardConfig) -> Option<&ConfigStats> {
+        self.configs.iter().find(|stats| stats.config == *config)
+    }
+
+    /// Adds up stats from another profile. The current streak can't be combined, so the longer one is kept.
+    pub fn merge(&mut self, other: Stats) {
+        self.played += other.played;
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.total_time_ms += other.total_time_ms;
+        self.current_streak = self.current_streak.max(other.current_streak);
+        self.longest_streak = self.longest_streak.max(other.longest_streak);
+        merge_configs(&mut self.configs, other.configs);
+        merge_configs(&mut self.assisted, other.assisted);
+        merge_configs(&mut self.unranked, other.unranked);
+    }
+
+    fn record(&mut self, board: &Board, game: &Game, outcome: Outcome) {
+        let time_ms = game.elapsed.as_millis() as u32;
+        let (config, efficiency, no_flags) =
+            (&board.config, efficiency(board, game), no_flags(game));
+        if game.assisted {
+            config_stats(&mut self.assisted, config).record(outcome, time_ms, efficiency, no_flags);
+            return;
+        }
+        if !game.is_ranked(board) {
+            config_stats(&mut self.unranked, config).record(outcome, time_ms, efficiency, no_flags);
+            return;
+        }
+        self.played += 1;
+        self.total_time_ms += time_ms as u64;
+        match outcome {
+            Outcome::Won => {
+                self.wins += 1;
+                self.current_streak += 1;
+                self.longest_streak = self.longest_streak.max(self.current_streak);
+            }
+            Outcome::Lost => {
+                self.losses += 1;
+                self.current_streak = 0;
+            }
+        }
+        config_stats(&mut self.configs, config).record(outcome, time_ms, efficiency, no_flags);
+    }
+}
+
+fn merge_configs(configs: &mut Vec<ConfigStats>, others: Vec<ConfigStats>) {
+    for other in others {
+        match configs
+            .iter_mut()
+            .find(|stats| stats.config == other.config)
+        {
+            Some(stats) => {
+                stats.played += other.played;
+                stats.win_times_ms.extend(other.win_times_ms);
+                stats.no_flag_times_ms.extend(other.no_flag_times_ms);
+                stats.best_efficiency = match (stats.best_efficiency, other.best_efficiency) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            None => configs.push(other),
+        }
+    }
+}
+
+/// Stats of the config, added if it wasn't played yet
+fn config_stats<'a>(
+    configs: &'a mut Vec<ConfigStats>,
+    config: &BoardConfig,
+) -> &'a mut ConfigStats {
+    let index = match configs.iter().position(|stats| stats.config == *config) {
+        Some(index) => index,
+        None => {
+            configs.push(ConfigStats::new(*config));
+            configs.len() - 1
+        }
+    };
+    &mut configs[index]
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfigStats {
+    pub config: BoardConfig,
+    pub played: u32,
+    /// Times of all wins in the order they happened
+    pub win_times_ms: Vec<u32>,
+    /// Highest [`efficiency`] of a win
+    #[serde(default)]
+    pub best_efficiency: Option<f32>,
+    /// Times of wins without a single flag placed, see [`no_flags`]
+    #[serde(default)]
+    pub no_flag_times_ms: Vec<u32>,
+}
+
+impl ConfigStats {
+    fn new(config: BoardConfig) -> Self {
+        Self {
+            config,
+            played: 0,
+            win_times_ms: Vec::new(),
+            best_efficiency: None,
+            no_flag_times_ms: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, outcome: Outcome, time_ms: u32, efficiency: f32, no_flags: bool) {
+        self.played += 1;
+        if outcome == Outcome::Won {
+            self.win_times_ms.push(time_ms);
+            if no_flags {
+                self.no_flag_times_ms.push(time_ms);
+            }
+            self.best_efficiency = Some(
+                self.best_efficiency
+                    .map_or(efficiency, |best| best.max(efficiency)),
+            );
+        }
+    }
+
+    pub fn wins(&self) -> u32 {
+        self.win_times_ms.len() as u32
+    }
+
+    pub fn best_ms(&self) -> Option<u32> {
+        self.win_times_ms.iter().min().copied()
+    }
+
+    /// Best time of a win without flags, a category of its own
+    pub fn best_no_flag_ms(&self) -> Option<u32> {
+        self.no_flag_times_ms.iter().min().copied()
+    }
+
+    pub fn average_ms(&self) -> Option<u32> {
+        let total: u64 = self.win_times_ms.iter().map(|time| *time as u64).sum();
+        total
+            .checked_div(self.win_times_ms.len() as u64)
+            .map(|average| average as u32)
+    }
+}
+
+/// Ratio of the minimal amount of clicks needed for the board to the clicks actually made,
+/// so 1.0 is a perfect game. Flags count as clicks as well.
+pub fn efficiency(board: &Board, game: &Game) -> f32 {
+    let clicks = game.replay.steps.len().max(1);
+    board.min_clicks() as f32 / clicks as f32
+}
+
+/// Whether the player hasn't placed a single flag, known as an NF game. Hint flags don't count.
+pub fn no_flags(game: &Game) -> bool {
+    !game
+        .replay
+        .steps
+        .iter()
+        .any(|step| matches!(step.action(), GameAction::ToggleFlag(_)))
+}
+
+fn record_games(
+    mut game_over: EventReader<GameOver>,
+    board: Res<Board>,
+    game: Res<Game>,
+    endless: Option<Res<EndlessRun>>,
+    mut stats: ResMut<Stats>,
+) {
+    for GameOver(outcome) in game_over.iter() {
+        if EndlessRun::is_stage_clear(endless.as_deref(), *outcome) {
+            continue;
+        }
+        stats.record(&board, &game, *outcome);
+        storage::save(Stats::FILE, &*stats);
+    }
+}
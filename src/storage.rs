@@ -0,0 +1,238 @@
+//! Persistence of settings and player data as JSON files in the platform data directory.
+//! There is no persistent storage in the browser yet, so on the web everything lives only for the session.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+fn data_dir() -> Option<std::path::PathBuf> {
+    use std::{env, path::PathBuf};
+
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+    base.map(|base| base.join("hex-minesweeper"))
+}
+
+/// Loads `name.json`, falling back to the default value if it's missing or broken
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let Some(path) = data_dir().map(|dir| dir.join(format!("{name}.json"))) else {
+        return T::default();
+    };
+    match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+            bevy::log::warn!("Failed to parse {}: {err}", path.display());
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Saves `value` to `name.json`, logging failures as there is nothing better to do with them
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save<T: Serialize>(name: &str, value: &T) {
+    let Some(dir) = data_dir() else {
+        return;
+    };
+    let path = dir.join(format!("{name}.json"));
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| {
+            serde_json::to_vec_pretty(value)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+        })
+        // write to a temporary file first, so a crash in the middle never leaves a half-written file
+        .and_then(|bytes| std::fs::write(path.with_extension("tmp"), bytes))
+        .and_then(|_| std::fs::rename(path.with_extension("tmp"), &path));
+    if let Err(err) = result {
+        bevy::log::warn!("Failed to save {}: {err}", path.display());
+    }
+}
+
+/// Removes `name.json`, if there is one
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(name: &str) {
+    let Some(path) = data_dir().map(|dir| dir.join(format!("{name}.json"))) else {
+        return;
+    };
+    if let Err(err) = std::fs::remove_file(&path) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            bevy::log::warn!("Failed to remove {}: {err}", path.display());
+        }
+    }
+}
+
+/// Saves `value` to `dir/name.json` within the data directory, returning the full path
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_in<T: Serialize>(dir: &str, name: &str, value: &T) -> Result<String, String> {
+    let dir = data_dir()
+        .ok_or_else(|| "no data directory".to_string())?
+        .join(dir);
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    let path = dir.join(format!("{name}.json")).display().to_string();
+    write_file(&path, value).map(|_| path)
+}
+
+/// Loads every `.json` file from `dir` within the data directory, ordered by file names.
+/// Broken files are skipped with a warning.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_all_in<T: DeserializeOwned>(dir: &str) -> Vec<T> {
+    let Some(dir) = data_dir().map(|data| data.join(dir)) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            read_file(&path.display().to_string())
+                .map_err(|err| bevy::log::warn!("Skipping {err}"))
+                .ok()
+        })
+        .collect()
+}
+
+/// Reads every file with the `extension` from `dir` within the data directory as text,
+/// ordered by file names. Returns file names without the extension along with the contents.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_all_text_in(dir: &str, extension: &str) -> Vec<(String, String)> {
+    read_all_in(dir, extension)
+        .into_iter()
+        .filter_map(|(name, bytes)| match String::from_utf8(bytes) {
+            Ok(text) => Some((name, text)),
+            Err(err) => {
+                bevy::log::warn!("Skipping {name}.{extension}: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// The same as [`read_all_text_in`], but for binary files
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_all_in(dir: &str, extension: &str) -> Vec<(String, Vec<u8>)> {
+    let Some(dir) = data_dir().map(|data| data.join(dir)) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            std::fs::read(&path)
+                .map_err(|err| bevy::log::warn!("Skipping {}: {err}", path.display()))
+                .ok()
+                .map(|bytes| (name, bytes))
+        })
+        .collect()
+}
+
+/// Default path for files that are moved between machines, like exported profiles
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_path(file_name: &str) -> String {
+    data_dir()
+        .map(|dir| dir.join(file_name))
+        .unwrap_or_else(|| file_name.into())
+        .display()
+        .to_string()
+}
+
+/// Unlike [`save`], writes to an arbitrary path chosen by the player and reports errors
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_file<T: Serialize>(path: &str, value: &T) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(value).map_err(|err| err.to_string())?;
+    std::fs::write(path, bytes).map_err(|err| format!("{path}: {err}"))
+}
+
+/// The same as [`write_file`], for formats other than JSON
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_text(path: &str, text: &str) -> Result<(), String> {
+    std::fs::write(path, text).map_err(|err| format!("{path}: {err}"))
+}
+
+/// The same as [`write_file`], for pictures saved as PNG
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_png(path: &str, image: bevy::prelude::Image) -> Result<(), String> {
+    let image = image
+        .try_into_dynamic()
+        .map_err(|err| format!("{path}: {err}"))?;
+    image.save(path).map_err(|err| format!("{path}: {err}"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_file<T: DeserializeOwned>(path: &str) -> Result<T, String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+    serde_json::from_slice(&bytes).map_err(|err| format!("{path}: {err}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load<T: DeserializeOwned + Default>(_name: &str) -> T {
+    T::default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save<T: Serialize>(_name: &str, _value: &T) {}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove(_name: &str) {}
+
+#[cfg(target_arch = "wasm32")]
+pub fn export_path(file_name: &str) -> String {
+    file_name.to_string()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_file<T: Serialize>(_path: &str, _value: &T) -> Result<(), String> {
+    Err("files aren't available in the browser".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_text(_path: &str, _text: &str) -> Result<(), String> {
+    Err("files aren't available in the browser".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_png(_path: &str, _image: bevy::prelude::Image) -> Result<(), String> {
+    Err("files aren't available in the browser".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_file<T: DeserializeOwned>(_path: &str) -> Result<T, String> {
+    Err("files aren't available in the browser".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_in<T: Serialize>(_dir: &str, _name: &str, _value: &T) -> Result<String, String> {
+    Err("files aren't available in the browser".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load_all_in<T: DeserializeOwned>(_dir: &str) -> Vec<T> {
+    Vec::new()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_all_text_in(_dir: &str, _extension: &str) -> Vec<(String, String)> {
+    Vec::new()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_all_in(_dir: &str, _extension: &str) -> Vec<(String, Vec<u8>)> {
+    Vec::new()
+}
@@ -0,0 +1,129 @@
+//! Streamer mode: a presentation for recording or streaming the game, toggled with `F6` or in the settings.
+//! The HUD is enlarged, the board code is hidden so chat can't spoil the board, the end of a game
+//! is announced with a big banner, and optionally a strip on the right is kept free for a webcam.
+//!
+//! Only the look changes, the game itself is the same.
+
+use bevy::prelude::*;
+
+use crate::{
+    any_events,
+    game::{GameOver, NewCustomGame, NewGame, Outcome},
+    settings::Settings,
+    ui::text_style,
+};
+
+/// Size of the HUD text relative to the normal one
+const HUD_SCALE: f32 = 1.6;
+/// Part of the window width kept free of the board for a webcam
+const WEBCAM_WIDTH: f32 = 0.25;
+const BANNER_SIZE: f32 = 96.0;
+const BANNER_TIME: f32 = 3.0;
+const WIN_COLOR: Color = Color::rgb(0.4, 0.85, 0.4);
+const LOSS_COLOR: Color = Color::rgb(0.95, 0.35, 0.3);
+
+pub struct StreamerPlugin;
+
+impl Plugin for StreamerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (toggle_streamer_mode, (spawn_banner, fade_banner).chain()),
+        );
+    }
+}
+
+/// Size of the HUD text relative to the normal one
+pub fn hud_scale(settings: &Settings) -> f32 {
+    if settings.streamer_mode {
+        HUD_SCALE
+    } else {
+        1.0
+    }
+}
+
+/// Pixels on the right of the window the board is kept out of
+pub fn webcam_margin(settings: &Settings, window: &Window) -> f32 {
+    if settings.streamer_mode && settings.webcam_margin {
+        window.width() * WEBCAM_WIDTH
+    } else {
+        0.0
+    }
+}
+
+/// Win or loss announcement, gone after [`BANNER_TIME`] seconds
+#[derive(Component)]
+struct Banner {
+    age: f32,
+}
+
+fn toggle_streamer_mode(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keys.just_pressed(KeyCode::F6) {
+        settings.streamer_mode = !settings.streamer_mode;
+    }
+}
+
+fn spawn_banner(
+    mut commands: Commands,
+    mut game_over: EventReader<GameOver>,
+    settings: Res<Settings>,
+) {
+    for GameOver(outcome) in game_over.iter() {
+        if !settings.streamer_mode {
+            continue;
+        }
+        let (label, color) = match outcome {
+            Outcome::Won => ("CLEARED!", WIN_COLOR),
+            Outcome::Lost => ("BOOM!", LOSS_COLOR),
+        };
+        let mut style = text_style(BANNER_SIZE);
+        style.color = color;
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        top: Val::Percent(8.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    // above the results dialog
+                    z_index: ZIndex::Global(11),
+                    ..default()
+                },
+                Banner { age: 0.0 },
+            ))
+            .with_children(|banner| {
+                banner.spawn(TextBundle::from_section(label, style));
+            });
+    }
+}
+
+/// Fades the banner out, or removes it right away when a new game starts or the mode is turned off
+fn fade_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    mut banners: Query<(Entity, &mut Banner, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    let new_game = any_events(&mut new_games) | any_events(&mut custom_games);
+    for (entity, mut banner, children) in &mut banners {
+        banner.age += time.delta_seconds();
+        if new_game || !settings.streamer_mode || banner.age >= BANNER_TIME {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // fully visible for the first half
+        let alpha = (2.0 * (1.0 - banner.age / BANNER_TIME)).min(1.0);
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                let color = text.sections[0].style.color;
+                text.sections[0].style.color = color.with_a(alpha);
+            }
+        }
+    }
+}
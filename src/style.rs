@@ -0,0 +1,330 @@
+//! Look of the hex grid: gap between hexes, outlines, rounded corners and drop shadows.
+//! Size, shape and shadows are baked into the shared hex meshes, while outlines and the bevel of covered hexes
+//! are drawn by [`CellMaterial`]. Both are updated whenever [`GridStyle`] or the [`Geometry`] of the board changes,
+//! so cell entities keep their meshes and materials.
+
+use std::f32::consts::PI;
+
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        mesh::Indices,
+        render_resource::{AsBindGroup, PrimitiveTopology, ShaderRef},
+    },
+    sprite::{Material2d, Material2dPlugin},
+};
+use hexx::Hex;
+
+use crate::{
+    board::Board,
+    game::GameSet,
+    geometry::Geometry,
+    lod::Summary,
+    settings::{GridStyle, Settings},
+    theme::Theme,
+    HexGrid,
+};
+
+/// Radius of rounded corners relative to the hex size
+const ROUNDING: f32 = 0.25;
+/// Points per rounded corner
+const CORNER_SEGMENTS: usize = 4;
+const OUTLINE_WIDTH: f32 = 1.5;
+/// Width of the lit and shaded edges of covered hexes
+const BEVEL_WIDTH: f32 = 2.5;
+const SHADOW_OFFSET: Vec2 = Vec2::new(1.5, -1.5);
+const SHADOW_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.4);
+
+pub struct GridStylePlugin;
+
+impl Plugin for GridStylePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<CellMaterial>::default())
+            // after a new game, which might be of another geometry
+            .add_systems(Update, apply_grid_style.after(GameSet::NewGame));
+    }
+}
+
+/// Flat color with an optional outline and a bevel, which looks raised for covered hexes
+/// and sunken for pressed ones. Vertex colors of the mesh are applied on top, see `assets/shaders/cell.wgsl`.
+/// Colors come from the [`Theme`].
+#[derive(AsBindGroup, TypeUuid, Debug, Clone)]
+// `Reflect` brings its own `TypePath`
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+#[cfg_attr(not(feature = "debug-tools"), derive(bevy::reflect::TypePath))]
+#[uuid = "6f1d0c52-93a4-4e0b-9a57-2c3f8e1b7d44"]
+pub struct CellMaterial {
+    #[uniform(0)]
+    pub color: Color,
+    #[uniform(0)]
+    pub outline_color: Color,
+    /// Corner radius of the hex, as built by [`cell_mesh`]
+    #[uniform(0)]
+    pub radius: f32,
+    /// Cells are regular polygons, see [`Geometry::sides`]
+    #[uniform(0)]
+    pub sides: f32,
+    #[uniform(0)]
+    pub rounding: f32,
+    /// Zero without outlines
+    #[uniform(0)]
+    pub outline: f32,
+    /// Positive for raised hexes, negative for sunken ones and zero for flat ones
+    #[uniform(0)]
+    pub bevel: f32,
+}
+
+impl Material2d for CellMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/cell.wgsl".into()
+    }
+}
+
+impl CellMaterial {
+    pub fn uncovered(style: &GridStyle, theme: &Theme, geometry: Geometry) -> Self {
+        Self::with_bevel(0.0, style, theme, geometry)
+    }
+
+    pub fn covered(style: &GridStyle, theme: &Theme, geometry: Geometry) -> Self {
+        Self::with_bevel(BEVEL_WIDTH, style, theme, geometry)
+    }
+
+    pub fn pressed(style: &GridStyle, theme: &Theme, geometry: Geometry) -> Self {
+        Self::with_bevel(-BEVEL_WIDTH, style, theme, geometry)
+    }
+
+    /// Cell of a zoomed out board, tinted by what's on it
+    pub fn summary(summary: Summary, style: &GridStyle, theme: &Theme, geometry: Geometry) -> Self {
+        let mut material = match summary {
+            Summary::Flagged => Self::covered(style, theme, geometry),
+            _ => Self::uncovered(style, theme, geometry),
+        };
+        material.color = summary.color(theme);
+        material
+    }
+
+    fn with_bevel(bevel: f32, style: &GridStyle, theme: &Theme, geometry: Geometry) -> Self {
+        let mut material = Self {
+            color: Color::NONE,
+            outline_color: Color::NONE,
+            radius: 0.0,
+            sides: 0.0,
+            rounding: 0.0,
+            outline: 0.0,
+            bevel,
+        };
+        material.apply(style, theme, geometry);
+        material
+    }
+
+    fn apply(&mut self, style: &GridStyle, theme: &Theme, geometry: Geometry) {
+        // only covered hexes have a bevel, raised or pressed
+        self.color = if self.bevel == 0.0 {
+            theme.uncovered
+        } else {
+            theme.covered
+        };
+        self.outline_color = theme.outline;
+        self.radius = face_radius(style, geometry);
+        self.sides = geometry.sides() as f32;
+        self.rounding = rounding(style, geometry);
+        self.outline = if style.outlines { OUTLINE_WIDTH } else { 0.0 };
+    }
+}
+
+/// Plain hex of the styled size and shape, for overlays like the cursor highlight.
+/// `flipped` mirrors it for [`Geometry::is_flipped`] cells.
+pub fn face_mesh(style: &GridStyle, geometry: Geometry, flipped: bool) -> Mesh {
+    build_mesh(
+        style,
+        &polygon_face(style, geometry),
+        face_radius(style, geometry),
+        false,
+        flipped,
+    )
+}
+
+/// Hex of the board cells, with the outline and the shadow when enabled
+pub fn cell_mesh(style: &GridStyle, geometry: Geometry, flipped: bool) -> Mesh {
+    build_mesh(
+        style,
+        &polygon_face(style, geometry),
+        face_radius(style, geometry),
+        true,
+        flipped,
+    )
+}
+
+/// Face and cell meshes of the organic cell at `hex`, see [`Geometry::corners`].
+/// The material draws these cells flat, without outlines and the bevel.
+pub fn organic_meshes(style: &GridStyle, hex: Hex) -> (Mesh, Mesh) {
+    let geometry = Geometry::Organic;
+    let face: Vec<_> = geometry
+        .corners(hex)
+        .into_iter()
+        .map(|corner| corner * (1.0 - style.gap))
+        .collect();
+    let radius = face_radius(style, geometry);
+    (
+        build_mesh(style, &face, radius, false, false),
+        build_mesh(style, &face, radius, true, false),
+    )
+}
+
+fn apply_grid_style(
+    settings: Res<Settings>,
+    theme: Res<Theme>,
+    board: Res<Board>,
+    grid: Res<HexGrid>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CellMaterial>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+    mut applied: Local<Option<(GridStyle, Geometry)>>,
+) {
+    let (style, geometry) = (&settings.grid_style, board.config.rules.geometry);
+    // settings and the board change for many other reasons
+    let style_changed = applied.as_ref() != Some(&(style.clone(), geometry));
+    if !style_changed && !theme.is_changed() {
+        return;
+    }
+    if style_changed {
+        *applied = Some((style.clone(), geometry));
+        for (handle, flipped) in [(&grid.mesh, false), (&grid.flipped_mesh, true)] {
+            if let Some(mesh) = meshes.get_mut(handle) {
+                *mesh = face_mesh(style, geometry, flipped);
+            }
+        }
+        for (handle, flipped) in [(&grid.cell_mesh, false), (&grid.flipped_cell_mesh, true)] {
+            if let Some(mesh) = meshes.get_mut(handle) {
+                *mesh = cell_mesh(style, geometry, flipped);
+            }
+        }
+        for (hex, (face, cell)) in &grid.organic_meshes {
+            let (face_mesh, cell_mesh) = organic_meshes(style, *hex);
+            if let Some(mesh) = meshes.get_mut(face) {
+                *mesh = face_mesh;
+            }
+            if let Some(mesh) = meshes.get_mut(cell) {
+                *mesh = cell_mesh;
+            }
+        }
+    }
+    for handle in [
+        &grid.covered_material,
+        &grid.uncovered_material,
+        &grid.pressed_material,
+    ] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.apply(style, &theme, geometry);
+        }
+    }
+    for (summary, handle) in Summary::ALL.into_iter().zip(&grid.summary_materials) {
+        if let Some(material) = materials.get_mut(handle) {
+            material.apply(style, &theme, geometry);
+            material.color = summary.color(&theme);
+        }
+    }
+    if let Some(material) = color_materials.get_mut(&grid.highlight_material) {
+        material.color = theme.highlight;
+    }
+}
+
+fn face_radius(style: &GridStyle, geometry: Geometry) -> f32 {
+    geometry.cell_radius() * (1.0 - style.gap)
+}
+
+fn rounding(style: &GridStyle, geometry: Geometry) -> f32 {
+    if style.rounded {
+        face_radius(style, geometry) * ROUNDING
+    } else {
+        0.0
+    }
+}
+
+fn polygon_face(style: &GridStyle, geometry: Geometry) -> Vec<Vec2> {
+    polygon_outline(
+        geometry.sides(),
+        face_radius(style, geometry),
+        rounding(style, geometry),
+    )
+}
+
+/// Mesh of the `face` outline, which fits into a circle of `radius`
+fn build_mesh(
+    style: &GridStyle,
+    face: &[Vec2],
+    radius: f32,
+    decorated: bool,
+    flipped: bool,
+) -> Mesh {
+    // back to front, the face covers the shadow
+    let mut layers = Vec::new();
+    if decorated && style.shadows {
+        layers.push((face, SHADOW_OFFSET, SHADOW_COLOR));
+    }
+    layers.push((face, Vec2::ZERO, Color::WHITE));
+
+    // uvs stay unmirrored, so the material draws the same polygon, only its bevel is lit from the other side
+    let mirror = if flipped {
+        Vec2::new(-1.0, 1.0)
+    } else {
+        Vec2::ONE
+    };
+    let (mut positions, mut uvs, mut colors, mut indices) = (vec![], vec![], vec![], vec![]);
+    for (outline, offset, color) in layers {
+        // triangle fan around the center
+        let first = positions.len() as u32;
+        for point in std::iter::once(Vec2::ZERO).chain(outline.iter().copied()) {
+            let point = point * mirror + offset;
+            positions.push([point.x, point.y, 0.0]);
+            // the face fits into the unit square, so the material can find its edges
+            uvs.push((point * mirror / (2.0 * radius) + 0.5).to_array());
+            colors.push(color.as_linear_rgba_f32());
+        }
+        let count = outline.len() as u32;
+        for i in 0..count {
+            let (a, b) = (first + 1 + i, first + 1 + (i + 1) % count);
+            // mirroring turns the outline clockwise
+            indices.extend(if flipped {
+                [first, b, a]
+            } else {
+                [first, a, b]
+            });
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 0.0, 1.0]; positions.len()],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// Counter-clockwise border of a regular polygon with an edge facing right and the given corner `radius`,
+/// which is a pointy hex for 6 `sides`. Corners are replaced by arcs of `rounding` radius,
+/// which touch both edges of the corner.
+fn polygon_outline(sides: u32, radius: f32, rounding: f32) -> Vec<Vec2> {
+    let segments = if rounding > 0.0 { CORNER_SEGMENTS } else { 0 };
+    // angle between the normals of neighbor edges, which is also the turn at every corner
+    let turn = 2.0 * PI / sides as f32;
+    (0..sides)
+        .flat_map(|corner| {
+            let angle = turn / 2.0 + corner as f32 * turn;
+            let center = Vec2::from_angle(angle) * (radius - rounding / (turn / 2.0).cos());
+            (0..=segments).map(move |i| {
+                let t = if segments == 0 {
+                    0.5
+                } else {
+                    i as f32 / segments as f32
+                };
+                center + Vec2::from_angle(angle + turn * (t - 0.5)) * rounding
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,279 @@
+//! Colors of the board and its background. Themes are plain colors, so the light and the dark one
+//! can be blended by [`ThemeMode::DayNight`] or the darkness slider, and everything drawn with them updates live.
+//!
+//! [`ThemeMode::Custom`] uses colors picked in the theme editor instead. Custom themes are saved as JSON files
+//! in the `themes` directory of the game data, with colors written as `#rrggbbaa`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock,
+    settings::{Settings, ThemeMode},
+    storage,
+};
+
+/// Darkness is rounded to this, so a slowly changing time of day doesn't restyle the board every frame
+const DARKNESS_PRECISION: f32 = 0.01;
+const THEMES_DIR: &str = "themes";
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Theme::LIGHT)
+            .add_systems(PreUpdate, update_theme);
+    }
+}
+
+/// Colors currently in use, see `style.rs` and `background.rs` for where they go.
+/// Missing colors of a theme file are taken from [`Theme::LIGHT`].
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug-tools", derive(Reflect))]
+#[serde(default)]
+pub struct Theme {
+    #[serde(with = "hex_color")]
+    pub clear: Color,
+    /// Decorative hexes of the background
+    #[serde(with = "hex_color")]
+    pub motif: Color,
+    #[serde(with = "hex_color")]
+    pub covered: Color,
+    #[serde(with = "hex_color")]
+    pub uncovered: Color,
+    #[serde(with = "hex_color")]
+    pub outline: Color,
+    /// Overlay of the hex under the cursor
+    #[serde(with = "hex_color")]
+    pub highlight: Color,
+    /// Tint of the flag picture, white leaves it as drawn
+    #[serde(with = "hex_color")]
+    pub flag: Color,
+    /// Tints of the number pictures, number 1 under index 0. See [`Theme::digit`] for numbers in fonts.
+    #[serde(with = "hex_colors")]
+    pub numbers: [Color; 6],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::LIGHT
+    }
+}
+
+impl Theme {
+    pub const LIGHT: Self = Self {
+        clear: Color::rgb(0.4, 0.4, 0.4),
+        motif: Color::rgba(1.0, 1.0, 1.0, 0.04),
+        covered: Color::rgb(0.25, 0.25, 0.25),
+        uncovered: Color::rgb(0.6, 0.6, 0.6),
+        outline: Color::rgb(0.1, 0.1, 0.1),
+        highlight: Color::rgba(1.0, 1.0, 1.0, 0.2),
+        flag: Color::WHITE,
+        numbers: [Color::WHITE; 6],
+    };
+
+    pub const DARK: Self = Self {
+        clear: Color::rgb(0.07, 0.07, 0.09),
+        motif: Color::rgba(0.6, 0.7, 1.0, 0.03),
+        covered: Color::rgb(0.17, 0.18, 0.21),
+        uncovered: Color::rgb(0.42, 0.43, 0.47),
+        outline: Color::rgb(0.02, 0.02, 0.03),
+        highlight: Color::rgba(1.0, 1.0, 1.0, 0.2),
+        flag: Color::WHITE,
+        numbers: [Color::WHITE; 6],
+    };
+
+    /// `self` at 0 and `other` at 1
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mix = |from: Color, to: Color| {
+            let from = Vec4::from(from.as_linear_rgba_f32());
+            let to = Vec4::from(to.as_linear_rgba_f32());
+            let [r, g, b, a] = from.lerp(to, t).to_array();
+            Color::rgba_linear(r, g, b, a)
+        };
+        Self {
+            clear: mix(self.clear, other.clear),
+            motif: mix(self.motif, other.motif),
+            covered: mix(self.covered, other.covered),
+            uncovered: mix(self.uncovered, other.uncovered),
+            outline: mix(self.outline, other.outline),
+            highlight: mix(self.highlight, other.highlight),
+            flag: mix(self.flag, other.flag),
+            numbers: std::array::from_fn(|i| mix(self.numbers[i], other.numbers[i])),
+        }
+    }
+
+    /// Color of `number` drawn with a font. Pictures are only tinted, so the white of an untouched theme
+    /// would make digits unreadable, they stay black then.
+    pub fn digit(&self, number: u8) -> Color {
+        match (number as usize)
+            .checked_sub(1)
+            .and_then(|i| self.numbers.get(i))
+        {
+            Some(color) if *color != Color::WHITE => *color,
+            _ => Color::BLACK,
+        }
+    }
+
+    pub fn color(&self, role: ThemeRole) -> Color {
+        let mut theme = *self;
+        *theme.color_mut(role)
+    }
+
+    pub fn color_mut(&mut self, role: ThemeRole) -> &mut Color {
+        match role {
+            ThemeRole::Background => &mut self.clear,
+            ThemeRole::Motif => &mut self.motif,
+            ThemeRole::Covered => &mut self.covered,
+            ThemeRole::Uncovered => &mut self.uncovered,
+            ThemeRole::Outline => &mut self.outline,
+            ThemeRole::Highlight => &mut self.highlight,
+            ThemeRole::Flag => &mut self.flag,
+            ThemeRole::Number(number) => &mut self.numbers[number as usize - 1],
+        }
+    }
+}
+
+/// Colors of a [`Theme`] the theme editor picks one by one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThemeRole {
+    Background,
+    Motif,
+    Covered,
+    Uncovered,
+    Outline,
+    Highlight,
+    Flag,
+    /// From 1 to 6
+    Number(u8),
+}
+
+impl ThemeRole {
+    pub const ALL: [Self; 13] = [
+        Self::Background,
+        Self::Motif,
+        Self::Covered,
+        Self::Uncovered,
+        Self::Outline,
+        Self::Highlight,
+        Self::Flag,
+        Self::Number(1),
+        Self::Number(2),
+        Self::Number(3),
+        Self::Number(4),
+        Self::Number(5),
+        Self::Number(6),
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::Background => "Background".to_string(),
+            Self::Motif => "Background hexes".to_string(),
+            Self::Covered => "Covered hexes".to_string(),
+            Self::Uncovered => "Revealed hexes".to_string(),
+            Self::Outline => "Outlines".to_string(),
+            Self::Highlight => "Cursor highlight".to_string(),
+            Self::Flag => "Flags".to_string(),
+            Self::Number(number) => format!("Number {number}"),
+        }
+    }
+}
+
+/// Custom theme saved by the theme editor
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedTheme {
+    pub name: String,
+    #[serde(flatten)]
+    pub theme: Theme,
+}
+
+impl SavedTheme {
+    /// Saves the theme under its name, replacing a theme with the same name. Returns the file path.
+    pub fn save(&self) -> Result<String, String> {
+        let file_name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        if file_name.is_empty() {
+            return Err("theme needs a name".to_string());
+        }
+        storage::save_in(THEMES_DIR, &file_name, self)
+    }
+}
+
+/// All saved themes, ordered by file names
+pub fn load_themes() -> Vec<SavedTheme> {
+    storage::load_all_in(THEMES_DIR)
+}
+
+/// Colors as `#rrggbbaa` strings, bevy colors aren't serializable without its `serialize` feature
+mod hex_color {
+    use bevy::prelude::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format(*color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).map_err(D::Error::custom)
+    }
+
+    pub fn format(color: Color) -> String {
+        let [r, g, b, a] = color.as_rgba_u8();
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+
+    pub fn parse(text: &str) -> Result<Color, String> {
+        let hex = text.trim().trim_start_matches('#');
+        Color::hex(hex).map_err(|err| format!("{text}: {err}"))
+    }
+}
+
+/// The same as [`hex_color`] for the number colors
+mod hex_colors {
+    use bevy::prelude::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(colors: &[Color; 6], serializer: S) -> Result<S::Ok, S::Error> {
+        colors.map(super::hex_color::format).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Color; 6], D::Error> {
+        let texts = <[String; 6]>::deserialize(deserializer)?;
+        let mut colors = [Color::WHITE; 6];
+        for (color, text) in colors.iter_mut().zip(&texts) {
+            *color = super::hex_color::parse(text).map_err(D::Error::custom)?;
+        }
+        Ok(colors)
+    }
+}
+
+/// From 0 at day to 1 at night: fully dark from about 22:00 to 6:00, fully light from 10:00 to 18:00
+/// and blending in between
+fn night_darkness(hour: f32) -> f32 {
+    let from_2am = (hour - 2.0) / 24.0 * std::f32::consts::TAU;
+    (0.5 + from_2am.cos()).clamp(0.0, 1.0)
+}
+
+fn update_theme(settings: Res<Settings>, mut theme: ResMut<Theme>) {
+    let darkness = match settings.theme {
+        ThemeMode::Manual => settings.darkness,
+        ThemeMode::DayNight => night_darkness(clock::local_hour()),
+        ThemeMode::Custom => {
+            theme.set_if_neq(settings.custom_theme);
+            return;
+        }
+    };
+    let darkness = (darkness.clamp(0.0, 1.0) / DARKNESS_PRECISION).round() * DARKNESS_PRECISION;
+    theme.set_if_neq(Theme::LIGHT.lerp(&Theme::DARK, darkness));
+}
@@ -0,0 +1,102 @@
+//! Window title with the mode, the time and the mines left, so the game can be followed from the taskbar
+//! or the window switcher while the window is in the background.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    board::Board,
+    chat_vote::ChatVote,
+    coop::CoopSession,
+    dual::DualBoards,
+    endless::EndlessRun,
+    game::{Game, Outcome},
+    hotseat::HotSeat,
+    tournament::Tournament,
+    tutorial::Tutorial,
+    versus::VersusSession,
+};
+
+pub const TITLE: &str = "Hex Minesweeper";
+/// Titles are updated about once a second, more often is just noise for the OS
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct TitlePlugin;
+
+impl Plugin for TitlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, update_title);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_title(
+    time: Res<Time>,
+    mut since_updated: Local<Duration>,
+    board: Res<Board>,
+    game: Res<Game>,
+    tutorial: Option<Res<Tutorial>>,
+    hot_seat: Option<Res<HotSeat>>,
+    versus: Option<Res<VersusSession>>,
+    coop: Option<Res<CoopSession>>,
+    endless: Option<Res<EndlessRun>>,
+    dual: Option<Res<DualBoards>>,
+    tournament: Option<Res<Tournament>>,
+    chat_vote: Option<Res<ChatVote>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    *since_updated += time.delta();
+    if *since_updated < UPDATE_INTERVAL {
+        return;
+    }
+    *since_updated = Duration::ZERO;
+
+    // sessions first, as they can run on top of any rules
+    let rules = &board.config.rules;
+    let mode = if tutorial.is_some() {
+        "Tutorial"
+    } else if hot_seat.is_some() {
+        "Hot seat"
+    } else if versus.is_some() {
+        "Versus"
+    } else if coop.is_some() {
+        "Co-op"
+    } else if endless.is_some() {
+        "Endless run"
+    } else if dual.is_some() {
+        "Dual boards"
+    } else if tournament.is_some() {
+        "Tournament"
+    } else if chat_vote.is_some() {
+        "Chat vote"
+    } else if rules.zen {
+        "Zen"
+    } else if rules.sandbox {
+        "Sandbox"
+    } else if rules.gravity {
+        "Gravity"
+    } else {
+        "Classic"
+    };
+    let mut title = format!("{TITLE} - {mode}");
+    let reconnecting = versus.is_some_and(|session| session.is_reconnecting())
+        || coop.is_some_and(|session| session.is_reconnecting());
+    if reconnecting {
+        title += " (reconnecting)";
+    }
+    if !rules.zen {
+        title += &format!(" - {}s", game.elapsed.as_secs());
+    }
+    title += &match game.outcome {
+        Some(Outcome::Won) => " - won".to_string(),
+        Some(Outcome::Lost) => " - lost".to_string(),
+        None => format!(" - {} mines left", board.mines_left()),
+    };
+    for mut window in &mut windows {
+        // setting the title is a call into the OS, even if it's the same one
+        if window.title != title {
+            window.title = title.clone();
+        }
+    }
+}
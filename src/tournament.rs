@@ -0,0 +1,182 @@
+//! Local tournament: several players pass the keyboard around and play the same series of boards.
+//!
+//! Every board is played by all players in turn before the next one, each attempt is a whole game on the
+//! same seed. The standings rank players by cleared boards, then by the total time of those boards,
+//! and then by how much of the lost boards was revealed.
+
+use bevy::prelude::*;
+
+use crate::{
+    board::{Board, BoardConfig},
+    game::{Game, GameOver, GameSet, NewGame, Outcome},
+    rng, AppState,
+};
+
+pub const MIN_PLAYERS: u32 = 2;
+pub const MAX_PLAYERS: u32 = 8;
+pub const MAX_BOARDS: u32 = 5;
+
+pub struct TournamentPlugin;
+
+impl Plugin for TournamentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            record_attempts
+                .after(GameSet::Actions)
+                .run_if(resource_exists::<Tournament>()),
+        );
+    }
+}
+
+/// Where the tournament is between attempts
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// The current player is on the board
+    Playing,
+    /// The keyboard goes to the next player, who starts the attempt
+    Handover,
+    /// Every player has played every board
+    Finished,
+}
+
+/// Result of one player on one board
+#[derive(Clone, Copy)]
+pub struct Attempt {
+    pub cleared: bool,
+    pub time_ms: u32,
+    /// Fraction of safe hexes revealed, see [`Board::progress`]
+    pub progress: f32,
+}
+
+/// Place of a player in the standings
+pub struct Standing {
+    pub player: u32,
+    pub cleared: u32,
+    /// Of the cleared boards only
+    pub time_ms: u32,
+    /// Sum over all boards
+    pub progress: f32,
+}
+
+/// Ongoing tournament. The mode is active as long as the resource exists.
+#[derive(Resource)]
+pub struct Tournament {
+    pub players: u32,
+    boards: Vec<NewGame>,
+    /// Attempts of every player on every board, indexed by the board first
+    attempts: Vec<Vec<Option<Attempt>>>,
+    /// Attempts made so far, which also tells the current board and player
+    turn: u32,
+    pub stage: Stage,
+}
+
+impl Tournament {
+    /// Starts with the first player on the first of `boards` boards of the `config`
+    pub fn new(players: u32, boards: u32, config: BoardConfig) -> Self {
+        Self {
+            players,
+            boards: (0..boards)
+                .map(|_| NewGame {
+                    config,
+                    seed: rng::random_seed(),
+                })
+                .collect(),
+            attempts: vec![vec![None; players as usize]; boards as usize],
+            turn: 0,
+            stage: Stage::Playing,
+        }
+    }
+
+    pub fn boards_count(&self) -> u32 {
+        self.boards.len() as u32
+    }
+
+    /// Board of the current attempt, counting from 0
+    pub fn board_index(&self) -> u32 {
+        self.turn / self.players
+    }
+
+    /// Player of the current attempt, counting from 0
+    pub fn player(&self) -> u32 {
+        self.turn % self.players
+    }
+
+    /// Game of the current attempt, the same for every player on this board
+    pub fn current_game(&self) -> Option<NewGame> {
+        self.boards.get(self.board_index() as usize).copied()
+    }
+
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<_> = (0..self.players)
+            .map(|player| {
+                let attempts = self
+                    .attempts
+                    .iter()
+                    .filter_map(|board| board[player as usize]);
+                attempts.fold(
+                    Standing {
+                        player,
+                        cleared: 0,
+                        time_ms: 0,
+                        progress: 0.0,
+                    },
+                    |mut standing, attempt| {
+                        if attempt.cleared {
+                            standing.cleared += 1;
+                            standing.time_ms += attempt.time_ms;
+                        }
+                        standing.progress += attempt.progress;
+                        standing
+                    },
+                )
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.cleared
+                .cmp(&a.cleared)
+                .then(a.time_ms.cmp(&b.time_ms))
+                .then(b.progress.total_cmp(&a.progress))
+        });
+        standings
+    }
+
+    /// Records the attempt of the current player and moves on to the next one
+    fn record(&mut self, attempt: Attempt) {
+        let (board, player) = (self.board_index() as usize, self.player() as usize);
+        self.attempts[board][player] = Some(attempt);
+        self.turn += 1;
+        self.stage = if self.current_game().is_some() {
+            Stage::Handover
+        } else {
+            Stage::Finished
+        };
+    }
+}
+
+pub fn player_name(player: u32) -> String {
+    format!("Player {}", player + 1)
+}
+
+fn record_attempts(
+    mut game_over: EventReader<GameOver>,
+    board: Res<Board>,
+    game: Res<Game>,
+    mut tournament: ResMut<Tournament>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for GameOver(outcome) in game_over.iter() {
+        // games started outside of the tournament don't count
+        let expected = tournament.current_game().map(|game| game.seed);
+        if tournament.stage != Stage::Playing || expected != Some(board.seed) {
+            continue;
+        }
+        tournament.record(Attempt {
+            cleared: *outcome == Outcome::Won,
+            time_ms: game.elapsed.as_millis() as u32,
+            progress: board.progress(),
+        });
+        // the tournament screen instead of the results, to pass the keyboard
+        next_state.set(AppState::Tournament);
+    }
+}
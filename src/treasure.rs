@@ -0,0 +1,105 @@
+//! Treasure variant: a few empty safe hexes hide bonuses that are collected by revealing them
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use hexx::Hex;
+
+use crate::{
+    board::{sorted_hexes, Board},
+    game::{CellsChanged, Game, GameSet, TreasureFound},
+    rng::Rng,
+};
+
+/// Treasures use their own random sequence, so they don't change mines of the same seed
+const TREASURE_SEED_SALT: u64 = 0x7EA5_0E5E;
+/// Taken off the clock by [`Treasure::Time`]
+const TIME_BONUS: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Treasure {
+    /// Flags the mine closest to the treasure
+    Hint,
+    /// Takes a few seconds off the clock
+    Time,
+    /// Extra points, see `score.rs`
+    Score,
+}
+
+impl Treasure {
+    const ALL: [Self; 3] = [Self::Hint, Self::Time, Self::Score];
+}
+
+pub struct TreasurePlugin;
+
+impl Plugin for TreasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, open_treasures.after(GameSet::Actions));
+    }
+}
+
+/// Hides treasures under `percent` of hexes that are neither mines nor numbers, so they don't hide anything useful
+pub fn place(
+    cells: &HashSet<Hex>,
+    mines: &HashSet<Hex>,
+    numbers: &HashMap<Hex, u8>,
+    percent: u32,
+    seed: u64,
+) -> HashMap<Hex, Treasure> {
+    if percent == 0 {
+        return HashMap::new();
+    }
+    let mut empty = sorted_hexes(
+        cells
+            .iter()
+            .filter(|hex| !mines.contains(*hex) && !numbers.contains_key(*hex))
+            .copied(),
+    );
+    let mut rng = Rng::new(seed ^ TREASURE_SEED_SALT);
+    rng.shuffle(&mut empty);
+
+    let count = (empty.len() as f32 * percent as f32 / 100.0).round() as usize;
+    empty
+        .into_iter()
+        .take(count)
+        .map(|hex| {
+            let treasure = Treasure::ALL[rng.below(Treasure::ALL.len() as u64) as usize];
+            (hex, treasure)
+        })
+        .collect()
+}
+
+fn open_treasures(
+    mut found: EventReader<TreasureFound>,
+    mut board: ResMut<Board>,
+    mut game: ResMut<Game>,
+    mut cells_changed: EventWriter<CellsChanged>,
+) {
+    for TreasureFound { hex, treasure } in found.iter() {
+        match treasure {
+            Treasure::Hint => {
+                // ties are broken by coordinates, so replays flag the same mine
+                let closest = board
+                    .mines
+                    .iter()
+                    .filter(|mine| board.covered.contains(*mine) && !board.flagged.contains(*mine))
+                    .min_by_key(|mine| (mine.unsigned_distance_to(*hex), mine.x, mine.y))
+                    .copied();
+                if let Some(mine) = closest {
+                    board.flagged.insert(mine);
+                    // hints are never wrong, so the flag gets the right color too
+                    if board.red_mines.contains(&mine) {
+                        board.red_flags.insert(mine);
+                    }
+                    cells_changed.send(CellsChanged(vec![mine]));
+                }
+            }
+            Treasure::Time => game.elapsed = game.elapsed.saturating_sub(TIME_BONUS),
+            // collected by the score itself
+            Treasure::Score => {}
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Guided tutorial on a tiny scripted board: revealing, reading hex numbers, flagging and chording.
+//! Every step waits for the action it asks for, and an arrow points at the hex to act on.
+//! Hex numbers count six neighbors instead of eight, which is what confuses players coming from square boards.
+
+use bevy::{prelude::*, utils::HashSet};
+use hexx::{shapes, Hex};
+
+use crate::{
+    board::{Board, Layout, Rules},
+    game::{Game, GameOver, GameSet, NewCustomGame, NewGame, Outcome},
+    AppState, CursorPos, HexGrid, GRID_LAYOUT,
+};
+
+const RADIUS: u32 = 3;
+/// Chosen so that the center opens most of the board and every step has something to act on
+const MINES: [Hex; 4] = [
+    Hex::new(-3, 0),
+    Hex::new(-3, 1),
+    Hex::new(-2, 3),
+    Hex::new(-1, -2),
+];
+/// Empty hex that opens the board
+const START: Hex = Hex::ZERO;
+/// 1 with a single covered neighbor, which is [`FLAG`]
+const CLUE: Hex = Hex::new(-1, 2);
+const FLAG: Hex = Hex::new(-2, 3);
+/// 1 next to [`FLAG`] with two more covered neighbors, which are safe
+const CHORD: Hex = Hex::new(-2, 2);
+const ARROW_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+/// The arrow is drawn above its hex, pointing down at it
+const ARROW_OFFSET: f32 = 22.0;
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                leave_tutorial.after(GameSet::NewGame),
+                (advance, restart_on_loss, point_arrow)
+                    .chain()
+                    .after(GameSet::Actions),
+            )
+                .run_if(resource_exists::<Tutorial>()),
+        )
+        .add_systems(
+            Update,
+            remove_arrow.run_if(not(resource_exists::<Tutorial>())),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    Reveal,
+    Read,
+    Flag,
+    Chord,
+    Clear,
+    Done,
+}
+
+impl Step {
+    pub fn instruction(self) -> &'static str {
+        match self {
+            Self::Reveal => "Left click the hex under the arrow to reveal it.\nEmpty hexes open up their neighbors on their own.",
+            Self::Read => "Numbers count mines among the 6 hexes touching a hex, not 8 like on square boards.\nPoint at the number under the arrow.",
+            Self::Flag => "This 1 touches just one covered hex, so that hex is the mine.\nRight click the hex under the arrow to flag it.",
+            Self::Chord => "The 1 under the arrow already touches a flag, so its other neighbors are safe.\nClick the number to reveal all of them at once.",
+            Self::Clear => "Now reveal the rest of the safe hexes on your own.",
+            Self::Done => "Tutorial complete! Press Esc to start a real game.",
+        }
+    }
+
+    /// Hex the arrow points at
+    fn target(self) -> Option<Hex> {
+        match self {
+            Self::Reveal => Some(START),
+            Self::Read => Some(CLUE),
+            Self::Flag => Some(FLAG),
+            Self::Chord => Some(CHORD),
+            Self::Clear | Self::Done => None,
+        }
+    }
+}
+
+/// Ongoing tutorial. The mode is active as long as the resource exists.
+#[derive(Resource)]
+pub struct Tutorial {
+    pub step: Step,
+}
+
+impl Tutorial {
+    /// Starts the tutorial from the first step
+    pub fn start(commands: &mut Commands, custom_game: &mut EventWriter<NewCustomGame>) {
+        commands.insert_resource(Tutorial { step: Step::Reveal });
+        custom_game.send(tutorial_game());
+    }
+}
+
+fn tutorial_game() -> NewCustomGame {
+    NewCustomGame {
+        layout: Layout {
+            cells: shapes::hexagon(Hex::ZERO, RADIUS).collect(),
+            mines: MINES.into_iter().collect(),
+            revealed: HashSet::new(),
+        },
+        rules: Rules::CLASSIC,
+        seed: 0,
+    }
+}
+
+#[derive(Component)]
+struct TutorialArrow;
+
+/// Any other game ends the tutorial, including hand-made boards from puzzles or the editor
+fn leave_tutorial(
+    mut commands: Commands,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+) {
+    let tutorial = tutorial_game().layout;
+    let other_games = new_games.iter().count()
+        + custom_games
+            .iter()
+            .filter(|custom_game| custom_game.layout != tutorial)
+            .count();
+    if other_games > 0 {
+        commands.remove_resource::<Tutorial>();
+    }
+}
+
+fn advance(
+    board: Res<Board>,
+    game: Res<Game>,
+    cursor_pos: Res<CursorPos>,
+    mut tutorial: ResMut<Tutorial>,
+) {
+    let done = match tutorial.step {
+        Step::Reveal => !board.covered.contains(&START),
+        Step::Read => cursor_pos.0 == Some(CLUE),
+        Step::Flag => board.flagged.contains(&FLAG),
+        Step::Chord => board
+            .neighbors(CHORD)
+            .all(|hex| !board.covered.contains(&hex) || board.flagged.contains(&hex)),
+        Step::Clear => game.outcome == Some(Outcome::Won),
+        Step::Done => false,
+    };
+    if !done {
+        return;
+    }
+    tutorial.step = match tutorial.step {
+        Step::Reveal => Step::Read,
+        Step::Read => Step::Flag,
+        Step::Flag => Step::Chord,
+        Step::Chord => Step::Clear,
+        Step::Clear | Step::Done => Step::Done,
+    };
+}
+
+/// Mines are part of learning, so the board starts over right away instead of the results screen
+fn restart_on_loss(
+    mut game_over: EventReader<GameOver>,
+    mut tutorial: ResMut<Tutorial>,
+    mut custom_game: EventWriter<NewCustomGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for GameOver(outcome) in game_over.iter() {
+        if *outcome == Outcome::Lost {
+            tutorial.step = Step::Reveal;
+            custom_game.send(tutorial_game());
+            next_state.set(AppState::Playing);
+        }
+    }
+}
+
+fn point_arrow(
+    mut commands: Commands,
+    tutorial: Res<Tutorial>,
+    grid: Res<HexGrid>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut arrows: Query<(Entity, &mut Transform), With<TutorialArrow>>,
+) {
+    if !tutorial.is_changed() {
+        return;
+    }
+    let Some(target) = tutorial.step.target() else {
+        for (entity, _) in &arrows {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+    let pos = GRID_LAYOUT.hex_to_world_pos(target);
+    // above the board and its sprites
+    let translation = Vec3::new(pos.x, pos.y + ARROW_OFFSET, 4.0);
+    if let Ok((_, mut transform)) = arrows.get_single_mut() {
+        transform.translation = translation;
+        return;
+    }
+    commands.spawn((
+        ColorMesh2dBundle {
+            mesh: grid.arrow_mesh.clone().into(),
+            material: materials.add(ARROW_COLOR.into()),
+            transform: Transform::from_translation(translation)
+                .with_rotation(Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2))
+                .with_scale(Vec3::splat(1.5)),
+            ..default()
+        },
+        TutorialArrow,
+    ));
+}
+
+fn remove_arrow(mut commands: Commands, arrows: Query<Entity, With<TutorialArrow>>) {
+    for entity in &arrows {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled, TextField},
+    set_label, spawn_button, spawn_dialog, spawn_option, spawn_text_field, text_style,
+};
+use crate::{
+    any_events,
+    chat_vote::{ChatStatus, ChatVote, VOTE_SECONDS},
+    settings::Settings,
+    AppState,
+};
+
+pub struct ChatVoteScreenPlugin;
+
+impl Plugin for ChatVoteScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::ChatVote), spawn_chat_vote_screen)
+            .add_systems(OnExit(AppState::ChatVote), despawn_screen::<ChatVoteScreen>)
+            .add_systems(
+                Update,
+                (handle_chat_vote_screen, update_labels, update_status)
+                    .chain()
+                    .run_if(in_state(AppState::ChatVote)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct ChatVoteScreen;
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct ChannelField;
+
+#[derive(Component, Clone, Copy)]
+enum ChatVoteAction {
+    Seconds,
+    Start,
+    Stop,
+    Back,
+}
+
+fn spawn_chat_vote_screen(mut commands: Commands, settings: Res<Settings>) {
+    spawn_dialog(&mut commands, ChatVoteScreen, "Chat vote", |panel| {
+        panel.spawn((TextBundle::from_section("", text_style(18.0)), StatusText));
+        panel.spawn(TextBundle::from_section(
+            "Twitch channel, Enter to start:",
+            text_style(18.0),
+        ));
+        spawn_text_field(panel, &settings.chat_vote.channel, 0, ChannelField);
+        spawn_option(panel, 1, ChatVoteAction::Seconds);
+        panel.spawn(TextBundle::from_section(
+            "Viewers vote with the number of a highlighted hex, like 2 or !2",
+            text_style(16.0),
+        ));
+        spawn_button(panel, "Start", 2, ChatVoteAction::Start);
+        spawn_button(panel, "Stop", 3, ChatVoteAction::Stop);
+        spawn_button(panel, "Back", 4, ChatVoteAction::Back);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_chat_vote_screen(
+    mut commands: Commands,
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&ChatVoteAction>,
+    channel: Query<&TextField, With<ChannelField>>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        // `Enter` in the channel field starts right away
+        let action = if channel.contains(entity) {
+            Some(ChatVoteAction::Start)
+        } else {
+            actions.get(entity).ok().copied()
+        };
+        match action {
+            Some(ChatVoteAction::Seconds) => {
+                let current = VOTE_SECONDS
+                    .iter()
+                    .position(|seconds| *seconds == settings.chat_vote.seconds)
+                    .unwrap_or(0) as i32;
+                let next = (current + delta).rem_euclid(VOTE_SECONDS.len() as i32);
+                settings.chat_vote.seconds = VOTE_SECONDS[next as usize];
+            }
+            Some(ChatVoteAction::Start) => {
+                let name = channel.single().value.trim().to_string();
+                if name.is_empty() {
+                    continue;
+                }
+                // a new vote replaces the previous one along with its connection
+                commands.insert_resource(ChatVote::start(&name));
+                settings.chat_vote.channel = name;
+                next_state.set(AppState::Playing);
+            }
+            Some(ChatVoteAction::Stop) => commands.remove_resource::<ChatVote>(),
+            Some(ChatVoteAction::Back) => next_state.set(AppState::Paused),
+            None => {}
+        }
+    }
+}
+
+fn update_labels(
+    settings: Res<Settings>,
+    options: Query<(&ChatVoteAction, &Children)>,
+    added: Query<(), Added<ChatVoteScreen>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !settings.is_changed() && added.is_empty() {
+        return;
+    }
+    for (action, children) in &options {
+        if let ChatVoteAction::Seconds = action {
+            let seconds = settings.chat_vote.seconds;
+            set_label(children, &mut texts, format!("< Round: {seconds}s >"));
+        }
+    }
+}
+
+fn update_status(vote: Option<Res<ChatVote>>, mut texts: Query<&mut Text, With<StatusText>>) {
+    let label = match vote.as_deref() {
+        None => "Not running".to_string(),
+        Some(vote) => match &vote.status {
+            ChatStatus::Connecting => format!("Joining #{}", vote.channel),
+            ChatStatus::Connected => format!("Running in #{}", vote.channel),
+            ChatStatus::Disconnected(reason) => format!("Disconnected: {reason}"),
+        },
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
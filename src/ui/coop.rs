@@ -0,0 +1,149 @@
+use std::net::SocketAddr;
+
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Cancelled, TextField},
+    lan::{spawn_lan_list, JoinLan},
+    spawn_button, spawn_dialog, spawn_text_field, text_style,
+};
+use crate::{
+    any_events,
+    coop::{CoopSession, RemoteCursors, Role, DEFAULT_PORT},
+    game::Authority,
+    net::discovery::GameMode,
+    AppState,
+};
+
+pub struct CoopScreenPlugin;
+
+impl Plugin for CoopScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CoopStatus>()
+            .add_systems(OnEnter(AppState::Coop), spawn_coop_screen)
+            .add_systems(OnExit(AppState::Coop), despawn_screen::<CoopScreen>)
+            .add_systems(
+                Update,
+                (handle_coop_screen, update_status)
+                    .chain()
+                    .run_if(in_state(AppState::Coop)),
+            );
+    }
+}
+
+/// Last error, shown instead of the session state until the next attempt
+#[derive(Resource, Default)]
+struct CoopStatus(Option<String>);
+
+#[derive(Component)]
+struct CoopScreen;
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct AddressField;
+
+#[derive(Component, Clone, Copy)]
+enum CoopAction {
+    Host,
+    Join,
+    JoinLan(SocketAddr),
+    Leave,
+    Back,
+}
+
+fn spawn_coop_screen(mut commands: Commands) {
+    spawn_dialog(&mut commands, CoopScreen, "Co-op", |panel| {
+        panel.spawn((TextBundle::from_section("", text_style(18.0)), StatusText));
+        spawn_button(panel, "Host a game", 0, CoopAction::Host);
+        panel.spawn(TextBundle::from_section("Host address:", text_style(18.0)));
+        spawn_text_field(panel, &format!("127.0.0.1:{DEFAULT_PORT}"), 1, AddressField);
+        spawn_button(panel, "Join", 2, CoopAction::Join);
+        spawn_button(panel, "Leave", 3, CoopAction::Leave);
+        spawn_button(panel, "Back", 4, CoopAction::Back);
+        spawn_lan_list(panel, GameMode::Coop);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_coop_screen(
+    mut commands: Commands,
+    mut activated: EventReader<Activated>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&CoopAction>,
+    address: Query<&TextField, With<AddressField>>,
+    lan_games: Query<&JoinLan>,
+    mut authority: ResMut<Authority>,
+    mut cursors: ResMut<RemoteCursors>,
+    mut status: ResMut<CoopStatus>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    for Activated(entity) in activated.iter() {
+        // `Enter` in the address field joins right away
+        let action = if address.contains(*entity) {
+            Some(CoopAction::Join)
+        } else if let Ok(JoinLan(lan_address)) = lan_games.get(*entity) {
+            Some(CoopAction::JoinLan(*lan_address))
+        } else {
+            actions.get(*entity).ok().copied()
+        };
+        let session = match action {
+            Some(CoopAction::Host) => CoopSession::host(DEFAULT_PORT),
+            Some(CoopAction::Join) => CoopSession::join(&address.single().value),
+            Some(CoopAction::JoinLan(lan_address)) => CoopSession::join(&lan_address.to_string()),
+            Some(CoopAction::Leave) => {
+                commands.remove_resource::<CoopSession>();
+                *authority = Authority::Local;
+                cursors.clear();
+                status.0 = None;
+                continue;
+            }
+            Some(CoopAction::Back) => {
+                next_state.set(AppState::Paused);
+                continue;
+            }
+            None => continue,
+        };
+
+        match session {
+            Ok(session) => {
+                *authority = session.authority();
+                commands.insert_resource(session);
+                status.0 = None;
+            }
+            Err(err) => status.0 = Some(format!("Failed: {err}")),
+        }
+    }
+}
+
+fn update_status(
+    session: Option<Res<CoopSession>>,
+    status: Res<CoopStatus>,
+    mut texts: Query<&mut Text, With<StatusText>>,
+) {
+    let label = match (&status.0, session) {
+        (Some(err), _) => err.clone(),
+        (None, None) => "Not connected".to_string(),
+        (None, Some(session)) => match session.role {
+            Role::Host => format!(
+                "Hosting on port {DEFAULT_PORT}, {} player(s) joined",
+                session.peers_count()
+            ),
+            Role::Client if session.is_reconnecting() => {
+                "Connection lost, reconnecting".to_string()
+            }
+            Role::Client => "Connected, waiting for the host".to_string(),
+        },
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Panel of the board editor. Unlike other screens it's docked to the left, so the board stays visible.
+
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled, FocusScope, TextField},
+    set_label, spawn_button, spawn_option, spawn_text_field, text_style, PANEL_COLOR,
+};
+use crate::{
+    any_events,
+    board::Rules,
+    board_text::{decode_board_text, encode_board_text},
+    clipboard,
+    editor::{Editor, Tool},
+    game::NewCustomGame,
+    puzzle::{validate, Puzzle},
+    AppState,
+};
+
+pub struct EditorScreenPlugin;
+
+impl Plugin for EditorScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Editor), spawn_editor_panel)
+            .add_systems(OnExit(AppState::Editor), despawn_screen::<EditorPanel>)
+            .add_systems(
+                Update,
+                (handle_editor_panel, update_labels)
+                    .chain()
+                    .run_if(in_state(AppState::Editor)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct EditorPanel;
+
+#[derive(Component)]
+struct SummaryText;
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct NameField;
+
+#[derive(Component)]
+struct ParField;
+
+#[derive(Component, Clone, Copy)]
+enum EditorAction {
+    Tool,
+    TestPlay,
+    Save,
+    CopyText,
+    PasteText,
+    Back,
+}
+
+fn spawn_editor_panel(mut commands: Commands, editor: Res<Editor>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(8.0),
+                    top: Val::Px(48.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Stretch,
+                    padding: UiRect::all(Val::Px(16.0)),
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                background_color: PANEL_COLOR.into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            // makes clicks on the panel background miss the board
+            Interaction::default(),
+            FocusScope,
+            EditorPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section("Board editor", text_style(32.0)));
+            spawn_option(panel, 0, EditorAction::Tool);
+            panel.spawn((TextBundle::from_section("", text_style(16.0)), SummaryText));
+            panel.spawn(TextBundle::from_section("Name:", text_style(18.0)));
+            spawn_text_field(panel, &editor.name, 1, NameField);
+            panel.spawn(TextBundle::from_section(
+                "Par time, seconds:",
+                text_style(18.0),
+            ));
+            spawn_text_field(panel, &editor.par_time, 2, ParField);
+            spawn_button(panel, "Test play", 3, EditorAction::TestPlay);
+            spawn_button(panel, "Save puzzle", 4, EditorAction::Save);
+            spawn_button(panel, "Copy as text", 5, EditorAction::CopyText);
+            spawn_button(panel, "Paste text", 6, EditorAction::PasteText);
+            spawn_button(panel, "Back", 7, EditorAction::Back);
+            panel.spawn((TextBundle::from_section("", text_style(16.0)), StatusText));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_editor_panel(
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&EditorAction>,
+    name: Query<&TextField, With<NameField>>,
+    par: Query<&TextField, With<ParField>>,
+    mut status_texts: Query<&mut Text, With<StatusText>>,
+    mut editor: ResMut<Editor>,
+    mut custom_game: EventWriter<NewCustomGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    // fields are kept in the editor, so they survive test plays
+    if let (Ok(name), Ok(par)) = (name.get_single(), par.get_single()) {
+        if editor.name != name.value || editor.par_time != par.value {
+            editor.name = name.value.clone();
+            editor.par_time = par.value.clone();
+        }
+    }
+
+    let mut status = None;
+    // activation cycles options forward, so they work with the mouse too
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        match actions.get(entity) {
+            Ok(EditorAction::Tool) => {
+                let count = Tool::ALL.len() as i32;
+                let index = Tool::ALL.iter().position(|tool| *tool == editor.tool);
+                let index = (index.unwrap_or(0) as i32 + delta).rem_euclid(count);
+                editor.tool = Tool::ALL[index as usize];
+            }
+            Ok(EditorAction::TestPlay) => match validate(&editor.layout) {
+                Ok(_) => custom_game.send(NewCustomGame {
+                    layout: editor.layout.clone(),
+                    rules: Rules::CLASSIC,
+                    seed: 0,
+                }),
+                Err(err) => status = Some(format!("Can't play: {err}")),
+            },
+            Ok(EditorAction::Save) => {
+                status = Some(match save_puzzle(&mut editor) {
+                    Ok(status) => status,
+                    Err(err) => format!("Not saved: {err}"),
+                });
+            }
+            Ok(EditorAction::CopyText) => {
+                status = Some(match clipboard::copy(&encode_board_text(&editor.layout)) {
+                    Ok(()) => "Copied as text".to_string(),
+                    Err(err) => format!("Copy failed: {err}"),
+                });
+            }
+            Ok(EditorAction::PasteText) => {
+                match clipboard::paste().and_then(|text| decode_board_text(&text)) {
+                    Ok(layout) => {
+                        editor.layout = layout;
+                        editor.unreachable.clear();
+                        status = Some("Pasted".to_string());
+                    }
+                    Err(err) => status = Some(format!("Paste failed: {err}")),
+                }
+            }
+            Ok(EditorAction::Back) => next_state.set(AppState::Paused),
+            Err(_) => {}
+        }
+    }
+
+    if let Some(status) = status {
+        for mut text in &mut status_texts {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
+
+/// Refuses broken puzzles and marks hexes that need guessing, but still saves such puzzles
+fn save_puzzle(editor: &mut Editor) -> Result<String, String> {
+    let solution = validate(&editor.layout)?;
+    let par_time = editor.par_time.trim();
+    let par_time_ms = if par_time.is_empty() {
+        None
+    } else {
+        let seconds: f32 = par_time
+            .parse()
+            .map_err(|_| "par time is not a number".to_string())?;
+        Some((seconds * 1000.0) as u32)
+    };
+    let path = Puzzle::new(editor.name.trim().to_string(), par_time_ms, &editor.layout).save()?;
+    let unreachable = solution.unreachable.len();
+    editor.unreachable = solution.unreachable;
+    Ok(if unreachable == 0 {
+        format!("Saved to {path}, solvable without guessing")
+    } else {
+        format!("Saved to {path}, but {unreachable} marked hex(es) need guessing")
+    })
+}
+
+fn update_labels(
+    editor: Res<Editor>,
+    options: Query<(&EditorAction, &Children)>,
+    summaries: Query<Entity, With<SummaryText>>,
+    added: Query<(), Added<EditorPanel>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !editor.is_changed() && added.is_empty() {
+        return;
+    }
+    for (action, children) in &options {
+        if let EditorAction::Tool = action {
+            set_label(
+                children,
+                &mut texts,
+                format!("< Tool: {} >", editor.tool.name()),
+            );
+        }
+    }
+
+    let layout = &editor.layout;
+    let summary = format!(
+        "{} hexes, {} mines, {} revealed\nLeft click paints, right click clears",
+        layout.cells.len(),
+        layout.mines.len(),
+        layout.revealed.len()
+    );
+    for entity in &summaries {
+        if let Ok(mut text) = texts.get_mut(entity) {
+            text.sections[0].value = summary.clone();
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Keyboard-friendly focus management for menus and dialogs.
+//!
+//! A menu or dialog root is marked with [`FocusScope`] and its interactive nodes with [`Focusable`].
+//! Only the most recently opened scope receives input, so a dialog on top of a menu captures the keyboard
+//! until it is closed. Mouse clicks and `Enter`/`Space` end up as the same [`Activated`] event, so screens
+//! handle a single input path regardless of the device.
+
+use bevy::prelude::*;
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Focus>()
+            .add_event::<Activated>()
+            .add_event::<Adjusted>()
+            .add_event::<Cancelled>()
+            .add_systems(
+                Update,
+                (
+                    track_scopes,
+                    handle_mouse,
+                    handle_keyboard,
+                    edit_text_fields,
+                    draw_focus_indicator,
+                    draw_text_fields,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Root of a menu or dialog. Focus never leaves the topmost scope.
+#[derive(Component)]
+pub struct FocusScope;
+
+/// UI node that can be focused and activated. Nodes within a scope are visited in ascending `order`.
+#[derive(Component, Default)]
+pub struct Focusable {
+    pub order: u32,
+    /// Adjustable nodes (like option selectors) take `Left`/`Right` as [`Adjusted`] instead of navigation
+    pub adjustable: bool,
+}
+
+/// Single-line text input. While it's focused, typed characters go to the `value`.
+/// The value is shown in the first child [`Text`] of the node.
+#[derive(Component, Default)]
+pub struct TextField {
+    pub value: String,
+}
+
+/// Currently focused node and the stack of open scopes with the topmost one being the last
+#[derive(Resource, Default)]
+pub struct Focus {
+    pub entity: Option<Entity>,
+    scopes: Vec<Entity>,
+}
+
+impl Focus {
+    /// Scope that currently receives input
+    pub fn top_scope(&self) -> Option<Entity> {
+        self.scopes.last().copied()
+    }
+}
+
+/// Focusable node was clicked or confirmed with `Enter`/`Space`
+#[derive(Event)]
+pub struct Activated(pub Entity);
+
+/// Adjustable node was changed with `Left` (-1) or `Right` (+1)
+#[derive(Event)]
+pub struct Adjusted {
+    pub entity: Entity,
+    pub delta: i32,
+}
+
+/// `Escape` was pressed while the scope was on top
+#[derive(Event)]
+pub struct Cancelled(pub Entity);
+
+const FOCUS_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+
+fn is_within(entity: Entity, scope: Entity, parents: &Query<&Parent>) -> bool {
+    let mut current = entity;
+    while let Ok(parent) = parents.get(current) {
+        current = parent.get();
+        if current == scope {
+            return true;
+        }
+    }
+    false
+}
+
+/// Focusable nodes of the `scope` in the navigation order
+fn ordered_focusables(
+    scope: Option<Entity>,
+    focusables: &Query<(Entity, &Focusable)>,
+    parents: &Query<&Parent>,
+) -> Vec<Entity> {
+    let Some(scope) = scope else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<_> = focusables
+        .iter()
+        .filter(|(entity, _)| is_within(*entity, scope, parents))
+        .map(|(entity, focusable)| (focusable.order, entity))
+        .collect();
+    nodes.sort();
+    nodes.into_iter().map(|(_order, entity)| entity).collect()
+}
+
+fn track_scopes(
+    mut focus: ResMut<Focus>,
+    added: Query<Entity, Added<FocusScope>>,
+    mut removed: RemovedComponents<FocusScope>,
+    focusables: Query<(Entity, &Focusable)>,
+    parents: Query<&Parent>,
+) {
+    let mut changed = false;
+    for entity in removed.iter() {
+        focus.scopes.retain(|scope| *scope != entity);
+        changed = true;
+    }
+    for entity in added.iter() {
+        focus.scopes.push(entity);
+        changed = true;
+    }
+    // focused node might be despawned without closing the whole scope, e.g. when a list is rebuilt
+    if let Some(entity) = focus.entity {
+        changed |= focusables.get(entity).is_err();
+    }
+
+    if changed {
+        focus.entity = ordered_focusables(focus.top_scope(), &focusables, &parents)
+            .first()
+            .copied();
+    }
+}
+
+fn handle_mouse(
+    mut focus: ResMut<Focus>,
+    interactions: Query<(Entity, &Interaction), Changed<Interaction>>,
+    focusables: Query<(Entity, &Focusable)>,
+    parents: Query<&Parent>,
+    mut activated: EventWriter<Activated>,
+) {
+    let Some(scope) = focus.top_scope() else {
+        return;
+    };
+
+    for (entity, interaction) in &interactions {
+        // nodes of the scopes underneath are still clickable, but shouldn't react
+        if focusables.get(entity).is_err() || !is_within(entity, scope, &parents) {
+            continue;
+        }
+        match interaction {
+            Interaction::Hovered => focus.entity = Some(entity),
+            Interaction::Pressed => {
+                focus.entity = Some(entity);
+                activated.send(Activated(entity));
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+fn handle_keyboard(
+    keys: Res<Input<KeyCode>>,
+    mut focus: ResMut<Focus>,
+    focusables: Query<(Entity, &Focusable)>,
+    parents: Query<&Parent>,
+    text_fields: Query<(), With<TextField>>,
+    mut activated: EventWriter<Activated>,
+    mut adjusted: EventWriter<Adjusted>,
+    mut cancelled: EventWriter<Cancelled>,
+) {
+    let Some(scope) = focus.top_scope() else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        cancelled.send(Cancelled(scope));
+        return;
+    }
+
+    let nodes = ordered_focusables(Some(scope), &focusables, &parents);
+    if nodes.is_empty() {
+        return;
+    }
+    let current = focus
+        .entity
+        .and_then(|entity| nodes.iter().position(|node| *node == entity));
+    let adjustable = current
+        .and_then(|i| focusables.get(nodes[i]).ok())
+        .map_or(false, |(_, focusable)| focusable.adjustable);
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let (left, right) = (
+        keys.just_pressed(KeyCode::Left),
+        keys.just_pressed(KeyCode::Right),
+    );
+    if adjustable && (left || right) {
+        let entity = nodes[current.unwrap_or_default()];
+        let delta = if right { 1 } else { -1 };
+        adjusted.send(Adjusted { entity, delta });
+        return;
+    }
+
+    let step: isize = if keys.just_pressed(KeyCode::Down) || right {
+        1
+    } else if keys.just_pressed(KeyCode::Up) || left {
+        -1
+    } else if keys.just_pressed(KeyCode::Tab) {
+        if shift {
+            -1
+        } else {
+            1
+        }
+    } else {
+        0
+    };
+
+    if step != 0 {
+        let next = match current {
+            Some(i) => (i as isize + step).rem_euclid(nodes.len() as isize) as usize,
+            None => 0,
+        };
+        focus.entity = Some(nodes[next]);
+    } else if let Some(i) = current {
+        // space is a regular character for text fields
+        let space = keys.just_pressed(KeyCode::Space) && !text_fields.contains(nodes[i]);
+        if space || keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+            activated.send(Activated(nodes[i]));
+        }
+    }
+}
+
+fn edit_text_fields(
+    focus: Res<Focus>,
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut fields: Query<&mut TextField>,
+) {
+    let Some(mut field) = focus.entity.and_then(|entity| fields.get_mut(entity).ok()) else {
+        characters.clear();
+        return;
+    };
+    if keys.just_pressed(KeyCode::Back) {
+        field.value.pop();
+    }
+    for event in characters.iter() {
+        // backspace, enter and friends come as characters too
+        if !event.char.is_control() {
+            field.value.push(event.char);
+        }
+    }
+}
+
+fn draw_text_fields(
+    focus: Res<Focus>,
+    fields: Query<(Entity, Ref<TextField>, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (entity, field, children) in &fields {
+        if !focus.is_changed() && !field.is_changed() {
+            continue;
+        }
+        let Some(mut text) = children.iter().find_map(|child| texts.get_mut(*child).ok()) else {
+            continue;
+        };
+        // poor man's caret
+        let caret = if focus.entity == Some(entity) {
+            "_"
+        } else {
+            ""
+        };
+        text.sections[0].value = format!("{}{caret}", field.value);
+    }
+}
+
+fn draw_focus_indicator(
+    focus: Res<Focus>,
+    mut nodes: Query<(Entity, &mut BorderColor), With<Focusable>>,
+    added: Query<(), Added<Focusable>>,
+) {
+    if !focus.is_changed() && added.is_empty() {
+        return;
+    }
+    for (entity, mut border) in &mut nodes {
+        border.0 = if Some(entity) == focus.entity {
+            FOCUS_COLOR
+        } else {
+            Color::NONE
+        };
+    }
+}
@@ -0,0 +1,209 @@
+//! Rules of hex minesweeper and common hex-specific patterns, each drawn as a small example board.
+//! Patterns are written in the board text format and numbers are counted from their mines,
+//! so an example can't show a wrong number.
+
+use bevy::prelude::*;
+use hexx::HexLayout;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Cancelled},
+    spawn_button, spawn_dialog, text_style,
+};
+use crate::{
+    any_events,
+    board::{Board, Rules},
+    board_text::decode_board_text,
+    game::Game,
+    AppState,
+};
+
+const HEX_SIZE: f32 = 12.0;
+const REVEALED_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const MINE_COLOR: Color = Color::rgb(0.85, 0.1, 0.1);
+const SAFE_COLOR: Color = Color::rgb(0.3, 0.7, 0.35);
+const NUMBER_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
+
+const RULES: &str = "Every hex touches 6 others, so numbers go from 0 to 6 instead of 0 to 8.\n\
+Hexes in the next row are shifted by half a hex: a hex touches 2 hexes above it and 2 below it.\n\
+Left click reveals a hex, right click flags it, and clicking a number with enough flags around reveals the rest.";
+
+/// Example board in the board text format with a caption. Covered hexes are drawn red for mines and green for safe ones.
+struct Pattern {
+    name: &'static str,
+    board: &'static str,
+    caption: &'static str,
+}
+
+const PATTERNS: [Pattern; 4] = [
+    Pattern {
+        name: "Neighbors",
+        board: " * .\n. o *\n . .",
+        caption: "A number counts mines\namong the 6 touching hexes",
+    },
+    Pattern {
+        name: "1-1 at a wall",
+        board: " * .\no o",
+        caption: "A 1 at the wall touches a\nsingle hex, so it's a mine",
+    },
+    Pattern {
+        name: "1-2-1",
+        board: " . * * .\n  o o o",
+        caption: "A row of 3 touches 4 hexes,\nthe middle pair are mines",
+    },
+    Pattern {
+        name: "1-2-2-1",
+        board: " . * * * .\n  o o o o",
+        caption: "The 2s share a mine,\nso the ends are safe",
+    },
+];
+
+pub struct HelpScreenPlugin;
+
+impl Plugin for HelpScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Help), spawn_help_screen)
+            .add_systems(OnExit(AppState::Help), despawn_screen::<HelpScreen>)
+            .add_systems(Update, handle_help_screen.run_if(in_state(AppState::Help)));
+    }
+}
+
+#[derive(Component)]
+struct HelpScreen;
+
+#[derive(Component)]
+struct BackButton;
+
+fn spawn_help_screen(mut commands: Commands) {
+    spawn_dialog(&mut commands, HelpScreen, "Rules and patterns", |panel| {
+        panel.spawn(TextBundle::from_section(RULES, text_style(18.0)));
+        panel.spawn(TextBundle::from_section("Patterns:", text_style(20.0)));
+        panel
+            .spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(24.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|row| {
+                for pattern in &PATTERNS {
+                    spawn_pattern(row, pattern);
+                }
+            });
+        spawn_button(panel, "Back", 0, BackButton);
+    });
+}
+
+fn spawn_pattern(parent: &mut ChildBuilder, pattern: &Pattern) {
+    let Ok(layout) = decode_board_text(pattern.board) else {
+        bevy::log::error!("Broken help pattern {}", pattern.name);
+        return;
+    };
+    let board = Board::from_layout(&layout, Rules::CLASSIC);
+    let grid = HexLayout {
+        hex_size: Vec2::splat(HEX_SIZE),
+        ..crate::GRID_LAYOUT
+    };
+    let dot_size = HEX_SIZE * 1.6;
+    let positions: Vec<_> = layout
+        .cells
+        .iter()
+        .map(|hex| (*hex, grid.hex_to_world_pos(*hex)))
+        .collect();
+    let min = positions
+        .iter()
+        .fold(Vec2::splat(f32::MAX), |min, (_, pos)| min.min(*pos));
+    let max = positions
+        .iter()
+        .fold(Vec2::splat(f32::MIN), |max, (_, pos)| max.max(*pos));
+    let size = max - min + dot_size;
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|column| {
+            column.spawn(TextBundle::from_section(pattern.name, text_style(16.0)));
+            column
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(size.x),
+                        height: Val::Px(size.y),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|map| {
+                    for (hex, pos) in positions {
+                        let color = if !board.covered.contains(&hex) {
+                            REVEALED_COLOR
+                        } else if board.mines.contains(&hex) {
+                            MINE_COLOR
+                        } else {
+                            SAFE_COLOR
+                        };
+                        map.spawn(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                // UI y axis points down, unlike the world one
+                                left: Val::Px(pos.x - min.x),
+                                top: Val::Px(max.y - pos.y),
+                                width: Val::Px(dot_size),
+                                height: Val::Px(dot_size),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: color.into(),
+                            ..default()
+                        })
+                        .with_children(|cell| {
+                            if board.covered.contains(&hex) {
+                                return;
+                            }
+                            if let Some(number) = board.numbers.get(&hex) {
+                                cell.spawn(TextBundle::from_section(
+                                    number.to_string(),
+                                    TextStyle {
+                                        font_size: 16.0,
+                                        color: NUMBER_COLOR,
+                                        ..default()
+                                    },
+                                ));
+                            }
+                        });
+                    }
+                });
+            column.spawn(
+                TextBundle::from_section(pattern.caption, text_style(14.0))
+                    .with_text_alignment(TextAlignment::Center),
+            );
+        });
+}
+
+fn handle_help_screen(
+    mut activated: EventReader<Activated>,
+    mut cancelled: EventReader<Cancelled>,
+    buttons: Query<(), With<BackButton>>,
+    game: Res<Game>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let back = any_events(&mut cancelled)
+        || activated
+            .iter()
+            .any(|Activated(entity)| buttons.contains(*entity));
+    if back {
+        next_state.set(if game.outcome.is_some() {
+            AppState::GameOver
+        } else {
+            AppState::Paused
+        });
+    }
+}
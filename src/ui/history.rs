@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled},
+    set_label, spawn_button, spawn_dialog, spawn_option, text_style,
+};
+use crate::{
+    any_events, clock,
+    game::{Game, NewCustomGame, NewGame, Outcome},
+    history::History,
+    playback::Playback,
+    AppState,
+};
+
+const PAGE_SIZE: usize = 8;
+
+pub struct HistoryScreenPlugin;
+
+impl Plugin for HistoryScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SelectedPage(0))
+            .add_systems(OnEnter(AppState::History), spawn_history_screen)
+            .add_systems(OnExit(AppState::History), despawn_screen::<HistoryScreen>)
+            .add_systems(
+                Update,
+                (handle_history_screen, update_entries)
+                    .chain()
+                    .run_if(in_state(AppState::History)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct SelectedPage(usize);
+
+#[derive(Component)]
+struct HistoryScreen;
+
+#[derive(Component)]
+struct EntriesList;
+
+#[derive(Component, Clone, Copy)]
+enum HistoryAction {
+    Page,
+    /// Play the board of the entry with the given index
+    Play(usize),
+    /// Watch the replay of the entry with the given index
+    Watch(usize),
+    Back,
+}
+
+fn spawn_history_screen(mut commands: Commands, mut page: ResMut<SelectedPage>) {
+    // the list changes after every game, so always start from the most recent games
+    page.0 = 0;
+    spawn_dialog(&mut commands, HistoryScreen, "History", |panel| {
+        spawn_option(panel, 0, HistoryAction::Page);
+        panel.spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+            EntriesList,
+        ));
+        // after all entry buttons
+        spawn_button(panel, "Back", 1000, HistoryAction::Back);
+    });
+}
+
+fn pages_count(history: &History) -> usize {
+    history.entries.len().div_ceil(PAGE_SIZE).max(1)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_history_screen(
+    mut commands: Commands,
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&HistoryAction>,
+    history: Res<History>,
+    game: Res<Game>,
+    mut page: ResMut<SelectedPage>,
+    mut new_game: EventWriter<NewGame>,
+    mut custom_game: EventWriter<NewCustomGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let mut back = any_events(&mut cancelled);
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        match actions.get(entity) {
+            Ok(HistoryAction::Page) => {
+                let pages = pages_count(&history) as i32;
+                page.0 = (page.0 as i32 + delta).rem_euclid(pages) as usize;
+            }
+            Ok(HistoryAction::Play(index)) => {
+                if let Some(entry) = history.entries.get(*index) {
+                    entry.start(&mut new_game, &mut custom_game);
+                }
+            }
+            Ok(HistoryAction::Watch(index)) => {
+                if let Some(entry) = history.entries.get(*index) {
+                    Playback::start(&mut commands, &mut new_game, &mut custom_game, entry);
+                }
+            }
+            Ok(HistoryAction::Back) => back = true,
+            Err(_) => {}
+        }
+    }
+
+    if back {
+        next_state.set(if game.outcome.is_some() {
+            AppState::GameOver
+        } else {
+            AppState::Paused
+        });
+    }
+}
+
+fn update_entries(
+    mut commands: Commands,
+    history: Res<History>,
+    page: Res<SelectedPage>,
+    lists: Query<Entity, With<EntriesList>>,
+    options: Query<(&HistoryAction, &Children)>,
+    added: Query<(), Added<EntriesList>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !page.is_changed() && !history.is_changed() && added.is_empty() {
+        return;
+    }
+
+    for (action, children) in &options {
+        if let HistoryAction::Page = action {
+            let label = format!("< Page {}/{} >", page.0 + 1, pages_count(&history));
+            set_label(children, &mut texts, label);
+        }
+    }
+
+    let first = page.0 * PAGE_SIZE;
+    for list in &lists {
+        commands
+            .entity(list)
+            .despawn_descendants()
+            .with_children(|list| {
+                if history.entries.is_empty() {
+                    list.spawn(TextBundle::from_section("No games yet", text_style(18.0)));
+                }
+                for (index, entry) in history
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .skip(first)
+                    .take(PAGE_SIZE)
+                {
+                    let result = match entry.outcome {
+                        Outcome::Won => "won",
+                        Outcome::Lost => "lost",
+                    };
+                    let mut summary = format!(
+                        "{}  {}  {result} in {:.1}s",
+                        clock::format_day((entry.finished_at / clock::MILLIS_PER_DAY) as u32),
+                        entry.config,
+                        entry.time_ms as f32 / 1000.0
+                    );
+                    if let Some(script) = &entry.script {
+                        summary += &format!("  ({})", script.name);
+                    }
+                    list.spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(8.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(
+                            TextBundle::from_section(summary, text_style(16.0)).with_style(Style {
+                                flex_grow: 1.0,
+                                ..default()
+                            }),
+                        );
+                        let order = 1 + 2 * index as u32;
+                        spawn_button(row, "Play", order, HistoryAction::Play(index));
+                        spawn_button(row, "Watch", order + 1, HistoryAction::Watch(index));
+                    });
+                }
+            });
+    }
+}
@@ -0,0 +1,416 @@
+//! Always visible overlay with the game stats. The left side is about the local game,
+//! the right side is reserved for other players in multiplayer modes.
+
+use bevy::prelude::*;
+
+use super::text_style;
+use crate::{
+    any_events,
+    blind::Memory,
+    board::Board,
+    chat_vote::{ChatStatus, ChatVote},
+    endless::EndlessRun,
+    game::{BoardProgress, Game, NewCustomGame, NewGame, Outcome},
+    ghost::Ghost,
+    hotseat::HotSeat,
+    mini::MiniMode,
+    powerups::{Inventory, PowerUp},
+    score::{Score, COMBO_WINDOW},
+    settings::Settings,
+    sketch::Sketching,
+    speedrun::{Splits, SPLITS},
+    streamer,
+    tutorial::Tutorial,
+    versus::VersusSession,
+};
+
+const AHEAD_COLOR: Color = Color::rgb(0.4, 0.85, 0.4);
+const BEHIND_COLOR: Color = Color::rgb(0.95, 0.35, 0.3);
+const COMBO_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+const PROGRESS_WIDTH: f32 = 240.0;
+const PROGRESS_BACKGROUND: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+const PROGRESS_COLOR: Color = Color::rgb(0.35, 0.6, 0.95);
+/// The bar turns green when only the last few safe hexes are left
+const NEAR_COMPLETION: f32 = 0.9;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hud).add_systems(
+            Update,
+            (
+                update_local_stats,
+                update_combo,
+                update_progress_bar,
+                update_speedrun_timer,
+                update_opponents,
+                scale_text,
+                hide_details,
+            ),
+        );
+    }
+}
+
+#[derive(Component)]
+struct LocalStats;
+
+#[derive(Component)]
+struct ComboText;
+
+/// Filled part of the board completion bar
+#[derive(Component)]
+struct ProgressFill;
+
+#[derive(Component)]
+struct SpeedrunTimer;
+
+#[derive(Component)]
+struct OpponentsStats;
+
+/// Part of the HUD hidden in the mini mode
+#[derive(Component)]
+struct Detail;
+
+/// Font size of the text before the streamer mode enlarges it
+#[derive(Component)]
+struct BaseSize(f32);
+
+fn spawn_hud(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::SpaceBetween,
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|hud| {
+            hud.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|local| {
+                local.spawn((
+                    TextBundle::from_section("", text_style(20.0)),
+                    BaseSize(20.0),
+                    LocalStats,
+                ));
+                local
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(PROGRESS_WIDTH),
+                                height: Val::Px(4.0),
+                                margin: UiRect::vertical(Val::Px(4.0)),
+                                ..default()
+                            },
+                            background_color: PROGRESS_BACKGROUND.into(),
+                            ..default()
+                        },
+                        Detail,
+                    ))
+                    .with_children(|bar| {
+                        bar.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(0.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                background_color: PROGRESS_COLOR.into(),
+                                ..default()
+                            },
+                            ProgressFill,
+                        ));
+                    });
+                local.spawn((
+                    TextBundle::from_section("", text_style(28.0)),
+                    BaseSize(28.0),
+                    ComboText,
+                    Detail,
+                ));
+                local.spawn((TextBundle::default(), SpeedrunTimer, Detail));
+            });
+            hud.spawn((
+                TextBundle::from_section("", text_style(20.0))
+                    .with_text_alignment(TextAlignment::Right),
+                BaseSize(20.0),
+                OpponentsStats,
+                Detail,
+            ));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_local_stats(
+    board: Res<Board>,
+    game: Res<Game>,
+    score: Res<Score>,
+    hot_seat: Option<Res<HotSeat>>,
+    endless: Option<Res<EndlessRun>>,
+    ghost: Option<Res<Ghost>>,
+    chat_vote: Option<Res<ChatVote>>,
+    inventory: Res<Inventory>,
+    memory: Res<Memory>,
+    settings: Res<Settings>,
+    tutorial: Option<Res<Tutorial>>,
+    sketching: Option<Res<Sketching>>,
+    mini: Option<Res<MiniMode>>,
+    mut texts: Query<&mut Text, With<LocalStats>>,
+) {
+    let mut label = if board.config.rules.zen {
+        format!("Zen mode   Mines {}", board.mines_left())
+    } else {
+        format!(
+            "Time {:.1}s   Mines {}",
+            game.elapsed.as_secs_f32(),
+            board.mines_left()
+        )
+    };
+    let headline = label.clone();
+    // gravity boards refill, so there is no end to count down to
+    if !board.config.rules.gravity {
+        label += &format!("   Safe {}", board.remaining_safe());
+    }
+    label += &format!("   Score {}", score.points);
+    if board.config.rules.gravity {
+        label += &format!("   Rows {}", board.collapsed * 2);
+    }
+    if board.config.rules.sandbox {
+        label += "   Sandbox";
+    }
+    if board.config.rules.lives > 1 {
+        label += &format!("   Lives {}", board.lives);
+    }
+    let assists: Vec<_> = [
+        (settings.auto_open, "auto-open"),
+        (settings.auto_flag, "auto-flag"),
+    ]
+    .into_iter()
+    .filter_map(|(on, name)| on.then_some(name))
+    .collect();
+    if !assists.is_empty() {
+        label += &format!("   Assist: {}", assists.join(", "));
+    }
+    if board.config.rules.blind {
+        label += &format!("   Pings {}", memory.pings_left);
+    }
+    if board.config.rules.powerups {
+        let power_ups: Vec<_> = PowerUp::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, power_up)| {
+                let count = inventory.count(*power_up);
+                format!("[{}] {} x{count}", i + 1, power_up.name())
+            })
+            .collect();
+        label += &format!("\n{}", power_ups.join("   "));
+        if inventory.targeting {
+            label += "\nClick a hex to scan it with the radar";
+        }
+    }
+    if let Some(endless) = endless {
+        label += &format!("\nEndless run: board {}", endless.cleared + 1);
+    }
+    if let Some(ghost) = ghost {
+        label += &format!(
+            "\nGhost of the best run ({:.1}s): {:.0}% revealed",
+            ghost.time.as_secs_f32(),
+            ghost.progress() * 100.0
+        );
+    }
+    if let Some(vote) = chat_vote {
+        label += &match &vote.status {
+            ChatStatus::Connecting => format!("\nChat vote: joining #{}", vote.channel),
+            ChatStatus::Connected => format!(
+                "\nChat vote in #{}: next reveal in {}s, {} vote(s)",
+                vote.channel,
+                vote.left.as_secs() + 1,
+                vote.votes_count()
+            ),
+            ChatStatus::Disconnected(reason) => format!("\nChat vote: disconnected, {reason}"),
+        };
+    }
+    if let Some(hot_seat) = hot_seat {
+        label += &format!(
+            "\nPlayer {}'s turn   Points {} : {}",
+            hot_seat.current + 1,
+            hot_seat.points[0],
+            hot_seat.points[1]
+        );
+    }
+    if let Some(tutorial) = tutorial {
+        label += &format!("\n{}", tutorial.step.instruction());
+    }
+    if sketching.is_some() {
+        label += "\nSketching: drag to draw, [C] to clear, [D] to go back to playing";
+    }
+    // the mini window has room for the time and the mines only
+    if mini.is_some() {
+        label = headline;
+    }
+    for mut text in &mut texts {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+/// Combo multiplier that fades out as the time to continue the combo runs out
+fn update_combo(game: Res<Game>, score: Res<Score>, mut texts: Query<&mut Text, With<ComboText>>) {
+    let combo_left = score
+        .combo_left(game.elapsed)
+        .filter(|_| game.outcome.is_none());
+    let label = match combo_left {
+        Some(_) => format!("Combo x{}!", score.combo_multiplier()),
+        None => String::new(),
+    };
+    let alpha = combo_left.map_or(0.0, |left| left.as_secs_f32() / COMBO_WINDOW.as_secs_f32());
+    for mut text in &mut texts {
+        let section = &mut text.sections[0];
+        if section.value != label {
+            section.value = label.clone();
+        }
+        section.style.color = COMBO_COLOR.with_a(0.3 + 0.7 * alpha);
+    }
+}
+
+/// Fraction of revealed safe hexes, from the same events as the speedrun splits
+fn update_progress_bar(
+    mut progress: EventReader<BoardProgress>,
+    mut new_games: EventReader<NewGame>,
+    mut custom_games: EventReader<NewCustomGame>,
+    mut fills: Query<(&mut Style, &mut BackgroundColor), With<ProgressFill>>,
+) {
+    let revealed = if any_events(&mut new_games) | any_events(&mut custom_games) {
+        0.0
+    } else if let Some(last) = progress.iter().last() {
+        last.revealed
+    } else {
+        return;
+    };
+    for (mut style, mut color) in &mut fills {
+        style.width = Val::Percent(revealed * 100.0);
+        *color = match revealed >= NEAR_COMPLETION {
+            true => AHEAD_COLOR,
+            false => PROGRESS_COLOR,
+        }
+        .into();
+    }
+}
+
+/// Millisecond timer with splits and deltas to the personal best, green when ahead and red when behind
+fn update_speedrun_timer(
+    settings: Res<Settings>,
+    board: Res<Board>,
+    game: Res<Game>,
+    splits: Res<Splits>,
+    mut texts: Query<&mut Text, With<SpeedrunTimer>>,
+) {
+    if !(settings.is_changed() || game.is_changed() || splits.is_changed()) {
+        return;
+    }
+    let scale = streamer::hud_scale(&settings);
+    let mut sections = Vec::new();
+    if settings.speedrun_timer && !board.config.rules.zen {
+        let time_ms = game.elapsed.as_millis();
+        sections.push(TextSection::new(
+            format!("{}.{:03}s", time_ms / 1000, time_ms % 1000),
+            text_style(28.0 * scale),
+        ));
+        for (split, fraction) in SPLITS.iter().enumerate() {
+            let label = match splits.times_ms.get(split) {
+                Some(time_ms) => format!(
+                    "\n{:.0}%  {:.3}s",
+                    fraction * 100.0,
+                    *time_ms as f32 / 1000.0
+                ),
+                None => format!("\n{:.0}%  -", fraction * 100.0),
+            };
+            sections.push(TextSection::new(label, text_style(18.0 * scale)));
+            if let Some(delta_ms) = splits.delta_ms(split).filter(|_| splits.ranked) {
+                let mut style = text_style(18.0 * scale);
+                style.color = if delta_ms < 0 {
+                    AHEAD_COLOR
+                } else {
+                    BEHIND_COLOR
+                };
+                sections.push(TextSection::new(
+                    format!("  {:+.3}", delta_ms as f32 / 1000.0),
+                    style,
+                ));
+            }
+        }
+    }
+    for mut text in &mut texts {
+        text.sections = sections.clone();
+    }
+}
+
+fn update_opponents(
+    session: Option<Res<VersusSession>>,
+    mut texts: Query<&mut Text, With<OpponentsStats>>,
+) {
+    let label = match session {
+        Some(session) if session.is_racing() => {
+            let mut opponents: Vec<_> = session.opponents.iter().collect();
+            opponents.sort_by_key(|(player, _)| **player);
+            opponents
+                .into_iter()
+                .map(|(player, progress)| {
+                    let state = match progress.outcome {
+                        Some(Outcome::Won) => "finished!",
+                        Some(Outcome::Lost) => "exploded",
+                        None => "",
+                    };
+                    format!(
+                        "Player {}: {:.0}% {:.1}s {state}",
+                        player + 1,
+                        progress.revealed * 100.0,
+                        progress.elapsed_ms as f32 / 1000.0
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        _ => String::new(),
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+/// Enlarges the HUD in the streamer mode, the speedrun timer is sized as it's rebuilt
+fn scale_text(settings: Res<Settings>, mut texts: Query<(&mut Text, &BaseSize)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let scale = streamer::hud_scale(&settings);
+    for (mut text, BaseSize(size)) in &mut texts {
+        for section in &mut text.sections {
+            section.style.font_size = size * scale;
+        }
+    }
+}
+
+/// Leaves only the time and the mines left in the mini mode
+fn hide_details(mini: Option<Res<MiniMode>>, mut details: Query<&mut Style, With<Detail>>) {
+    let display = match mini {
+        Some(_) => Display::None,
+        None => Display::Flex,
+    };
+    for mut style in &mut details {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
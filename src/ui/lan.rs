@@ -0,0 +1,94 @@
+//! List of games found on the local network, shared by the multiplayer screens
+
+use std::net::SocketAddr;
+
+use bevy::prelude::*;
+
+use super::{spawn_button, text_style};
+use crate::{lan::LanGames, net::discovery::GameMode};
+
+pub struct LanListPlugin;
+
+impl Plugin for LanListPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_lan_lists);
+    }
+}
+
+/// Container of the buttons to join discovered games of the mode
+#[derive(Component)]
+struct LanGamesList {
+    mode: GameMode,
+    /// Spawned entries, to rebuild the list only when it changes. Entries without an address are plain text.
+    shown: Vec<(Option<SocketAddr>, String)>,
+}
+
+/// Button to join the game at the address
+#[derive(Component)]
+pub struct JoinLan(pub SocketAddr);
+
+/// Focus order of the list buttons, they go after all other screen buttons
+const FIRST_ORDER: u32 = 100;
+
+pub fn spawn_lan_list(panel: &mut ChildBuilder, mode: GameMode) {
+    panel.spawn(TextBundle::from_section(
+        "Games on your network:",
+        text_style(18.0),
+    ));
+    panel.spawn((
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            ..default()
+        },
+        LanGamesList {
+            mode,
+            shown: Vec::new(),
+        },
+    ));
+}
+
+fn update_lan_lists(
+    mut commands: Commands,
+    lan_games: Res<LanGames>,
+    mut lists: Query<(Entity, &mut LanGamesList)>,
+) {
+    for (entity, mut list) in &mut lists {
+        let mut entries: Vec<_> = lan_games
+            .games(list.mode)
+            .into_iter()
+            .map(|(address, announcement)| {
+                let label = format!(
+                    "{} ({} player(s)) at {address}",
+                    announcement.host_name, announcement.players
+                );
+                (Some(address), label)
+            })
+            .collect();
+        if let Some(err) = lan_games.error() {
+            entries.push((None, format!("Search failed: {err}")));
+        }
+        if entries == list.shown {
+            continue;
+        }
+
+        let mut list_entity = commands.entity(entity);
+        list_entity.despawn_descendants();
+        list_entity.with_children(|parent| {
+            for (i, (address, label)) in entries.iter().enumerate() {
+                match address {
+                    Some(address) => {
+                        spawn_button(parent, label, FIRST_ORDER + i as u32, JoinLan(*address));
+                    }
+                    None => {
+                        parent.spawn(TextBundle::from_section(label.clone(), text_style(16.0)));
+                    }
+                }
+            }
+        });
+        list.shown = entries;
+    }
+}
@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled},
+    set_label, spawn_button, spawn_dialog, spawn_option, text_style,
+};
+use crate::{
+    any_events,
+    assist::AssistPreset,
+    game::Game,
+    leaderboard::{Leaderboard, Period, Status},
+    settings::Settings,
+    AppState,
+};
+
+/// How many top entries fit on the screen
+const SHOWN_ENTRIES: usize = 10;
+
+pub struct LeaderboardScreenPlugin;
+
+impl Plugin for LeaderboardScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SelectedPeriod(Period::Global))
+            .init_resource::<SelectedAssists>()
+            .add_systems(
+                OnEnter(AppState::Leaderboard),
+                (spawn_leaderboard, fetch_rankings),
+            )
+            .add_systems(
+                OnExit(AppState::Leaderboard),
+                despawn_screen::<LeaderboardScreen>,
+            )
+            .add_systems(
+                Update,
+                (handle_leaderboard, update_labels, update_rankings)
+                    .chain()
+                    .run_if(in_state(AppState::Leaderboard)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct SelectedPeriod(Period);
+
+/// Results of only one preset are ranked, `None` ranks all of them together
+#[derive(Resource, Default)]
+struct SelectedAssists(Option<AssistPreset>);
+
+#[derive(Component)]
+struct LeaderboardScreen;
+
+#[derive(Component)]
+struct RankingsList;
+
+#[derive(Component, Clone, Copy)]
+enum LeaderboardAction {
+    Period,
+    Assists,
+    SubmitWins,
+    Back,
+}
+
+fn spawn_leaderboard(mut commands: Commands) {
+    spawn_dialog(&mut commands, LeaderboardScreen, "Leaderboard", |panel| {
+        spawn_option(panel, 0, LeaderboardAction::Period);
+        panel.spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    min_height: Val::Px(SHOWN_ENTRIES as f32 * 22.0),
+                    ..default()
+                },
+                ..default()
+            },
+            RankingsList,
+        ));
+        spawn_option(panel, 1, LeaderboardAction::Assists);
+        spawn_option(panel, 2, LeaderboardAction::SubmitWins);
+        spawn_button(panel, "Back", 3, LeaderboardAction::Back);
+    });
+}
+
+fn fetch_rankings(
+    settings: Res<Settings>,
+    period: Res<SelectedPeriod>,
+    assists: Res<SelectedAssists>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    leaderboard.fetch_rankings(&settings.leaderboard.endpoint, period.0, assists.0);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_leaderboard(
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&LeaderboardAction>,
+    game: Res<Game>,
+    mut period: ResMut<SelectedPeriod>,
+    mut assists: ResMut<SelectedAssists>,
+    mut settings: ResMut<Settings>,
+    mut leaderboard: ResMut<Leaderboard>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let mut back = any_events(&mut cancelled);
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        match actions.get(entity) {
+            // the period has just two values, so any adjustment toggles it
+            Ok(LeaderboardAction::Period) => {
+                period.0 = match period.0 {
+                    Period::Global => Period::Daily,
+                    Period::Daily => Period::Global,
+                };
+                leaderboard.fetch_rankings(&settings.leaderboard.endpoint, period.0, assists.0);
+            }
+            Ok(LeaderboardAction::Assists) => {
+                // all presets together are the extra last value
+                let count = AssistPreset::ALL.len() as i32 + 1;
+                let index = assists.0.map_or(count - 1, |preset| preset as i32);
+                let index = (index + delta).rem_euclid(count) as usize;
+                assists.0 = AssistPreset::ALL.get(index).copied();
+                leaderboard.fetch_rankings(&settings.leaderboard.endpoint, period.0, assists.0);
+            }
+            Ok(LeaderboardAction::SubmitWins) => {
+                settings.leaderboard.enabled = !settings.leaderboard.enabled;
+            }
+            Ok(LeaderboardAction::Back) => back = true,
+            Err(_) => {}
+        }
+    }
+
+    if back {
+        next_state.set(if game.outcome.is_some() {
+            AppState::GameOver
+        } else {
+            AppState::Paused
+        });
+    }
+}
+
+fn update_labels(
+    period: Res<SelectedPeriod>,
+    assists: Res<SelectedAssists>,
+    settings: Res<Settings>,
+    options: Query<(&LeaderboardAction, &Children)>,
+    added: Query<(), Added<LeaderboardScreen>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !period.is_changed() && !assists.is_changed() && !settings.is_changed() && added.is_empty() {
+        return;
+    }
+    for (action, children) in &options {
+        let label = match action {
+            LeaderboardAction::Period => match period.0 {
+                Period::Global => "< All time >".to_string(),
+                Period::Daily => "< Today >".to_string(),
+            },
+            LeaderboardAction::Assists => match assists.0 {
+                Some(preset) => format!("< Assists: {} >", preset.name()),
+                None => "< Assists: any >".to_string(),
+            },
+            LeaderboardAction::SubmitWins if settings.leaderboard.enabled => {
+                "Submit my wins: On".to_string()
+            }
+            LeaderboardAction::SubmitWins => "Submit my wins: Off".to_string(),
+            LeaderboardAction::Back => continue,
+        };
+        set_label(children, &mut texts, label);
+    }
+}
+
+fn update_rankings(
+    mut commands: Commands,
+    leaderboard: Res<Leaderboard>,
+    lists: Query<Entity, With<RankingsList>>,
+    added: Query<(), Added<RankingsList>>,
+) {
+    if !leaderboard.is_changed() && added.is_empty() {
+        return;
+    }
+
+    let rows: Vec<String> = match &leaderboard.rankings {
+        Status::Idle => Vec::new(),
+        Status::Pending => vec!["Loading...".to_string()],
+        Status::Failed(err) => vec![format!("Failed to load rankings: {err}")],
+        Status::Done(rankings) if rankings.entries.is_empty() => vec!["No results yet".to_string()],
+        Status::Done(rankings) => rankings
+            .entries
+            .iter()
+            .take(SHOWN_ENTRIES)
+            .map(|entry| {
+                format!(
+                    "{}. {}  {:.2}s  {} pts  ({}, {})",
+                    entry.rank,
+                    entry.player,
+                    entry.time_ms as f32 / 1000.0,
+                    entry.score,
+                    entry.config,
+                    entry.assists.name()
+                )
+            })
+            .collect(),
+    };
+
+    for list in &lists {
+        commands
+            .entity(list)
+            .despawn_descendants()
+            .with_children(|list| {
+                for row in &rows {
+                    list.spawn(TextBundle::from_section(row.clone(), text_style(18.0)));
+                }
+            });
+    }
+}
@@ -0,0 +1,315 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled, TextField},
+    set_label, spawn_button, spawn_dialog, spawn_option, spawn_text_field, text_style,
+};
+use crate::{
+    any_events,
+    board::Board,
+    coop::PLAYER_COLORS,
+    lobby::{Lobby, LobbyMessage, LobbyStatus, Role, MIN_PLAYERS},
+    net::discovery::GameMode,
+    settings::Settings,
+    AppState,
+};
+
+/// Same order as [`PLAYER_COLORS`]
+const COLOR_NAMES: [&str; 6] = ["Red", "Blue", "Green", "Yellow", "Purple", "Cyan"];
+const RADIUS_STEP: u32 = 2;
+const MIN_RADIUS: u32 = 4;
+const MAX_RADIUS: u32 = 24;
+/// In permille, like in the new game screen
+const DENSITY_STEP: u32 = 20;
+const MIN_DENSITY: u32 = 80;
+const MAX_DENSITY: u32 = 300;
+
+pub struct LobbyScreenPlugin;
+
+impl Plugin for LobbyScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Lobby), spawn_lobby_screen)
+            .add_systems(OnExit(AppState::Lobby), despawn_screen::<LobbyScreen>)
+            .add_systems(
+                Update,
+                (
+                    handle_lobby_screen,
+                    update_status,
+                    update_players,
+                    update_labels,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Lobby)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct LobbyScreen;
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct PlayersText;
+
+#[derive(Component)]
+struct CodeField;
+
+#[derive(Component, Clone, Copy)]
+enum LobbyAction {
+    Create,
+    Join,
+    Color,
+    Mode,
+    Radius,
+    Density,
+    Ready,
+    Leave,
+    Back,
+}
+
+fn spawn_lobby_screen(mut commands: Commands) {
+    spawn_dialog(&mut commands, LobbyScreen, "Lobby", |panel| {
+        panel.spawn((TextBundle::from_section("", text_style(18.0)), StatusText));
+        spawn_button(panel, "Create a room", 0, LobbyAction::Create);
+        panel.spawn(TextBundle::from_section("Room code:", text_style(18.0)));
+        spawn_text_field(panel, "", 1, CodeField);
+        spawn_button(panel, "Join", 2, LobbyAction::Join);
+        panel.spawn((TextBundle::default(), PlayersText));
+        spawn_option(panel, 3, LobbyAction::Color);
+        spawn_option(panel, 4, LobbyAction::Mode);
+        spawn_option(panel, 5, LobbyAction::Radius);
+        spawn_option(panel, 6, LobbyAction::Density);
+        spawn_button(panel, "Ready", 7, LobbyAction::Ready);
+        spawn_button(panel, "Leave the room", 8, LobbyAction::Leave);
+        spawn_button(panel, "Back", 9, LobbyAction::Back);
+    });
+}
+
+fn step(value: u32, delta: i32, step: u32, min: u32, max: u32) -> u32 {
+    let value = value as i32 + delta * step as i32;
+    value.clamp(min as i32, max as i32) as u32
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_lobby_screen(
+    mut commands: Commands,
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&LobbyAction>,
+    code: Query<&TextField, With<CodeField>>,
+    mut lobby: Option<ResMut<Lobby>>,
+    mut settings: ResMut<Settings>,
+    board: Res<Board>,
+    mut status: ResMut<LobbyStatus>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    // activation cycles options forward, so they work with the mouse too
+    let pressed: Vec<_> = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        )
+        .collect();
+    for (entity, delta) in pressed {
+        // `Enter` in the code field joins right away
+        let action = match code.contains(entity) {
+            true => Some(LobbyAction::Join),
+            false => actions.get(entity).ok().copied(),
+        };
+        let joined = match action {
+            Some(LobbyAction::Create | LobbyAction::Join) if lobby.is_some() => {
+                status.0 = Some("Leave the room first".to_string());
+                continue;
+            }
+            Some(LobbyAction::Create) => Lobby::create(
+                &settings.player_name,
+                settings.player_color,
+                // the board played right now is a good first proposal
+                board.config,
+            ),
+            Some(LobbyAction::Join) => Lobby::join(
+                &code.single().value,
+                &settings.player_name,
+                settings.player_color,
+            ),
+            Some(LobbyAction::Color) => {
+                let colors = PLAYER_COLORS.len() as i32;
+                let color = (settings.player_color as i32 + delta).rem_euclid(colors) as u8;
+                settings.player_color = color;
+                if let Some(lobby) = lobby.as_deref_mut() {
+                    lobby.request(LobbyMessage::SetColor(color));
+                }
+                continue;
+            }
+            Some(action @ (LobbyAction::Mode | LobbyAction::Radius | LobbyAction::Density)) => {
+                if let Some(lobby) = lobby.as_deref_mut() {
+                    let (mut mode, mut config) = (lobby.room.mode, lobby.room.config);
+                    match action {
+                        LobbyAction::Mode => {
+                            mode = match mode {
+                                GameMode::Coop => GameMode::Versus,
+                                GameMode::Versus => GameMode::Coop,
+                            };
+                        }
+                        LobbyAction::Radius => {
+                            config.radius =
+                                step(config.radius, delta, RADIUS_STEP, MIN_RADIUS, MAX_RADIUS);
+                        }
+                        _ => {
+                            let permille = (config.mine_density * 1000.0).round() as u32;
+                            let permille =
+                                step(permille, delta, DENSITY_STEP, MIN_DENSITY, MAX_DENSITY);
+                            config.mine_density = permille as f32 / 1000.0;
+                        }
+                    }
+                    lobby.request(LobbyMessage::Propose { mode, config });
+                }
+                continue;
+            }
+            Some(LobbyAction::Ready) => {
+                if let Some(lobby) = lobby.as_deref_mut() {
+                    let ready = lobby.local_player().is_some_and(|player| player.ready);
+                    lobby.request(LobbyMessage::SetReady(!ready));
+                }
+                continue;
+            }
+            Some(LobbyAction::Leave) => {
+                commands.remove_resource::<Lobby>();
+                status.0 = None;
+                continue;
+            }
+            Some(LobbyAction::Back) => {
+                next_state.set(AppState::Paused);
+                continue;
+            }
+            None => continue,
+        };
+
+        match joined {
+            Ok(joined) => {
+                commands.insert_resource(joined);
+                status.0 = None;
+            }
+            Err(err) => status.0 = Some(format!("Failed: {err}")),
+        }
+    }
+}
+
+fn update_status(
+    lobby: Option<Res<Lobby>>,
+    status: Res<LobbyStatus>,
+    mut texts: Query<&mut Text, With<StatusText>>,
+) {
+    let label = match (&status.0, lobby) {
+        (Some(err), _) => err.clone(),
+        (None, None) => "Not in a room".to_string(),
+        (None, Some(lobby)) => {
+            let (start, end) = lobby.code.split_at(lobby.code.len() / 2);
+            let state = match lobby.countdown {
+                Some(countdown) => format!("starting in {:.0}", countdown.as_secs_f32().ceil()),
+                None if lobby.is_reconnecting() => "connection lost, reconnecting".to_string(),
+                None if lobby.room.players.len() < MIN_PLAYERS => "waiting for players".to_string(),
+                None => "waiting for everyone to get ready".to_string(),
+            };
+            match lobby.role {
+                Role::Host => format!("Room code {start}-{end}, {state}"),
+                Role::Client => format!("In room {start}-{end}, {state}"),
+            }
+        }
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+/// One line per player in their color
+fn update_players(lobby: Option<Res<Lobby>>, mut texts: Query<&mut Text, With<PlayersText>>) {
+    let sections: Vec<_> = lobby
+        .iter()
+        .flat_map(|lobby| &lobby.room.players)
+        .map(|player| {
+            let you = match Some(player.id) == lobby.as_ref().and_then(|lobby| lobby.player) {
+                true => " (you)",
+                false => "",
+            };
+            let ready = match player.ready {
+                true => "ready",
+                false => "not ready",
+            };
+            let color = PLAYER_COLORS[player.color as usize % PLAYER_COLORS.len()];
+            TextSection::new(
+                format!("{}{you} - {ready}\n", player.name),
+                TextStyle {
+                    color,
+                    ..text_style(20.0)
+                },
+            )
+        })
+        .collect();
+    for mut text in &mut texts {
+        let same = text.sections.len() == sections.len()
+            && text
+                .sections
+                .iter()
+                .zip(&sections)
+                .all(|(old, new)| old.value == new.value && old.style.color == new.style.color);
+        if !same {
+            text.sections = sections.clone();
+        }
+    }
+}
+
+fn update_labels(
+    lobby: Option<Res<Lobby>>,
+    settings: Res<Settings>,
+    options: Query<(&LobbyAction, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    let room = lobby.as_ref().map(|lobby| &lobby.room);
+    for (action, children) in &options {
+        let label = match (action, room) {
+            (LobbyAction::Color, _) => {
+                let color = settings.player_color as usize % COLOR_NAMES.len();
+                format!("< Color: {} >", COLOR_NAMES[color])
+            }
+            (LobbyAction::Mode, Some(room)) => match room.mode {
+                GameMode::Coop => "< Mode: co-op >".to_string(),
+                GameMode::Versus => "< Mode: versus race >".to_string(),
+            },
+            (LobbyAction::Radius, Some(room)) => format!("< Radius {} >", room.config.radius),
+            (LobbyAction::Density, Some(room)) => {
+                format!("< {:.0}% mines >", room.config.mine_density * 100.0)
+            }
+            (LobbyAction::Mode | LobbyAction::Radius | LobbyAction::Density, None) => {
+                "-".to_string()
+            }
+            (LobbyAction::Ready, _) => {
+                match lobby.as_ref().and_then(|lobby| lobby.local_player()) {
+                    Some(player) if player.ready => "Not ready".to_string(),
+                    _ => "Ready".to_string(),
+                }
+            }
+            _ => continue,
+        };
+        let current = children
+            .iter()
+            .find_map(|child| texts.get(*child).ok())
+            .map(|text| text.sections[0].value.clone());
+        if current.as_deref() != Some(label.as_str()) {
+            set_label(children, &mut texts, label);
+        }
+    }
+}
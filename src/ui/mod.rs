@@ -0,0 +1,216 @@
+//! Menus, dialogs and the shared building blocks for them
+
+mod chat_vote;
+mod coop;
+mod editor;
+pub mod focus;
+mod help;
+mod history;
+mod hud;
+mod lan;
+mod leaderboard;
+mod lobby;
+mod new_game;
+mod pause;
+mod play_code;
+mod profile;
+mod puzzles;
+mod results;
+mod settings;
+mod stats;
+mod theme_editor;
+mod tournament;
+mod versus;
+
+use bevy::prelude::*;
+
+use focus::{FocusScope, Focusable, TextField};
+
+pub struct ScreensPlugin;
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            focus::FocusPlugin,
+            pause::PausePlugin,
+            new_game::NewGameScreenPlugin,
+            play_code::PlayCodeScreenPlugin,
+            results::ResultsPlugin,
+            leaderboard::LeaderboardScreenPlugin,
+            stats::StatsScreenPlugin,
+            history::HistoryScreenPlugin,
+            profile::ProfileScreenPlugin,
+            editor::EditorScreenPlugin,
+            puzzles::PuzzlesScreenPlugin,
+            coop::CoopScreenPlugin,
+            versus::VersusScreenPlugin,
+            hud::HudPlugin,
+            lan::LanListPlugin,
+        ))
+        .add_plugins((
+            help::HelpScreenPlugin,
+            settings::SettingsScreenPlugin,
+            tournament::TournamentScreenPlugin,
+            chat_vote::ChatVoteScreenPlugin,
+            theme_editor::ThemeEditorPlugin,
+            lobby::LobbyScreenPlugin,
+        ));
+    }
+}
+
+const PANEL_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.9);
+const BUTTON_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+pub fn text_style(font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: TEXT_COLOR,
+        ..default()
+    }
+}
+
+/// Spawns a centered dialog with a title, tagged with `marker` so it can be cleaned up with [`despawn_screen`].
+/// The dialog is a [`FocusScope`], so it captures keyboard input while it's on top.
+pub fn spawn_dialog(
+    commands: &mut Commands,
+    marker: impl Component,
+    title: &str,
+    content: impl FnOnce(&mut ChildBuilder),
+) -> Entity {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                // dialogs are always above the board and HUD
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            FocusScope,
+            marker,
+        ))
+        .with_children(|root| {
+            root.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Stretch,
+                    padding: UiRect::all(Val::Px(16.0)),
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                background_color: PANEL_COLOR.into(),
+                ..default()
+            })
+            .with_children(|panel| {
+                panel.spawn(
+                    TextBundle::from_section(title, text_style(32.0)).with_style(Style {
+                        margin: UiRect::bottom(Val::Px(8.0)),
+                        align_self: AlignSelf::Center,
+                        ..default()
+                    }),
+                );
+                content(panel);
+            });
+        })
+        .id()
+}
+
+/// Spawns a focusable button that reports clicks and `Enter` as [`focus::Activated`].
+/// The `action` component is attached to the button to tell buttons apart.
+pub fn spawn_button(parent: &mut ChildBuilder, label: &str, order: u32, action: impl Component) {
+    spawn_focusable(
+        parent,
+        label,
+        Focusable {
+            order,
+            adjustable: false,
+        },
+        action,
+    );
+}
+
+/// Same as [`spawn_button`], but also reports `Left`/`Right` as [`focus::Adjusted`] to switch between values.
+/// The label is expected to be updated with [`set_label`] to show the current value.
+pub fn spawn_option(parent: &mut ChildBuilder, order: u32, action: impl Component) {
+    spawn_focusable(
+        parent,
+        "",
+        Focusable {
+            order,
+            adjustable: true,
+        },
+        action,
+    );
+}
+
+/// Spawns a focusable single-line text input, see [`TextField`]
+pub fn spawn_text_field(
+    parent: &mut ChildBuilder,
+    value: &str,
+    order: u32,
+    marker: impl Component,
+) {
+    let field = TextField {
+        value: value.to_string(),
+    };
+    spawn_focusable(
+        parent,
+        value,
+        Focusable {
+            order,
+            adjustable: false,
+        },
+        (field, marker),
+    );
+}
+
+/// Replaces text of a button spawned with [`spawn_button`] or [`spawn_option`]
+pub fn set_label(children: &Children, texts: &mut Query<&mut Text>, label: String) {
+    for child in children.iter() {
+        if let Ok(mut text) = texts.get_mut(*child) {
+            text.sections[0].value = label;
+            return;
+        }
+    }
+}
+
+fn spawn_focusable(
+    parent: &mut ChildBuilder,
+    label: &str,
+    focusable: Focusable,
+    action: impl Bundle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    min_width: Val::Px(200.0),
+                    padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+            focusable,
+            action,
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(label, text_style(24.0)));
+        });
+}
+
+/// Despawns every screen tagged with `T`, used as `OnExit` system for the screen states
+pub fn despawn_screen<T: Component>(mut commands: Commands, screens: Query<Entity, With<T>>) {
+    for entity in &screens {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,478 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled},
+    set_label, spawn_button, spawn_dialog, spawn_option, text_style,
+};
+use crate::{
+    any_events,
+    assist::AssistPreset,
+    board::{Board, BoardConfig},
+    dual::DualBoards,
+    game::{NewCustomGame, NewGame},
+    geometry::Geometry,
+    gradient::Gradient,
+    kernel::{self, Kernel},
+    mask::{self, Mask},
+    mods::Mods,
+    rng,
+    settings::Settings,
+    stats::Stats,
+    AppState,
+};
+
+const RADIUS_STEP: u32 = 2;
+const MIN_RADIUS: u32 = 4;
+const MAX_RADIUS: u32 = 24;
+/// Densities are adjusted in permille, so configs picked here compare equal to the same configs from board codes
+const DENSITY_STEP: u32 = 20;
+const MIN_DENSITY: u32 = 80;
+const MAX_DENSITY: u32 = 300;
+/// Treasures are in percent of empty hexes
+const TREASURES_STEP: u32 = 2;
+const MAX_TREASURES: u32 = 10;
+/// Mines move after this many reveals, 0 is off
+const MOVING_MINES_STEP: u32 = 5;
+const MAX_MOVING_MINES: u32 = 20;
+/// Darkness radius, 0 is off
+const MAX_DARKNESS: u32 = 5;
+/// Pings of blind numbers, which can be on without any
+const BLIND_PINGS_STEP: u32 = 5;
+const MAX_BLIND_PINGS: u32 = 20;
+/// Defuse countdown in seconds, 0 is off
+const DEFUSE_STEP: u32 = 10;
+const MAX_DEFUSE: u32 = 30;
+
+pub struct NewGameScreenPlugin;
+
+impl Plugin for NewGameScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::NewGameSetup),
+            (init_selected_config, spawn_new_game_screen),
+        )
+        .add_systems(
+            OnExit(AppState::NewGameSetup),
+            despawn_screen::<NewGameScreen>,
+        )
+        .add_systems(
+            Update,
+            (handle_new_game_screen, update_labels)
+                .chain()
+                .run_if(in_state(AppState::NewGameSetup)),
+        );
+    }
+}
+
+/// Config that will be used for the new game, starts as the config of the current board
+#[derive(Resource)]
+struct SelectedConfig(BoardConfig);
+
+/// Index of the rule script for the new game, `None` is classic rules
+#[derive(Resource)]
+struct SelectedMod(Option<usize>);
+
+/// Kernels to pick from, reloaded every time the screen opens so new files show up
+#[derive(Resource)]
+struct Kernels(Vec<(String, Kernel)>);
+
+/// Board shapes to pick from, reloaded the same way as [`Kernels`]
+#[derive(Resource)]
+struct Masks(Vec<Mask>);
+
+/// Index of the mask for the new game, `None` is a hexagon of the selected radius
+#[derive(Resource, Default)]
+struct SelectedShape(Option<usize>);
+
+/// Assists for the new game, applied to the settings when it starts
+#[derive(Resource)]
+struct SelectedAssists(AssistPreset);
+
+#[derive(Component)]
+struct NewGameScreen;
+
+#[derive(Component)]
+struct ConfigText;
+
+#[derive(Component)]
+struct BestText;
+
+#[derive(Component, Clone, Copy)]
+enum NewGameAction {
+    Radius,
+    Density,
+    Zen,
+    Sandbox,
+    Treasures,
+    MovingMines,
+    Darkness,
+    Gravity,
+    Defuse,
+    PowerUps,
+    Neighbors,
+    Colored,
+    Blind,
+    Directional,
+    Gradient,
+    Geometry,
+    Shape,
+    Assists,
+    Mod,
+    Start,
+    Back,
+}
+
+fn init_selected_config(
+    mut commands: Commands,
+    board: Res<Board>,
+    dual: Option<Res<DualBoards>>,
+    mods: Res<Mods>,
+    settings: Res<Settings>,
+) {
+    // the board of dual boards covers both of them
+    let config = dual.map_or(board.config, |dual| dual.config);
+    commands.insert_resource(SelectedConfig(config));
+    commands.insert_resource(SelectedMod(mods.active));
+    commands.insert_resource(Kernels(kernel::available()));
+    commands.insert_resource(Masks(mask::available()));
+    commands.init_resource::<SelectedShape>();
+    commands.insert_resource(SelectedAssists(AssistPreset::of(&settings)));
+}
+
+fn spawn_new_game_screen(mut commands: Commands, mods: Res<Mods>) {
+    spawn_dialog(&mut commands, NewGameScreen, "New game", |panel| {
+        spawn_option(panel, 0, NewGameAction::Radius);
+        spawn_option(panel, 1, NewGameAction::Density);
+        spawn_option(panel, 2, NewGameAction::Zen);
+        spawn_option(panel, 3, NewGameAction::Sandbox);
+        spawn_option(panel, 4, NewGameAction::Treasures);
+        spawn_option(panel, 5, NewGameAction::MovingMines);
+        spawn_option(panel, 6, NewGameAction::Darkness);
+        spawn_option(panel, 7, NewGameAction::Gravity);
+        spawn_option(panel, 8, NewGameAction::Defuse);
+        spawn_option(panel, 9, NewGameAction::PowerUps);
+        spawn_option(panel, 10, NewGameAction::Neighbors);
+        spawn_option(panel, 11, NewGameAction::Colored);
+        spawn_option(panel, 12, NewGameAction::Blind);
+        spawn_option(panel, 13, NewGameAction::Directional);
+        spawn_option(panel, 14, NewGameAction::Gradient);
+        spawn_option(panel, 15, NewGameAction::Geometry);
+        spawn_option(panel, 16, NewGameAction::Shape);
+        spawn_option(panel, 17, NewGameAction::Assists);
+        // rule scripts are for modders, so the option shows up only if there are any
+        if !mods.scripts.is_empty() {
+            spawn_option(panel, 18, NewGameAction::Mod);
+        }
+        panel.spawn((TextBundle::from_section("", text_style(16.0)), ConfigText));
+        panel.spawn((TextBundle::from_section("", text_style(18.0)), BestText));
+        spawn_button(panel, "Start", 19, NewGameAction::Start);
+        spawn_button(panel, "Back", 20, NewGameAction::Back);
+    });
+}
+
+fn step(value: u32, delta: i32, step: u32, min: u32, max: u32) -> u32 {
+    let value = value as i32 + delta * step as i32;
+    value.clamp(min as i32, max as i32) as u32
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_new_game_screen(
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&NewGameAction>,
+    mut selected: ResMut<SelectedConfig>,
+    mut selected_mod: ResMut<SelectedMod>,
+    mut mods: ResMut<Mods>,
+    kernels: Res<Kernels>,
+    masks: Res<Masks>,
+    mut selected_shape: ResMut<SelectedShape>,
+    mut selected_assists: ResMut<SelectedAssists>,
+    mut settings: ResMut<Settings>,
+    mut new_game: EventWriter<NewGame>,
+    mut custom_game: EventWriter<NewCustomGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    // activation cycles options forward, so they work with the mouse too
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        let config = &mut selected.0;
+        match actions.get(entity) {
+            Ok(NewGameAction::Radius) => {
+                config.radius = step(config.radius, delta, RADIUS_STEP, MIN_RADIUS, MAX_RADIUS);
+            }
+            Ok(NewGameAction::Density) => {
+                let permille = (config.mine_density * 1000.0).round() as u32;
+                let permille = step(permille, delta, DENSITY_STEP, MIN_DENSITY, MAX_DENSITY);
+                config.mine_density = permille as f32 / 1000.0;
+            }
+            Ok(NewGameAction::Zen) => config.rules.zen = !config.rules.zen,
+            Ok(NewGameAction::Sandbox) => config.rules.sandbox = !config.rules.sandbox,
+            Ok(NewGameAction::Treasures) => {
+                let treasures = config.rules.treasures;
+                config.rules.treasures = step(treasures, delta, TREASURES_STEP, 0, MAX_TREASURES);
+            }
+            Ok(NewGameAction::MovingMines) => {
+                let every = config.rules.moving_mines;
+                config.rules.moving_mines =
+                    step(every, delta, MOVING_MINES_STEP, 0, MAX_MOVING_MINES);
+            }
+            Ok(NewGameAction::Gravity) => {
+                config.rules.gravity = !config.rules.gravity;
+                // falling mines need hex rows
+                if config.rules.gravity {
+                    config.rules.geometry = Geometry::Hex;
+                }
+            }
+            Ok(NewGameAction::PowerUps) => config.rules.powerups = !config.rules.powerups,
+            Ok(NewGameAction::Colored) => config.rules.colored = !config.rules.colored,
+            Ok(NewGameAction::Directional) => {
+                config.rules.directional = !config.rules.directional;
+            }
+            Ok(NewGameAction::Blind) => {
+                // off is one step before blind numbers without pings
+                let value = match config.rules.blind {
+                    true => config.rules.blind_pings + BLIND_PINGS_STEP,
+                    false => 0,
+                };
+                let max = MAX_BLIND_PINGS + BLIND_PINGS_STEP;
+                let value = step(value, delta, BLIND_PINGS_STEP, 0, max);
+                config.rules.blind = value > 0;
+                config.rules.blind_pings = value.saturating_sub(BLIND_PINGS_STEP);
+            }
+            Ok(NewGameAction::Defuse) => {
+                let seconds = config.rules.defuse_seconds;
+                config.rules.defuse_seconds = step(seconds, delta, DEFUSE_STEP, 0, MAX_DEFUSE);
+            }
+            Ok(NewGameAction::Darkness) => {
+                let radius = step(
+                    config.rules.darkness.unwrap_or(0),
+                    delta,
+                    1,
+                    0,
+                    MAX_DARKNESS,
+                );
+                config.rules.darkness = (radius > 0).then_some(radius);
+            }
+            Ok(NewGameAction::Neighbors) => {
+                // the same as with mods, classic neighbors are the extra last value
+                let count = kernels.0.len() as i32 + 1;
+                let index = config
+                    .rules
+                    .kernel
+                    .and_then(|selected| {
+                        kernels.0.iter().position(|(_, kernel)| *kernel == selected)
+                    })
+                    .map_or(count - 1, |index| index as i32);
+                let index = (index + delta).rem_euclid(count) as usize;
+                config.rules.kernel = kernels.0.get(index).map(|(_, kernel)| *kernel);
+            }
+            Ok(NewGameAction::Gradient) => {
+                let count = Gradient::ALL.len() as i32;
+                let index = (config.rules.gradient as i32 + delta).rem_euclid(count);
+                config.rules.gradient = Gradient::ALL[index as usize];
+            }
+            Ok(NewGameAction::Geometry) => {
+                let count = Geometry::ALL.len() as i32;
+                let index = (config.rules.geometry as i32 + delta).rem_euclid(count);
+                config.rules.geometry = Geometry::ALL[index as usize];
+                if config.rules.geometry != Geometry::Hex {
+                    config.rules.gravity = false;
+                }
+            }
+            Ok(NewGameAction::Shape) => {
+                // the same as with mods, the hexagon is the extra last value
+                let count = masks.0.len() as i32 + 1;
+                let index = selected_shape.0.map_or(count - 1, |index| index as i32);
+                let index = (index + delta).rem_euclid(count) as usize;
+                selected_shape.0 = (index < masks.0.len()).then_some(index);
+            }
+            Ok(NewGameAction::Assists) => {
+                let count = AssistPreset::ALL.len() as i32;
+                let index = (selected_assists.0 as i32 + delta).rem_euclid(count);
+                selected_assists.0 = AssistPreset::ALL[index as usize];
+            }
+            Ok(NewGameAction::Mod) => {
+                // classic rules are the extra last value
+                let count = mods.scripts.len() as i32 + 1;
+                let index = selected_mod.0.map_or(count - 1, |index| index as i32);
+                let index = (index + delta).rem_euclid(count) as usize;
+                selected_mod.0 = (index < mods.scripts.len()).then_some(index);
+            }
+            Ok(NewGameAction::Start) => {
+                mods.active = selected_mod.0;
+                // only if it's another preset, so individually picked assists stay as they are
+                if AssistPreset::of(&settings) != selected_assists.0 {
+                    selected_assists.0.apply(&mut settings);
+                }
+                match selected_shape.0.and_then(|index| masks.0.get(index)) {
+                    // a mask can't be reproduced from a seed, so it's played as a hand-made board
+                    Some(mask) => {
+                        let seed = rng::random_seed();
+                        custom_game.send(NewCustomGame {
+                            layout: mask.layout(config, seed),
+                            rules: config.rules,
+                            seed,
+                        });
+                    }
+                    None => new_game.send(NewGame::random(*config)),
+                }
+            }
+            Ok(NewGameAction::Back) => next_state.set(AppState::Paused),
+            Err(_) => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_labels(
+    selected: Res<SelectedConfig>,
+    selected_mod: Res<SelectedMod>,
+    mods: Res<Mods>,
+    kernels: Res<Kernels>,
+    masks: Res<Masks>,
+    selected_shape: Res<SelectedShape>,
+    selected_assists: Res<SelectedAssists>,
+    stats: Res<Stats>,
+    options: Query<(&NewGameAction, &Children)>,
+    config_texts: Query<Entity, With<ConfigText>>,
+    best_texts: Query<Entity, With<BestText>>,
+    added: Query<(), Added<NewGameScreen>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !selected.is_changed()
+        && !selected_mod.is_changed()
+        && !selected_shape.is_changed()
+        && !selected_assists.is_changed()
+        && added.is_empty()
+    {
+        return;
+    }
+    let config = &selected.0;
+    for (action, children) in &options {
+        let label = match action {
+            NewGameAction::Radius => format!("< Radius {} >", config.radius),
+            NewGameAction::Density => format!("< {:.0}% mines >", config.mine_density * 100.0),
+            NewGameAction::Zen => match config.rules.zen {
+                true => "< Zen mode: on >".to_string(),
+                false => "< Zen mode: off >".to_string(),
+            },
+            NewGameAction::Sandbox => match config.rules.sandbox {
+                true => "< Sandbox: on >".to_string(),
+                false => "< Sandbox: off >".to_string(),
+            },
+            NewGameAction::Treasures => match config.rules.treasures {
+                0 => "< Treasures: off >".to_string(),
+                percent => format!("< Treasures: {percent}% >"),
+            },
+            NewGameAction::MovingMines => match config.rules.moving_mines {
+                0 => "< Moving mines: off >".to_string(),
+                every => format!("< Mines move every {every} reveals >"),
+            },
+            NewGameAction::Darkness => match config.rules.darkness {
+                None => "< Darkness: off >".to_string(),
+                Some(radius) => format!("< Darkness: {radius} hexes >"),
+            },
+            NewGameAction::Gravity => match config.rules.gravity {
+                true => "< Gravity (experimental): on >".to_string(),
+                false => "< Gravity (experimental): off >".to_string(),
+            },
+            NewGameAction::Defuse => match config.rules.defuse_seconds {
+                0 => "< Defuse timers: off >".to_string(),
+                seconds => format!("< Defuse timers: {seconds}s >"),
+            },
+            NewGameAction::PowerUps => match config.rules.powerups {
+                true => "< Power-ups: on >".to_string(),
+                false => "< Power-ups: off >".to_string(),
+            },
+            NewGameAction::Neighbors => match config.rules.kernel {
+                None => "< Neighbors: classic >".to_string(),
+                Some(selected) => {
+                    let name = kernels
+                        .0
+                        .iter()
+                        .find(|(_, kernel)| *kernel == selected)
+                        // e.g. a kernel from a shared code without the file
+                        .map_or("custom", |(name, _)| name.as_str());
+                    format!("< Neighbors: {name} >")
+                }
+            },
+            NewGameAction::Colored => match config.rules.colored {
+                true => "< Colored mines: on >".to_string(),
+                false => "< Colored mines: off >".to_string(),
+            },
+            NewGameAction::Blind => match (config.rules.blind, config.rules.blind_pings) {
+                (false, _) => "< Blind numbers: off >".to_string(),
+                (true, 0) => "< Blind numbers: no pings >".to_string(),
+                (true, pings) => format!("< Blind numbers: {pings} pings >"),
+            },
+            NewGameAction::Directional => match config.rules.directional {
+                true => "< Directional clues: on >".to_string(),
+                false => "< Directional clues: off >".to_string(),
+            },
+            NewGameAction::Gradient => {
+                format!("< Mine density: {} >", config.rules.gradient.name())
+            }
+            NewGameAction::Geometry => {
+                format!("< Cells: {} >", config.rules.geometry.name())
+            }
+            NewGameAction::Shape => match selected_shape.0.and_then(|index| masks.0.get(index)) {
+                Some(mask) => format!("< Shape: {} >", mask.name),
+                None => "< Shape: hexagon >".to_string(),
+            },
+            NewGameAction::Assists => format!("< Assists: {} >", selected_assists.0.name()),
+            NewGameAction::Mod => match selected_mod.0.and_then(|index| mods.scripts.get(index)) {
+                Some(script) => format!("< Rules: {} >", script.name),
+                None => "< Rules: classic >".to_string(),
+            },
+            _ => continue,
+        };
+        set_label(children, &mut texts, label);
+    }
+
+    let description = match selected_shape.0.and_then(|index| masks.0.get(index)) {
+        Some(mask) => format!(
+            "{} shape, {:.0}% mines: {} hexes",
+            mask.name,
+            config.mine_density * 100.0,
+            mask.cells.len()
+        ),
+        None => format!(
+            "{config}: {} hexes, {} mines",
+            config.cells_count(),
+            config.mines_count()
+        ),
+    };
+    let best = match stats.config(config) {
+        Some(stats) => match (stats.best_ms(), stats.best_efficiency) {
+            (Some(best), Some(efficiency)) => format!(
+                "Personal best: {:.1}s, efficiency {:.0}%",
+                best as f32 / 1000.0,
+                efficiency * 100.0
+            ),
+            _ => format!("No wins yet in {} game(s)", stats.played),
+        },
+        None => "Never played".to_string(),
+    };
+    let labels = config_texts
+        .iter()
+        .map(|entity| (entity, &description))
+        .chain(best_texts.iter().map(|entity| (entity, &best)));
+    for (entity, label) in labels {
+        if let Ok(mut text) = texts.get_mut(entity) {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
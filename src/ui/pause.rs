@@ -0,0 +1,369 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled},
+    set_label, spawn_button, spawn_dialog, spawn_option, text_style,
+};
+use crate::{
+    autosave::{RecoverGame, Recoverable},
+    board::Board,
+    challenge::{week, weekly_challenge},
+    clock,
+    daily::{daily_game, DailyStreak},
+    dual::DualBoards,
+    endless::EndlessRun,
+    game::{NewCustomGame, NewGame},
+    gamelog::GameLog,
+    hotseat::HotSeat,
+    mods::Mods,
+    picture::Gallery,
+    tutorial::Tutorial,
+    AppState,
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, pause_on_escape.run_if(in_state(AppState::Playing)))
+            .add_systems(OnEnter(AppState::Paused), spawn_pause_menu)
+            .add_systems(OnExit(AppState::Paused), despawn_screen::<PauseMenu>)
+            .init_resource::<MenuPage>()
+            .add_systems(
+                Update,
+                (handle_pause_menu, switch_page, spawn_page)
+                    .chain()
+                    .run_if(in_state(AppState::Paused)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct PauseMenu;
+
+/// The whole menu doesn't fit into the window, so everything but resuming, settings and quitting is split into pages
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq)]
+enum MenuPage {
+    #[default]
+    Play,
+    Boards,
+    Records,
+    Online,
+}
+
+impl MenuPage {
+    const ALL: [Self; 4] = [Self::Play, Self::Boards, Self::Records, Self::Online];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Play => "Play",
+            Self::Boards => "Boards",
+            Self::Records => "Records",
+            Self::Online => "Online",
+        }
+    }
+}
+
+/// Option that picks the [`MenuPage`]
+#[derive(Component)]
+struct PageOption;
+
+/// Buttons of the selected page
+#[derive(Component)]
+struct PageContent;
+
+#[derive(Component, Clone, Copy)]
+enum PauseAction {
+    Resume,
+    Recover,
+    NewGame,
+    Daily,
+    Weekly,
+    HotSeat,
+    Endless,
+    Dual,
+    Picture,
+    Tutorial,
+    Help,
+    Settings,
+    PlayFromCode,
+    Puzzles,
+    Editor,
+    Stats,
+    History,
+    Profile,
+    Leaderboard,
+    Coop,
+    Versus,
+    Lobby,
+    Tournament,
+    ChatVote,
+    SaveLog,
+    #[cfg(not(target_arch = "wasm32"))]
+    Quit,
+}
+
+fn pause_on_escape(keys: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(AppState::Paused);
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    spawn_dialog(&mut commands, PauseMenu, "Paused", |panel| {
+        spawn_button(panel, "Resume", 0, PauseAction::Resume);
+        spawn_option(panel, 1, PageOption);
+        panel.spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Stretch,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            },
+            PageContent,
+        ));
+        // after all buttons of every page
+        spawn_button(panel, "Settings", 1000, PauseAction::Settings);
+        // there is nowhere to quit to in the browser
+        #[cfg(not(target_arch = "wasm32"))]
+        spawn_button(panel, "Quit", 1001, PauseAction::Quit);
+    });
+}
+
+/// Picks the page with [`PageOption`] and shows which one is picked
+fn switch_page(
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    options: Query<&Children, With<PageOption>>,
+    added: Query<(), Added<PageOption>>,
+    mut texts: Query<&mut Text>,
+    mut page: ResMut<MenuPage>,
+) {
+    // activating steps forward, the same as in the other options
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        if options.contains(entity) {
+            let pages = MenuPage::ALL;
+            let current = pages.iter().position(|other| *other == *page).unwrap_or(0) as i32;
+            *page = pages[(current + delta).rem_euclid(pages.len() as i32) as usize];
+        }
+    }
+
+    if !page.is_changed() && added.is_empty() {
+        return;
+    }
+    for children in &options {
+        set_label(children, &mut texts, format!("< {} >", page.name()));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_page(
+    mut commands: Commands,
+    page: Res<MenuPage>,
+    contents: Query<Entity, With<PageContent>>,
+    added: Query<(), Added<PageContent>>,
+    streak: Res<DailyStreak>,
+    hot_seat: Option<Res<HotSeat>>,
+    endless: Option<Res<EndlessRun>>,
+    dual: Option<Res<DualBoards>>,
+    gallery: Res<Gallery>,
+    recoverable: Option<Res<Recoverable>>,
+) {
+    if !page.is_changed() && added.is_empty() {
+        return;
+    }
+    let today = clock::today();
+    let daily_label = match (streak.is_completed(today), streak.current(today)) {
+        (true, streak) => format!("Daily puzzle (solved, streak {streak})"),
+        (false, 0) => "Daily puzzle".to_string(),
+        (false, streak) => format!("Daily puzzle (streak {streak})"),
+    };
+    let (challenge, _) = weekly_challenge(week(today));
+    for content in &contents {
+        commands
+            .entity(content)
+            .despawn_descendants()
+            .with_children(|panel| match *page {
+                MenuPage::Play => {
+                    if let Some(Recoverable(autosave)) = recoverable.as_deref() {
+                        let label = format!(
+                            "Recover unfinished game ({}, {:.0}s)",
+                            autosave.config,
+                            autosave.elapsed_ms as f32 / 1000.0
+                        );
+                        spawn_button(panel, &label, 2, PauseAction::Recover);
+                    }
+                    spawn_button(panel, "New game", 3, PauseAction::NewGame);
+                    spawn_button(panel, &daily_label, 4, PauseAction::Daily);
+                    spawn_button(
+                        panel,
+                        &format!("Weekly challenge: {}", challenge.name),
+                        5,
+                        PauseAction::Weekly,
+                    );
+                    panel.spawn(TextBundle::from_section(
+                        challenge.description,
+                        text_style(16.0),
+                    ));
+                    let hot_seat_label = match &hot_seat {
+                        Some(_) => "Leave hot-seat duel",
+                        None => "Hot-seat duel (2 players)",
+                    };
+                    spawn_button(panel, hot_seat_label, 6, PauseAction::HotSeat);
+                    let endless_label = match &endless {
+                        Some(_) => "Leave endless run",
+                        None => "Endless run",
+                    };
+                    spawn_button(panel, endless_label, 7, PauseAction::Endless);
+                    let dual_label = match &dual {
+                        Some(_) => "Leave dual boards",
+                        None => "Dual boards",
+                    };
+                    spawn_button(panel, dual_label, 8, PauseAction::Dual);
+                }
+                MenuPage::Boards => {
+                    spawn_button(panel, &gallery.label(), 2, PauseAction::Picture);
+                    spawn_button(panel, "Tutorial", 3, PauseAction::Tutorial);
+                    spawn_button(panel, "Rules and patterns", 4, PauseAction::Help);
+                    spawn_button(panel, "Play from code", 5, PauseAction::PlayFromCode);
+                    spawn_button(panel, "Puzzles", 6, PauseAction::Puzzles);
+                    spawn_button(panel, "Board editor", 7, PauseAction::Editor);
+                }
+                MenuPage::Records => {
+                    spawn_button(panel, "Statistics", 2, PauseAction::Stats);
+                    spawn_button(panel, "History", 3, PauseAction::History);
+                    spawn_button(panel, "Profile", 4, PauseAction::Profile);
+                    spawn_button(panel, "Leaderboard", 5, PauseAction::Leaderboard);
+                    spawn_button(panel, "Save game log", 6, PauseAction::SaveLog);
+                }
+                MenuPage::Online => {
+                    spawn_button(panel, "Co-op", 2, PauseAction::Coop);
+                    spawn_button(panel, "Versus race", 3, PauseAction::Versus);
+                    spawn_button(panel, "Lobby", 4, PauseAction::Lobby);
+                    spawn_button(panel, "Tournament", 5, PauseAction::Tournament);
+                    spawn_button(panel, "Chat vote", 6, PauseAction::ChatVote);
+                }
+            });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_pause_menu(
+    mut commands: Commands,
+    mut activated: EventReader<Activated>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<(&PauseAction, &Children)>,
+    mut texts: Query<&mut Text>,
+    menus: Query<(), With<PauseMenu>>,
+    board: Res<Board>,
+    // grouped to stay within the amount of parameters a system can have
+    modes: (
+        Option<Res<HotSeat>>,
+        Option<Res<EndlessRun>>,
+        Option<Res<DualBoards>>,
+    ),
+    game_log: Res<GameLog>,
+    mut gallery: ResMut<Gallery>,
+    mut mods: ResMut<Mods>,
+    mut new_game: EventWriter<NewGame>,
+    mut custom_game: EventWriter<NewCustomGame>,
+    mut recover: EventWriter<RecoverGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+    #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<bevy::app::AppExit>,
+) {
+    let (hot_seat, endless, dual) = modes;
+    if cancelled
+        .iter()
+        .any(|Cancelled(scope)| menus.contains(*scope))
+    {
+        next_state.set(AppState::Playing);
+    }
+
+    for Activated(entity) in activated.iter() {
+        let Ok((action, children)) = actions.get(*entity) else {
+            continue;
+        };
+        match action {
+            PauseAction::Resume => next_state.set(AppState::Playing),
+            PauseAction::Recover => recover.send(RecoverGame),
+            PauseAction::NewGame => next_state.set(AppState::NewGameSetup),
+            // everyone plays the same daily and weekly boards, so rule scripts are off for them
+            PauseAction::Daily => {
+                mods.active = None;
+                new_game.send(daily_game(clock::today()));
+            }
+            PauseAction::Weekly => {
+                mods.active = None;
+                new_game.send(weekly_challenge(week(clock::today())).1);
+            }
+            PauseAction::HotSeat if hot_seat.is_some() => {
+                commands.remove_resource::<HotSeat>();
+                next_state.set(AppState::Playing);
+            }
+            PauseAction::HotSeat => {
+                commands.init_resource::<HotSeat>();
+                new_game.send(NewGame::random(board.config));
+            }
+            PauseAction::Endless if endless.is_some() => {
+                commands.remove_resource::<EndlessRun>();
+                next_state.set(AppState::Playing);
+            }
+            PauseAction::Endless => {
+                commands.init_resource::<EndlessRun>();
+                new_game.send(EndlessRun::stage_game(0));
+            }
+            // back to a single board of the same config
+            PauseAction::Dual => match &dual {
+                Some(dual) => {
+                    commands.remove_resource::<DualBoards>();
+                    new_game.send(NewGame::random(dual.config));
+                }
+                None => {
+                    commands.init_resource::<DualBoards>();
+                    new_game.send(NewGame::random(board.config));
+                }
+            },
+            // the menu stays open to flip through the gallery
+            PauseAction::Picture => {
+                gallery.cycle();
+                set_label(children, &mut texts, gallery.label());
+            }
+            PauseAction::Tutorial => Tutorial::start(&mut commands, &mut custom_game),
+            PauseAction::Help => next_state.set(AppState::Help),
+            PauseAction::Settings => next_state.set(AppState::Settings),
+            PauseAction::PlayFromCode => next_state.set(AppState::PlayFromCode),
+            PauseAction::Puzzles => next_state.set(AppState::Puzzles),
+            PauseAction::Editor => next_state.set(AppState::Editor),
+            PauseAction::Stats => next_state.set(AppState::Stats),
+            PauseAction::History => next_state.set(AppState::History),
+            PauseAction::Profile => next_state.set(AppState::Profile),
+            PauseAction::Leaderboard => next_state.set(AppState::Leaderboard),
+            PauseAction::Coop => next_state.set(AppState::Coop),
+            PauseAction::Versus => next_state.set(AppState::Versus),
+            PauseAction::Lobby => next_state.set(AppState::Lobby),
+            PauseAction::Tournament => next_state.set(AppState::Tournament),
+            PauseAction::ChatVote => next_state.set(AppState::ChatVote),
+            PauseAction::SaveLog => {
+                let label = match game_log.save() {
+                    Ok(path) => format!("Game log saved to {path}"),
+                    Err(err) => format!("Saving failed: {err}"),
+                };
+                set_label(children, &mut texts, label);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            PauseAction::Quit => exit.send(bevy::app::AppExit),
+        }
+    }
+}
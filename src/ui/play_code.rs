@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Cancelled, TextField},
+    spawn_button, spawn_dialog, spawn_text_field, text_style,
+};
+use crate::{
+    any_events, clipboard,
+    game::{NewCustomGame, NewGame},
+    share::{decode_board_code, SharedBoard},
+    AppState,
+};
+
+pub struct PlayCodeScreenPlugin;
+
+impl Plugin for PlayCodeScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::PlayFromCode), spawn_play_code_screen)
+            .add_systems(
+                OnExit(AppState::PlayFromCode),
+                despawn_screen::<PlayCodeScreen>,
+            )
+            .add_systems(
+                Update,
+                handle_play_code_screen.run_if(in_state(AppState::PlayFromCode)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct PlayCodeScreen;
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct CodeField;
+
+#[derive(Component, Clone, Copy)]
+enum PlayCodeAction {
+    Paste,
+    Play,
+    Back,
+}
+
+fn spawn_play_code_screen(mut commands: Commands) {
+    spawn_dialog(&mut commands, PlayCodeScreen, "Play from code", |panel| {
+        panel.spawn(TextBundle::from_section("Board code:", text_style(18.0)));
+        spawn_text_field(panel, "", 0, CodeField);
+        panel.spawn((TextBundle::from_section("", text_style(16.0)), StatusText));
+        spawn_button(panel, "Paste", 1, PlayCodeAction::Paste);
+        spawn_button(panel, "Play", 2, PlayCodeAction::Play);
+        spawn_button(panel, "Back", 3, PlayCodeAction::Back);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_play_code_screen(
+    mut activated: EventReader<Activated>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&PlayCodeAction>,
+    mut code: Query<&mut TextField, With<CodeField>>,
+    mut texts: Query<&mut Text, With<StatusText>>,
+    mut new_game: EventWriter<NewGame>,
+    mut custom_game: EventWriter<NewCustomGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    let mut status = None;
+    for Activated(entity) in activated.iter() {
+        // `Enter` in the code field plays right away
+        let action = if code.contains(*entity) {
+            Some(PlayCodeAction::Play)
+        } else {
+            actions.get(*entity).ok().copied()
+        };
+        match action {
+            Some(PlayCodeAction::Paste) => match clipboard::paste() {
+                Ok(text) => code.single_mut().value = text.trim().to_string(),
+                Err(err) => status = Some(format!("Paste failed: {err}")),
+            },
+            Some(PlayCodeAction::Play) => match decode_board_code(&code.single().value) {
+                Ok(SharedBoard::Seed { config, seed }) => new_game.send(NewGame { config, seed }),
+                Ok(SharedBoard::Layout { layout, rules }) => {
+                    custom_game.send(NewCustomGame {
+                        layout,
+                        rules,
+                        seed: 0,
+                    });
+                }
+                Err(err) => status = Some(format!("Invalid code: {err}")),
+            },
+            Some(PlayCodeAction::Back) => next_state.set(AppState::Paused),
+            None => {}
+        }
+    }
+
+    if let Some(status) = status {
+        for mut text in &mut texts {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Cancelled, TextField},
+    spawn_button, spawn_dialog, spawn_text_field, text_style,
+};
+use crate::{
+    any_events,
+    daily::DailyStreak,
+    endless::EndlessRecord,
+    export,
+    history::History,
+    profile::{self, ImportMode, Profile, DEFAULT_FILE_NAME, PROFILE_VERSION},
+    settings::Settings,
+    speedrun::BestRuns,
+    stats::Stats,
+    storage, AppState,
+};
+
+pub struct ProfileScreenPlugin;
+
+impl Plugin for ProfileScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Profile), spawn_profile_screen)
+            .add_systems(OnExit(AppState::Profile), despawn_screen::<ProfileScreen>)
+            .add_systems(
+                Update,
+                handle_profile_screen.run_if(in_state(AppState::Profile)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct ProfileScreen;
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct PathField;
+
+#[derive(Component, Clone, Copy)]
+enum ProfileAction {
+    Export,
+    /// Games and statistics as CSV and JSON tables, see `export.rs`
+    ExportTables,
+    Import(ImportMode),
+    Back,
+}
+
+fn spawn_profile_screen(mut commands: Commands) {
+    spawn_dialog(&mut commands, ProfileScreen, "Profile", |panel| {
+        panel.spawn(TextBundle::from_section(
+            "Settings, statistics and recent games in a single file",
+            text_style(16.0),
+        ));
+        panel.spawn(TextBundle::from_section("File:", text_style(18.0)));
+        spawn_text_field(
+            panel,
+            &storage::export_path(DEFAULT_FILE_NAME),
+            0,
+            PathField,
+        );
+        spawn_button(panel, "Export", 1, ProfileAction::Export);
+        spawn_button(
+            panel,
+            "Export games and stats as CSV and JSON",
+            2,
+            ProfileAction::ExportTables,
+        );
+        spawn_button(
+            panel,
+            "Import and merge",
+            3,
+            ProfileAction::Import(ImportMode::Merge),
+        );
+        spawn_button(
+            panel,
+            "Import and replace",
+            4,
+            ProfileAction::Import(ImportMode::Replace),
+        );
+        panel.spawn((TextBundle::from_section("", text_style(16.0)), StatusText));
+        spawn_button(panel, "Back", 5, ProfileAction::Back);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_profile_screen(
+    mut activated: EventReader<Activated>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&ProfileAction>,
+    path: Query<&TextField, With<PathField>>,
+    mut texts: Query<&mut Text, With<StatusText>>,
+    mut settings: ResMut<Settings>,
+    mut stats: ResMut<Stats>,
+    mut daily: ResMut<DailyStreak>,
+    mut history: ResMut<History>,
+    mut splits: ResMut<BestRuns>,
+    mut endless: ResMut<EndlessRecord>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    let mut status = None;
+    for Activated(entity) in activated.iter() {
+        let path = &path.single().value;
+        match actions.get(*entity) {
+            Ok(ProfileAction::Export) => {
+                let exported = Profile {
+                    version: PROFILE_VERSION,
+                    settings: settings.clone(),
+                    stats: stats.clone(),
+                    daily: daily.clone(),
+                    history: history.clone(),
+                    splits: splits.clone(),
+                    endless: endless.clone(),
+                };
+                status = Some(match profile::export(path, &exported) {
+                    Ok(()) => format!("Exported to {path}"),
+                    Err(err) => format!("Export failed: {err}"),
+                });
+            }
+            Ok(ProfileAction::ExportTables) => {
+                status = Some(match export::export(path, &stats, &history) {
+                    Ok(files) => format!("Exported to {}", files.join(", ")),
+                    Err(err) => format!("Export failed: {err}"),
+                });
+            }
+            Ok(ProfileAction::Import(mode)) => {
+                let imported = match profile::import(path) {
+                    Ok(imported) => imported,
+                    Err(err) => {
+                        status = Some(format!("Import failed: {err}"));
+                        continue;
+                    }
+                };
+                match mode {
+                    ImportMode::Merge => {
+                        stats.merge(imported.stats);
+                        daily.merge(imported.daily);
+                        history.merge(imported.history);
+                        splits.merge(imported.splits);
+                        endless.merge(imported.endless);
+                    }
+                    ImportMode::Replace => {
+                        *settings = imported.settings;
+                        *stats = imported.stats;
+                        *daily = imported.daily;
+                        *history = imported.history;
+                        *splits = imported.splits;
+                        *endless = imported.endless;
+                    }
+                }
+                // settings are saved on change by themselves
+                storage::save(Stats::FILE, &*stats);
+                storage::save(DailyStreak::FILE, &*daily);
+                storage::save(History::FILE, &*history);
+                storage::save(BestRuns::FILE, &*splits);
+                storage::save(EndlessRecord::FILE, &*endless);
+                status = Some("Imported".to_string());
+            }
+            Ok(ProfileAction::Back) => next_state.set(AppState::Paused),
+            Err(_) => {}
+        }
+    }
+
+    if let Some(status) = status {
+        for mut text in &mut texts {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Cancelled},
+    spawn_button, spawn_dialog, text_style,
+};
+use crate::{
+    any_events,
+    board::Rules,
+    game::NewCustomGame,
+    mbf::{self, Mapping, SquareBoard},
+    puzzle::{load_campaign, Puzzle},
+    AppState,
+};
+
+pub struct PuzzlesScreenPlugin;
+
+impl Plugin for PuzzlesScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Puzzles), spawn_puzzles_screen)
+            .add_systems(OnExit(AppState::Puzzles), despawn_screen::<PuzzlesScreen>)
+            .add_systems(
+                Update,
+                handle_puzzles_screen.run_if(in_state(AppState::Puzzles)),
+            );
+    }
+}
+
+/// Puzzles listed on the screen, loaded when the screen opens to pick up freshly saved ones
+#[derive(Resource)]
+struct Campaign(Vec<Puzzle>);
+
+/// Classic square boards from `.mbf` files, loaded along with the campaign
+#[derive(Resource)]
+struct ImportedBoards(Vec<SquareBoard>);
+
+#[derive(Component)]
+struct PuzzlesScreen;
+
+#[derive(Component, Clone, Copy)]
+enum PuzzlesAction {
+    /// Play the puzzle with the given index in the campaign
+    Play(usize),
+    /// Play the imported board with the given index, laid onto hexes one way or the other
+    PlayImported(usize, Mapping),
+    Back,
+}
+
+fn spawn_puzzles_screen(mut commands: Commands) {
+    let campaign = load_campaign();
+    let imported = mbf::available();
+    spawn_dialog(&mut commands, PuzzlesScreen, "Puzzles", |panel| {
+        if campaign.is_empty() {
+            panel.spawn(TextBundle::from_section(
+                "No puzzles yet, make one in the board editor",
+                text_style(18.0),
+            ));
+        }
+        for (index, puzzle) in campaign.iter().enumerate() {
+            let label = match puzzle.par_time_ms {
+                Some(par) => format!("{} (par {:.1}s)", puzzle.name, par as f32 / 1000.0),
+                None => puzzle.name.clone(),
+            };
+            spawn_button(panel, &label, index as u32, PuzzlesAction::Play(index));
+        }
+        let mut order = campaign.len() as u32;
+        if !imported.is_empty() {
+            panel.spawn(TextBundle::from_section(
+                "Square boards, on hexes or as squares:",
+                text_style(16.0),
+            ));
+        }
+        for (index, board) in imported.iter().enumerate() {
+            let name = format!("{} ({}x{})", board.name, board.width, board.height);
+            for (suffix, mapping) in [("hex", Mapping::Hex), ("square", Mapping::Square)] {
+                let label = format!("{name}, {suffix}");
+                spawn_button(
+                    panel,
+                    &label,
+                    order,
+                    PuzzlesAction::PlayImported(index, mapping),
+                );
+                order += 1;
+            }
+        }
+        spawn_button(panel, "Back", order, PuzzlesAction::Back);
+    });
+    commands.insert_resource(Campaign(campaign));
+    commands.insert_resource(ImportedBoards(imported));
+}
+
+fn handle_puzzles_screen(
+    mut activated: EventReader<Activated>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&PuzzlesAction>,
+    campaign: Res<Campaign>,
+    imported: Res<ImportedBoards>,
+    mut custom_game: EventWriter<NewCustomGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    for Activated(entity) in activated.iter() {
+        match actions.get(*entity) {
+            Ok(PuzzlesAction::Play(index)) => {
+                if let Some(puzzle) = campaign.0.get(*index) {
+                    custom_game.send(NewCustomGame {
+                        layout: puzzle.layout(),
+                        rules: Rules::CLASSIC,
+                        seed: 0,
+                    });
+                }
+            }
+            Ok(PuzzlesAction::PlayImported(index, mapping)) => {
+                if let Some(board) = imported.0.get(*index) {
+                    custom_game.send(NewCustomGame {
+                        layout: board.layout(*mapping),
+                        rules: SquareBoard::rules(*mapping),
+                        seed: 0,
+                    });
+                }
+            }
+            Ok(PuzzlesAction::Back) => next_state.set(AppState::Paused),
+            Err(_) => {}
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use bevy::prelude::*;
+
+use super::{despawn_screen, focus::Activated, spawn_button, spawn_dialog, text_style};
+use crate::{
+    board::Board,
+    board_text::encode_board_text,
+    card::{CardExported, ExportCard},
+    clipboard, clock,
+    daily::{is_daily, DailyStreak},
+    endless::{EndlessRecord, EndlessRun},
+    game::{Game, NewCustomGame, NewGame, Outcome},
+    hotseat::{HotSeat, POINTS_TO_WIN},
+    leaderboard::{Leaderboard, Status},
+    result_text::encode_result_text,
+    score::Score,
+    settings::Settings,
+    share::encode_board_code,
+    stats::{efficiency, no_flags},
+    AppState,
+};
+
+/// Background of the badge of wins without flags
+const BADGE_COLOR: Color = Color::rgb(0.85, 0.65, 0.1);
+
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::GameOver), spawn_results)
+            .add_systems(OnExit(AppState::GameOver), despawn_screen::<ResultsDialog>)
+            .add_systems(
+                Update,
+                (handle_results, update_submission_status, show_card_export)
+                    .run_if(in_state(AppState::GameOver)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct ResultsDialog;
+
+#[derive(Component)]
+struct SubmissionText;
+
+#[derive(Component)]
+struct BoardCodeText;
+
+#[derive(Component, Clone, Copy)]
+enum ResultsAction {
+    Retry,
+    NewGame,
+    CopyResult,
+    CopyCode,
+    CopyText,
+    ExportCard,
+    Stats,
+    Leaderboard,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_results(
+    mut commands: Commands,
+    game: Res<Game>,
+    board: Res<Board>,
+    score: Res<Score>,
+    streak: Res<DailyStreak>,
+    hot_seat: Option<Res<HotSeat>>,
+    endless: Option<Res<EndlessRun>>,
+    endless_record: Res<EndlessRecord>,
+    settings: Res<Settings>,
+) {
+    let title = match game.outcome {
+        Some(Outcome::Won) => "You won!",
+        _ => "Boom!",
+    };
+    spawn_dialog(&mut commands, ResultsDialog, title, |panel| {
+        let time = if board.config.rules.zen {
+            "Cleared at your own pace".to_string()
+        } else {
+            format!("Time: {:.1}s", game.elapsed.as_secs_f32())
+        };
+        panel.spawn(TextBundle::from_section(time, text_style(24.0)));
+        if game.outcome == Some(Outcome::Won) && no_flags(&game) {
+            panel
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                        ..default()
+                    },
+                    background_color: BADGE_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|badge| {
+                    badge.spawn(TextBundle::from_section(
+                        "NF: won without flags",
+                        text_style(18.0),
+                    ));
+                });
+        }
+        if game.assisted {
+            panel.spawn(TextBundle::from_section(
+                "Assisted game, kept apart in the statistics",
+                text_style(16.0),
+            ));
+        }
+        if let Some(script) = &game.script {
+            panel.spawn(TextBundle::from_section(
+                format!(
+                    "Played with the {} rule script, kept apart in the statistics",
+                    script.name
+                ),
+                text_style(16.0),
+            ));
+        }
+        panel.spawn(TextBundle::from_section(
+            format!("Score: {}", score.points),
+            text_style(18.0),
+        ));
+        if game.outcome == Some(Outcome::Won) {
+            panel.spawn(TextBundle::from_section(
+                format!("Efficiency: {:.0}%", efficiency(&board, &game) * 100.0),
+                text_style(18.0),
+            ));
+        }
+        let wrong_flags = board.wrong_flags().count();
+        if wrong_flags > 0 {
+            panel.spawn(TextBundle::from_section(
+                format!("Wrong flags: {wrong_flags} (crossed out on the board)"),
+                text_style(18.0),
+            ));
+        }
+        let lost_alone = game.outcome == Some(Outcome::Lost) && hot_seat.is_none();
+        if let Some(hot_seat) = hot_seat {
+            let summary = match hot_seat.match_winner() {
+                Some(winner) => format!("Player {} wins the match!", winner + 1),
+                None => format!("First to {POINTS_TO_WIN} points wins the match"),
+            };
+            panel.spawn(TextBundle::from_section(
+                format!(
+                    "Points {} : {}. {summary}",
+                    hot_seat.points[0], hot_seat.points[1]
+                ),
+                text_style(18.0),
+            ));
+        }
+        if let Some(endless) = endless {
+            panel.spawn(TextBundle::from_section(
+                format!(
+                    "Endless run: {} boards cleared (best {})",
+                    endless.cleared, endless_record.best
+                ),
+                text_style(18.0),
+            ));
+        }
+        let today = clock::today();
+        if is_daily(&board, today) {
+            panel.spawn(TextBundle::from_section(
+                format!(
+                    "Daily puzzle streak: {} (best {})",
+                    streak.current(today),
+                    streak.best_streak
+                ),
+                text_style(18.0),
+            ));
+        }
+        panel.spawn((
+            TextBundle::from_section("", text_style(16.0)),
+            SubmissionText,
+        ));
+        panel.spawn((
+            TextBundle::from_section(
+                code_label(&encode_board_code(&board), &settings),
+                text_style(16.0),
+            ),
+            BoardCodeText,
+        ));
+        // practicing the exact situation that ended the game makes sense only after a loss
+        if lost_alone {
+            spawn_button(panel, "Retry this board", 0, ResultsAction::Retry);
+        }
+        spawn_button(panel, "New game", 1, ResultsAction::NewGame);
+        spawn_button(panel, "Copy result to share", 2, ResultsAction::CopyResult);
+        spawn_button(panel, "Copy board code", 3, ResultsAction::CopyCode);
+        spawn_button(panel, "Copy board as text", 4, ResultsAction::CopyText);
+        spawn_button(panel, "Export card", 5, ResultsAction::ExportCard);
+        spawn_button(panel, "Statistics", 6, ResultsAction::Stats);
+        spawn_button(panel, "Leaderboard", 7, ResultsAction::Leaderboard);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_results(
+    mut activated: EventReader<Activated>,
+    actions: Query<&ResultsAction>,
+    board: Res<Board>,
+    game: Res<Game>,
+    settings: Res<Settings>,
+    mut code_texts: Query<&mut Text, With<BoardCodeText>>,
+    mut new_game: EventWriter<NewGame>,
+    mut custom_game: EventWriter<NewCustomGame>,
+    mut export_card: EventWriter<ExportCard>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for Activated(entity) in activated.iter() {
+        match actions.get(*entity) {
+            // the same seed generates the same mines, while hand-made boards are restarted from their layout
+            Ok(ResultsAction::Retry) => match &board.layout {
+                Some(layout) => custom_game.send(NewCustomGame {
+                    layout: layout.clone(),
+                    rules: board.config.rules,
+                    seed: board.seed,
+                }),
+                None => new_game.send(NewGame {
+                    config: board.config,
+                    seed: board.seed,
+                }),
+            },
+            Ok(ResultsAction::NewGame) => new_game.send(NewGame::random(board.config)),
+            Ok(ResultsAction::CopyResult) => {
+                // the grid shows no mines, so it's safe to post before others play the board
+                let label = match clipboard::copy(&encode_result_text(&board, &game)) {
+                    Ok(()) => "Result copied, ready to paste".to_string(),
+                    Err(err) => format!("Copy failed: {err}"),
+                };
+                for mut text in &mut code_texts {
+                    text.sections[0].value = label.clone();
+                }
+            }
+            Ok(ResultsAction::CopyCode) => {
+                let code = encode_board_code(&board);
+                let label = code_label(&code, &settings);
+                let label = match code.map(|code| clipboard::copy(&code)) {
+                    Ok(Ok(())) => format!("{label} (copied)"),
+                    Ok(Err(err)) => format!("{label} (copy failed: {err})"),
+                    Err(_) => label,
+                };
+                for mut text in &mut code_texts {
+                    text.sections[0].value = label.clone();
+                }
+            }
+            Ok(ResultsAction::CopyText) => {
+                // the whole board with mines, handy for bug reports
+                let label = match clipboard::copy(&encode_board_text(&board.layout())) {
+                    Ok(()) => "Board copied as text".to_string(),
+                    Err(err) => format!("Copy failed: {err}"),
+                };
+                for mut text in &mut code_texts {
+                    text.sections[0].value = label.clone();
+                }
+            }
+            Ok(ResultsAction::ExportCard) => {
+                export_card.send(ExportCard);
+                for mut text in &mut code_texts {
+                    text.sections[0].value = "Exporting the card...".to_string();
+                }
+            }
+            Ok(ResultsAction::Stats) => next_state.set(AppState::Stats),
+            Ok(ResultsAction::Leaderboard) => next_state.set(AppState::Leaderboard),
+            Err(_) => {}
+        }
+    }
+}
+
+fn show_card_export(
+    mut exported: EventReader<CardExported>,
+    mut code_texts: Query<&mut Text, With<BoardCodeText>>,
+) {
+    for CardExported(result) in exported.iter() {
+        let label = match result {
+            Ok(path) => format!("Card saved to {path}"),
+            Err(err) => format!("Export failed: {err}"),
+        };
+        for mut text in &mut code_texts {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+/// The code is hidden in the streamer mode, so chat can't play or spoil the same board
+fn code_label(code: &Result<String, String>, settings: &Settings) -> String {
+    match code {
+        _ if settings.streamer_mode => "Board code: hidden in streamer mode".to_string(),
+        Ok(code) => format!("Board code: {code}"),
+        Err(err) => format!("No board code: {err}"),
+    }
+}
+
+fn update_submission_status(
+    leaderboard: Res<Leaderboard>,
+    mut texts: Query<&mut Text, With<SubmissionText>>,
+    added: Query<(), Added<SubmissionText>>,
+) {
+    if !leaderboard.is_changed() && added.is_empty() {
+        return;
+    }
+    let status = match &leaderboard.submission {
+        Status::Idle => String::new(),
+        Status::Pending => "Submitting to the leaderboard...".to_string(),
+        Status::Done(()) => "Submitted to the leaderboard".to_string(),
+        Status::Failed(err) => format!("Leaderboard submission failed: {err}"),
+    };
+    for mut text in &mut texts {
+        text.sections[0].value = status.clone();
+    }
+}
@@ -0,0 +1,463 @@
+//! Gameplay preferences. Every change is saved right away by `settings.rs`.
+
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled, TextField},
+    set_label, spawn_button, spawn_dialog, spawn_option, spawn_text_field, text_style,
+};
+use crate::{
+    any_events,
+    notes::NOTE_COLORS,
+    settings::{GridStyle, NumberFont, NumberStyle, Settings, ThemeMode},
+    AppState,
+};
+
+pub struct SettingsScreenPlugin;
+
+impl Plugin for SettingsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsPage>()
+            .add_systems(OnEnter(AppState::Settings), spawn_settings_screen)
+            .add_systems(OnExit(AppState::Settings), despawn_screen::<SettingsScreen>)
+            .add_systems(
+                Update,
+                (handle_settings_screen, spawn_page, update_labels)
+                    .chain()
+                    .run_if(in_state(AppState::Settings)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct SettingsScreen;
+
+/// All settings don't fit into the window at once, so they are split into pages.
+/// The page is kept, so coming back from the theme editor shows the theme settings again.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq)]
+enum SettingsPage {
+    #[default]
+    Gameplay,
+    Numbers,
+    Board,
+    Theme,
+    Streaming,
+}
+
+impl SettingsPage {
+    const ALL: [Self; 5] = [
+        Self::Gameplay,
+        Self::Numbers,
+        Self::Board,
+        Self::Theme,
+        Self::Streaming,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Gameplay => "Gameplay",
+            Self::Numbers => "Numbers",
+            Self::Board => "Board",
+            Self::Theme => "Theme",
+            Self::Streaming => "Streaming",
+        }
+    }
+}
+
+/// Options of the selected page
+#[derive(Component)]
+struct PageContent;
+
+#[derive(Component, Clone, Copy)]
+enum SettingsAction {
+    Page,
+    SafeChord,
+    AutoOpen,
+    AutoFlag,
+    DragSweep,
+    Ghost,
+    IdlePause,
+    IdleVeil,
+    EffectiveNumbers,
+    NumberFont,
+    NumberSize,
+    MaxZoom,
+    GridGap,
+    Outlines,
+    Rounded,
+    Shadows,
+    Rotation,
+    FlatTop,
+    InvertX,
+    InvertY,
+    Theme,
+    Darkness,
+    EditTheme,
+    ReducedMotion,
+    Magnifier,
+    FlagColors,
+    SketchesInScreenshots,
+    StreamerMode,
+    WebcamMargin,
+    Back,
+}
+
+/// Path to a TTF file for [`NumberFont::Custom`]
+#[derive(Component)]
+struct FontPathField;
+
+fn spawn_settings_screen(mut commands: Commands) {
+    spawn_dialog(&mut commands, SettingsScreen, "Settings", |panel| {
+        spawn_option(panel, 0, SettingsAction::Page);
+        panel.spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Stretch,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            },
+            PageContent,
+        ));
+        // after all options of every page
+        spawn_button(panel, "Back", 1000, SettingsAction::Back);
+    });
+}
+
+fn spawn_page(
+    mut commands: Commands,
+    page: Res<SettingsPage>,
+    settings: Res<Settings>,
+    contents: Query<Entity, With<PageContent>>,
+    added: Query<(), Added<PageContent>>,
+) {
+    if !page.is_changed() && added.is_empty() {
+        return;
+    }
+    for content in &contents {
+        commands
+            .entity(content)
+            .despawn_descendants()
+            .with_children(|panel| match *page {
+                SettingsPage::Gameplay => {
+                    spawn_option(panel, 1, SettingsAction::SafeChord);
+                    spawn_option(panel, 2, SettingsAction::DragSweep);
+                    spawn_option(panel, 3, SettingsAction::Ghost);
+                    spawn_option(panel, 4, SettingsAction::IdlePause);
+                    spawn_option(panel, 5, SettingsAction::IdleVeil);
+                    spawn_option(panel, 6, SettingsAction::Magnifier);
+                    panel.spawn(TextBundle::from_section(
+                        "Assisted games are kept apart in the statistics:",
+                        text_style(16.0),
+                    ));
+                    spawn_option(panel, 7, SettingsAction::AutoOpen);
+                    spawn_option(panel, 8, SettingsAction::AutoFlag);
+                }
+                SettingsPage::Numbers => {
+                    spawn_option(panel, 1, SettingsAction::EffectiveNumbers);
+                    spawn_option(panel, 2, SettingsAction::NumberFont);
+                    spawn_option(panel, 3, SettingsAction::NumberSize);
+                    panel.spawn(TextBundle::from_section(
+                        "Custom font file, Enter to use it:",
+                        text_style(16.0),
+                    ));
+                    spawn_text_field(panel, &settings.number_style.custom_path, 4, FontPathField);
+                    spawn_option(panel, 5, SettingsAction::FlagColors);
+                }
+                SettingsPage::Board => {
+                    spawn_option(panel, 1, SettingsAction::MaxZoom);
+                    spawn_option(panel, 2, SettingsAction::GridGap);
+                    spawn_option(panel, 3, SettingsAction::Outlines);
+                    spawn_option(panel, 4, SettingsAction::Rounded);
+                    spawn_option(panel, 5, SettingsAction::Shadows);
+                    spawn_option(panel, 6, SettingsAction::Rotation);
+                    spawn_option(panel, 7, SettingsAction::FlatTop);
+                    spawn_option(panel, 8, SettingsAction::InvertX);
+                    spawn_option(panel, 9, SettingsAction::InvertY);
+                }
+                SettingsPage::Theme => {
+                    spawn_option(panel, 1, SettingsAction::Theme);
+                    spawn_option(panel, 2, SettingsAction::Darkness);
+                    spawn_button(panel, "Edit theme", 3, SettingsAction::EditTheme);
+                    spawn_option(panel, 4, SettingsAction::ReducedMotion);
+                }
+                SettingsPage::Streaming => {
+                    spawn_option(panel, 1, SettingsAction::StreamerMode);
+                    spawn_option(panel, 2, SettingsAction::WebcamMargin);
+                    spawn_option(panel, 3, SettingsAction::SketchesInScreenshots);
+                }
+            });
+    }
+}
+
+fn handle_settings_screen(
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&SettingsAction>,
+    font_paths: Query<&TextField, With<FontPathField>>,
+    mut settings: ResMut<Settings>,
+    mut page: ResMut<SettingsPage>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    // toggles flip in both directions, activating steps forward
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        if let Ok(field) = font_paths.get(entity) {
+            let style = &mut settings.number_style;
+            style.custom_path = field.value.trim().to_string();
+            style.font = NumberFont::Custom;
+            continue;
+        }
+        match actions.get(entity) {
+            Ok(SettingsAction::Page) => {
+                let pages = SettingsPage::ALL;
+                let current = pages.iter().position(|other| *other == *page).unwrap_or(0) as i32;
+                *page = pages[(current + delta).rem_euclid(pages.len() as i32) as usize];
+            }
+            Ok(SettingsAction::SafeChord) => settings.safe_chord = !settings.safe_chord,
+            Ok(SettingsAction::AutoOpen) => settings.auto_open = !settings.auto_open,
+            Ok(SettingsAction::AutoFlag) => settings.auto_flag = !settings.auto_flag,
+            Ok(SettingsAction::DragSweep) => settings.drag_sweep = !settings.drag_sweep,
+            Ok(SettingsAction::Ghost) => settings.ghost = !settings.ghost,
+            Ok(SettingsAction::IdlePause) => {
+                let choices = Settings::IDLE_PAUSE_SECONDS;
+                let current = choices
+                    .iter()
+                    .position(|seconds| *seconds == settings.idle_pause_seconds)
+                    .unwrap_or(0) as i32;
+                let next = (current + delta).rem_euclid(choices.len() as i32);
+                settings.idle_pause_seconds = choices[next as usize];
+            }
+            Ok(SettingsAction::IdleVeil) => settings.idle_veil = !settings.idle_veil,
+            Ok(SettingsAction::EffectiveNumbers) => {
+                settings.effective_numbers = !settings.effective_numbers;
+            }
+            Ok(SettingsAction::NumberFont) => {
+                let fonts = [
+                    NumberFont::Pictures,
+                    NumberFont::BuiltIn,
+                    NumberFont::Custom,
+                ];
+                let current = fonts
+                    .iter()
+                    .position(|font| *font == settings.number_style.font)
+                    .unwrap_or(0) as i32;
+                let next = (current + delta).rem_euclid(fonts.len() as i32);
+                settings.number_style.font = fonts[next as usize];
+            }
+            Ok(SettingsAction::NumberSize) => {
+                let (min, max) = NumberStyle::SIZE_RANGE;
+                let steps = ((max - min) / NumberStyle::SIZE_STEP).round() as i32 + 1;
+                let step =
+                    ((settings.number_style.size - min) / NumberStyle::SIZE_STEP).round() as i32;
+                let step = (step + delta).rem_euclid(steps);
+                settings.number_style.size = min + step as f32 * NumberStyle::SIZE_STEP;
+            }
+            Ok(SettingsAction::MaxZoom) => {
+                let (min, max) = GridStyle::MAX_ZOOM_RANGE;
+                let steps = ((max - min) / GridStyle::MAX_ZOOM_STEP).round() as i32 + 1;
+                let step = ((settings.grid_style.max_zoom - min) / GridStyle::MAX_ZOOM_STEP).round()
+                    as i32;
+                let step = (step + delta).rem_euclid(steps);
+                settings.grid_style.max_zoom = min + step as f32 * GridStyle::MAX_ZOOM_STEP;
+            }
+            Ok(SettingsAction::GridGap) => {
+                // wraps around, so activating alone reaches every step
+                let steps = (GridStyle::MAX_GAP / GridStyle::GAP_STEP).round() as i32 + 1;
+                let step = (settings.grid_style.gap / GridStyle::GAP_STEP).round() as i32;
+                settings.grid_style.gap =
+                    (step + delta).rem_euclid(steps) as f32 * GridStyle::GAP_STEP;
+            }
+            Ok(SettingsAction::Outlines) => {
+                settings.grid_style.outlines = !settings.grid_style.outlines;
+            }
+            Ok(SettingsAction::Rounded) => {
+                settings.grid_style.rounded = !settings.grid_style.rounded;
+            }
+            Ok(SettingsAction::Shadows) => {
+                settings.grid_style.shadows = !settings.grid_style.shadows;
+            }
+            Ok(SettingsAction::Rotation) => {
+                settings.board_rotation =
+                    (settings.board_rotation as i32 + delta).rem_euclid(6) as u8;
+            }
+            Ok(SettingsAction::FlatTop) => {
+                settings.grid_style.flat_top = !settings.grid_style.flat_top;
+            }
+            Ok(SettingsAction::InvertX) => {
+                settings.grid_style.invert_x = !settings.grid_style.invert_x;
+            }
+            Ok(SettingsAction::InvertY) => {
+                settings.grid_style.invert_y = !settings.grid_style.invert_y;
+            }
+            Ok(SettingsAction::Theme) => {
+                settings.theme = match settings.theme {
+                    ThemeMode::Manual => ThemeMode::DayNight,
+                    ThemeMode::DayNight => ThemeMode::Custom,
+                    ThemeMode::Custom => ThemeMode::Manual,
+                };
+            }
+            Ok(SettingsAction::Darkness) => {
+                let steps = (1.0 / Settings::DARKNESS_STEP).round() as i32 + 1;
+                let step = (settings.darkness / Settings::DARKNESS_STEP).round() as i32;
+                settings.darkness =
+                    (step + delta).rem_euclid(steps) as f32 * Settings::DARKNESS_STEP;
+                // moving the slider means picking the theme by hand
+                settings.theme = ThemeMode::Manual;
+            }
+            Ok(SettingsAction::EditTheme) => next_state.set(AppState::ThemeEditor),
+            Ok(SettingsAction::ReducedMotion) => {
+                settings.reduced_motion = !settings.reduced_motion;
+            }
+            Ok(SettingsAction::Magnifier) => settings.magnifier = !settings.magnifier,
+            Ok(SettingsAction::SketchesInScreenshots) => {
+                settings.sketches_in_screenshots = !settings.sketches_in_screenshots;
+            }
+            Ok(SettingsAction::StreamerMode) => {
+                settings.streamer_mode = !settings.streamer_mode;
+            }
+            Ok(SettingsAction::WebcamMargin) => settings.webcam_margin = !settings.webcam_margin,
+            Ok(SettingsAction::FlagColors) => {
+                let count = NOTE_COLORS.len() as i32;
+                let colors = settings.flag_colors.max(1) as i32;
+                settings.flag_colors = ((colors - 1 + delta).rem_euclid(count) + 1) as u8;
+            }
+            Ok(SettingsAction::Back) => next_state.set(AppState::Paused),
+            Err(_) => {}
+        }
+    }
+}
+
+fn update_labels(
+    settings: Res<Settings>,
+    page: Res<SettingsPage>,
+    options: Query<(&SettingsAction, &Children)>,
+    // options of a page show up a frame after it's picked
+    added: Query<(), Added<SettingsAction>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !settings.is_changed() && !page.is_changed() && added.is_empty() {
+        return;
+    }
+    for (action, children) in &options {
+        let (name, on) = match action {
+            SettingsAction::Page => {
+                set_label(children, &mut texts, format!("< {} >", page.name()));
+                continue;
+            }
+            SettingsAction::SafeChord => ("Safe chording", settings.safe_chord),
+            SettingsAction::AutoOpen => ("Auto-open assist", settings.auto_open),
+            SettingsAction::AutoFlag => ("Auto-flag assist", settings.auto_flag),
+            SettingsAction::DragSweep => ("Drag to sweep", settings.drag_sweep),
+            SettingsAction::Ghost => ("Race the ghost of my best run", settings.ghost),
+            SettingsAction::IdlePause => {
+                let pause = match settings.idle_pause_seconds {
+                    0 => "off".to_string(),
+                    seconds => format!("after {seconds}s"),
+                };
+                set_label(
+                    children,
+                    &mut texts,
+                    format!("< Pause when idle: {pause} >"),
+                );
+                continue;
+            }
+            SettingsAction::IdleVeil => ("Hide the board when idle", settings.idle_veil),
+            SettingsAction::EffectiveNumbers => ("Effective numbers", settings.effective_numbers),
+            SettingsAction::NumberFont => {
+                let font = match settings.number_style.font {
+                    NumberFont::Pictures => "pictures",
+                    NumberFont::BuiltIn => "built-in",
+                    NumberFont::Custom => "custom file",
+                };
+                set_label(children, &mut texts, format!("< Numbers: {font} >"));
+                continue;
+            }
+            SettingsAction::NumberSize => {
+                let percent = (settings.number_style.size * 100.0).round();
+                set_label(children, &mut texts, format!("< Digit size: {percent}% >"));
+                continue;
+            }
+            SettingsAction::MaxZoom => {
+                let percent = (settings.grid_style.max_zoom * 100.0).round();
+                set_label(children, &mut texts, format!("< Max zoom: {percent}% >"));
+                continue;
+            }
+            SettingsAction::GridGap => {
+                let percent = (settings.grid_style.gap * 100.0).round();
+                set_label(
+                    children,
+                    &mut texts,
+                    format!("< Gap between hexes: {percent}% >"),
+                );
+                continue;
+            }
+            SettingsAction::Outlines => ("Outlines", settings.grid_style.outlines),
+            SettingsAction::Rounded => ("Rounded corners", settings.grid_style.rounded),
+            SettingsAction::Shadows => ("Shadows", settings.grid_style.shadows),
+            SettingsAction::FlatTop => ("Flat-topped hexes", settings.grid_style.flat_top),
+            SettingsAction::InvertX => ("Mirror left to right", settings.grid_style.invert_x),
+            SettingsAction::InvertY => ("Mirror upside down", settings.grid_style.invert_y),
+            SettingsAction::Rotation => {
+                let degrees = settings.board_rotation as u32 * 60;
+                set_label(
+                    children,
+                    &mut texts,
+                    format!("< Board rotation: {degrees}° >"),
+                );
+                continue;
+            }
+            SettingsAction::Theme => {
+                let theme = match settings.theme {
+                    ThemeMode::Manual => "manual",
+                    ThemeMode::DayNight => "day and night",
+                    ThemeMode::Custom if settings.custom_theme_name.is_empty() => "custom",
+                    ThemeMode::Custom => settings.custom_theme_name.as_str(),
+                };
+                set_label(children, &mut texts, format!("< Theme: {theme} >"));
+                continue;
+            }
+            SettingsAction::Darkness => {
+                let darkness = match settings.theme {
+                    ThemeMode::Manual => format!("{}%", (settings.darkness * 100.0).round()),
+                    ThemeMode::DayNight => "by the time of day".to_string(),
+                    ThemeMode::Custom => "set by the theme".to_string(),
+                };
+                set_label(children, &mut texts, format!("< Darkness: {darkness} >"));
+                continue;
+            }
+            SettingsAction::ReducedMotion => ("Reduced motion", settings.reduced_motion),
+            SettingsAction::Magnifier => ("Magnifier (F5)", settings.magnifier),
+            SettingsAction::SketchesInScreenshots => {
+                ("Sketches in screenshots", settings.sketches_in_screenshots)
+            }
+            SettingsAction::StreamerMode => ("Streamer mode (F6)", settings.streamer_mode),
+            SettingsAction::WebcamMargin => {
+                ("Webcam space in streamer mode", settings.webcam_margin)
+            }
+            SettingsAction::FlagColors => {
+                let colors = settings.flag_colors.max(1);
+                set_label(children, &mut texts, format!("< Flag colors: {colors} >"));
+                continue;
+            }
+            SettingsAction::Back => continue,
+        };
+        let state = if on { "on" } else { "off" };
+        set_label(children, &mut texts, format!("< {name}: {state} >"));
+    }
+}
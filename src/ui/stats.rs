@@ -0,0 +1,357 @@
+use bevy::{prelude::*, utils::HashMap};
+use hexx::{shapes, Hex, HexLayout};
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Cancelled},
+    spawn_button, spawn_dialog, text_style,
+};
+use crate::{
+    any_events,
+    board::Board,
+    clock,
+    daily::DailyStreak,
+    game::Game,
+    history::History,
+    stats::{ConfigStats, Stats},
+    AppState,
+};
+
+/// Most played boards that fit on the screen
+const SHOWN_CONFIGS: usize = 8;
+/// Amount of bars in the histogram of win times
+const HISTOGRAM_BINS: usize = 12;
+const HISTOGRAM_HEIGHT: f32 = 80.0;
+/// Weeks of daily puzzles shown in the calendar
+const CALENDAR_WEEKS: u32 = 4;
+
+const BAR_COLOR: Color = Color::rgb(0.35, 0.6, 0.95);
+const COMPLETED_DAY_COLOR: Color = Color::rgb(0.4, 0.85, 0.4);
+const MISSED_DAY_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+/// Width and height of a heatmap
+const HEATMAP_SIZE: f32 = 180.0;
+const HEATMAP_COLD: Color = Color::rgb(0.2, 0.2, 0.25);
+const HEATMAP_HOT: Color = Color::rgb(1.0, 0.3, 0.1);
+
+pub struct StatsScreenPlugin;
+
+impl Plugin for StatsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Stats), spawn_stats_screen)
+            .add_systems(OnExit(AppState::Stats), despawn_screen::<StatsScreen>)
+            .add_systems(
+                Update,
+                handle_stats_screen.run_if(in_state(AppState::Stats)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct StatsScreen;
+
+#[derive(Component)]
+struct BackButton;
+
+fn spawn_stats_screen(
+    mut commands: Commands,
+    stats: Res<Stats>,
+    streak: Res<DailyStreak>,
+    history: Res<History>,
+    board: Res<Board>,
+) {
+    spawn_dialog(&mut commands, StatsScreen, "Statistics", |panel| {
+        let win_rate = percent(stats.wins, stats.played);
+        panel.spawn(TextBundle::from_section(
+            format!(
+                "Played {}, won {} ({win_rate:.0}%), lost {}\nTotal time {}\nWin streak {} (longest {})",
+                stats.played,
+                stats.wins,
+                stats.losses,
+                format_duration(stats.total_time_ms),
+                stats.current_streak,
+                stats.longest_streak
+            ),
+            text_style(18.0),
+        ));
+
+        panel.spawn(TextBundle::from_section("Boards:", text_style(20.0)));
+        if stats.configs.is_empty() {
+            panel.spawn(TextBundle::from_section("No games yet", text_style(16.0)));
+        }
+        let mut configs: Vec<_> = stats.configs.iter().collect();
+        configs.sort_by_key(|config_stats| std::cmp::Reverse(config_stats.played));
+        for config_stats in configs.into_iter().take(SHOWN_CONFIGS) {
+            panel.spawn(TextBundle::from_section(
+                config_summary(config_stats),
+                text_style(16.0),
+            ));
+        }
+        if !stats.assisted.is_empty() {
+            let played: u32 = stats.assisted.iter().map(|stats| stats.played).sum();
+            let wins: u32 = stats.assisted.iter().map(|stats| stats.wins()).sum();
+            panel.spawn(TextBundle::from_section(
+                format!("Assisted games, not counted above: played {played}, won {wins}"),
+                text_style(16.0),
+            ));
+        }
+        if !stats.unranked.is_empty() {
+            let played: u32 = stats.unranked.iter().map(|stats| stats.played).sum();
+            let wins: u32 = stats.unranked.iter().map(|stats| stats.wins()).sum();
+            panel.spawn(TextBundle::from_section(
+                format!("Unranked games (zen, sandbox, scripts): played {played}, won {wins}"),
+                text_style(16.0),
+            ));
+        }
+
+        panel.spawn(TextBundle::from_section(
+            format!("Win times ({}):", board.config),
+            text_style(20.0),
+        ));
+        match stats.config(&board.config).filter(|stats| stats.wins() > 0) {
+            Some(config_stats) => spawn_histogram(panel, &config_stats.win_times_ms),
+            None => {
+                panel.spawn(TextBundle::from_section(
+                    "No wins on this board yet",
+                    text_style(16.0),
+                ));
+            }
+        }
+
+        panel.spawn(TextBundle::from_section(
+            format!(
+                "Daily puzzles (streak {}, best {}):",
+                streak.current(clock::today()),
+                streak.best_streak
+            ),
+            text_style(20.0),
+        ));
+        spawn_calendar(panel, &streak, clock::today());
+
+        panel.spawn(TextBundle::from_section(
+            format!("Clicks in the last {} games:", history.entries.len()),
+            text_style(20.0),
+        ));
+        let radius = history
+            .entries
+            .iter()
+            .map(|entry| entry.config.radius)
+            .max()
+            .unwrap_or(board.config.radius);
+        panel
+            .spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(16.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_heatmap(row, "First reveals", &history.first_clicks(), radius);
+                spawn_heatmap(row, "Fatal reveals", &history.fatal_clicks(), radius);
+            });
+
+        spawn_button(panel, "Back", 0, BackButton);
+    });
+}
+
+fn percent(part: u32, total: u32) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f32 * 100.0 / total as f32
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    format!(
+        "{}h {:02}m {:02}s",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn config_summary(stats: &ConfigStats) -> String {
+    let times = match (stats.best_ms(), stats.average_ms()) {
+        (Some(best), Some(average)) => format!(
+            ", best {:.1}s, average {:.1}s, efficiency {:.0}%",
+            best as f32 / 1000.0,
+            average as f32 / 1000.0,
+            stats.best_efficiency.unwrap_or(0.0) * 100.0
+        ),
+        _ => String::new(),
+    };
+    let no_flags = match stats.best_no_flag_ms() {
+        Some(best) => format!(", NF best {:.1}s", best as f32 / 1000.0),
+        None => String::new(),
+    };
+    format!(
+        "{}: won {}/{} ({:.0}%){times}{no_flags}",
+        stats.config,
+        stats.wins(),
+        stats.played,
+        percent(stats.wins(), stats.played)
+    )
+}
+
+/// Bars of win counts over equal time ranges from zero to the slowest win
+fn spawn_histogram(panel: &mut ChildBuilder, times_ms: &[u32]) {
+    let slowest = times_ms.iter().max().copied().unwrap_or(0).max(1);
+    let mut bins = [0u32; HISTOGRAM_BINS];
+    for time in times_ms {
+        let bin = (*time as u64 * HISTOGRAM_BINS as u64 / (slowest as u64 + 1)) as usize;
+        bins[bin] += 1;
+    }
+    let highest = bins.iter().max().copied().unwrap_or(0).max(1);
+
+    panel
+        .spawn(NodeBundle {
+            style: Style {
+                height: Val::Px(HISTOGRAM_HEIGHT),
+                align_items: AlignItems::FlexEnd,
+                column_gap: Val::Px(2.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|histogram| {
+            for count in bins {
+                histogram.spawn(NodeBundle {
+                    style: Style {
+                        flex_grow: 1.0,
+                        height: Val::Px(HISTOGRAM_HEIGHT * count as f32 / highest as f32),
+                        ..default()
+                    },
+                    background_color: BAR_COLOR.into(),
+                    ..default()
+                });
+            }
+        });
+    panel.spawn(TextBundle::from_section(
+        format!("0s .. {:.1}s", slowest as f32 / 1000.0),
+        text_style(14.0),
+    ));
+}
+
+/// Grid of the last weeks with a square per day, the bottom right one is today
+fn spawn_calendar(panel: &mut ChildBuilder, streak: &DailyStreak, today: u32) {
+    let first_day = (today + 1).saturating_sub(CALENDAR_WEEKS * 7);
+    panel
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|calendar| {
+            for week in 0..CALENDAR_WEEKS {
+                calendar
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(4.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for day in first_day + week * 7..first_day + (week + 1) * 7 {
+                            let color = if streak.completed_days.binary_search(&day).is_ok() {
+                                COMPLETED_DAY_COLOR
+                            } else {
+                                MISSED_DAY_COLOR
+                            };
+                            row.spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(16.0),
+                                    height: Val::Px(16.0),
+                                    ..default()
+                                },
+                                background_color: color.into(),
+                                ..default()
+                            });
+                        }
+                    });
+            }
+        });
+}
+
+/// Hexagonal board of the given radius with hexes colored by the counts, drawn as dots
+fn spawn_heatmap(parent: &mut ChildBuilder, title: &str, counts: &HashMap<Hex, u32>, radius: u32) {
+    // pointy hexes, so the widest row has `2 * radius + 1` hexes of `sqrt(3) * size` each
+    let hex_size = HEATMAP_SIZE / (3f32.sqrt() * (2 * radius + 1) as f32);
+    let layout = HexLayout {
+        hex_size: Vec2::splat(hex_size),
+        ..crate::GRID_LAYOUT
+    };
+    let dot_size = hex_size * 1.5;
+    let hottest = counts.values().max().copied().unwrap_or(0).max(1);
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|column| {
+            column.spawn(TextBundle::from_section(title, text_style(16.0)));
+            column
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(HEATMAP_SIZE),
+                        height: Val::Px(HEATMAP_SIZE),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|map| {
+                    for hex in shapes::hexagon(Hex::ZERO, radius) {
+                        let heat = counts.get(&hex).copied().unwrap_or(0) as f32 / hottest as f32;
+                        let pos = layout.hex_to_world_pos(hex);
+                        map.spawn(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                // UI y axis points down, unlike the world one
+                                left: Val::Px(HEATMAP_SIZE / 2.0 + pos.x - dot_size / 2.0),
+                                top: Val::Px(HEATMAP_SIZE / 2.0 - pos.y - dot_size / 2.0),
+                                width: Val::Px(dot_size),
+                                height: Val::Px(dot_size),
+                                ..default()
+                            },
+                            background_color: lerp_color(HEATMAP_COLD, HEATMAP_HOT, heat).into(),
+                            ..default()
+                        });
+                    }
+                });
+        });
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let (from, to) = (Vec4::from(from.as_rgba_f32()), Vec4::from(to.as_rgba_f32()));
+    Color::from(from.lerp(to, t))
+}
+
+fn handle_stats_screen(
+    mut activated: EventReader<Activated>,
+    mut cancelled: EventReader<Cancelled>,
+    buttons: Query<(), With<BackButton>>,
+    game: Res<Game>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let back = any_events(&mut cancelled)
+        || activated
+            .iter()
+            .any(|Activated(entity)| buttons.contains(*entity));
+    if back {
+        next_state.set(if game.outcome.is_some() {
+            AppState::GameOver
+        } else {
+            AppState::Paused
+        });
+    }
+}
@@ -0,0 +1,362 @@
+//! Colors of the custom theme, picked one role at a time. Every change applies to the board right away
+//! and switches to [`ThemeMode::Custom`], the preview on top shows the roles the dialog covers up.
+
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled, TextField},
+    set_label, spawn_button, spawn_dialog, spawn_option, spawn_text_field, text_style,
+};
+use crate::{
+    any_events,
+    settings::{Settings, ThemeMode},
+    theme::{load_themes, SavedTheme, Theme, ThemeRole},
+    AppState,
+};
+
+/// Channels go from 0 to 255 in steps of this
+const CHANNEL_STEP: i32 = 15;
+const PREVIEW_CELL: f32 = 32.0;
+
+pub struct ThemeEditorPlugin;
+
+impl Plugin for ThemeEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::ThemeEditor), spawn_theme_editor)
+            .add_systems(
+                OnExit(AppState::ThemeEditor),
+                despawn_screen::<ThemeEditorScreen>,
+            )
+            .add_systems(
+                Update,
+                (handle_theme_editor, update_labels, update_preview)
+                    .chain()
+                    .run_if(in_state(AppState::ThemeEditor)),
+            );
+    }
+}
+
+/// Index of the edited role in [`ThemeRole::ALL`]
+#[derive(Resource, Default)]
+struct EditedRole(usize);
+
+/// Themes listed on the screen, loaded when the screen opens to pick up freshly saved ones
+#[derive(Resource)]
+struct SavedThemes(Vec<SavedTheme>);
+
+#[derive(Component)]
+struct ThemeEditorScreen;
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct NameField;
+
+/// Part of the preview painted with the color of the role
+#[derive(Component)]
+struct Swatch(ThemeRole);
+
+/// Preview cell, its border is the outline
+#[derive(Component)]
+struct PreviewCell;
+
+#[derive(Component, Clone, Copy)]
+enum ThemeEditorAction {
+    Role,
+    /// Red, green, blue and alpha
+    Channel(usize),
+    Save,
+    /// Load the saved theme with the given index
+    Load(usize),
+    ResetLight,
+    ResetDark,
+    Back,
+}
+
+fn spawn_theme_editor(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+) {
+    let themes = load_themes();
+    spawn_dialog(&mut commands, ThemeEditorScreen, "Theme editor", |panel| {
+        spawn_preview(panel, &asset_server);
+        spawn_option(panel, 0, ThemeEditorAction::Role);
+        for channel in 0..4 {
+            spawn_option(
+                panel,
+                1 + channel as u32,
+                ThemeEditorAction::Channel(channel),
+            );
+        }
+        panel.spawn(TextBundle::from_section(
+            "Theme name, Enter to save:",
+            text_style(16.0),
+        ));
+        let name = if settings.theme == ThemeMode::Custom {
+            settings.custom_theme_name.as_str()
+        } else {
+            ""
+        };
+        spawn_text_field(panel, name, 5, NameField);
+        spawn_button(panel, "Save", 6, ThemeEditorAction::Save);
+        panel.spawn((TextBundle::from_section("", text_style(16.0)), StatusText));
+        let mut order = 7;
+        for (index, theme) in themes.iter().enumerate() {
+            let label = format!("Load {}", theme.name);
+            spawn_button(panel, &label, order, ThemeEditorAction::Load(index));
+            order += 1;
+        }
+        spawn_button(
+            panel,
+            "Start from light",
+            order,
+            ThemeEditorAction::ResetLight,
+        );
+        spawn_button(
+            panel,
+            "Start from dark",
+            order + 1,
+            ThemeEditorAction::ResetDark,
+        );
+        spawn_button(panel, "Back", order + 2, ThemeEditorAction::Back);
+    });
+    commands.insert_resource(EditedRole::default());
+    commands.insert_resource(SavedThemes(themes));
+}
+
+/// Row of cells on the background: covered, highlighted, flagged and revealed with every number
+fn spawn_preview(panel: &mut ChildBuilder, asset_server: &AssetServer) {
+    let cell = |role| {
+        (
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(PREVIEW_CELL),
+                    height: Val::Px(PREVIEW_CELL),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            Swatch(role),
+            PreviewCell,
+        )
+    };
+    let picture = |path: &str, role| {
+        (
+            ImageBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                image: UiImage::new(asset_server.load(path)),
+                ..default()
+            },
+            Swatch(role),
+        )
+    };
+    panel
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(8.0)),
+                    column_gap: Val::Px(4.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            Swatch(ThemeRole::Background),
+        ))
+        .with_children(|preview| {
+            preview.spawn(cell(ThemeRole::Covered));
+            preview
+                .spawn(cell(ThemeRole::Covered))
+                .with_children(|cell| {
+                    cell.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        Swatch(ThemeRole::Highlight),
+                    ));
+                });
+            preview
+                .spawn(cell(ThemeRole::Covered))
+                .with_children(|cell| {
+                    cell.spawn(picture("sign.png", ThemeRole::Flag));
+                });
+            for number in 1..=6 {
+                preview
+                    .spawn(cell(ThemeRole::Uncovered))
+                    .with_children(|cell| {
+                        let role = ThemeRole::Number(number);
+                        cell.spawn(picture(&format!("{number}.png"), role));
+                    });
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_theme_editor(
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&ThemeEditorAction>,
+    name_field: Query<&TextField, With<NameField>>,
+    mut status: Query<&mut Text, With<StatusText>>,
+    saved: Res<SavedThemes>,
+    theme: Res<Theme>,
+    mut role: ResMut<EditedRole>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Settings);
+    }
+
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        // `Enter` in the name field saves right away
+        let action = if name_field.contains(entity) {
+            Some(ThemeEditorAction::Save)
+        } else {
+            actions.get(entity).ok().copied()
+        };
+        let message = match action {
+            Some(ThemeEditorAction::Role) => {
+                let count = ThemeRole::ALL.len() as i32;
+                role.0 = (role.0 as i32 + delta).rem_euclid(count) as usize;
+                continue;
+            }
+            Some(ThemeEditorAction::Channel(channel)) => {
+                // editing starts from the colors on screen
+                if settings.theme != ThemeMode::Custom {
+                    settings.custom_theme = *theme;
+                    settings.theme = ThemeMode::Custom;
+                }
+                let color = settings.custom_theme.color_mut(ThemeRole::ALL[role.0]);
+                let mut channels = color.as_rgba_f32();
+                let value = (channels[channel] * 255.0 / CHANNEL_STEP as f32).round() as i32;
+                let steps = 255 / CHANNEL_STEP + 1;
+                channels[channel] =
+                    ((value + delta).rem_euclid(steps) * CHANNEL_STEP) as f32 / 255.0;
+                let [r, g, b, a] = channels;
+                *color = Color::rgba(r, g, b, a);
+                continue;
+            }
+            Some(ThemeEditorAction::Save) => {
+                let name = name_field.single().value.trim().to_string();
+                let custom = SavedTheme {
+                    name: name.clone(),
+                    theme: edited_theme(&settings, &theme),
+                };
+                match custom.save() {
+                    Ok(path) => {
+                        settings.custom_theme = custom.theme;
+                        settings.custom_theme_name = name;
+                        settings.theme = ThemeMode::Custom;
+                        format!("Saved to {path}")
+                    }
+                    Err(err) => format!("Failed to save: {err}"),
+                }
+            }
+            Some(ThemeEditorAction::Load(index)) => {
+                let Some(loaded) = saved.0.get(index) else {
+                    continue;
+                };
+                settings.custom_theme = loaded.theme;
+                settings.custom_theme_name = loaded.name.clone();
+                settings.theme = ThemeMode::Custom;
+                format!("Loaded {}", loaded.name)
+            }
+            Some(ThemeEditorAction::ResetLight) => {
+                settings.custom_theme = Theme::LIGHT;
+                settings.theme = ThemeMode::Custom;
+                continue;
+            }
+            Some(ThemeEditorAction::ResetDark) => {
+                settings.custom_theme = Theme::DARK;
+                settings.theme = ThemeMode::Custom;
+                continue;
+            }
+            Some(ThemeEditorAction::Back) => {
+                next_state.set(AppState::Settings);
+                continue;
+            }
+            None => continue,
+        };
+        for mut text in &mut status {
+            text.sections[0].value = message.clone();
+        }
+    }
+}
+
+/// Colors the editor shows: the custom ones, or the ones on screen until the first change
+fn edited_theme(settings: &Settings, theme: &Theme) -> Theme {
+    match settings.theme {
+        ThemeMode::Custom => settings.custom_theme,
+        _ => *theme,
+    }
+}
+
+fn update_labels(
+    settings: Res<Settings>,
+    theme: Res<Theme>,
+    role: Res<EditedRole>,
+    options: Query<(&ThemeEditorAction, &Children)>,
+    added: Query<(), Added<ThemeEditorScreen>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !settings.is_changed() && !role.is_changed() && added.is_empty() {
+        return;
+    }
+    let role = ThemeRole::ALL[role.0];
+    let channels = edited_theme(&settings, &theme).color(role).as_rgba_u8();
+    for (action, children) in &options {
+        let label = match action {
+            ThemeEditorAction::Role => format!("< Editing: {} >", role.label()),
+            ThemeEditorAction::Channel(channel) => {
+                let name = ["Red", "Green", "Blue", "Opacity"][*channel];
+                format!("< {name}: {} >", channels[*channel])
+            }
+            _ => continue,
+        };
+        set_label(children, &mut texts, label);
+    }
+}
+
+fn update_preview(
+    theme: Res<Theme>,
+    added: Query<(), Added<ThemeEditorScreen>>,
+    mut swatches: Query<(
+        &Swatch,
+        &mut BackgroundColor,
+        Option<&mut BorderColor>,
+        Option<&PreviewCell>,
+    )>,
+) {
+    if !theme.is_changed() && added.is_empty() {
+        return;
+    }
+    for (Swatch(role), mut background, border, cell) in &mut swatches {
+        background.0 = theme.color(*role);
+        if let (Some(mut border), Some(_)) = (border, cell) {
+            border.0 = theme.outline;
+        }
+    }
+}
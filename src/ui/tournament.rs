@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Adjusted, Cancelled},
+    set_label, spawn_button, spawn_dialog, spawn_option, text_style,
+};
+use crate::{
+    any_events,
+    board::Board,
+    game::NewGame,
+    tournament::{self, Stage, Tournament, MAX_BOARDS, MAX_PLAYERS, MIN_PLAYERS},
+    AppState,
+};
+
+pub struct TournamentScreenPlugin;
+
+impl Plugin for TournamentScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TournamentSetup>()
+            .add_systems(OnEnter(AppState::Tournament), spawn_tournament_screen)
+            .add_systems(
+                OnExit(AppState::Tournament),
+                despawn_screen::<TournamentScreen>,
+            )
+            .add_systems(
+                Update,
+                (handle_tournament_screen, update_labels)
+                    .chain()
+                    .run_if(in_state(AppState::Tournament)),
+            );
+    }
+}
+
+/// Options for the next tournament, kept between visits of the screen
+#[derive(Resource)]
+struct TournamentSetup {
+    players: u32,
+    boards: u32,
+}
+
+impl Default for TournamentSetup {
+    fn default() -> Self {
+        Self {
+            players: MIN_PLAYERS,
+            boards: 3,
+        }
+    }
+}
+
+#[derive(Component)]
+struct TournamentScreen;
+
+#[derive(Component, Clone, Copy)]
+enum TournamentAction {
+    Players,
+    Boards,
+    Start,
+    Play,
+    Leave,
+    Back,
+}
+
+fn spawn_tournament_screen(mut commands: Commands, tournament: Option<Res<Tournament>>) {
+    spawn_dialog(&mut commands, TournamentScreen, "Tournament", |panel| {
+        let Some(tournament) = tournament else {
+            spawn_option(panel, 0, TournamentAction::Players);
+            spawn_option(panel, 1, TournamentAction::Boards);
+            panel.spawn(TextBundle::from_section(
+                "Everyone plays the same boards of the current config in turn",
+                text_style(16.0),
+            ));
+            spawn_button(panel, "Start", 2, TournamentAction::Start);
+            spawn_button(panel, "Back", 3, TournamentAction::Back);
+            return;
+        };
+
+        let board = tournament.board_index() + 1;
+        let boards = tournament.boards_count();
+        let player = tournament::player_name(tournament.player());
+        let status = match tournament.stage {
+            Stage::Playing => format!("{player} is playing board {board} of {boards}"),
+            Stage::Handover => format!("Board {board} of {boards}: pass the keyboard to {player}"),
+            Stage::Finished => "Final standings".to_string(),
+        };
+        panel.spawn(TextBundle::from_section(status, text_style(22.0)));
+        for (place, standing) in tournament.standings().iter().enumerate() {
+            panel.spawn(TextBundle::from_section(
+                format!(
+                    "{}. {}  {} cleared  {:.1}s  {:.0}% revealed",
+                    place + 1,
+                    tournament::player_name(standing.player),
+                    standing.cleared,
+                    standing.time_ms as f32 / 1000.0,
+                    standing.progress / boards as f32 * 100.0
+                ),
+                text_style(18.0),
+            ));
+        }
+        if tournament.stage == Stage::Handover {
+            spawn_button(panel, &format!("{player}: play"), 0, TournamentAction::Play);
+        }
+        spawn_button(panel, "Leave tournament", 1, TournamentAction::Leave);
+        spawn_button(panel, "Back", 2, TournamentAction::Back);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_tournament_screen(
+    mut commands: Commands,
+    mut activated: EventReader<Activated>,
+    mut adjusted: EventReader<Adjusted>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&TournamentAction>,
+    board: Res<Board>,
+    mut setup: ResMut<TournamentSetup>,
+    mut tournament: Option<ResMut<Tournament>>,
+    mut new_game: EventWriter<NewGame>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    // activation cycles options forward, so they work with the mouse too
+    let pressed = activated
+        .iter()
+        .map(|Activated(entity)| (*entity, 1))
+        .chain(
+            adjusted
+                .iter()
+                .map(|adjusted| (adjusted.entity, adjusted.delta)),
+        );
+    for (entity, delta) in pressed {
+        match actions.get(entity) {
+            Ok(TournamentAction::Players) => {
+                let count = (MAX_PLAYERS - MIN_PLAYERS + 1) as i32;
+                let index = (setup.players - MIN_PLAYERS) as i32 + delta;
+                setup.players = MIN_PLAYERS + index.rem_euclid(count) as u32;
+            }
+            Ok(TournamentAction::Boards) => {
+                let index = (setup.boards - 1) as i32 + delta;
+                setup.boards = 1 + index.rem_euclid(MAX_BOARDS as i32) as u32;
+            }
+            // the first player is already at the keyboard
+            Ok(TournamentAction::Start) => {
+                let tournament = Tournament::new(setup.players, setup.boards, board.config);
+                if let Some(first) = tournament.current_game() {
+                    new_game.send(first);
+                }
+                commands.insert_resource(tournament);
+                next_state.set(AppState::Playing);
+            }
+            Ok(TournamentAction::Play) => {
+                let Some(tournament) = tournament.as_deref_mut() else {
+                    continue;
+                };
+                if let Some(game) = tournament.current_game() {
+                    tournament.stage = Stage::Playing;
+                    new_game.send(game);
+                    next_state.set(AppState::Playing);
+                }
+            }
+            Ok(TournamentAction::Leave) => {
+                commands.remove_resource::<Tournament>();
+                next_state.set(AppState::Paused);
+            }
+            Ok(TournamentAction::Back) => next_state.set(AppState::Paused),
+            Err(_) => {}
+        }
+    }
+}
+
+fn update_labels(
+    setup: Res<TournamentSetup>,
+    options: Query<(&TournamentAction, &Children)>,
+    added: Query<(), Added<TournamentScreen>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !setup.is_changed() && added.is_empty() {
+        return;
+    }
+    for (action, children) in &options {
+        let label = match action {
+            TournamentAction::Players => format!("< {} players >", setup.players),
+            TournamentAction::Boards => match setup.boards {
+                1 => "< 1 board >".to_string(),
+                boards => format!("< {boards} boards >"),
+            },
+            _ => continue,
+        };
+        set_label(children, &mut texts, label);
+    }
+}
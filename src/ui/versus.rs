@@ -0,0 +1,167 @@
+use std::net::SocketAddr;
+
+use bevy::prelude::*;
+
+use super::{
+    despawn_screen,
+    focus::{Activated, Cancelled, TextField},
+    lan::{spawn_lan_list, JoinLan},
+    spawn_button, spawn_dialog, spawn_text_field, text_style,
+};
+use crate::{
+    any_events,
+    board::Board,
+    game::NewGame,
+    mods::Mods,
+    net::discovery::GameMode,
+    versus::{Role, VersusSession, DEFAULT_PORT},
+    AppState,
+};
+
+pub struct VersusScreenPlugin;
+
+impl Plugin for VersusScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VersusStatus>()
+            .add_systems(OnEnter(AppState::Versus), spawn_versus_screen)
+            .add_systems(OnExit(AppState::Versus), despawn_screen::<VersusScreen>)
+            .add_systems(
+                Update,
+                (handle_versus_screen, update_status)
+                    .chain()
+                    .run_if(in_state(AppState::Versus)),
+            );
+    }
+}
+
+/// Last error, shown instead of the session state until the next attempt
+#[derive(Resource, Default)]
+struct VersusStatus(Option<String>);
+
+#[derive(Component)]
+struct VersusScreen;
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component)]
+struct AddressField;
+
+#[derive(Component, Clone, Copy)]
+enum VersusAction {
+    Host,
+    Join,
+    JoinLan(SocketAddr),
+    Start,
+    Leave,
+    Back,
+}
+
+fn spawn_versus_screen(mut commands: Commands) {
+    spawn_dialog(&mut commands, VersusScreen, "Versus race", |panel| {
+        panel.spawn((TextBundle::from_section("", text_style(18.0)), StatusText));
+        spawn_button(panel, "Host a race", 0, VersusAction::Host);
+        panel.spawn(TextBundle::from_section("Host address:", text_style(18.0)));
+        spawn_text_field(panel, &format!("127.0.0.1:{DEFAULT_PORT}"), 1, AddressField);
+        spawn_button(panel, "Join", 2, VersusAction::Join);
+        spawn_button(panel, "Start race", 3, VersusAction::Start);
+        spawn_button(panel, "Leave", 4, VersusAction::Leave);
+        spawn_button(panel, "Back", 5, VersusAction::Back);
+        spawn_lan_list(panel, GameMode::Versus);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_versus_screen(
+    mut commands: Commands,
+    mut activated: EventReader<Activated>,
+    mut cancelled: EventReader<Cancelled>,
+    actions: Query<&VersusAction>,
+    address: Query<&TextField, With<AddressField>>,
+    lan_games: Query<&JoinLan>,
+    mut session: Option<ResMut<VersusSession>>,
+    board: Res<Board>,
+    mut mods: ResMut<Mods>,
+    mut new_game: EventWriter<NewGame>,
+    mut status: ResMut<VersusStatus>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if any_events(&mut cancelled) {
+        next_state.set(AppState::Paused);
+    }
+
+    for Activated(entity) in activated.iter() {
+        // `Enter` in the address field joins right away
+        let action = if address.contains(*entity) {
+            Some(VersusAction::Join)
+        } else if let Ok(JoinLan(lan_address)) = lan_games.get(*entity) {
+            Some(VersusAction::JoinLan(*lan_address))
+        } else {
+            actions.get(*entity).ok().copied()
+        };
+        let connected = match action {
+            Some(VersusAction::Host) => VersusSession::host(DEFAULT_PORT),
+            Some(VersusAction::Join) => VersusSession::join(&address.single().value),
+            Some(VersusAction::JoinLan(lan_address)) => {
+                VersusSession::join(&lan_address.to_string())
+            }
+            Some(VersusAction::Start) => {
+                match session.as_deref_mut() {
+                    Some(session) if session.role == Role::Host && session.peers_count() > 0 => {
+                        let race = NewGame::random(board.config);
+                        session.start(race);
+                        // everyone races the same board under the core rules
+                        mods.active = None;
+                        new_game.send(race);
+                    }
+                    _ => status.0 = Some("Host a race and wait for an opponent first".to_string()),
+                }
+                continue;
+            }
+            Some(VersusAction::Leave) => {
+                commands.remove_resource::<VersusSession>();
+                status.0 = None;
+                continue;
+            }
+            Some(VersusAction::Back) => {
+                next_state.set(AppState::Paused);
+                continue;
+            }
+            None => continue,
+        };
+
+        match connected {
+            Ok(session) => {
+                commands.insert_resource(session);
+                status.0 = None;
+            }
+            Err(err) => status.0 = Some(format!("Failed: {err}")),
+        }
+    }
+}
+
+fn update_status(
+    session: Option<Res<VersusSession>>,
+    status: Res<VersusStatus>,
+    mut texts: Query<&mut Text, With<StatusText>>,
+) {
+    let label = match (&status.0, session) {
+        (Some(err), _) => err.clone(),
+        (None, None) => "Not connected".to_string(),
+        (None, Some(session)) => match session.role {
+            Role::Host => format!(
+                "Hosting on port {DEFAULT_PORT}, {} opponent(s) joined",
+                session.peers_count()
+            ),
+            Role::Client if session.is_reconnecting() => {
+                "Connection lost, reconnecting".to_string()
+            }
+            Role::Client => "Connected, waiting for the host to start".to_string(),
+        },
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Versus race: every player gets an identical board from the same seed and races to clear it first.
+//!
+//! Unlike co-op, boards are independent and never synced, players only exchange their progress.
+//! Clients send the actions they applied to the host, which plays them on its own copy of their board,
+//! so nobody can claim a board they never cleared, and relays the progress of every copy to everyone else.
+//! A client back after a dropped connection just keeps racing, the host only catches it up on the others.
+//! The host plays the copies under the core rules, so rule scripts are off for the race.
+
+use std::{io, sync::Mutex, time::Duration};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    any_events,
+    board::{Board, BoardConfig},
+    game::{CellsChanged, Game, GameAction, GameSet, NewGame, Outcome},
+    mods::Mods,
+    net::transport::{PeerId, Transport, TransportEvent, HOST},
+    sim::Simulation,
+};
+
+pub const DEFAULT_PORT: u16 = 7879;
+
+/// How often progress is sent even if nothing changed, so opponents see the timer running
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(VersusSessionPlugin).add_systems(
+            Update,
+            send_progress
+                .after(GameSet::Actions)
+                .run_if(resource_exists::<VersusSession>()),
+        );
+    }
+}
+
+/// Messages of the session without the local player, which is all the dedicated server needs
+pub struct VersusSessionPlugin;
+
+impl Plugin for VersusSessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            receive_messages
+                .in_set(GameSet::Route)
+                .run_if(resource_exists::<VersusSession>()),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum VersusMessage {
+    /// Host -> clients, everyone starts the same board right away
+    Start { config: BoardConfig, seed: u64 },
+    /// Client -> host, action the client applied to its board
+    Action(GameAction),
+    /// Client -> host, the player retried the race board after losing it, and its actions start over
+    Restart,
+    /// Host -> clients, progress of the player, of the host's copy of the board for clients
+    Progress { player: PeerId, progress: Progress },
+    /// Host -> clients, the player disconnected
+    Left { player: PeerId },
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Progress {
+    /// Fraction of revealed safe hexes
+    pub revealed: f32,
+    pub elapsed_ms: u32,
+    pub outcome: Option<Outcome>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Host,
+    Client,
+}
+
+/// Ongoing race. Removing the resource closes all connections.
+#[derive(Resource)]
+pub struct VersusSession {
+    transport: Transport<VersusMessage>,
+    pub role: Role,
+    /// Progress of other players by their ids
+    pub opponents: HashMap<PeerId, Progress>,
+    /// Board of the current race, local games with other seeds don't count
+    race: Option<NewGame>,
+    /// Host's copies of the boards of clients that made a move in the race
+    racers: HashMap<PeerId, Mutex<Simulation>>,
+}
+
+impl VersusSession {
+    pub fn host(port: u16) -> io::Result<Self> {
+        Ok(Self {
+            transport: Transport::host(port)?,
+            role: Role::Host,
+            opponents: HashMap::new(),
+            race: None,
+            racers: HashMap::new(),
+        })
+    }
+
+    pub fn join(address: &str) -> io::Result<Self> {
+        Ok(Self {
+            transport: Transport::connect(address)?,
+            role: Role::Client,
+            opponents: HashMap::new(),
+            race: None,
+            racers: HashMap::new(),
+        })
+    }
+
+    pub fn peers_count(&self) -> usize {
+        self.transport.peers_count()
+    }
+
+    /// The client lost its connection to the host and is trying to get it back
+    pub fn is_reconnecting(&self) -> bool {
+        self.transport.is_reconnecting()
+    }
+
+    pub fn is_racing(&self) -> bool {
+        self.race.is_some()
+    }
+
+    /// Starts the race for everyone, only the host can do it
+    pub fn start(&mut self, new_game: NewGame) {
+        if self.role != Role::Host {
+            return;
+        }
+        self.race = Some(new_game);
+        self.racers.clear();
+        self.opponents
+            .values_mut()
+            .for_each(|progress| *progress = Progress::default());
+        self.transport.broadcast(&VersusMessage::Start {
+            config: new_game.config,
+            seed: new_game.seed,
+        });
+    }
+}
+
+fn receive_messages(
+    time: Res<Time>,
+    mut session: ResMut<VersusSession>,
+    mut mods: ResMut<Mods>,
+    mut new_game: EventWriter<NewGame>,
+    mut since_relayed: Local<Duration>,
+) {
+    let role = session.role;
+    for event in session.transport.poll() {
+        match (role, event) {
+            (Role::Host, TransportEvent::Connected(peer)) => {
+                session.opponents.insert(peer, Progress::default());
+            }
+            (Role::Host, TransportEvent::Message(peer, VersusMessage::Action(action))) => {
+                let Some(NewGame { config, seed }) = session.race else {
+                    continue;
+                };
+                let racer = session
+                    .racers
+                    .entry(peer)
+                    .or_insert_with(|| Mutex::new(Simulation::new(config, seed)));
+                if let Ok(racer) = racer.get_mut() {
+                    racer.request(action);
+                }
+            }
+            // a won board stays won, while a lost one is played again from the start
+            (Role::Host, TransportEvent::Message(peer, VersusMessage::Restart)) => {
+                let won = session
+                    .racers
+                    .get_mut(&peer)
+                    .and_then(|racer| racer.get_mut().ok())
+                    .is_some_and(|racer| racer.game().outcome == Some(Outcome::Won));
+                if !won {
+                    session.racers.remove(&peer);
+                }
+            }
+            // the race might have started meanwhile, and the player missed progress of everyone
+            (Role::Host, TransportEvent::Reconnected(peer)) => {
+                if let Some(NewGame { config, seed }) = session.race {
+                    session
+                        .transport
+                        .send(peer, &VersusMessage::Start { config, seed });
+                }
+                for (player, progress) in &session.opponents {
+                    if *player != peer {
+                        let progress = VersusMessage::Progress {
+                            player: *player,
+                            progress: *progress,
+                        };
+                        session.transport.send(peer, &progress);
+                    }
+                }
+            }
+            (Role::Host, TransportEvent::Disconnected(peer)) => {
+                session.opponents.remove(&peer);
+                session.racers.remove(&peer);
+                session
+                    .transport
+                    .broadcast(&VersusMessage::Left { player: peer });
+            }
+            (Role::Client, TransportEvent::Message(_, VersusMessage::Start { config, seed })) => {
+                // sent again after reconnecting, the race goes on then
+                if session.race.map(|race| race.seed) == Some(seed) {
+                    continue;
+                }
+                session.race = Some(NewGame { config, seed });
+                session.opponents.clear();
+                // the host's copy of the board wouldn't follow the script
+                mods.active = None;
+                new_game.send(NewGame { config, seed });
+            }
+            (
+                Role::Client,
+                TransportEvent::Message(_, VersusMessage::Progress { player, progress }),
+            ) => {
+                session.opponents.insert(player, progress);
+            }
+            (Role::Client, TransportEvent::Message(_, VersusMessage::Left { player })) => {
+                session.opponents.remove(&player);
+            }
+            (Role::Client, TransportEvent::Reconnecting(_)) => {
+                warn!("Lost connection to the race host, reconnecting");
+            }
+            (Role::Client, TransportEvent::Disconnected(_)) => {
+                warn!("Lost connection to the race host");
+                session.race = None;
+                session.opponents.clear();
+            }
+            _ => {}
+        }
+    }
+    if role == Role::Host {
+        *since_relayed += time.delta();
+        relay_racers(&mut session, *since_relayed >= PROGRESS_INTERVAL);
+        if *since_relayed >= PROGRESS_INTERVAL {
+            *since_relayed = Duration::ZERO;
+        }
+    }
+}
+
+/// Plays the received actions on the host's copies of the boards, and relays the progress of those that changed,
+/// or of all of them once in a while to keep their timers running
+fn relay_racers(session: &mut VersusSession, all: bool) {
+    let VersusSession {
+        transport,
+        opponents,
+        racers,
+        ..
+    } = session;
+    for (peer, racer) in racers.iter_mut() {
+        let Ok(racer) = racer.get_mut() else {
+            continue;
+        };
+        racer.step();
+        let progress = Progress {
+            revealed: racer.board().progress(),
+            elapsed_ms: racer.game().elapsed.as_millis() as u32,
+            outcome: racer.game().outcome,
+        };
+        let changed = opponents.get(peer).map_or(true, |known| {
+            known.revealed != progress.revealed || known.outcome != progress.outcome
+        });
+        if changed || all {
+            let relayed = VersusMessage::Progress {
+                player: *peer,
+                progress,
+            };
+            transport.broadcast_except(*peer, &relayed);
+            opponents.insert(*peer, progress);
+        }
+    }
+}
+
+/// Host sends its own progress, clients send their actions for the host to work out theirs
+fn send_progress(
+    time: Res<Time>,
+    mut since_sent: Local<Duration>,
+    mut sent_actions: Local<(u64, usize)>,
+    session: Res<VersusSession>,
+    board: Res<Board>,
+    game: Res<Game>,
+    mut cells_changed: EventReader<CellsChanged>,
+) {
+    *since_sent += time.delta();
+    let changed = any_events(&mut cells_changed);
+    if session.race.map(|race| race.seed) != Some(board.seed) {
+        return;
+    }
+    match session.role {
+        Role::Host => {
+            if !changed && *since_sent < PROGRESS_INTERVAL {
+                return;
+            }
+            *since_sent = Duration::ZERO;
+            session.transport.broadcast(&VersusMessage::Progress {
+                player: HOST,
+                progress: Progress {
+                    revealed: board.progress(),
+                    elapsed_ms: game.elapsed.as_millis() as u32,
+                    outcome: game.outcome,
+                },
+            });
+        }
+        Role::Client => {
+            // a replay shorter than what was sent is a restart of the same board
+            if sent_actions.0 == board.seed && game.replay.steps.len() < sent_actions.1 {
+                session.transport.send(HOST, &VersusMessage::Restart);
+                sent_actions.1 = 0;
+            } else if sent_actions.0 != board.seed {
+                *sent_actions = (board.seed, 0);
+            }
+            for step in &game.replay.steps[sent_actions.1..] {
+                session
+                    .transport
+                    .send(HOST, &VersusMessage::Action(step.action()));
+            }
+            sent_actions.1 = game.replay.steps.len();
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Replays recorded games from `tests/replays` and compares the final boards with the expected ones,
+//! so changes to reveals, flags, chords and flood fill can't slip through unnoticed.
+//!
+//! Every file has a hand-made board and the expected final board in the text format of `board_text`,
+//! the replay itself and the expected outcome. Instead of a hand-made board, a file can have a config
+//! and a seed with the mines they generate, which catches changes to the RNG and the mine placement.
+
+use std::fs;
+
+use hex_minesweeper::{
+    board::{BoardConfig, Rules},
+    board_text::{decode_board_text, encode_board_text},
+    game::Outcome,
+    replay::Replay,
+    sim::Simulation,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Golden {
+    description: String,
+    #[serde(default)]
+    board: Option<String>,
+    #[serde(default)]
+    generated: Option<Generated>,
+    replay: Replay,
+    expected: String,
+    outcome: Option<Outcome>,
+}
+
+#[derive(Deserialize)]
+struct Generated {
+    config: BoardConfig,
+    seed: u64,
+    /// Sorted by `x`, then by `y`
+    mines: Vec<[i32; 2]>,
+}
+
+#[test]
+fn golden_replays() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/replays");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .expect("replays directory exists")
+        .map(|entry| entry.expect("readable entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no replays in {dir}");
+
+    for path in paths {
+        let name = path.display();
+        let golden: Golden = serde_json::from_slice(&fs::read(&path).expect("readable replay"))
+            .expect("valid replay");
+        let mut sim = match (&golden.board, &golden.generated) {
+            (Some(board), None) => {
+                let layout = decode_board_text(board).expect("valid board");
+                Simulation::from_layout(layout, Rules::CLASSIC)
+            }
+            (None, Some(generated)) => {
+                let sim = Simulation::new(generated.config, generated.seed);
+                let mut mines: Vec<_> =
+                    sim.board().mines.iter().map(|hex| [hex.x, hex.y]).collect();
+                mines.sort_unstable();
+                assert_eq!(
+                    mines, generated.mines,
+                    "{name}: seed {} generates other mines, did the RNG or the mine placement change?",
+                    generated.seed
+                );
+                sim
+            }
+            _ => panic!("{name}: needs either a board or a generated one"),
+        };
+        for step in &golden.replay.steps {
+            sim.request(step.action()).step();
+        }
+
+        let board = encode_board_text(&sim.board().layout());
+        assert_eq!(
+            board, golden.expected,
+            "{name}: {}\nfinal board:\n{board}",
+            golden.description
+        );
+        assert_eq!(
+            sim.game().outcome,
+            golden.outcome,
+            "{name}: {}",
+            golden.description
+        );
+    }
+}